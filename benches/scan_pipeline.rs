@@ -0,0 +1,123 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use futures_util::future::BoxFuture;
+use solana_arbitrage_bot::{
+    arbitrage_engine::{rank_opportunities, ArbitrageEngine},
+    config::Config,
+    monitoring::MonitoringService,
+    portfolio_manager::PortfolioManager,
+    price_source::PriceSource,
+    risk_manager::RiskManager,
+    types::{ArbitrageOpportunity, PriceData, WSOL_MINT},
+    DexMonitor,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tokio::sync::RwLock;
+
+const PAIR_COUNTS: [usize; 3] = [10, 100, 1000];
+const DEXES: [&str; 3] = ["Raydium", "Orca", "Serum"];
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+// Serves a fixed snapshot so the scan never touches the network
+struct StaticPriceSource(Vec<PriceData>);
+
+impl PriceSource for StaticPriceSource {
+    fn get_all_prices(&self) -> BoxFuture<'_, anyhow::Result<Vec<PriceData>>> {
+        Box::pin(async move { Ok(self.0.clone()) })
+    }
+}
+
+// Synthetic tokens don't resolve to real mints, so gas estimation skips the
+// token-account RPC lookups and only needs the primed SOL and USDC prices
+fn synthetic_prices(pairs: usize) -> Vec<PriceData> {
+    (0..pairs)
+        .flat_map(|pair| {
+            DEXES.iter().enumerate().map(move |(i, dex)| PriceData {
+                dex_name: dex.to_string(),
+                token_pair: format!("TKN{}/USDC", pair),
+                base_token: format!("TKN{}", pair),
+                quote_token: "USDC".to_string(),
+                price: 1.0 + (pair % 7) as f64 * 0.01 + i as f64 * 0.005,
+                volume_24h: 1_000_000.0,
+                liquidity: 500_000.0,
+                timestamp: 0,
+                pool_address: format!("{}-pool-{}", dex, pair),
+                price_impact: 0.1,
+            })
+        })
+        .collect()
+}
+
+fn build_engine(prices: Vec<PriceData>) -> ArbitrageEngine {
+    let mut config = Config::default();
+    config.jupiter.enabled = false;
+    config.trading.pending_trades_path = std::env::temp_dir()
+        .join("bench_pending_trades.json")
+        .to_string_lossy()
+        .to_string();
+    config.trading.history_path = None;
+
+    ArbitrageEngine::new(
+        config.clone(),
+        Arc::new(DexMonitor::new(config.dex_endpoints.clone())),
+        Arc::new(RwLock::new(RiskManager::new(config.risk_settings.clone()))),
+        Arc::new(PortfolioManager::new(config.clone())),
+        None,
+        None,
+        Arc::new(MonitoringService::new(config.monitoring.clone())),
+    )
+    .expect("Failed to build engine")
+    .with_price_source(Arc::new(StaticPriceSource(prices)))
+}
+
+fn bench_scan_opportunities(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("scan_opportunities");
+
+    for pairs in PAIR_COUNTS {
+        let engine = build_engine(synthetic_prices(pairs));
+        runtime.block_on(engine.prime_prices(HashMap::from([
+            (WSOL_MINT.to_string(), 150.0),
+            (USDC_MINT.to_string(), 1.0),
+        ])));
+
+        group.throughput(Throughput::Elements(pairs as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(pairs), &engine, |b, engine| {
+            b.to_async(&runtime).iter(|| async {
+                black_box(engine.scan_opportunities(0.1, 1000.0).await.unwrap())
+            });
+        });
+    }
+    group.finish();
+}
+
+fn bench_rank_opportunities(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("rank_opportunities");
+
+    for pairs in PAIR_COUNTS {
+        let engine = build_engine(synthetic_prices(pairs));
+        runtime.block_on(engine.prime_prices(HashMap::from([
+            (WSOL_MINT.to_string(), 150.0),
+            (USDC_MINT.to_string(), 1.0),
+        ])));
+        let opportunities: Vec<ArbitrageOpportunity> =
+            runtime.block_on(engine.scan_opportunities(0.0, 1000.0)).unwrap();
+        let weights = Config::default().scoring_weights;
+        let now = chrono::Utc::now().timestamp_millis();
+
+        group.throughput(Throughput::Elements(opportunities.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(pairs), &opportunities, |b, opportunities| {
+            b.iter_batched(
+                || opportunities.clone(),
+                |mut opportunities| rank_opportunities(black_box(&mut opportunities), &weights, now),
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_scan_opportunities, bench_rank_opportunities);
+criterion_main!(benches);
@@ -0,0 +1,98 @@
+use anyhow::Result;
+
+// Mints with more decimals than this are shown as raw amounts
+const MAX_DISPLAY_DECIMALS: u8 = 18;
+
+fn scale(decimals: u8) -> Result<u128> {
+    10u128
+        .checked_pow(decimals as u32)
+        .ok_or_else(|| anyhow::anyhow!("{} decimals overflows the amount scale", decimals))
+}
+
+// Converts a UI amount into raw base units, erroring instead of wrapping or
+// saturating when the result doesn't fit in a u64
+pub fn to_raw_amount(ui_amount: f64, decimals: u8) -> Result<u64> {
+    if !ui_amount.is_finite() || ui_amount < 0.0 {
+        return Err(anyhow::anyhow!("Invalid UI amount: {}", ui_amount));
+    }
+
+    // Shift the decimal point on the digits rather than multiplying by
+    // 10^decimals in f64, which loses precision and overflows for large scales
+    let formatted = format!("{:.*}", decimals as usize, ui_amount);
+    let digits: String = formatted.chars().filter(|c| *c != '.').collect();
+    let raw: u128 = digits
+        .parse()
+        .map_err(|_| anyhow::anyhow!("{} with {} decimals overflows a raw amount", ui_amount, decimals))?;
+
+    u64::try_from(raw)
+        .map_err(|_| anyhow::anyhow!("{} with {} decimals overflows a raw amount", ui_amount, decimals))
+}
+
+// Rounds a raw amount down to a whole number of `step_ui` lots, so trades
+// leave no dust behind. A zero step leaves the amount unchanged.
+pub fn quantize_raw_amount(raw_amount: u64, step_ui: f64, decimals: u8) -> Result<u64> {
+    let step = to_raw_amount(step_ui, decimals)?;
+    if step == 0 {
+        return Ok(raw_amount);
+    }
+    Ok(raw_amount / step * step)
+}
+
+pub fn to_ui_amount(raw_amount: u64, decimals: u8) -> Result<f64> {
+    Ok(raw_amount as f64 / scale(decimals)? as f64)
+}
+
+// Exact decimal rendering of a raw amount, e.g. 1500000 with 6 decimals -> "1.5"
+pub fn format_amount(raw_amount: u64, decimals: u8) -> String {
+    if decimals > MAX_DISPLAY_DECIMALS {
+        return format!("{} (raw)", raw_amount);
+    }
+    if decimals == 0 {
+        return raw_amount.to_string();
+    }
+
+    let scale = 10u128.pow(decimals as u32);
+    let whole = raw_amount as u128 / scale;
+    let fraction = raw_amount as u128 % scale;
+    if fraction == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = format!("{:0width$}", fraction, width = decimals as usize);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_without_float_drift() {
+        assert_eq!(to_raw_amount(0.1, 9).unwrap(), 100_000_000);
+        assert_eq!(to_raw_amount(1.5, 6).unwrap(), 1_500_000);
+        assert_eq!(to_ui_amount(1_500_000, 6).unwrap(), 1.5);
+    }
+
+    #[test]
+    fn refuses_amounts_that_do_not_fit() {
+        assert!(to_raw_amount(-1.0, 6).is_err());
+        assert!(to_raw_amount(f64::NAN, 6).is_err());
+        assert!(to_raw_amount(1e12, 9).is_err());
+        assert!(to_ui_amount(1, 40).is_err());
+    }
+
+    #[test]
+    fn quantizes_down_to_whole_lots() {
+        assert_eq!(quantize_raw_amount(1_234_567, 0.5, 6).unwrap(), 1_000_000);
+        assert_eq!(quantize_raw_amount(1_234_567, 0.0, 6).unwrap(), 1_234_567);
+    }
+
+    #[test]
+    fn formats_exactly() {
+        assert_eq!(format_amount(1_500_000, 6), "1.5");
+        assert_eq!(format_amount(2_000_000, 6), "2");
+        assert_eq!(format_amount(1, 9), "0.000000001");
+        assert_eq!(format_amount(42, 0), "42");
+        assert_eq!(format_amount(42, 19), "42 (raw)");
+    }
+}
@@ -1,33 +1,147 @@
 use crate::{
-    config::Config,
+    amounts::{quantize_raw_amount, to_raw_amount, to_ui_amount},
+    backpressure::AdaptiveScope,
+    blackout::Blackout,
+    clock::{check_timestamp, Clock, SharedClock, SystemClock},
+    compute_budget::{merge_instruction_sets, ComputeBudget, MAX_COMPUTE_UNIT_LIMIT},
+    config::{
+        Config, MintRestrictionPolicy, PairSettings, PositionSizingMode, ProfitCommitment, RouteOverlapPolicy, ScanScope,
+        ScoringWeights, WatchedPair,
+    },
+    dex_fees::DexFeeModel,
     dex_monitor::DexMonitor,
+    event_sink::{EventSink, TradeEvent},
+    executor::{ChainExecutor, Executor, SimExecutor},
+    fee_estimator::{cu_price_for_profit, FeeEstimator},
+    finality::{ChainStatus, Finality, FinalityTracker, UnfinalizedTrade},
+    history::{HistoryPage, HistoryQuery, HistoryStore},
+    housekeeping::{plan_housekeeping, HousekeepingAction, TOKEN_ACCOUNT_RENT_SOL},
+    impact_monitor::ImpactTracker,
+    opportunity_claims::{ClaimOutcome, OpportunityClaims},
+    opportunity_feed::{OpportunityEvent, OpportunityFeed},
     risk_manager::RiskManager,
+    strategy_budget::StrategyBudgets,
     portfolio_manager::PortfolioManager,
+    rebalancer::compute_rebalance_trades,
     jito_client::JitoClient,
-    jupiter_client::JupiterClient,
+    jito_failover::JitoFailover,
+    supervisor::{Heartbeat, Supervisor},
+    tip_estimator::TipEstimator,
+    trade_queue::{Admission, PendingGuard, PendingTrade, TradeQueue, BLOCKHASH_EXPIRY_MS},
+    warm_cache::WarmCache,
+    win_rate::WinRateMonitor,
+    jupiter_client::{is_no_route_error, JupiterClient, JupiterQuoteRequest},
+    mint_safety::{fetch_mint_restrictions, MintRestrictions},
     monitoring::MonitoringService,
+    oracle::{deviation_pct, Oracle, PythOracle},
+    price_history::{PriceHistory, PriceSample},
+    price_quorum::{agreeing_prices, independent_sources},
+    price_source::PriceSource,
+    quote_cache::{QuoteCache, QuoteKey},
+    quote_source::{quote_divergence_pct, QuoteSource},
+    route_availability::RouteAvailability,
+    rpc_client::SolanaRpcClient,
+    signer::{sign_transaction, KeypairSigner, RemoteSigner, TransactionSigner},
+    stats::StatsHandle,
+    slippage::{realized_slippage_bps, received_amount, SlippageDistribution, SlippageSample, SlippageTracker},
+    transaction_builder::{compile_transaction, parse_lookup_table, trade_legs, TradeLeg},
+    transaction_simulator::{
+        associated_token_address, close_token_account_instruction, create_associated_token_account_instruction,
+        TransactionSimulator,
+    },
     types::{
-        ArbitrageOpportunity, PriceData, TradeRequest, TradeResponse,
-        EnhancedArbitrageOpportunity, JupiterQuote, SwapRequest, SwapResponse,
-        ExecutionMethod, DexPrice, ArbitrageError
+        ApiHealth, ArbitrageOpportunity, PriceData, SwapMode, TradeRequest, TradeResponse,
+        EnhancedArbitrageOpportunity, JupiterConfig, JupiterQuote, SwapRequest,
+        ExecutionMethod, DexPrice, ArbitrageError, RejectionReason,
+        CycleArbitrageOpportunity, GasCost, PriceConfidence, CanaryStatus, KillSwitchState, StrategyKind, TradingStats,
+        BASE_FEE_LAMPORTS_PER_SIGNATURE, LAMPORTS_PER_SOL, WSOL_MINT,
     },
 };
 use anyhow::Result;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use base64::Engine;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    message::{AddressLookupTableAccount, Message, VersionedMessage},
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::VersionedTransaction,
+};
+use std::str::FromStr;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{info, warn, error, debug};
-use uuid::Uuid;
-use chrono::Utc;
+use futures_util::future::join_all;
+use serde_json::json;
+
+// Jito tip attached to auto-traded opportunities (0.0001 SOL)
+const DEFAULT_JITO_TIP_LAMPORTS: u64 = 100_000;
+
+// A signed trade transaction and the per-leg quotes it was built from
+struct BuiltTrade {
+    transaction: VersionedTransaction,
+    quotes: Vec<JupiterQuote>,
+    budget: ComputeBudget,
+}
 
 pub struct ArbitrageEngine {
     config: Config,
-    dex_monitor: Arc<DexMonitor>,
+    price_source: Arc<dyn PriceSource>,
     risk_manager: Arc<RwLock<RiskManager>>,
     portfolio_manager: Arc<PortfolioManager>,
     jito_client: Option<Arc<JitoClient>>,
     jupiter_client: Option<Arc<JupiterClient>>,
     monitoring: Arc<MonitoringService>,
     is_running: Arc<RwLock<bool>>,
+    blocked_pools: Arc<RwLock<HashSet<String>>>,
+    simulator: Option<Arc<TransactionSimulator>>,
+    rejection_counts: Arc<RwLock<HashMap<RejectionReason, u64>>>,
+    strategy_pnl: Arc<RwLock<HashMap<StrategyKind, f64>>>,
+    strategy_budgets: Arc<RwLock<StrategyBudgets>>,
+    // risk_settings.max_position_size, or its equity-based replacement
+    max_position_size: Arc<RwLock<f64>>,
+    executor: Arc<dyn Executor>,
+    warm_cache: Arc<RwLock<WarmCache>>,
+    supervisor: Supervisor,
+    oracle: Option<Arc<dyn Oracle>>,
+    secondary_quote_source: Option<Arc<dyn QuoteSource>>,
+    watchlist: Arc<RwLock<Vec<WatchedPair>>>,
+    rpc_client: Arc<SolanaRpcClient>,
+    known_token_accounts: Arc<RwLock<HashSet<String>>>,
+    // Pairs inside the profit band, per scan: the direct and enhanced scans
+    // price the same pair differently and must not flip each other's state
+    pursued_pairs: Arc<RwLock<HashSet<(StrategyKind, String)>>>,
+    // The latest cycle scan's opportunities in executable form, by id
+    cycle_opportunities: Arc<RwLock<HashMap<String, ArbitrageOpportunity>>>,
+    trade_queue: Arc<TradeQueue>,
+    fee_estimator: Option<Arc<FeeEstimator>>,
+    tip_estimator: Option<Arc<TipEstimator>>,
+    impact_tracker: Arc<RwLock<ImpactTracker>>,
+    paused_pairs: Arc<RwLock<HashMap<String, i64>>>,
+    win_rate_monitor: Arc<RwLock<WinRateMonitor>>,
+    quote_cache: Arc<RwLock<QuoteCache>>,
+    api_health: Arc<RwLock<ApiHealth>>,
+    dex_fees: Arc<DexFeeModel>,
+    adaptive_scope: Arc<RwLock<AdaptiveScope>>,
+    history: Arc<HistoryStore>,
+    event_sink: Arc<EventSink>,
+    slippage_tracker: Arc<RwLock<SlippageTracker>>,
+    canary_states: Arc<RwLock<HashMap<WatchedPair, CanaryStatus>>>,
+    mint_restrictions: Arc<RwLock<HashMap<String, MintRestrictions>>>,
+    opportunity_claims: Arc<RwLock<OpportunityClaims>>,
+    route_availability: Arc<RwLock<RouteAvailability>>,
+    // None when neither a valid private key nor a remote signer is configured
+    signer: Option<Arc<dyn TransactionSigner>>,
+    opportunity_feed: Arc<OpportunityFeed>,
+    price_history: Option<Arc<PriceHistory>>,
+    kill_switch: Arc<RwLock<KillSwitchState>>,
+    clock: Arc<SharedClock>,
+    finality: Arc<RwLock<FinalityTracker>>,
+    stats: StatsHandle,
+    blackout: Arc<RwLock<Blackout>>,
 }
 
 impl ArbitrageEngine {
@@ -39,250 +153,1859 @@ impl ArbitrageEngine {
         jito_client: Option<Arc<JitoClient>>,
         jupiter_client: Option<Arc<JupiterClient>>,
         monitoring: Arc<MonitoringService>,
-    ) -> Self {
-        Self {
-            config,
-            dex_monitor,
+    ) -> Result<Self> {
+        let blocked_pools = config.risk_settings.blocked_pools.iter().cloned().collect();
+        let watchlist = config.scan_scope.watchlist.clone();
+        let fee_estimator = if config.priority_fee.dynamic {
+            Some(Arc::new(FeeEstimator::new(
+                config.rpc_endpoints.primary.clone(),
+                config.rpc_endpoints.timeout_ms,
+                config.priority_fee.percentile,
+                config.priority_fee.floor_micro_lamports,
+                config.priority_fee.ceiling_micro_lamports.min(config.risk_settings.max_gas_price),
+            )))
+        } else {
+            None
+        };
+        let tip_estimator = if config.jito.enabled && config.jito.dynamic_tip {
+            match TipEstimator::new(&config.jito, config.rpc_endpoints.timeout_ms) {
+                Ok(estimator) => Some(Arc::new(estimator)),
+                Err(e) => {
+                    warn!("⚠️ Dynamic Jito tips disabled: cannot build tip estimator: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let impact_tracker = ImpactTracker::new(
+            config.impact_alerts.baseline_alpha,
+            config.impact_alerts.spike_multiple,
+            config.impact_alerts.min_samples,
+        );
+        let dex_fees = Arc::new(DexFeeModel::from_config(&config.dex_endpoints));
+        let adaptive_scope = AdaptiveScope::new(
+            config.backpressure.high_watermark,
+            config.backpressure.low_watermark,
+            config.backpressure.persist_scans,
+            config.backpressure.min_pairs,
+        );
+        let win_rate_monitor = WinRateMonitor::new(
+            config.win_rate_alerts.window,
+            config.win_rate_alerts.min_win_rate,
+        );
+        let history = Arc::new(
+            HistoryStore::open(config.trading.history_path.as_ref().map(Into::into))
+                .map_err(|e| anyhow::anyhow!("Failed to open trade history: {}", e))?,
+        );
+        let event_sink = Arc::new(EventSink::start(&config.event_sink));
+        let stats = StatsHandle::start(config.stats.channel_capacity);
+        let blackout = Blackout::new(&config.blackout)
+            .map_err(|e| anyhow::anyhow!("Invalid blackout schedule: {}", e))?;
+        // Every component reads this one clock, so `with_clock` reaches them all
+        let clock = Arc::new(SharedClock::new(Arc::new(SystemClock)));
+        let jupiter_client = jupiter_client
+            .map(|client| Arc::new(client.as_ref().clone().with_clock(clock.clone())));
+        let price_history = if config.price_history.enabled {
+            Some(Arc::new(
+                PriceHistory::open(
+                    config.price_history.path.as_ref().map(Into::into),
+                    config.price_history.sample_interval_ms,
+                    config.price_history.retention_secs,
+                    clock.now_ms(),
+                )
+                .map_err(|e| anyhow::anyhow!("Failed to open price history: {}", e))?,
+            ))
+        } else {
+            None
+        };
+        let trade_queue = Arc::new(
+            TradeQueue::open(&config.trading.pending_trades_path)
+                .map_err(|e| anyhow::anyhow!("Failed to open pending trade queue: {}", e))?,
+        );
+        let rpc_client = Arc::new(SolanaRpcClient::new(
+            config.rpc_endpoints.primary.clone(),
+            config.rpc_endpoints.timeout_ms,
+        ));
+        let jito_failover = Arc::new(JitoFailover::new(
+            config.jito.failover_threshold,
+            config.jito.failover_window_secs,
+            config.jito.recovery_probe_secs,
+            clock.clone(),
+        ));
+        let executor: Arc<dyn Executor> = if config.simulation.enabled {
+            warn!("🧪 Simulation enabled: trades are simulated, nothing is sent to chain");
+            Arc::new(SimExecutor::new(config.simulation.clone()))
+        } else {
+            Arc::new(ChainExecutor::new(
+                jito_client.clone(),
+                jito_failover,
+                rpc_client.clone(),
+                config.trading.execution_timeout_ms,
+            ))
+        };
+        let supervisor = Supervisor::new(
+            config.trading.heartbeat_timeout_ms,
+            config.trading.task_restart_delay_ms,
+            clock.clone(),
+        );
+        let oracle: Option<Arc<dyn Oracle>> = if config.oracle.enabled {
+            Some(Arc::new(PythOracle::new(
+                config.rpc_endpoints.primary.clone(),
+                config.rpc_endpoints.timeout_ms,
+                config.oracle.price_accounts.clone(),
+            ).with_max_staleness_slots(config.oracle.max_staleness_slots)))
+        } else {
+            None
+        };
+        let secondary_quote_source: Option<Arc<dyn QuoteSource>> = match &config.quote_cross_check.secondary_api_url {
+            Some(url) if config.quote_cross_check.enabled => {
+                let secondary_config = JupiterConfig {
+                    api_url: url.clone(),
+                    api_type: None,
+                    base_urls: HashMap::new(),
+                    quote_cache_ttl_ms: 0,
+                    ..config.jupiter.clone()
+                };
+                match JupiterClient::new_with_config(&secondary_config) {
+                    Ok(client) => Some(Arc::new(client)),
+                    Err(e) => {
+                        warn!("⚠️ Quote cross-check disabled: cannot build secondary client for {}: {}", url, e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        let signer: Result<Arc<dyn TransactionSigner>> = match &config.wallet.remote_signer {
+            Some(remote) => RemoteSigner::new(remote, &config.wallet.public_key)
+                .map(|signer| Arc::new(signer) as Arc<dyn TransactionSigner>),
+            None => KeypairSigner::from_base58(&config.wallet.private_key)
+                .map(|signer| Arc::new(signer) as Arc<dyn TransactionSigner>),
+        };
+        let signer = match signer {
+            Ok(signer) => Some(signer),
+            Err(e) => {
+                warn!("⚠️ No wallet signer available, transactions can't be signed: {}", e);
+                None
+            }
+        };
+        let simulator = if config.trading.simulate_before_execute {
+            Some(Arc::new(TransactionSimulator::new(
+                config.rpc_endpoints.primary.clone(),
+                config.rpc_endpoints.timeout_ms,
+            )))
+        } else {
+            None
+        };
+        Ok(Self {
+            price_source: dex_monitor,
             risk_manager,
             portfolio_manager,
             jito_client,
             jupiter_client,
             monitoring,
             is_running: Arc::new(RwLock::new(false)),
-        }
+            blocked_pools: Arc::new(RwLock::new(blocked_pools)),
+            simulator,
+            rejection_counts: Arc::new(RwLock::new(HashMap::new())),
+            strategy_pnl: Arc::new(RwLock::new(HashMap::new())),
+            strategy_budgets: Arc::new(RwLock::new(StrategyBudgets::new(config.strategies.budgets.clone()))),
+            max_position_size: Arc::new(RwLock::new(config.risk_settings.max_position_size)),
+            executor,
+            warm_cache: Arc::new(RwLock::new(WarmCache::default())),
+            supervisor,
+            oracle,
+            secondary_quote_source,
+            watchlist: Arc::new(RwLock::new(watchlist)),
+            rpc_client,
+            known_token_accounts: Arc::new(RwLock::new(HashSet::new())),
+            pursued_pairs: Arc::new(RwLock::new(HashSet::new())),
+            cycle_opportunities: Arc::new(RwLock::new(HashMap::new())),
+            trade_queue,
+            fee_estimator,
+            tip_estimator,
+            impact_tracker: Arc::new(RwLock::new(impact_tracker)),
+            paused_pairs: Arc::new(RwLock::new(HashMap::new())),
+            win_rate_monitor: Arc::new(RwLock::new(win_rate_monitor)),
+            quote_cache: Arc::new(RwLock::new(QuoteCache::default())),
+            api_health: Arc::new(RwLock::new(ApiHealth::Healthy)),
+            dex_fees,
+            adaptive_scope: Arc::new(RwLock::new(adaptive_scope)),
+            history,
+            event_sink,
+            slippage_tracker: Arc::new(RwLock::new(SlippageTracker::default())),
+            canary_states: Arc::new(RwLock::new(HashMap::new())),
+            mint_restrictions: Arc::new(RwLock::new(HashMap::new())),
+            opportunity_claims: Arc::new(RwLock::new(OpportunityClaims::new(config.trading.opportunity_claim_ttl_ms))),
+            route_availability: Arc::new(RwLock::new(RouteAvailability::new(
+                config.scan_scope.route_miss_tolerance,
+                config.scan_scope.route_recheck_scans,
+            ))),
+            signer,
+            opportunity_feed: Arc::new(OpportunityFeed::new(config.trading.feed_change_threshold_pct)),
+            price_history,
+            kill_switch: Arc::new(RwLock::new(KillSwitchState::default())),
+            clock,
+            finality: Arc::new(RwLock::new(FinalityTracker::default())),
+            stats,
+            blackout: Arc::new(RwLock::new(blackout)),
+            config,
+        })
+    }
+
+    // Replaces the signer built from [wallet], e.g. with a custom hardware integration
+    pub fn with_signer(mut self, signer: Arc<dyn TransactionSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    // Replaces the system clock, e.g. with a `ManualClock` to drive expiries
+    // and daily windows deterministically
+    pub fn with_clock(self, clock: Arc<dyn Clock>) -> Self {
+        self.clock.set(clock);
+        self
+    }
+
+    // Replaces the execution backend, e.g. with a `SimExecutor` for strategy tests
+    pub fn with_executor(mut self, executor: Arc<dyn Executor>) -> Self {
+        self.executor = executor;
+        self
+    }
+
+    // Replaces the live DEX monitor, e.g. with synthetic prices for benchmarks
+    pub fn with_price_source(mut self, price_source: Arc<dyn PriceSource>) -> Self {
+        self.price_source = price_source;
+        self
+    }
+
+    // Replaces the configured oracle, e.g. with fixed prices for tests
+    pub fn with_oracle(mut self, oracle: Arc<dyn Oracle>) -> Self {
+        self.oracle = Some(oracle);
+        self
+    }
+
+    pub fn with_secondary_quote_source(mut self, quote_source: Arc<dyn QuoteSource>) -> Self {
+        self.secondary_quote_source = Some(quote_source);
+        self
     }
 
     pub async fn start(&self) -> Result<()> {
+        if self.config.trading.skip_warmup {
+            info!("⏭️ Skipping cache warm-up");
+            self.warm_cache.write().await.ready = true;
+        } else {
+            self.warm_up().await?;
+        }
+
+        // Resolve trades a previous run left in flight before submitting anything new
+        let recovered = self.trade_queue.recover(&self.rpc_client, self.clock.now_ms(), &self.config.clock, 0).await;
+        if !recovered.is_empty() {
+            info!("♻️ Reconciled {} pending trades from the previous run", recovered.len());
+        }
+
+        if self.config.trading.precreate_token_accounts {
+            let mints: Vec<String> = self.watchlist.read().await.iter()
+                .map(|pair| pair.output_mint.clone())
+                .collect();
+            if let Err(e) = self.ensure_atas(&mints).await {
+                warn!("⚠️ Failed to pre-create token accounts, swaps will create them: {}", e);
+            }
+        }
+
+        self.refresh_kill_switch().await;
+        if self.config.kill_switch.listen_for_signal {
+            self.listen_for_kill_signal()?;
+        }
+
         let mut running = self.is_running.write().await;
         *running = true;
         drop(running);
 
         info!("🚀 Starting arbitrage engine");
         
-        // Start the main arbitrage loop
-        let engine_clone = self.clone_for_task();
-        tokio::spawn(async move {
-            if let Err(e) = engine_clone.arbitrage_loop().await {
-                error!("❌ Arbitrage loop error: {}", e);
-            }
+        // Start the main arbitrage loop under the watchdog
+        let engine = Arc::new(self.clone_for_task());
+        let scan_interval = std::time::Duration::from_millis(self.config.trading.scan_interval_ms);
+        self.supervisor.supervise_periodic("arbitrage_loop", scan_interval, move |heartbeat| {
+            let engine = engine.clone();
+            async move { engine.arbitrage_loop(heartbeat).await }
         });
 
-        Ok(())
-    }
+        if self.config.api_health.enabled && self.jupiter_client.is_some() {
+            let engine = Arc::new(self.clone_for_task());
+            let poll_interval = std::time::Duration::from_millis(self.config.api_health.poll_interval_ms);
+            self.supervisor.supervise_periodic("api_health_poller", poll_interval, move |heartbeat| {
+                let engine = engine.clone();
+                async move { engine.poll_api_health(heartbeat).await }
+            });
+        }
 
-    pub async fn stop(&self) -> Result<()> {
-        let mut running = self.is_running.write().await;
-        *running = false;
-        info!("🛑 Stopping arbitrage engine");
-        Ok(())
-    }
+        let engine = Arc::new(self.clone_for_task());
+        let finality_poll = std::time::Duration::from_millis(self.config.trading.finality_poll_ms);
+        self.supervisor.supervise_periodic("finality_watch", finality_poll, move |heartbeat| {
+            let engine = engine.clone();
+            async move { engine.run_finality_watch(heartbeat).await }
+        });
 
-    pub async fn scan_enhanced_opportunities(
-        &self,
-        min_profit_percentage: f64,
-        max_amount: f64,
-    ) -> Result<Vec<EnhancedArbitrageOpportunity>> {
-        debug!("🔍 Scanning for enhanced arbitrage opportunities with Jupiter");
-        
-        let mut opportunities = Vec::new();
-        
-        // Get direct DEX prices
-        let dex_prices = self.dex_monitor.get_all_prices().await?;
-        
-        // Group prices by token pair
-        let mut price_groups: std::collections::HashMap<String, Vec<PriceData>> = 
-            std::collections::HashMap::new();
-        
-        for price in dex_prices {
-            price_groups.entry(price.token_pair.clone()).or_default().push(price);
+        if self.config.position_sizing.mode == PositionSizingMode::EquityPercent {
+            let engine = Arc::new(self.clone_for_task());
+            let refresh_interval = std::time::Duration::from_millis(self.config.position_sizing.refresh_interval_ms);
+            self.supervisor.supervise_periodic("position_sizer", refresh_interval, move |heartbeat| {
+                let engine = engine.clone();
+                async move { engine.run_position_sizer(heartbeat).await }
+            });
         }
 
-        // Process each token pair
-        for (token_pair, prices) in price_groups {
-            if prices.len() < 2 {
-                continue;
-            }
+        if self.config.housekeeping.enabled {
+            let engine = Arc::new(self.clone_for_task());
+            let interval = std::time::Duration::from_secs(self.config.housekeeping.interval_secs);
+            self.supervisor.supervise_periodic("housekeeping", interval, move |heartbeat| {
+                let engine = engine.clone();
+                async move { engine.run_housekeeping(heartbeat).await }
+            });
+        }
 
-            // Extract token mints (simplified - in real implementation, you'd have a mapping)
-            let (input_mint, output_mint) = self.extract_token_mints(&token_pair)?;
-            
-            // Get Jupiter quote if enabled
-            let jupiter_quote = if self.config.jupiter.enabled && self.jupiter_client.is_some() {
-                match self.get_jupiter_quote(&input_mint, &output_mint, max_amount as u64).await {
-                    Ok(quote) => Some(quote),
-                    Err(e) => {
-                        warn!("⚠️ Failed to get Jupiter quote for {}: {}", token_pair, e);
-                        None
-                    }
+        if self.config.history_retention.enabled {
+            let history = self.history.clone();
+            let retention = self.config.history_retention.clone();
+            let clock = self.clock.clone();
+            let interval = std::time::Duration::from_secs(retention.vacuum_interval_secs);
+            self.supervisor.supervise_periodic("history_vacuum", interval, move |heartbeat| {
+                let history = history.clone();
+                let retention = retention.clone();
+                let clock = clock.clone();
+                async move {
+                    history.run_vacuum(
+                        retention.opportunity_retention_days,
+                        retention.trade_retention_days,
+                        retention.vacuum_interval_secs,
+                        clock,
+                        heartbeat,
+                    ).await
                 }
-            } else {
-                None
-            };
+            });
+        }
 
-            // Convert DEX prices to DexPrice format
-            let direct_dex_prices: Vec<DexPrice> = prices.iter().map(|p| DexPrice {
-                dex_name: p.dex_name.clone(),
-                price: p.price,
-                liquidity: p.liquidity,
-                pool_address: p.pool_address.clone(),
-                price_impact: p.price_impact,
-            }).collect();
+        if let Some(price_history) = &self.price_history {
+            let price_history = price_history.clone();
+            let flush_interval_ms = self.config.price_history.flush_interval_ms;
+            let interval = std::time::Duration::from_millis(flush_interval_ms);
+            self.supervisor.supervise_periodic("price_history_flusher", interval, move |heartbeat| {
+                let price_history = price_history.clone();
+                async move { price_history.run_flusher(flush_interval_ms, heartbeat).await }
+            });
+        }
 
-            // Find best prices
-            let best_jupiter_price = jupiter_quote.as_ref()
-                .map(|q| (q.out_amount as f64) / (q.in_amount as f64))
-                .unwrap_or(0.0);
-            
-            let best_direct_price = direct_dex_prices.iter()
-                .map(|p| p.price)
-                .fold(0.0, f64::max);
+        Ok(())
+    }
 
-            // Calculate profit opportunities
-            if best_jupiter_price > 0.0 && best_direct_price > 0.0 {
-                let profit_percentage = ((best_jupiter_price - best_direct_price) / best_direct_price) * 100.0;
-                
-                if profit_percentage >= min_profit_percentage {
-                    let estimated_profit = (best_jupiter_price - best_direct_price) * max_amount;
-                    let gas_cost = self.estimate_gas_cost().await?;
-                    
-                    if estimated_profit > gas_cost {
-                        let execution_method = if jupiter_quote.is_some() {
-                            ExecutionMethod::Jupiter
-                        } else {
-                            ExecutionMethod::DirectDex
-                        };
+    pub async fn get_kill_switch(&self) -> KillSwitchState {
+        *self.kill_switch.read().await
+    }
 
-                        let opportunity = EnhancedArbitrageOpportunity {
-                            id: Uuid::new_v4().to_string(),
-                            token_pair: token_pair.clone(),
-                            input_mint,
-                            output_mint,
-                            jupiter_quote,
-                            direct_dex_prices,
-                            best_jupiter_price,
-                            best_direct_price,
-                            profit_percentage,
-                            estimated_profit: estimated_profit - gas_cost,
-                            max_amount,
-                            gas_cost,
-                            timestamp: Utc::now().timestamp_millis(),
-                            slippage: self.config.jupiter.default_slippage_bps as f64 / 100.0,
-                            is_profitable: true,
-                            execution_method,
-                        };
+    // Why execution is blacked out, if it is
+    pub async fn get_blackout(&self) -> Option<String> {
+        self.blackout.read().await.active_reason().map(str::to_string)
+    }
 
-                        opportunities.push(opportunity);
-                    }
-                }
-            }
-        }
+    // Manual override for the gRPC control surface: Some(true) pauses
+    // execution, Some(false) trades through scheduled windows and RPC error
+    // pauses, None returns to the schedule
+    pub async fn set_blackout_override(&self, manual: Option<bool>) {
+        let mut blackout = self.blackout.write().await;
+        blackout.set_manual(manual);
+        blackout.refresh(self.clock.now_ms(), self.rpc_client.call_counts());
+    }
 
-        // Sort by profit percentage
-        opportunities.sort_by(|a, b| b.profit_percentage.partial_cmp(&a.profit_percentage).unwrap());
+    // Re-reads the sentinel file; called every scan cycle
+    async fn refresh_kill_switch(&self) {
+        let Some(path) = &self.config.kill_switch.sentinel_path else {
+            return;
+        };
+        let file_present = std::path::Path::new(path).exists();
 
-        info!("✅ Found {} enhanced arbitrage opportunities", opportunities.len());
-        Ok(opportunities)
+        let mut state = self.kill_switch.write().await;
+        if state.file_present == file_present {
+            return;
+        }
+        let was_engaged = state.engaged();
+        state.file_present = file_present;
+
+        if file_present && !was_engaged {
+            error!("🛑 KILL SWITCH ENGAGED: sentinel file {} present, all execution halted", path);
+        } else if !state.engaged() {
+            warn!("✅ Kill switch sentinel {} removed, execution re-enabled", path);
+        }
     }
 
-    pub async fn scan_opportunities(
-        &self,
-        min_profit_percentage: f64,
-        max_amount: f64,
-    ) -> Result<Vec<ArbitrageOpportunity>> {
-        debug!("🔍 Scanning for arbitrage opportunities");
-        
-        let prices = self.dex_monitor.get_all_prices().await?;
-        let mut opportunities = Vec::new();
+    async fn record_trade_outcome(&self, win: bool) {
+        let Some(alert) = self.win_rate_monitor.write().await.record(win) else {
+            return;
+        };
 
-        // Group prices by token pair
-        let mut price_groups: std::collections::HashMap<String, Vec<PriceData>> = 
-            std::collections::HashMap::new();
-        
-        for price in prices {
-            price_groups.entry(price.token_pair.clone()).or_default().push(price);
+        error!("🚨 Win rate over the last {} trades fell to {:.0}% (floor {:.0}%); parameters may be stale",
+               alert.window, alert.win_rate * 100.0, self.config.win_rate_alerts.min_win_rate * 100.0);
+        if self.config.win_rate_alerts.auto_pause {
+            self.kill_switch.write().await.signalled = true;
+            error!("🛑 KILL SWITCH ENGAGED by win rate alert; send SIGUSR2 to resume");
         }
+    }
 
-        // Find arbitrage opportunities
-        for (token_pair, prices) in price_groups {
-            if prices.len() < 2 {
-                continue;
+    pub async fn get_rolling_win_rate(&self) -> Option<f64> {
+        self.win_rate_monitor.read().await.win_rate()
+    }
+
+    #[cfg(unix)]
+    fn listen_for_kill_signal(&self) -> Result<()> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut signals = signal(SignalKind::user_defined2())?;
+        let kill_switch = self.kill_switch.clone();
+        tokio::spawn(async move {
+            while signals.recv().await.is_some() {
+                let mut state = kill_switch.write().await;
+                state.signalled = !state.signalled;
+                if state.signalled {
+                    error!("🛑 KILL SWITCH ENGAGED by SIGUSR2, all execution halted");
+                } else if state.engaged() {
+                    warn!("⚠️ SIGUSR2 released, but the sentinel file still holds the kill switch");
+                } else {
+                    warn!("✅ Kill switch released by SIGUSR2, execution re-enabled");
+                }
             }
+        });
+        Ok(())
+    }
 
-            // Sort by price to find best buy/sell opportunities
-            let mut sorted_prices = prices.clone();
-            sorted_prices.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
+    #[cfg(not(unix))]
+    fn listen_for_kill_signal(&self) -> Result<()> {
+        warn!("⚠️ SIGUSR2 kill switch is only available on unix; use the sentinel file");
+        Ok(())
+    }
 
-            let lowest_price = &sorted_prices[0];
-            let highest_price = &sorted_prices[sorted_prices.len() - 1];
+    pub async fn warm_up(&self) -> Result<()> {
+        let timeout_ms = self.config.trading.warmup_timeout_ms;
+        info!("🔥 Warming caches before trading (timeout {}ms)", timeout_ms);
 
-            let profit_percentage = ((highest_price.price - lowest_price.price) / lowest_price.price) * 100.0;
-            
-            if profit_percentage >= min_profit_percentage {
-                let estimated_profit = (highest_price.price - lowest_price.price) * max_amount;
-                let gas_cost = self.estimate_gas_cost().await?;
-                
-                if estimated_profit > gas_cost {
-                    let opportunity = ArbitrageOpportunity {
-                        id: Uuid::new_v4().to_string(),
-                        token_pair: token_pair.clone(),
-                        buy_dex: lowest_price.dex_name.clone(),
-                        sell_dex: highest_price.dex_name.clone(),
-                        buy_price: lowest_price.price,
-                        sell_price: highest_price.price,
-                        profit_percentage,
-                        estimated_profit: estimated_profit - gas_cost,
-                        max_amount,
-                        gas_cost,
-                        timestamp: Utc::now().timestamp_millis(),
-                        buy_pool: lowest_price.pool_address.clone(),
-                        sell_pool: highest_price.pool_address.clone(),
-                        slippage: 0.5, // Default slippage
-                        is_profitable: true,
-                    };
+        let cache = tokio::time::timeout(
+            std::time::Duration::from_millis(timeout_ms),
+            WarmCache::load(&self.config, self.jupiter_client.as_deref(), self.configured_mints()),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("Cache warm-up timed out after {}ms", timeout_ms))??;
 
-                    opportunities.push(opportunity);
-                }
-            }
-        }
+        *self.warm_cache.write().await = cache;
+        info!("✅ Cache warm-up complete");
+        Ok(())
+    }
 
-        // Sort by profit percentage
-        opportunities.sort_by(|a, b| b.profit_percentage.partial_cmp(&a.profit_percentage).unwrap());
+    // Seeds cached price baselines without the network warm-up
+    pub async fn prime_prices(&self, prices: HashMap<String, f64>) {
+        self.warm_cache.write().await.prices.extend(prices);
+    }
 
-        info!("✅ Found {} arbitrage opportunities", opportunities.len());
-        Ok(opportunities)
+    pub async fn is_warm(&self) -> bool {
+        self.warm_cache.read().await.ready
     }
 
-    pub async fn execute_trade(&self, request: TradeRequest) -> Result<TradeResponse> {
-        info!("💼 Executing trade for opportunity: {}", request.opportunity_id);
-        
-        // Risk check
-        let risk_manager = self.risk_manager.read().await;
-        if !risk_manager.can_execute_trade(&request).await? {
-            return Ok(TradeResponse {
-                transaction_id: "".to_string(),
-                success: false,
-                error_message: "Risk check failed".to_string(),
-                actual_profit: 0.0,
-                gas_used: 0.0,
-                execution_time: 0,
-                bundle_id: "".to_string(),
-            });
+    fn configured_mints(&self) -> Vec<String> {
+        let mut mints: HashSet<String> = HashSet::new();
+        for pair in self.config.pair_overrides.keys() {
+            if let Ok((input_mint, output_mint)) = self.extract_token_mints(pair) {
+                mints.insert(input_mint);
+                mints.insert(output_mint);
+            }
         }
-        drop(risk_manager);
+        mints.extend(self.config.multi_hop.base_mints.iter().cloned());
+        mints.extend(self.config.multi_hop.intermediate_mints.iter().cloned());
+        mints.extend(self.config.rebalancing.target_weights.keys().cloned());
+        mints.insert(self.config.rebalancing.base_mint.clone());
+        mints.into_iter().collect()
+    }
 
-        let start_time = std::time::Instant::now();
+    pub async fn get_pending_trades(&self) -> Vec<PendingTrade> {
+        self.trade_queue.pending().await
+    }
 
-        // Get opportunity details (in real implementation, this would be from a database)
-        let opportunity = self.get_opportunity_by_id(&request.opportunity_id).await?;
-        
-        // Build and execute transaction
-        let transaction_result = if request.use_jito && self.jito_client.is_some() {
-            self.execute_jito_trade(&request, &opportunity).await?
+    pub async fn get_committed_capital(&self) -> f64 {
+        self.trade_queue.committed_capital().await
+    }
+
+    // Compares what a confirmed swap actually delivered with its quote and
+    // records the shortfall; returns the realized slippage in bps
+    pub async fn record_realized_slippage(
+        &self,
+        token_pair: &str,
+        signature: &str,
+        quote: &JupiterQuote,
+        tolerated_bps: u16,
+    ) -> Result<f64> {
+        let received = received_amount(
+            &self.rpc_client,
+            signature,
+            &self.config.wallet.public_key,
+            &quote.output_mint,
+        ).await?;
+
+        let realized_bps = realized_slippage_bps(quote.out_amount, received);
+        if realized_bps > tolerated_bps as f64 {
+            warn!("📉 {} realized {:.1} bps slippage, above the {} bps tolerated", token_pair, realized_bps, tolerated_bps);
         } else {
-            self.execute_regular_trade(&request, &opportunity).await?
-        };
+            debug!("📉 {} realized {:.1} bps slippage ({} bps tolerated)", token_pair, realized_bps, tolerated_bps);
+        }
 
-        let execution_time = start_time.elapsed().as_millis() as i64;
+        self.slippage_tracker.write().await.record(token_pair, SlippageSample { realized_bps, tolerated_bps });
+        Ok(realized_bps)
+    }
+
+    // Feeds PerformanceMetrics::slippage_avg
+    pub async fn get_average_slippage_bps(&self) -> f64 {
+        self.slippage_tracker.read().await.average_bps()
+    }
+
+    pub async fn get_slippage_distributions(&self) -> HashMap<String, SlippageDistribution> {
+        self.slippage_tracker.read().await.distributions()
+    }
+
+    // Opportunity/trade events the message-bus sink dropped under backpressure
+    pub fn get_dropped_sink_events(&self) -> u64 {
+        self.event_sink.dropped_count()
+    }
+
+    pub async fn query_history(&self, query: &HistoryQuery) -> Result<HistoryPage> {
+        self.history.query(query).await
+    }
+
+    pub async fn get_task_restarts(&self) -> HashMap<String, u64> {
+        self.supervisor.get_restart_counts().await
+    }
+
+    pub async fn stop(&self) -> Result<()> {
+        let mut running = self.is_running.write().await;
+        *running = false;
+        info!("🛑 Stopping arbitrage engine");
+        Ok(())
+    }
+
+    pub async fn block_pool(&self, pool: &str) {
+        self.blocked_pools.write().await.insert(pool.to_string());
+        info!("🚫 Blocked pool {}", pool);
+    }
+
+    pub async fn unblock_pool(&self, pool: &str) -> bool {
+        let removed = self.blocked_pools.write().await.remove(pool);
+        if removed {
+            info!("✅ Unblocked pool {}", pool);
+        }
+        removed
+    }
+
+    pub async fn get_blocked_pools(&self) -> Vec<String> {
+        self.blocked_pools.read().await.iter().cloned().collect()
+    }
+
+    pub async fn watch_pair(&self, pair: WatchedPair) -> bool {
+        let mut watchlist = self.watchlist.write().await;
+        if watchlist.contains(&pair) {
+            return false;
+        }
+        info!("👀 Watching {} -> {}", pair.input_mint, pair.output_mint);
+        if self.config.canary.enabled {
+            self.canary_states.write().await.entry(pair.clone()).or_insert(CanaryStatus::Required);
+        }
+        watchlist.push(pair);
+        true
+    }
+
+    // For the opportunities stream: unexpired opportunities to replay to a
+    // (re)connecting client, then per-scan deltas (read with `recv_live`)
+    pub async fn subscribe_opportunities(
+        &self,
+    ) -> (Vec<ArbitrageOpportunity>, tokio::sync::broadcast::Receiver<OpportunityEvent>) {
+        self.opportunity_feed.subscribe(self.clock.now_ms()).await
+    }
+
+    // Unexpired opportunities from the latest scan
+    pub async fn get_live_opportunities(&self) -> Vec<ArbitrageOpportunity> {
+        self.opportunity_feed.live(self.clock.now_ms()).await
+    }
+
+    // One fresh poll of the price source, for the price stream
+    pub async fn get_current_prices(&self) -> Result<Vec<PriceData>> {
+        self.price_source.get_all_prices().await
+    }
+
+    pub async fn query_price_history(&self, token_pair: &str, from_ts: i64, to_ts: i64) -> Result<Vec<PriceSample>> {
+        match &self.price_history {
+            Some(price_history) => Ok(price_history.query(token_pair, from_ts, to_ts).await),
+            None => Err(anyhow::anyhow!("Price history is disabled")),
+        }
+    }
+
+    pub async fn get_canary_states(&self) -> HashMap<WatchedPair, CanaryStatus> {
+        self.canary_states.read().await.clone()
+    }
+
+    // Applies the mint safety policy to both mints of the pair. Returns the
+    // reason to reject, or None once the pair may proceed (possibly only
+    // after a canary, which the caller runs next)
+    async fn check_mint_safety(&self, opportunity: &ArbitrageOpportunity) -> Result<Option<String>> {
+        let settings = &self.config.mint_safety;
+        if settings.policy == MintRestrictionPolicy::Allow {
+            return Ok(None);
+        }
+
+        let (input_mint, output_mint) = self.trade_mints(opportunity)?;
+        for mint in [&input_mint, &output_mint] {
+            if settings.trusted_mints.contains(mint) {
+                continue;
+            }
+            let restrictions = self.mint_restrictions(mint).await?;
+            if !restrictions.is_restricted() {
+                continue;
+            }
+
+            match settings.policy {
+                MintRestrictionPolicy::Skip => {
+                    warn!("🧊 Skipping {}: mint {} is restricted ({:?})", opportunity.token_pair, mint, restrictions);
+                    return Ok(Some(format!("Mint {} can be frozen or is non-transferable", mint)));
+                }
+                MintRestrictionPolicy::Canary => {
+                    let pair = WatchedPair { input_mint: input_mint.clone(), output_mint: output_mint.clone() };
+                    self.canary_states.write().await.entry(pair).or_insert(CanaryStatus::Required);
+                }
+                MintRestrictionPolicy::Allow => {}
+            }
+        }
+        Ok(None)
+    }
+
+    // Cached per mint: freeze authorities and extensions don't change often
+    // enough to be worth re-reading every trade
+    async fn mint_restrictions(&self, mint: &str) -> Result<MintRestrictions> {
+        if let Some(restrictions) = self.mint_restrictions.read().await.get(mint) {
+            return Ok(restrictions.clone());
+        }
+        let restrictions = fetch_mint_restrictions(&self.rpc_client, mint).await?;
+        self.mint_restrictions.write().await.insert(mint.to_string(), restrictions.clone());
+        Ok(restrictions)
+    }
+
+    // Runs the canary for a pair that still needs one. Returns the reason the
+    // full-size trade must not go ahead, if any.
+    async fn check_canary(&self, request: &TradeRequest, opportunity: &ArbitrageOpportunity) -> Result<Option<String>> {
+        let (input_mint, output_mint) = self.trade_mints(opportunity)?;
+        let forward = WatchedPair { input_mint: input_mint.clone(), output_mint: output_mint.clone() };
+        let reverse = WatchedPair { input_mint: output_mint, output_mint: input_mint };
+
+        let (pair, status) = {
+            let states = self.canary_states.read().await;
+            match states.get(&forward).map(|s| (forward.clone(), s.clone()))
+                .or_else(|| states.get(&reverse).map(|s| (reverse.clone(), s.clone())))
+            {
+                Some(entry) => entry,
+                None => return Ok(None),
+            }
+        };
+
+        match status {
+            CanaryStatus::Passed => return Ok(None),
+            CanaryStatus::Failed(reason) => return Ok(Some(format!("Pair failed its canary trade: {}", reason))),
+            CanaryStatus::Required => {}
+        }
+
+        let canary = TradeRequest {
+            amount: self.config.canary.trade_amount.min(request.amount),
+            ..request.clone()
+        };
+        info!("🐤 Running canary trade of {} on {} before full size", canary.amount, opportunity.token_pair);
+
+        let failure = match self.submit(&canary, opportunity).await {
+            Ok(response) if response.success => None,
+            Ok(response) => Some(response.error_message),
+            Err(e) => Some(e.to_string()),
+        };
+
+        let mut states = self.canary_states.write().await;
+        match failure {
+            None => {
+                info!("🐤 Canary on {} landed, full-size trading enabled", opportunity.token_pair);
+                states.insert(pair, CanaryStatus::Passed);
+                Ok(None)
+            }
+            Some(reason) => {
+                error!("🐤 Canary on {} failed, blacklisting pair: {}", opportunity.token_pair, reason);
+                states.insert(pair, CanaryStatus::Failed(reason.clone()));
+                Ok(Some(format!("Canary trade failed: {}", reason)))
+            }
+        }
+    }
+
+    pub async fn unwatch_pair(&self, pair: &WatchedPair) -> bool {
+        let mut watchlist = self.watchlist.write().await;
+        let len_before = watchlist.len();
+        watchlist.retain(|p| p != pair);
+        let removed = watchlist.len() != len_before;
+        if removed {
+            info!("🙈 Stopped watching {} -> {}", pair.input_mint, pair.output_mint);
+        }
+        removed
+    }
+
+    pub async fn pause_pair(&self, token_pair: &str, duration_secs: u64) {
+        let until = self.clock.now_ms() + (duration_secs * 1000) as i64;
+        self.paused_pairs.write().await.insert(token_pair.to_string(), until);
+        warn!("⏸️ Trading paused on {} for {}s", token_pair, duration_secs);
+    }
+
+    pub async fn resume_pair(&self, token_pair: &str) -> bool {
+        let resumed = self.paused_pairs.write().await.remove(token_pair).is_some();
+        if resumed {
+            info!("▶️ Trading resumed on {}", token_pair);
+        }
+        resumed
+    }
+
+    pub async fn get_paused_pairs(&self) -> HashMap<String, i64> {
+        let now = self.clock.now_ms();
+        self.paused_pairs.read().await.iter()
+            .filter(|(_, until)| **until > now)
+            .map(|(pair, until)| (pair.clone(), *until))
+            .collect()
+    }
+
+    async fn is_pair_paused(&self, token_pair: &str) -> bool {
+        self.paused_pairs.read().await
+            .get(token_pair)
+            .is_some_and(|until| *until > self.clock.now_ms())
+    }
+
+    // Feeds every pool's price impact into the spike detector, alerting (and
+    // pausing the pair when configured) on spikes, then drops paused pairs
+    async fn screen_price_impact(&self, price_groups: &mut HashMap<String, Vec<PriceData>>) {
+        if self.config.impact_alerts.enabled {
+            let mut spiked_pairs = Vec::new();
+            {
+                let mut tracker = self.impact_tracker.write().await;
+                for (token_pair, prices) in price_groups.iter() {
+                    for price in prices {
+                        if let Some(spike) = tracker.observe(&price.pool_address, price.price_impact) {
+                            error!("🚨 Price impact spike on {} ({} pool {}): {:.3}% vs baseline {:.3}%",
+                                   token_pair, price.dex_name, price.pool_address, spike.observed, spike.baseline);
+                            spiked_pairs.push(token_pair.clone());
+                        }
+                    }
+                }
+            }
+
+            if self.config.impact_alerts.auto_pause {
+                for token_pair in spiked_pairs {
+                    self.pause_pair(&token_pair, self.config.impact_alerts.pause_secs).await;
+                }
+            }
+        }
+
+        let mut paused = Vec::new();
+        for token_pair in price_groups.keys() {
+            if self.is_pair_paused(token_pair).await {
+                paused.push(token_pair.clone());
+            }
+        }
+        for token_pair in paused {
+            price_groups.remove(&token_pair);
+            self.record_rejection(&token_pair, RejectionReason::CircuitOpen).await;
+        }
+    }
+
+    // Keeps only prices the pair's DEXes agree on, and drops pairs without
+    // `min_sources` independent DEXes behind that price: a single feed is
+    // too easy to manipulate.
+    async fn screen_price_quorum(&self, price_groups: &mut HashMap<String, Vec<PriceData>>) {
+        let quorum = &self.config.price_quorum;
+        let mut rejected = Vec::new();
+        for (token_pair, prices) in price_groups.iter_mut() {
+            let agreeing = agreeing_prices(prices, quorum.agreement_tolerance_pct);
+            let sources = independent_sources(&agreeing);
+            if sources < quorum.min_sources {
+                debug!("🗳️ Skipping {}: {} of {} DEX sources agree on its price, {} required",
+                       token_pair, sources, independent_sources(prices), quorum.min_sources);
+                rejected.push(token_pair.clone());
+            } else {
+                *prices = agreeing;
+            }
+        }
+        for token_pair in rejected {
+            price_groups.remove(&token_pair);
+            self.record_rejection(&token_pair, RejectionReason::InsufficientSources).await;
+        }
+    }
+
+    pub async fn get_scan_scope(&self) -> ScanScope {
+        let watchlist = self.watchlist.read().await.clone();
+        if !watchlist.is_empty() {
+            ScanScope::Watchlist(watchlist)
+        } else if self.config.scan_scope.auto_discover {
+            ScanScope::TopVolume(self.config.scan_scope.auto_discover_limit)
+        } else {
+            ScanScope::All
+        }
+    }
+
+    async fn restrict_to_scan_scope(&self, price_groups: &mut HashMap<String, Vec<PriceData>>) {
+        match self.get_scan_scope().await {
+            ScanScope::All => {}
+            ScanScope::Watchlist(watchlist) => {
+                price_groups.retain(|token_pair, _| match self.extract_token_mints(token_pair) {
+                    Ok((input_mint, output_mint)) => watchlist.contains(&WatchedPair { input_mint, output_mint }),
+                    Err(_) => false,
+                });
+            }
+            ScanScope::TopVolume(limit) => {
+                let mut volumes: Vec<(String, f64)> = price_groups.iter()
+                    .map(|(token_pair, prices)| (token_pair.clone(), prices.iter().map(|p| p.volume_24h).sum()))
+                    .collect();
+                volumes.sort_by(|a, b| b.1.total_cmp(&a.1));
+                let top_pairs: HashSet<String> = volumes.into_iter().take(limit).map(|(pair, _)| pair).collect();
+                price_groups.retain(|token_pair, _| top_pairs.contains(token_pair));
+            }
+        }
+
+        // Under executor backpressure, only the highest-EV pairs are worth quoting
+        if let Some(retained) = self.adaptive_scope.read().await.retained_pairs(price_groups.keys()) {
+            price_groups.retain(|token_pair, _| retained.contains(token_pair));
+        }
+    }
+
+    pub async fn get_queue_depth(&self) -> usize {
+        self.adaptive_scope.read().await.queue_depth()
+    }
+
+    // Pairs the scan is narrowed to under backpressure; None when unrestricted
+    pub async fn get_adaptive_scan_limit(&self) -> Option<usize> {
+        self.adaptive_scope.read().await.limit()
+    }
+
+    // Consecutive scans without a Jupiter route, for pairs currently missing one
+    pub async fn get_no_route_counts(&self) -> HashMap<String, u32> {
+        self.route_availability.read().await.no_route_counts()
+    }
+
+    // Latest dynamic Jito tip in lamports; None until one has been fetched or
+    // when tips are flat
+    pub async fn get_tip_estimate(&self) -> Option<u64> {
+        match &self.tip_estimator {
+            Some(tip_estimator) => tip_estimator.latest().await,
+            None => None,
+        }
+    }
+
+    pub async fn get_trading_stats(&self) -> Result<TradingStats> {
+        self.stats.snapshot().await
+    }
+
+    pub async fn get_rejection_counts(&self) -> HashMap<RejectionReason, u64> {
+        self.rejection_counts.read().await.clone()
+    }
+
+    // Realized profit of executed trades, by the strategy that found them
+    pub async fn get_strategy_pnl(&self) -> HashMap<StrategyKind, f64> {
+        self.strategy_pnl.read().await.clone()
+    }
+
+    async fn record_rejection(&self, token_pair: &str, reason: RejectionReason) {
+        debug!("⛔ {} rejected: {}", token_pair, reason);
+        *self.rejection_counts.write().await.entry(reason).or_default() += 1;
+        self.monitoring.record_rejection(reason);
+    }
+
+    // A found opportunity turned down at execution: counted like a scan
+    // rejection, and the live feed shows why it was not taken
+    async fn reject_opportunity(&self, opportunity: &ArbitrageOpportunity, reason: RejectionReason) {
+        self.record_rejection(&opportunity.token_pair, reason).await;
+        self.opportunity_feed.mark_rejected(&opportunity.id, reason).await;
+    }
+
+    pub async fn scan_enhanced_opportunities(
+        &self,
+        min_profit_percentage: f64,
+        max_amount: f64,
+    ) -> Result<Vec<EnhancedArbitrageOpportunity>> {
+        if !self.config.strategies.is_enabled(StrategyKind::JupiterRoute) {
+            return Ok(Vec::new());
+        }
+        debug!("🔍 Scanning for enhanced arbitrage opportunities with Jupiter");
+        
+        let mut opportunities = Vec::new();
+        
+        // Get direct DEX prices
+        let dex_prices = self.fetch_prices().await?;
+        
+        // Group prices by token pair
+        let mut price_groups: std::collections::HashMap<String, Vec<PriceData>> = 
+            std::collections::HashMap::new();
+        
+        let blocked_pools = self.blocked_pools.read().await.clone();
+        for price in dex_prices {
+            if blocked_pools.contains(&price.pool_address) {
+                continue;
+            }
+            price_groups.entry(price.token_pair.clone()).or_default().push(price);
+        }
+        self.restrict_to_scan_scope(&mut price_groups).await;
+        {
+            let mut route_availability = self.route_availability.write().await;
+            price_groups.retain(|token_pair, _| route_availability.should_scan(token_pair));
+        }
+        self.screen_price_impact(&mut price_groups).await;
+        self.screen_price_quorum(&mut price_groups).await;
+        let scanned_pairs: HashSet<String> = price_groups.keys().cloned().collect();
+
+        let price_confidence = self.fetch_price_confidence(&price_groups).await;
+
+        // Process each token pair
+        for (token_pair, prices) in price_groups {
+            if prices.len() < 2 {
+                continue;
+            }
+
+            // Extract token mints (simplified - in real implementation, you'd have a mapping)
+            let (input_mint, output_mint) = self.extract_token_mints(&token_pair)?;
+            let mut pair_settings = self.resolve_pair_settings(&token_pair, min_profit_percentage, max_amount);
+            if self.is_off_peg(&token_pair, &prices) {
+                self.record_rejection(&token_pair, RejectionReason::DepegRisk).await;
+                continue;
+            }
+
+            // A pair is only as reliable as the less confident of its two prices
+            let pair_confidence = match (price_confidence.get(&input_mint), price_confidence.get(&output_mint)) {
+                (Some(input), Some(output)) => Some(*input.min(output)),
+                _ if price_confidence.is_empty() => None,
+                _ => Some(PriceConfidence::Low),
+            };
+            if let Some(confidence) = pair_confidence {
+                if confidence < self.config.risk_settings.min_price_confidence {
+                    self.record_rejection(&token_pair, RejectionReason::LowPriceConfidence).await;
+                    continue;
+                }
+                pair_settings.min_profit_threshold *=
+                    self.config.risk_settings.confidence_profit_multipliers.for_confidence(confidence);
+            }
+            
+            // Size the quote in raw units, converting from USD when configured
+            let quote_amount = match self.config.trading.trade_size_usd {
+                Some(usd) if self.jupiter_client.is_some() => {
+                    match self.usd_to_raw(&input_mint, usd).await {
+                        Ok(amount) => amount,
+                        Err(e) => {
+                            warn!("⚠️ Skipping {}: cannot size ${:.2} trade: {}", token_pair, usd, e);
+                            continue;
+                        }
+                    }
+                }
+                _ => pair_settings.max_amount as u64,
+            };
+            let quote_amount = match self.quantize_trade_size(&input_mint, quote_amount).await {
+                Ok(Some(amount)) => amount,
+                Ok(None) => {
+                    self.record_rejection(&token_pair, RejectionReason::BelowMinNotional).await;
+                    continue;
+                }
+                Err(e) => {
+                    warn!("⚠️ Skipping {}: cannot quantize trade size: {}", token_pair, e);
+                    continue;
+                }
+            };
+
+            // Get Jupiter quote if enabled, shrinking it if the full size moves the price too far
+            let mut size_fraction = 1.0;
+            let jupiter_quote = if self.config.jupiter.enabled && self.jupiter_client.is_some() {
+                match self.quote_within_impact(&input_mint, &output_mint, quote_amount, &pair_settings).await {
+                    Ok((quote, fraction)) => {
+                        self.route_availability.write().await.record_route(&token_pair);
+                        match Self::find_blocked_pool(&quote, &blocked_pools) {
+                            Some(pool) => {
+                                warn!("🚫 Rejecting Jupiter route for {}: touches blocked pool {}", token_pair, pool);
+                                self.record_rejection(&token_pair, RejectionReason::Blacklisted).await;
+                                None
+                            }
+                            None if quote.price_impact_pct > pair_settings.max_price_impact_pct => {
+                                warn!("⚠️ Rejecting Jupiter route for {}: price impact {:.2}% exceeds {:.2}%",
+                                      token_pair, quote.price_impact_pct, pair_settings.max_price_impact_pct);
+                                self.record_rejection(&token_pair, RejectionReason::PriceImpactTooHigh).await;
+                                None
+                            }
+                            None => match self.cross_check_quote(&quote).await {
+                                Ok(()) => {
+                                    size_fraction = fraction;
+                                    Some(quote)
+                                }
+                                Err(e) => {
+                                    warn!("⚖️ Rejecting Jupiter route for {}: {}", token_pair, e);
+                                    self.record_rejection(&token_pair, RejectionReason::QuoteDivergence).await;
+                                    None
+                                }
+                            },
+                        }
+                    }
+                    Err(e) if is_no_route_error(&e) => {
+                        self.route_availability.write().await.record_no_route(&token_pair);
+                        None
+                    }
+                    Err(e) => {
+                        warn!("⚠️ Failed to get Jupiter quote for {}: {}", token_pair, e);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+
+            // Convert DEX prices to DexPrice format
+            let direct_dex_prices: Vec<DexPrice> = prices.iter().map(|p| DexPrice {
+                dex_name: p.dex_name.clone(),
+                price: p.price,
+                liquidity: p.liquidity,
+                pool_address: p.pool_address.clone(),
+                price_impact: p.price_impact,
+            }).collect();
+
+            // Find best prices
+            let best_jupiter_price = jupiter_quote.as_ref()
+                .map(|q| (q.out_amount as f64) / (q.in_amount as f64))
+                .unwrap_or(0.0);
+            
+            // Jupiter quotes are net of fees; direct prices must be made so to compare fairly
+            let best_direct_price = direct_dex_prices.iter()
+                .map(|p| self.dex_fees.net_price(&p.dex_name, p.price))
+                .fold(0.0, f64::max);
+
+            // Calculate profit opportunities
+            if best_jupiter_price > 0.0 && best_direct_price > 0.0 {
+                let profit_percentage = ((best_jupiter_price - best_direct_price) / best_direct_price) * 100.0;
+                
+                if self.passes_profit_threshold(StrategyKind::JupiterRoute, &token_pair, profit_percentage, pair_settings.min_profit_threshold).await {
+                    // The size actually quoted: USD-sized, quantized and shrunk for impact
+                    let sized_amount = jupiter_quote.as_ref()
+                        .map(|quote| quote.in_amount)
+                        .unwrap_or((quote_amount as f64 * size_fraction) as u64);
+                    let trade_amount = match self.token_decimals(&input_mint).await
+                        .and_then(|decimals| to_ui_amount(sized_amount, decimals))
+                    {
+                        Ok(amount) => amount,
+                        Err(e) => {
+                            warn!("⚠️ Skipping {}: cannot size profit: {}", token_pair, e);
+                            continue;
+                        }
+                    };
+                    let output_profit = (best_jupiter_price - best_direct_price) * trade_amount;
+                    let estimated_profit = match self.denominate(output_profit, &output_mint).await {
+                        Ok(profit) => profit,
+                        Err(e) => {
+                            warn!("⚠️ Skipping {}: cannot denominate profit: {}", token_pair, e);
+                            continue;
+                        }
+                    };
+                    let gas_cost = match self.estimate_gas_cost(&[input_mint.clone(), output_mint.clone()]).await {
+                        Ok(gas_cost) => gas_cost,
+                        Err(e) => {
+                            warn!("⚠️ Skipping {}: cannot estimate gas: {}", token_pair, e);
+                            continue;
+                        }
+                    };
+                    
+                    if estimated_profit > gas_cost {
+                        let notional = match self.denominate(trade_amount, &input_mint).await {
+                            Ok(notional) => notional,
+                            Err(e) => {
+                                warn!("⚠️ Skipping {}: cannot denominate trade size: {}", token_pair, e);
+                                continue;
+                            }
+                        };
+                        let tip_cost = match self.tip_cost().await {
+                            Ok(tip_cost) => tip_cost,
+                            Err(e) => {
+                                warn!("⚠️ Skipping {}: cannot denominate the Jito tip: {}", token_pair, e);
+                                continue;
+                            }
+                        };
+                        // Both prices are already net of swap and platform fees; impact is what's left
+                        let percentage_costs = jupiter_quote.as_ref().map_or(0.0, |quote| quote.price_impact_pct)
+                            + direct_dex_prices.iter()
+                                .find(|p| self.dex_fees.net_price(&p.dex_name, p.price) == best_direct_price)
+                                .map_or(0.0, |p| p.price_impact);
+                        let edge = edge_over_fees_pct(profit_percentage, percentage_costs, gas_cost + tip_cost, notional);
+                        if edge < self.config.trading.min_edge_over_fees_pct {
+                            debug!("⛔ {} spreads {:.3}% but nets only {:.3}% over fees", token_pair, profit_percentage, edge);
+                            self.record_rejection(&token_pair, RejectionReason::BelowMinEdge).await;
+                            continue;
+                        }
+
+                        let execution_method = if jupiter_quote.is_some() {
+                            ExecutionMethod::Jupiter
+                        } else {
+                            ExecutionMethod::DirectDex
+                        };
+
+                        let mut opportunity = EnhancedArbitrageOpportunity {
+                            id: String::new(),
+                            token_pair: token_pair.clone(),
+                            input_mint,
+                            output_mint,
+                            jupiter_quote,
+                            direct_dex_prices,
+                            best_jupiter_price,
+                            best_direct_price,
+                            profit_percentage,
+                            estimated_profit: estimated_profit - gas_cost,
+                            max_amount: trade_amount,
+                            gas_cost,
+                            timestamp: self.clock.now_ms(),
+                            slippage: pair_settings.slippage_bps as f64 / 100.0,
+                            is_profitable: true,
+                            execution_method,
+                            price_confidence: pair_confidence,
+                            edge_over_fees_pct: edge,
+                        };
+                        opportunity.id = opportunity.compute_id();
+
+                        opportunities.push(opportunity);
+                    } else {
+                        self.record_rejection(&token_pair, RejectionReason::BelowGasCost).await;
+                    }
+                } else {
+                    self.record_rejection(&token_pair, RejectionReason::BelowMinProfit).await;
+                }
+            }
+        }
+
+        self.adaptive_scope.write().await.record_scan(
+            &scanned_pairs,
+            opportunities.iter().map(|o| (o.token_pair.as_str(), o.estimated_profit)),
+        );
+
+        // Sort by profit percentage
+        opportunities.sort_by(|a, b| b.profit_percentage.partial_cmp(&a.profit_percentage).unwrap());
+
+        info!("✅ Found {} enhanced arbitrage opportunities", opportunities.len());
+        Ok(opportunities)
+    }
+
+    // Profit threshold with hysteresis, so a pair hovering at the threshold
+    // isn't alternately pursued and dropped on every scan
+    async fn passes_profit_threshold(
+        &self,
+        strategy: StrategyKind,
+        token_pair: &str,
+        profit_percentage: f64,
+        min_profit: f64,
+    ) -> bool {
+        let trading = &self.config.trading;
+        let mut pursued_pairs = self.pursued_pairs.write().await;
+        let key = (strategy, token_pair.to_string());
+        let was_pursued = pursued_pairs.contains(&key);
+        let pursue = hysteresis_decision(
+            was_pursued,
+            profit_percentage,
+            min_profit,
+            trading.hysteresis_enter_margin,
+            trading.hysteresis_exit_margin,
+        );
+
+        if pursue && !was_pursued {
+            debug!("📈 {} entered the profit band at {:.3}%", token_pair, profit_percentage);
+            pursued_pairs.insert(key);
+        } else if !pursue && was_pursued {
+            debug!("📉 {} left the profit band at {:.3}%", token_pair, profit_percentage);
+            pursued_pairs.remove(&key);
+        }
+        pursue
+    }
+
+    // Pair settings with stable-pair defaults applied; explicit pair overrides still win
+    fn resolve_pair_settings(&self, token_pair: &str, min_profit_threshold: f64, max_amount: f64) -> PairSettings {
+        let mut settings = self.config.pair_settings(token_pair, min_profit_threshold, max_amount);
+        if !self.is_stable_pair(token_pair) {
+            return settings;
+        }
+
+        let pair_override = self.config.pair_overrides.get(token_pair);
+        if pair_override.and_then(|o| o.slippage_bps).is_none() {
+            settings.slippage_bps = self.config.stable_pairs.slippage_bps;
+        }
+        if pair_override.and_then(|o| o.min_profit_threshold).is_none() {
+            settings.min_profit_threshold = self.config.stable_pairs.min_profit_threshold;
+        }
+        settings
+    }
+
+    fn is_stable_pair(&self, token_pair: &str) -> bool {
+        self.extract_token_mints(token_pair)
+            .is_ok_and(|(input_mint, output_mint)| self.config.is_stable_pair(&input_mint, &output_mint))
+    }
+
+    fn is_off_peg(&self, token_pair: &str, prices: &[PriceData]) -> bool {
+        if !self.is_stable_pair(token_pair) {
+            return false;
+        }
+
+        let max_deviation = self.config.stable_pairs.max_peg_deviation_pct;
+        match prices.iter().find(|p| (p.price - 1.0).abs() * 100.0 > max_deviation) {
+            Some(price) => {
+                warn!("🪙 {} on {} is priced {:.4}, more than {:.2}% off peg",
+                      token_pair, price.dex_name, price.price, max_deviation);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Price API v2 confidence for every mint in the scanned pairs. Empty when
+    // Jupiter is unavailable, in which case confidence gating is skipped.
+    async fn fetch_price_confidence(
+        &self,
+        price_groups: &HashMap<String, Vec<PriceData>>,
+    ) -> HashMap<String, PriceConfidence> {
+        let Some(jupiter_client) = &self.jupiter_client else {
+            return HashMap::new();
+        };
+
+        let mints: HashSet<String> = price_groups.keys()
+            .filter_map(|token_pair| self.extract_token_mints(token_pair).ok())
+            .flat_map(|(input_mint, output_mint)| [input_mint, output_mint])
+            .collect();
+        if mints.is_empty() {
+            return HashMap::new();
+        }
+
+        match jupiter_client.get_price_v2(&mints.into_iter().collect::<Vec<_>>()).await {
+            Ok(prices) => prices.into_iter().map(|(mint, price)| (mint, price.confidence)).collect(),
+            Err(e) => {
+                warn!("⚠️ Price confidence unavailable, skipping confidence gate: {}", e);
+                HashMap::new()
+            }
+        }
+    }
+
+    // Prices from the price source, with future-dated ones clamped or dropped
+    // per [clock] so they can't pass staleness and scoring checks as fresh
+    async fn fetch_prices(&self) -> Result<Vec<PriceData>> {
+        let now = self.clock.now_ms();
+        let prices = self.price_source.get_all_prices().await?;
+        Ok(prices.into_iter()
+            .filter_map(|mut price| {
+                let source = || format!("{} price from {}", price.token_pair, price.dex_name);
+                price.timestamp = check_timestamp(price.timestamp, now, &self.config.clock, source)?;
+                Some(price)
+            })
+            .collect())
+    }
+
+    pub async fn scan_opportunities(
+        &self,
+        min_profit_percentage: f64,
+        max_amount: f64,
+    ) -> Result<Vec<ArbitrageOpportunity>> {
+        debug!("🔍 Scanning for arbitrage opportunities");
+        
+        let prices = self.fetch_prices().await?;
+        if let Some(price_history) = &self.price_history {
+            price_history.record(&prices, self.clock.now_ms()).await;
+        }
+        // Prices are still sampled above for history and analytics
+        if !self.config.strategies.is_enabled(StrategyKind::Direct) {
+            return Ok(Vec::new());
+        }
+        let mut opportunities = Vec::new();
+
+        // Group prices by token pair
+        let mut price_groups: std::collections::HashMap<String, Vec<PriceData>> = 
+            std::collections::HashMap::new();
+        
+        let blocked_pools = self.blocked_pools.read().await.clone();
+        for price in prices {
+            if blocked_pools.contains(&price.pool_address) {
+                continue;
+            }
+            price_groups.entry(price.token_pair.clone()).or_default().push(price);
+        }
+        self.restrict_to_scan_scope(&mut price_groups).await;
+        self.screen_price_impact(&mut price_groups).await;
+        self.screen_price_quorum(&mut price_groups).await;
+        let scanned_pairs: HashSet<String> = price_groups.keys().cloned().collect();
+
+        // Find arbitrage opportunities
+        for (token_pair, prices) in price_groups {
+            if prices.len() < 2 {
+                continue;
+            }
+
+            if self.is_off_peg(&token_pair, &prices) {
+                self.record_rejection(&token_pair, RejectionReason::DepegRisk).await;
+                continue;
+            }
+
+            // Sort by price to find best buy/sell opportunities
+            let mut sorted_prices = prices.clone();
+            sorted_prices.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
+
+            let lowest_price = &sorted_prices[0];
+            let highest_price = &sorted_prices[sorted_prices.len() - 1];
+
+            let profit_percentage = ((highest_price.price - lowest_price.price) / lowest_price.price) * 100.0;
+            let pair_settings = self.resolve_pair_settings(&token_pair, min_profit_percentage, max_amount);
+            
+            if self.passes_profit_threshold(StrategyKind::Direct, &token_pair, profit_percentage, pair_settings.min_profit_threshold).await {
+                // Prices are quoted in the pair's quote token
+                let quote_profit = (highest_price.price - lowest_price.price) * pair_settings.max_amount;
+                let quote_mint = match token_pair.split('/').nth(1).map(Self::mint_for_symbol) {
+                    Some(Ok(mint)) => mint,
+                    _ => {
+                        warn!("⚠️ Skipping {}: unknown quote token", token_pair);
+                        continue;
+                    }
+                };
+                let estimated_profit = match self.denominate(quote_profit, &quote_mint).await {
+                    Ok(profit) => profit,
+                    Err(e) => {
+                        warn!("⚠️ Skipping {}: cannot denominate profit: {}", token_pair, e);
+                        continue;
+                    }
+                };
+                let mints = match self.extract_token_mints(&token_pair) {
+                    Ok((base_mint, quote_mint)) => vec![base_mint, quote_mint],
+                    Err(e) => {
+                        warn!("⚠️ Skipping {}: cannot cost gas without its mints: {}", token_pair, e);
+                        continue;
+                    }
+                };
+                let gas_cost = match self.estimate_gas_cost(&mints).await {
+                    Ok(gas_cost) => gas_cost,
+                    Err(e) => {
+                        warn!("⚠️ Skipping {}: cannot estimate gas: {}", token_pair, e);
+                        continue;
+                    }
+                };
+                
+                if estimated_profit > gas_cost {
+                    let notional = match self.denominate(lowest_price.price * pair_settings.max_amount, &quote_mint).await {
+                        Ok(notional) => notional,
+                        Err(e) => {
+                            warn!("⚠️ Skipping {}: cannot denominate trade size: {}", token_pair, e);
+                            continue;
+                        }
+                    };
+                    let tip_cost = match self.tip_cost().await {
+                        Ok(tip_cost) => tip_cost,
+                        Err(e) => {
+                            warn!("⚠️ Skipping {}: cannot denominate the Jito tip: {}", token_pair, e);
+                            continue;
+                        }
+                    };
+                    // Direct DEX legs pay no Jupiter platform fee
+                    let percentage_costs = lowest_price.price_impact + highest_price.price_impact
+                        + self.dex_fees.fee_percentage(&lowest_price.dex_name)
+                        + self.dex_fees.fee_percentage(&highest_price.dex_name);
+                    let edge = edge_over_fees_pct(profit_percentage, percentage_costs, gas_cost + tip_cost, notional);
+                    if edge < self.config.trading.min_edge_over_fees_pct {
+                        debug!("⛔ {} spreads {:.3}% but nets only {:.3}% over fees", token_pair, profit_percentage, edge);
+                        self.record_rejection(&token_pair, RejectionReason::BelowMinEdge).await;
+                        continue;
+                    }
+
+                    let mut opportunity = ArbitrageOpportunity {
+                        id: String::new(),
+                        token_pair: token_pair.clone(),
+                        buy_dex: lowest_price.dex_name.clone(),
+                        sell_dex: highest_price.dex_name.clone(),
+                        buy_price: lowest_price.price,
+                        sell_price: highest_price.price,
+                        profit_percentage,
+                        estimated_profit: estimated_profit - gas_cost,
+                        max_amount: pair_settings.max_amount,
+                        gas_cost,
+                        timestamp: self.clock.now_ms(),
+                        buy_pool: lowest_price.pool_address.clone(),
+                        sell_pool: highest_price.pool_address.clone(),
+                        slippage: pair_settings.slippage_bps as f64 / 100.0,
+                        is_profitable: true,
+                        rejection_reason: None,
+                        price_impact: lowest_price.price_impact + highest_price.price_impact,
+                        price_timestamp: lowest_price.timestamp.min(highest_price.timestamp),
+                        expires_at: 0,
+                        strategy: StrategyKind::Direct,
+                        edge_over_fees_pct: edge,
+                    };
+                    opportunity.expires_at = opportunity.timestamp + self.config.trading.opportunity_ttl_ms as i64;
+                    opportunity.id = opportunity.compute_id();
+
+                    opportunities.push(opportunity);
+                } else {
+                    self.record_rejection(&token_pair, RejectionReason::BelowGasCost).await;
+                }
+            } else {
+                self.record_rejection(&token_pair, RejectionReason::BelowMinProfit).await;
+            }
+        }
+
+        self.adaptive_scope.write().await.record_scan(
+            &scanned_pairs,
+            opportunities.iter().map(|o| (o.token_pair.as_str(), o.estimated_profit)),
+        );
+        rank_opportunities(&mut opportunities, &self.config.scoring_weights, self.clock.now_ms());
+
+        for opportunity in &opportunities {
+            if let Err(e) = self.history.record_opportunity(opportunity).await {
+                warn!("⚠️ Failed to record opportunity {} in history: {}", opportunity.id, e);
+            }
+            self.event_sink.publish_opportunity(opportunity);
+        }
+        self.opportunity_feed.publish(&opportunities, self.clock.now_ms()).await;
+
+        info!("✅ Found {} arbitrage opportunities", opportunities.len());
+        Ok(opportunities)
+    }
+
+    pub async fn scan_cycle_opportunities(&self) -> Result<Vec<CycleArbitrageOpportunity>> {
+        if !self.config.strategies.is_enabled(StrategyKind::MultiHop) {
+            return Ok(Vec::new());
+        }
+        let settings = &self.config.multi_hop;
+        debug!("🔄 Scanning base -> X -> base cycles via Jupiter");
+
+        // Each (base, intermediate) cycle is quoted once, even if listed twice
+        let mut cycles = Vec::new();
+        let mut seen = HashSet::new();
+        for base in &settings.base_mints {
+            for intermediate in &settings.intermediate_mints {
+                if base != intermediate && seen.insert((base.clone(), intermediate.clone())) {
+                    cycles.push((base.clone(), intermediate.clone()));
+                }
+            }
+        }
+
+        let quotes = join_all(cycles.iter().map(|(base, intermediate)| {
+            self.quote_round_trip(base, intermediate, settings.amount)
+        })).await;
+
+        let mut opportunities = Vec::new();
+        for ((base, intermediate), result) in cycles.into_iter().zip(quotes) {
+            let (first_leg, second_leg) = match result {
+                Ok(legs) => legs,
+                Err(e) => {
+                    warn!("⚠️ Failed to quote cycle {} -> {}: {}", base, intermediate, e);
+                    continue;
+                }
+            };
+
+            let mut profit_percentage =
+                (second_leg.out_amount as f64 / settings.amount as f64 - 1.0) * 100.0;
+
+            // The second leg trades against pools the first leg has just moved
+            let (shared_pools, self_impact_pct) = route_overlap(&first_leg, &second_leg);
+            if !shared_pools.is_empty() {
+                match settings.route_overlap {
+                    RouteOverlapPolicy::Reject => {
+                        debug!("🔁 Rejecting cycle {} -> {}: legs share {:?}", base, intermediate, shared_pools);
+                        self.record_rejection(&format!("{}/{}", base, intermediate), RejectionReason::SharedPool).await;
+                        continue;
+                    }
+                    RouteOverlapPolicy::Adjust => {
+                        debug!("🔁 Cycle {} -> {} shares {:?}, discounting {:.4}% self-impact",
+                               base, intermediate, shared_pools, self_impact_pct);
+                        profit_percentage -= self_impact_pct;
+                    }
+                }
+            }
+
+            // Jupiter's out amounts are already net of swap fees; what is left
+            // is the transaction itself, rent for new token accounts included
+            let (gas_cost, value_in) = match self.cycle_costs(&base, &intermediate, settings.amount).await {
+                Ok(costs) => costs,
+                Err(e) => {
+                    warn!("⚠️ Cannot price gas for cycle {} -> {}: {}", base, intermediate, e);
+                    continue;
+                }
+            };
+            profit_percentage -= gas_cost / value_in * 100.0;
+
+            if profit_percentage < settings.min_profit_percentage {
+                continue;
+            }
+
+            let mut opportunity = CycleArbitrageOpportunity {
+                id: String::new(),
+                base_mint: base,
+                intermediate_mint: intermediate,
+                amount_in: settings.amount,
+                amount_out: second_leg.out_amount,
+                profit_percentage,
+                first_leg,
+                second_leg,
+                timestamp: self.clock.now_ms(),
+                shared_pools,
+                gas_cost,
+                estimated_profit: value_in * profit_percentage / 100.0,
+            };
+            opportunity.id = opportunity.compute_id();
+            opportunities.push(opportunity);
+        }
+
+        opportunities.sort_by(|a, b| b.profit_percentage.total_cmp(&a.profit_percentage));
+
+        let mut executable = HashMap::new();
+        for cycle in &opportunities {
+            match self.cycle_opportunity(cycle).await {
+                Ok(opportunity) => {
+                    executable.insert(cycle.id.clone(), opportunity);
+                }
+                Err(e) => warn!("⚠️ Cycle {} cannot be executed: {}", cycle.id, e),
+            }
+        }
+        *self.cycle_opportunities.write().await = executable;
+
+        info!("✅ Found {} cycle arbitrage opportunities", opportunities.len());
+        Ok(opportunities)
+    }
+
+    // Gas for one cycle and what its input is worth, both in the profit denomination
+    async fn cycle_costs(&self, base: &str, intermediate: &str, amount: u64) -> Result<(f64, f64)> {
+        let gas_cost = self.estimate_gas_cost(&[base.to_string(), intermediate.to_string()]).await?;
+        let amount_in = to_ui_amount(amount, self.token_decimals(base).await?)?;
+        let value_in = self.denominate(amount_in, base).await?;
+        if !value_in.is_finite() || value_in <= 0.0 {
+            return Err(anyhow::anyhow!("invalid value {} for {} of {}", value_in, amount_in, base));
+        }
+        Ok((gas_cost, value_in))
+    }
+
+    // A cycle in the shape execution takes: the pair holds the base then the
+    // intermediate mint (see `trade_mints`) and max_amount is the base spent
+    async fn cycle_opportunity(&self, cycle: &CycleArbitrageOpportunity) -> Result<ArbitrageOpportunity> {
+        let base_decimals = self.token_decimals(&cycle.base_mint).await?;
+        let spent = to_ui_amount(cycle.amount_in, base_decimals)?;
+        let held = to_ui_amount(cycle.first_leg.out_amount, self.token_decimals(&cycle.intermediate_mint).await?)?;
+        let returned = to_ui_amount(cycle.amount_out, base_decimals)?;
+        if held <= 0.0 {
+            return Err(anyhow::anyhow!("first leg fills nothing"));
+        }
+
+        Ok(ArbitrageOpportunity {
+            id: cycle.id.clone(),
+            token_pair: format!("{}/{}", cycle.base_mint, cycle.intermediate_mint),
+            buy_dex: "Jupiter".to_string(),
+            sell_dex: "Jupiter".to_string(),
+            buy_price: spent / held,
+            sell_price: returned / held,
+            profit_percentage: cycle.profit_percentage,
+            estimated_profit: cycle.estimated_profit,
+            max_amount: spent,
+            gas_cost: cycle.gas_cost,
+            timestamp: cycle.timestamp,
+            slippage: self.config.risk_settings.max_slippage,
+            is_profitable: true,
+            price_impact: cycle.first_leg.price_impact_pct + cycle.second_leg.price_impact_pct,
+            price_timestamp: cycle.timestamp,
+            expires_at: cycle.timestamp + self.config.trading.opportunity_ttl_ms as i64,
+            strategy: StrategyKind::MultiHop,
+            ..Default::default()
+        })
+    }
+
+    // Raw `mint_a` back per raw `mint_a` in, for swapping `amount` to `mint_b`
+    // and the proceeds straight back through Jupiter. Net of swap fees, and of
+    // the legs' self-impact on shared pools under `multi_hop.route_overlap =
+    // "adjust"`; above 1.0 is profitable before network fees. Needs no DEX feed.
+    pub async fn round_trip_edge(&self, mint_a: &str, mint_b: &str, amount: u64) -> Result<f64> {
+        if amount == 0 {
+            return Err(anyhow::anyhow!("Round trip {} -> {} needs a positive amount", mint_a, mint_b));
+        }
+        let (first_leg, second_leg) = self.quote_round_trip(mint_a, mint_b, amount).await?;
+        let mut multiplier = second_leg.out_amount as f64 / amount as f64;
+
+        let (shared_pools, self_impact_pct) = route_overlap(&first_leg, &second_leg);
+        if !shared_pools.is_empty() {
+            match self.config.multi_hop.route_overlap {
+                RouteOverlapPolicy::Reject => {
+                    return Err(anyhow::anyhow!("Round trip {} -> {} routes both legs through {:?}", mint_a, mint_b, shared_pools));
+                }
+                RouteOverlapPolicy::Adjust => multiplier -= self_impact_pct / 100.0,
+            }
+        }
+        debug!("🔄 Round trip {} -> {} -> {}: x{:.6}", mint_a, mint_b, mint_a, multiplier);
+        Ok(multiplier)
+    }
+
+    // `round_trip_edge` for many (mint_a, mint_b) pairs, quoted concurrently
+    pub async fn round_trip_edges(&self, pairs: &[(String, String)], amount: u64) -> Vec<Result<f64>> {
+        join_all(pairs.iter().map(|(mint_a, mint_b)| self.round_trip_edge(mint_a, mint_b, amount))).await
+    }
+
+    // Quotes `amount` of `mint_a` into `mint_b`, then that output back
+    async fn quote_round_trip(&self, mint_a: &str, mint_b: &str, amount: u64) -> Result<(JupiterQuote, JupiterQuote)> {
+        let slippage_bps = self.config.jupiter.default_slippage_bps;
+        let quote_ttl_ms = self.config.jupiter.quote_cache_ttl_ms;
+        let first_leg = self.get_jupiter_quote(mint_a, mint_b, amount, slippage_bps, quote_ttl_ms, None).await?;
+        let second_leg = self.get_jupiter_quote(
+            mint_b, mint_a, first_leg.out_amount, slippage_bps, quote_ttl_ms, Some(first_leg.context_slot),
+        ).await?;
+        Ok((first_leg, second_leg))
+    }
+
+    // Claims the opportunity for the request's wallet first, so two wallets
+    // never chase the same mispricing. A wallet that declines (rejected
+    // before submitting, e.g. on balance) hands it back for the others.
+    pub async fn execute_trade(&self, request: TradeRequest) -> Result<TradeResponse> {
+        if self.config.trading.opportunity_claim_ttl_ms == 0 {
+            return self.execute_claimed_trade(request).await;
+        }
+
+        let wallet = match self.wallet_id(&request) {
+            Ok(wallet) => wallet,
+            Err(e) => return Ok(TradeResponse::rejected(e.to_string())),
+        };
+        let outcome = self.opportunity_claims.write().await
+            .try_claim(&request.opportunity_id, &wallet, self.clock.now_ms());
+        if let ClaimOutcome::HeldBy(owner) = outcome {
+            info!("🤝 Opportunity {} is claimed by wallet {}, skipping for {}", request.opportunity_id, owner, wallet);
+            match self.opportunity_feed.find(&request.opportunity_id).await {
+                Some(opportunity) => self.reject_opportunity(&opportunity, RejectionReason::ClaimedByOtherWallet).await,
+                None => self.record_rejection(&request.opportunity_id, RejectionReason::ClaimedByOtherWallet).await,
+            }
+            return Ok(TradeResponse::rejected("Opportunity is claimed by another wallet"));
+        }
+
+        let opportunity_id = request.opportunity_id.clone();
+        let result = self.execute_claimed_trade(request).await;
+        // An error counts as declining too, rather than holding the claim until it expires
+        let declined = match &result {
+            Ok(response) => !response.success && response.transaction_id.is_empty(),
+            Err(_) => true,
+        };
+        if declined {
+            self.opportunity_claims.write().await.release(&opportunity_id, &wallet);
+        }
+        result
+    }
+
+    // Identifies a wallet by the public key it trades with. Requests without a
+    // private key trade through the engine's own signer, which may be remote.
+    fn wallet_id(&self, request: &TradeRequest) -> Result<String> {
+        Ok(self.signer_for(request)?.pubkey().to_string())
+    }
+
+    // Why nothing may be sent right now, if anything: the kill switch, a
+    // blackout window or Jupiter maintenance. Trades and housekeeping swaps
+    // alike stop for these.
+    async fn execution_halt_reason(&self) -> Option<String> {
+        if self.kill_switch.read().await.engaged() {
+            return Some("Kill switch engaged".to_string());
+        }
+        if let Some(reason) = self.blackout.read().await.active_reason() {
+            return Some(format!("Execution blacked out: {}", reason));
+        }
+        if self.get_api_health().await == ApiHealth::Maintenance {
+            return Some("Jupiter API is in maintenance".to_string());
+        }
+        None
+    }
+
+    async fn execute_claimed_trade(&self, request: TradeRequest) -> Result<TradeResponse> {
+        info!("💼 Executing trade for opportunity: {}", request.opportunity_id);
+
+        if let Some(reason) = self.execution_halt_reason().await {
+            warn!("🛑 {}, refusing trade {}", reason, request.opportunity_id);
+            return Ok(TradeResponse::rejected(reason));
+        }
+        
+        // Get opportunity details (in real implementation, this would be from a database)
+        let mut opportunity = self.get_opportunity_by_id(&request.opportunity_id).await?;
+
+        // Risk check
+        let risk_manager = self.risk_manager.read().await;
+        if !risk_manager.can_execute_trade(&request, self.clock.now_ms()).await? {
+            drop(risk_manager);
+            self.reject_opportunity(&opportunity, RejectionReason::RiskCheckFailed).await;
+            return Ok(TradeResponse::rejected("Risk check failed"));
+        }
+        drop(risk_manager);
+
+        let start_time = std::time::Instant::now();
+        {
+            let blocked_pools = self.blocked_pools.read().await;
+            if blocked_pools.contains(&opportunity.buy_pool) || blocked_pools.contains(&opportunity.sell_pool) {
+                warn!("🚫 Opportunity {} touches a blocked pool", opportunity.id);
+                self.reject_opportunity(&opportunity, RejectionReason::Blacklisted).await;
+                return Ok(TradeResponse::rejected("Opportunity touches a blocked pool"));
+            }
+        }
+
+        if opportunity.is_expired(self.clock.now_ms()) {
+            warn!("⌛ Opportunity {} expired before execution", opportunity.id);
+            self.reject_opportunity(&opportunity, RejectionReason::StaleQuote).await;
+            return Ok(TradeResponse::rejected("Opportunity has expired"));
+        }
+
+        // The Jito tip comes out of the same edge as gas; estimated_profit is already net of gas
+        let tip_lamports = match request.jito_tip_lamports() {
+            Ok(tip_lamports) => tip_lamports,
+            Err(e) => return Ok(TradeResponse::rejected(e.to_string())),
+        };
+        let tip_cost = match self.denominate(tip_lamports as f64 / LAMPORTS_PER_SOL, WSOL_MINT).await {
+            Ok(tip_cost) => tip_cost,
+            Err(e) => {
+                warn!("⚠️ Skipping opportunity {}: cannot price its Jito tip: {}", opportunity.id, e);
+                return Ok(TradeResponse::rejected(format!("Cannot price Jito tip: {}", e)));
+            }
+        };
+        if opportunity.estimated_profit <= tip_cost {
+            warn!("💸 Opportunity {} nets {:.4} after gas, not enough to cover a {:.4} Jito tip",
+                  opportunity.id, opportunity.estimated_profit, tip_cost);
+            self.reject_opportunity(&opportunity, RejectionReason::BelowGasCost).await;
+            return Ok(TradeResponse::rejected("Jito tip exceeds expected profit"));
+        }
+
+        if self.is_pair_paused(&opportunity.token_pair).await {
+            warn!("⏸️ Opportunity {} is on paused pair {}", opportunity.id, opportunity.token_pair);
+            self.reject_opportunity(&opportunity, RejectionReason::CircuitOpen).await;
+            return Ok(TradeResponse::rejected("Trading on this pair is paused"));
+        }
+
+        if let Err(e) = self.check_oracle_deviation(&opportunity).await {
+            warn!("🔮 Opportunity {} failed oracle check: {}", opportunity.id, e);
+            self.reject_opportunity(&opportunity, RejectionReason::OracleDeviation).await;
+            return Ok(TradeResponse::rejected(e.to_string()));
+        }
+
+        // The buy leg accumulates the base token of the pair (a cycle's
+        // intermediate) for what the first leg spends; groups are capped in USD
+        if !self.risk_manager.read().await.settings().exposure_groups.is_empty() {
+            let (base_mint, quote_mint) = self.trade_mints(&opportunity)?;
+            let amount_usd = self.price_usd(&quote_mint).await.and_then(|price| match price {
+                price if price.is_finite() && price > 0.0 => Ok(first_leg_amount(&request, &opportunity) * price),
+                price => Err(anyhow::anyhow!("invalid price {} for {}", price, quote_mint)),
+            });
+            let amount_usd = match amount_usd {
+                Ok(amount_usd) => amount_usd,
+                Err(e) => {
+                    warn!("🚫 Cannot value trade {} for the exposure check: {}", request.opportunity_id, e);
+                    self.reject_opportunity(&opportunity, RejectionReason::RiskCheckFailed).await;
+                    return Ok(TradeResponse::rejected("Cannot value trade for the exposure check"));
+                }
+            };
+            let portfolio = self.portfolio_manager.get_portfolio().await?;
+            let exposure = self.risk_manager.read().await.check_group_exposure(&portfolio, &base_mint, amount_usd);
+            if let Err(e) = exposure {
+                self.reject_opportunity(&opportunity, RejectionReason::RiskCheckFailed).await;
+                return Ok(TradeResponse::rejected(e.to_string()));
+            }
+        }
+        // The global caps passed above; the strategy must also be within its share
+        let max_position_size = self.get_max_position_size().await;
+        if let Err(e) = self.strategy_budgets.write().await.check(
+            opportunity.strategy,
+            request.amount,
+            max_position_size,
+            &self.config.risk_settings,
+            self.clock.now_ms(),
+        ) {
+            self.reject_opportunity(&opportunity, RejectionReason::StrategyBudgetExceeded).await;
+            return Ok(TradeResponse::rejected(e.to_string()));
+        }
+        if let Some(reason) = self.check_mint_safety(&opportunity).await? {
+            self.reject_opportunity(&opportunity, RejectionReason::RestrictedMint).await;
+            return Ok(TradeResponse::rejected(reason));
+        }
+        if self.config.canary.enabled {
+            if let Some(reason) = self.check_canary(&request, &opportunity).await? {
+                self.reject_opportunity(&opportunity, RejectionReason::Blacklisted).await;
+                return Ok(TradeResponse::rejected(reason));
+            }
+        }
+        let max_inflight = self.config.risk_settings.max_inflight_capital;
+        match self.trade_queue.mark_pending(&request.opportunity_id, &opportunity.token_pair, request.amount, max_inflight, self.clock.now_ms()).await? {
+            Admission::Admitted => {}
+            Admission::AlreadyPending => {
+                warn!("⏸️ Opportunity {} already has a trade in flight", request.opportunity_id);
+                return Ok(TradeResponse::rejected("Trade for this opportunity is already pending"));
+            }
+            Admission::OverCapacity { committed } => {
+                warn!("🧱 Trade {} of {} would exceed in-flight cap ({} already committed)",
+                      request.opportunity_id, request.amount, committed);
+                self.reject_opportunity(&opportunity, RejectionReason::RiskCheckFailed).await;
+                return Ok(TradeResponse::rejected("In-flight capital limit reached"));
+            }
+        }
+        let pending = PendingGuard::new(self.trade_queue.clone(), &request.opportunity_id);
+        let mut retries = 0;
+
+        // Build and execute transaction, resubmitting with a fresh blockhash if it expired
+        let transaction_result = loop {
+            let result = self.submit(&request, &opportunity).await?;
+
+            if !should_retry_expired(&result, retries, self.config.trading.max_blockhash_retries) {
+                break result;
+            }
+
+            retries += 1;
+            warn!("⏳ Transaction for {} expired before landing, re-quoting (retry {}/{})",
+                  request.opportunity_id, retries, self.config.trading.max_blockhash_retries);
+            // The history is the audit trail: every expired attempt is kept, not just the last
+            let attempt = TradeResponse {
+                error_message: format!("{} (attempt {}, retrying)", result.error_message, retries),
+                ..result.clone()
+            };
+            if let Err(e) = self.history.record_trade(self.clock.now_ms(), &opportunity.token_pair, &request.opportunity_id, &attempt).await {
+                warn!("⚠️ Failed to record expired attempt for {} in history: {}", request.opportunity_id, e);
+            }
+
+            // Prices may have moved while the transaction was in flight
+            opportunity = match self.requote_opportunity(&request, &opportunity).await {
+                Ok(requoted) => requoted,
+                Err(e) => {
+                    warn!("⚠️ Re-quote for {} failed, giving up: {}", request.opportunity_id, e);
+                    self.reject_opportunity(&opportunity, RejectionReason::StaleQuote).await;
+                    break TradeResponse {
+                        error_message: format!("{} (re-quote failed: {})", ArbitrageError::BlockhashExpired, e),
+                        ..result
+                    };
+                }
+            };
+            if !self.is_still_profitable(&opportunity, tip_cost) {
+                self.reject_opportunity(&opportunity, RejectionReason::StaleQuote).await;
+                break TradeResponse {
+                    error_message: format!("{} (no longer profitable after re-quote)", ArbitrageError::BlockhashExpired),
+                    ..result
+                };
+            }
+        };
+
+        let hold_for_finality = self.config.trading.profit_commitment == ProfitCommitment::Finalized;
+        let tracked = transaction_result.success && self.finality.write().await.track(
+            &request.opportunity_id,
+            &opportunity.token_pair,
+            opportunity.strategy,
+            &transaction_result,
+            !hold_for_finality,
+        );
+        // The entry holds its capital until the outcome is final: tracked
+        // trades settle when they finalize or roll back, and one that never
+        // confirmed either way waits for the pending-trade sweep
+        if tracked || Self::is_unconfirmed(&transaction_result.error_message) {
+            pending.keep();
+        } else {
+            pending.settle().await?;
+        }
+        if !(tracked && hold_for_finality) {
+            self.account_trade(&request.opportunity_id, &opportunity.token_pair, opportunity.strategy, &transaction_result).await;
+        }
+
+        if retries > 0 {
+            info!("🔁 Trade {} finished after {} blockhash retries (success: {})",
+                  request.opportunity_id, retries, transaction_result.success);
+        }
+
+        let execution_time = start_time.elapsed().as_millis() as i64;
 
         // Update monitoring metrics
         self.monitoring.record_trade_execution(
@@ -291,220 +2014,2858 @@ impl ArbitrageEngine {
             execution_time,
         ).await;
 
-        Ok(transaction_result)
+        Ok(transaction_result)
+    }
+
+    // Records a trade's outcome in history, PnL, budgets, win rate and the event sink
+    async fn account_trade(&self, opportunity_id: &str, token_pair: &str, strategy: StrategyKind, response: &TradeResponse) {
+        let now = self.clock.now_ms();
+        if let Err(e) = self.history.record_trade(now, token_pair, opportunity_id, response).await {
+            warn!("⚠️ Failed to record trade {} in history: {}", opportunity_id, e);
+        }
+        *self.strategy_pnl.write().await.entry(strategy).or_default() += response.actual_profit;
+        self.strategy_budgets.write().await.record_trade(strategy, response.actual_profit, now);
+        self.risk_manager.write().await.record_trade(now, response.actual_profit);
+        if let Err(e) = self.stats.record_trade(strategy, response.success, response.actual_profit).await {
+            warn!("⚠️ Failed to record trade {} in stats: {}", opportunity_id, e);
+        }
+        if self.config.win_rate_alerts.enabled {
+            self.record_trade_outcome(response.success && response.actual_profit > 0.0).await;
+        }
+        self.event_sink.publish_trade(&TradeEvent {
+            opportunity_id,
+            token_pair,
+            timestamp: now,
+            response,
+        });
+    }
+
+    // Undoes an accounted trade a reorg rolled back. History is append-only,
+    // so the reversal is a second entry that cancels the first's profit.
+    async fn reverse_trade(&self, trade: &UnfinalizedTrade) {
+        let now = self.clock.now_ms();
+        let profit = trade.response.actual_profit;
+        let reversal = TradeResponse {
+            success: false,
+            error_message: "Rolled back by a reorg".to_string(),
+            actual_profit: -profit,
+            ..trade.response.clone()
+        };
+        if let Err(e) = self.history.record_trade(now, &trade.token_pair, &trade.opportunity_id, &reversal).await {
+            warn!("⚠️ Failed to record reversal of trade {} in history: {}", trade.opportunity_id, e);
+        }
+        *self.strategy_pnl.write().await.entry(trade.strategy).or_default() -= profit;
+        self.strategy_budgets.write().await.correct_pnl(trade.strategy, -profit, now);
+        self.risk_manager.write().await.record_trade(now, -profit);
+        if self.config.win_rate_alerts.enabled && trade.response.success && profit > 0.0 {
+            self.win_rate_monitor.write().await.reverse_win();
+        }
+        if let Err(e) = self.stats.record_reversal(profit).await {
+            warn!("⚠️ Failed to record reversal of trade {} in stats: {}", trade.opportunity_id, e);
+        }
+        self.event_sink.publish_trade(&TradeEvent {
+            opportunity_id: &trade.opportunity_id,
+            token_pair: &trade.token_pair,
+            timestamp: now,
+            response: &reversal,
+        });
+    }
+
+    // Follows successful trades to finalization: held-back trades are
+    // accounted once final, accounted ones are reversed if rolled back
+    async fn run_finality_watch(&self, heartbeat: Heartbeat) -> Result<()> {
+        let mut interval = tokio::time::interval(
+            std::time::Duration::from_millis(self.config.trading.finality_poll_ms)
+        );
+
+        loop {
+            interval.tick().await;
+            heartbeat.beat();
+
+            if let Err(e) = self.check_finality().await {
+                warn!("⚠️ Finality check failed, retrying next poll: {}", e);
+            }
+            // Trades whose blockhash has expired have a final outcome by now
+            self.trade_queue.recover(&self.rpc_client, self.clock.now_ms(), &self.config.clock, BLOCKHASH_EXPIRY_MS).await;
+        }
+    }
+
+    async fn check_finality(&self) -> Result<()> {
+        let signatures = self.finality.read().await.signatures();
+        // getSignatureStatuses takes at most 256 signatures per call
+        for chunk in signatures.chunks(256) {
+            let ids: Vec<String> = chunk.iter().map(|signature| signature.to_string()).collect();
+            let result = self.rpc_client.call("getSignatureStatuses", json!([
+                ids,
+                { "searchTransactionHistory": true }
+            ])).await?;
+            let statuses = result["value"].as_array()
+                .ok_or_else(|| anyhow::anyhow!("Malformed getSignatureStatuses response"))?;
+
+            for (signature, status) in chunk.iter().zip(statuses) {
+                let finality = self.finality.write().await.update(signature, ChainStatus::from_json(status));
+                if let Some(Finality::Finalized(trade) | Finality::RolledBack(trade)) = &finality {
+                    if let Err(e) = self.trade_queue.mark_settled(&trade.opportunity_id).await {
+                        warn!("⚠️ Failed to settle trade {}: {}", trade.opportunity_id, e);
+                    }
+                }
+                match finality {
+                    Some(Finality::Finalized(trade)) if !trade.accounted => {
+                        debug!("🔒 Trade {} finalized, recording its profit", trade.opportunity_id);
+                        self.account_trade(&trade.opportunity_id, &trade.token_pair, trade.strategy, &trade.response).await;
+                    }
+                    Some(Finality::RolledBack(trade)) if trade.accounted => {
+                        error!("🔙 Trade {} ({}) was rolled back by a reorg, reversing its {:.4} profit",
+                               trade.opportunity_id, trade.signature, trade.response.actual_profit);
+                        self.reverse_trade(&trade).await;
+                    }
+                    Some(Finality::RolledBack(trade)) => {
+                        warn!("🔙 Trade {} ({}) was rolled back before finalization; its profit was never recorded",
+                              trade.opportunity_id, trade.signature);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn get_max_position_size(&self) -> f64 {
+        *self.max_position_size.read().await
+    }
+
+    // Re-derives the max position size from portfolio value so profits
+    // compound, and hands it to the risk manager so its checks agree
+    async fn run_position_sizer(&self, heartbeat: Heartbeat) -> Result<()> {
+        let mut interval = tokio::time::interval(
+            std::time::Duration::from_millis(self.config.position_sizing.refresh_interval_ms)
+        );
+
+        loop {
+            interval.tick().await;
+            heartbeat.beat();
+            self.refresh_position_size().await;
+        }
+    }
+
+    async fn refresh_position_size(&self) {
+        let sizing = &self.config.position_sizing;
+        let portfolio_value = match self.portfolio_manager.get_portfolio().await {
+            Ok(portfolio) => portfolio.total_value_usd,
+            Err(e) => {
+                warn!("⚠️ Keeping max position size: cannot value portfolio: {}", e);
+                return;
+            }
+        };
+        let size = equity_position_size(portfolio_value, sizing.equity_percent, sizing.max_position_ceiling);
+
+        let mut current = self.max_position_size.write().await;
+        if (*current - size).abs() > f64::EPSILON {
+            info!("📐 Max position size {:.2} -> {:.2} ({:.1}% of ${:.2} portfolio)",
+                  *current, size, sizing.equity_percent, portfolio_value);
+            *current = size;
+            self.risk_manager.write().await.update_max_position_size(size);
+        }
+    }
+
+    // Unwraps leftover wSOL and sweeps dust into the base token
+    async fn run_housekeeping(&self, heartbeat: Heartbeat) -> Result<()> {
+        let mut interval = tokio::time::interval(
+            std::time::Duration::from_secs(self.config.housekeeping.interval_secs)
+        );
+
+        loop {
+            interval.tick().await;
+            heartbeat.beat();
+
+            if let Err(e) = self.housekeep().await {
+                warn!("🧹 Housekeeping skipped: {}", e);
+            }
+        }
+    }
+
+    async fn housekeep(&self) -> Result<()> {
+        let settings = &self.config.housekeeping;
+        let portfolio = self.portfolio_manager.get_portfolio().await?;
+        let actions = plan_housekeeping(&portfolio, settings);
+        if actions.is_empty() {
+            debug!("🧹 No leftover wSOL or dust");
+            return Ok(());
+        }
+
+        for action in actions {
+            match action {
+                HousekeepingAction::CloseWsol { amount } => {
+                    info!("🧹 wSOL account holds {:.6} SOL; closing it reclaims {:.5} SOL rent",
+                          amount, TOKEN_ACCOUNT_RENT_SOL);
+                    if !settings.auto_execute {
+                        continue;
+                    }
+                    // A swap in flight may be wrapping through the same account
+                    if !self.trade_queue.pending().await.is_empty() {
+                        debug!("🧹 Leaving the wSOL account open while trades are pending");
+                        continue;
+                    }
+                    if let Some(reason) = self.execution_halt_reason().await {
+                        warn!("🛑 {}, leaving the wSOL account open", reason);
+                        continue;
+                    }
+                    match self.close_wsol_account().await {
+                        Ok(signature) => info!("🧹 Closed wSOL account: {}", signature),
+                        Err(e) => warn!("⚠️ Failed to close wSOL account: {}", e),
+                    }
+                }
+                HousekeepingAction::ConsolidateDust { mint, symbol, amount, value_usd } => {
+                    info!("🧹 {} {} of dust (${:.4}) can be consolidated into {}",
+                          amount, symbol, value_usd, settings.base_mint);
+                    if settings.auto_execute {
+                        if let Err(e) = self.consolidate_dust(&mint, amount, value_usd).await {
+                            warn!("⚠️ Failed to consolidate {} dust: {}", symbol, e);
+                        }
+                    }
+                }
+                HousekeepingAction::ReportDust { symbol, value_usd, .. } => {
+                    info!("🧹 {} dust (${:.4}) is worth less than a swap, leaving it", symbol, value_usd);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn close_wsol_account(&self) -> Result<Signature> {
+        let owner = self.signer()?.pubkey();
+        let instruction = close_token_account_instruction(&owner, WSOL_MINT)?;
+        self.send_signed(&[instruction]).await
+    }
+
+    fn signer(&self) -> Result<&Arc<dyn TransactionSigner>> {
+        self.signer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No wallet signer configured (private_key or remote_signer)"))
+    }
+
+    // The signer a request trades with: its own key, or the engine's signer
+    fn signer_for(&self, request: &TradeRequest) -> Result<Arc<dyn TransactionSigner>> {
+        if request.private_key.is_empty() {
+            return Ok(self.signer()?.clone());
+        }
+        Ok(Arc::new(KeypairSigner::from_base58(&request.private_key)?))
+    }
+
+    // Hands a trade to the executor, building and signing its transaction
+    // first when the executor goes to chain. A trade that can't be built is
+    // rejected: nothing was sent.
+    async fn submit(&self, request: &TradeRequest, opportunity: &ArbitrageOpportunity) -> Result<TradeResponse> {
+        if !self.executor.submits_transactions() {
+            return self.executor.execute(request, opportunity, None).await;
+        }
+        let built = match self.build_within_latency_budget(request, opportunity).await {
+            Ok(built) => built,
+            Err(e) => {
+                warn!("🧱 Could not build a transaction for {}: {}", opportunity.id, e);
+                return Ok(TradeResponse::rejected(format!("Failed to build transaction: {}", e)));
+            }
+        };
+        if let Err(e) = self.verify_simulated_output(&built, opportunity, request.jito_tip_lamports()?).await {
+            return Ok(TradeResponse::rejected(e.to_string()));
+        }
+        // Recorded before sending, so a crash or error mid-send leaves a
+        // signature to look the trade up by
+        if let Some(signature) = built.transaction.signatures.first() {
+            self.trade_queue.record_signature(&request.opportunity_id, &signature.to_string(), self.clock.now_ms()).await?;
+        }
+        self.executor.execute(request, opportunity, Some(&built.transaction)).await
+    }
+
+    // Builds the trade, rebuilding once on fresh quotes if the build took
+    // longer than the latency budget and `requote_on_latency_breach` is set
+    async fn build_within_latency_budget(&self, request: &TradeRequest, opportunity: &ArbitrageOpportunity) -> Result<BuiltTrade> {
+        let mut requoted = false;
+        loop {
+            let built = self.build_trade_transaction(request, opportunity).await?;
+            match self.check_latency_budget(&built) {
+                Ok(()) => return Ok(built),
+                Err(e) if self.config.trading.requote_on_latency_breach && !requoted => {
+                    warn!("⏱️ {}, re-quoting {}", e, opportunity.token_pair);
+                    requoted = true;
+                }
+                Err(e) => {
+                    self.reject_opportunity(opportunity, RejectionReason::StaleQuote).await;
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    // Base and quote mints of the pair an opportunity trades. A cycle's pair
+    // already holds mints: the cycle's base, then its intermediate token.
+    fn trade_mints(&self, opportunity: &ArbitrageOpportunity) -> Result<(String, String)> {
+        if opportunity.strategy != StrategyKind::MultiHop {
+            return self.extract_token_mints(&opportunity.token_pair);
+        }
+        let (base, intermediate) = opportunity.token_pair.split_once('/')
+            .ok_or_else(|| anyhow::anyhow!("Invalid cycle pair: {}", opportunity.token_pair))?;
+        Ok((intermediate.to_string(), base.to_string()))
+    }
+
+    // One leg of a trade, restricted to the leg's DEX when it has one
+    async fn quote_leg(
+        &self,
+        jupiter_client: &JupiterClient,
+        leg: &TradeLeg,
+        amount: u64,
+        swap_mode: SwapMode,
+        slippage_bps: u16,
+        context_slot: Option<u64>,
+    ) -> Result<JupiterQuote> {
+        jupiter_client.get_quote(JupiterQuoteRequest {
+            input_mint: leg.input_mint.clone(),
+            output_mint: leg.output_mint.clone(),
+            amount,
+            slippage_bps,
+            swap_mode: Some(swap_mode.as_str().to_string()),
+            dexes: Some(match &leg.dex {
+                Some(dex) => vec![dex.clone()],
+                None => self.config.jupiter.preferred_dexes.clone(),
+            }),
+            exclude_dexes: Some(self.config.jupiter.excluded_dexes.clone()),
+            platform_fee_bps: None,
+            max_accounts: None,
+            context_slot,
+        }).await
+    }
+
+    // Prices the opportunity afresh from Jupiter quotes of its legs at the
+    // request's size, for a retry to check it still pays
+    async fn requote_opportunity(&self, request: &TradeRequest, opportunity: &ArbitrageOpportunity) -> Result<ArbitrageOpportunity> {
+        let jupiter_client = self.jupiter_client.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Jupiter client not available"))?;
+        let (base_mint, quote_mint) = self.trade_mints(opportunity)?;
+        let legs = trade_legs(opportunity, &base_mint, &quote_mint);
+        let slippage_bps = (request.max_slippage * 100.0).round() as u16;
+
+        let input_decimals = self.token_decimals(&legs[0].input_mint).await?;
+        let held_decimals = self.token_decimals(&legs[0].output_mint).await?;
+        let amount = to_raw_amount(first_leg_amount(request, opportunity), input_decimals)?;
+        let first = self.quote_leg(jupiter_client, &legs[0], amount, SwapMode::ExactIn, slippage_bps, None).await?;
+        let second = self.quote_leg(jupiter_client, &legs[1], first.out_amount, SwapMode::ExactIn, slippage_bps, Some(first.context_slot)).await?;
+
+        let spent = to_ui_amount(first.in_amount, input_decimals)?;
+        let held = to_ui_amount(first.out_amount, held_decimals)?;
+        let returned = to_ui_amount(second.out_amount, input_decimals)?;
+        let profit = self.denominate(returned - spent, &legs[0].input_mint).await?;
+        let mut requoted = reprice_opportunity(opportunity, spent, held, returned, profit, self.clock.now_ms())?;
+        requoted.price_impact = first.price_impact_pct + second.price_impact_pct;
+        requoted.expires_at = requoted.timestamp + self.config.trading.opportunity_ttl_ms as i64;
+        debug!("🔄 Re-quoted {}: {:.3}% -> {:.3}%", opportunity.id, opportunity.profit_percentage, requoted.profit_percentage);
+        Ok(requoted)
+    }
+
+    // Every leg of the trade in one signed v0 transaction. Each leg is quoted
+    // afresh on its own DEX and fetched from Jupiter as instructions; the
+    // next leg spends no more than the previous one's minimum output. In
+    // ExactOut mode the first leg buys exactly the trade size instead. The
+    // legs' compute budgets are merged into one and the Jito tip rides along.
+    async fn build_trade_transaction(&self, request: &TradeRequest, opportunity: &ArbitrageOpportunity) -> Result<BuiltTrade> {
+        let jupiter_client = self.jupiter_client.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Jupiter client not available"))?;
+        let signer = self.signer_for(request)?;
+        let payer = signer.pubkey();
+        let (base_mint, quote_mint) = self.trade_mints(opportunity)?;
+        let legs = trade_legs(opportunity, &base_mint, &quote_mint);
+
+        let exact_out = self.config.trading.swap_mode == SwapMode::ExactOut && opportunity.strategy != StrategyKind::MultiHop;
+        let mut amount = if exact_out {
+            to_raw_amount(request.amount, self.token_decimals(&legs[0].output_mint).await?)?
+        } else {
+            to_raw_amount(first_leg_amount(request, opportunity), self.token_decimals(&legs[0].input_mint).await?)?
+        };
+        let slippage_bps = (request.max_slippage * 100.0).round() as u16;
+
+        let mut quotes: Vec<JupiterQuote> = Vec::with_capacity(legs.len());
+        let mut instruction_sets = Vec::with_capacity(legs.len() + 1);
+        let mut lookup_table_keys = Vec::new();
+        for leg in &legs {
+            let context_slot = quotes.first().map(|first| first.context_slot);
+            let swap_mode = if exact_out && quotes.is_empty() { SwapMode::ExactOut } else { SwapMode::ExactIn };
+            let quote = self.quote_leg(jupiter_client, leg, amount, swap_mode, slippage_bps, context_slot).await?;
+            let swap = jupiter_client.get_swap_instructions(&quote, &SwapRequest {
+                input_mint: leg.input_mint.clone(),
+                output_mint: leg.output_mint.clone(),
+                amount,
+                user_public_key: payer.to_string(),
+                slippage: request.max_slippage,
+                priority_fee: self.config.jupiter.prioritization_fee_lamports,
+                allowed_dexes: leg.dex.clone().map(|dex| vec![dex]),
+                excluded_dexes: Some(self.config.jupiter.excluded_dexes.clone()),
+                use_jupiter: true,
+                dynamic_slippage: self.config.jupiter.dynamic_slippage.clone(),
+                compute_unit_price_micro_lamports: None,
+                tracking_account: self.config.jupiter.tracking_accounts.get(&opportunity.strategy).cloned(),
+                // Rent is already in the opportunity's gas estimate
+                created_token_accounts: 0,
+            }).await?;
+
+            amount = match quote.swap_mode {
+                SwapMode::ExactOut => quote.out_amount,
+                SwapMode::ExactIn if quote.other_amount_threshold > 0 => quote.other_amount_threshold,
+                SwapMode::ExactIn => quote.out_amount,
+            };
+            instruction_sets.push(swap.instructions);
+            lookup_table_keys.extend(swap.address_lookup_tables);
+            quotes.push(quote);
+        }
+        self.revalidate_exact_out(&quotes, opportunity).await?;
+
+        // Jupiter prices each leg on its own; the merge keeps the highest bid
+        if let Some(price) = self.cu_price_for(opportunity.estimated_profit).await {
+            instruction_sets.push(vec![ComputeBudgetInstruction::set_compute_unit_price(price)]);
+        }
+
+        let tip_lamports = request.jito_tip_lamports()?;
+        if tip_lamports > 0 {
+            let jito_client = self.jito_client.as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Jito tip requested but Jito is not configured"))?;
+            instruction_sets.push(vec![jito_client.tip_instruction(&payer, tip_lamports)?]);
+        }
+
+        let (instructions, budget) = merge_instruction_sets(&instruction_sets, MAX_COMPUTE_UNIT_LIMIT)?;
+        lookup_table_keys.sort();
+        lookup_table_keys.dedup();
+        let lookup_tables = self.fetch_lookup_tables(&lookup_table_keys).await?;
+        let mut transaction = compile_transaction(&payer, &instructions, &lookup_tables, self.latest_blockhash().await?)?;
+        sign_transaction(signer.as_ref(), &mut transaction).await?;
+
+        let quote_ids: Vec<&str> = quotes.iter().map(|quote| quote.request_id.as_str()).collect();
+        info!("🧾 Built {}-leg transaction for {} (quote requests {})", quotes.len(), opportunity.id, quote_ids.join(", "));
+        Ok(BuiltTrade { transaction, quotes, budget })
+    }
+
+    // The dynamic CU price when fee estimation is on and the estimate succeeded
+    async fn market_cu_price(&self) -> Option<u64> {
+        let fee_estimator = self.fee_estimator.as_ref()?;
+        match fee_estimator.estimate_cu_price().await {
+            Ok(price) => Some(price),
+            Err(e) => {
+                warn!("⚠️ CU price estimation failed, using flat priority fee: {}", e);
+                None
+            }
+        }
+    }
+
+    // A profitable opportunity may outbid the market, but never bids below it
+    async fn cu_price_for(&self, estimated_profit: f64) -> Option<u64> {
+        let market_price = self.market_cu_price().await;
+        let fees = &self.config.priority_fee;
+        let profit_price = fees.profit_scaling.as_ref().map(|mapping| cu_price_for_profit(
+            estimated_profit,
+            mapping,
+            fees.floor_micro_lamports,
+            fees.ceiling_micro_lamports.min(self.config.risk_settings.max_gas_price),
+        ));
+        match (market_price, profit_price) {
+            (Some(market), Some(profit)) => Some(market.max(profit)),
+            (market, profit) => market.or(profit),
+        }
+    }
+
+    async fn fetch_lookup_tables(&self, keys: &[Pubkey]) -> Result<Vec<AddressLookupTableAccount>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let addresses: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+        let result = self.rpc_client.call("getMultipleAccounts", json!([addresses, { "encoding": "base64" }])).await?;
+        let accounts = result["value"].as_array()
+            .ok_or_else(|| anyhow::anyhow!("Malformed getMultipleAccounts response"))?;
+
+        keys.iter().zip(accounts)
+            .map(|(key, account)| {
+                let data = account["data"][0].as_str()
+                    .ok_or_else(|| anyhow::anyhow!("Lookup table {} not found", key))?;
+                parse_lookup_table(*key, &base64::engine::general_purpose::STANDARD.decode(data)?)
+            })
+            .collect()
+    }
+
+    async fn latest_blockhash(&self) -> Result<Hash> {
+        let result = self.rpc_client.call("getLatestBlockhash", json!([{ "commitment": "confirmed" }])).await?;
+        let blockhash = result["value"]["blockhash"].as_str()
+            .ok_or_else(|| anyhow::anyhow!("Malformed getLatestBlockhash response"))?;
+        Ok(Hash::from_str(blockhash)?)
+    }
+
+    // Signs with the wallet's signer, which may be remote, and waits for confirmation
+    async fn send_signed(&self, instructions: &[Instruction]) -> Result<Signature> {
+        let signer = self.signer()?;
+        let rpc = RpcClient::new(self.config.rpc_endpoints.primary.clone());
+        let mut message = Message::new(instructions, Some(&signer.pubkey()));
+        message.recent_blockhash = rpc.get_latest_blockhash().await?;
+        let mut transaction = VersionedTransaction {
+            signatures: Vec::new(),
+            message: VersionedMessage::Legacy(message),
+        };
+        sign_transaction(signer.as_ref(), &mut transaction).await?;
+        Ok(rpc.send_and_confirm_transaction(&transaction).await?)
+    }
+
+    // Sends a housekeeping swap (dust, rebalancing) through the same gates
+    // and executor as a trade. `value_usd` is what the risk limits see. None
+    // when a gate held it back or the executor is simulated, so nothing was sent.
+    async fn submit_housekeeping_swap(&self, kind: &str, swap_request: SwapRequest, value_usd: f64) -> Result<Option<TradeResponse>> {
+        let label = format!("{}:{}->{}", kind, swap_request.input_mint, swap_request.output_mint);
+        if let Some(reason) = self.execution_halt_reason().await {
+            warn!("🛑 {}, holding {} swap", reason, label);
+            return Ok(None);
+        }
+        let jupiter_client = self.jupiter_client.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Jupiter client not available"))?;
+
+        let request = TradeRequest {
+            opportunity_id: label.clone(),
+            amount: value_usd,
+            private_key: String::new(),
+            max_slippage: swap_request.slippage,
+            priority_fee: 0,
+            use_jito: false,
+            jito_tip: "0".to_string(),
+        };
+        if !self.risk_manager.read().await.can_execute_trade(&request, self.clock.now_ms()).await? {
+            warn!("🚫 Risk limits hold {} swap", label);
+            return Ok(None);
+        }
+        if !self.executor.submits_transactions() {
+            info!("🧪 Simulated execution, not sending {} swap", label);
+            return Ok(None);
+        }
+
+        let swap = jupiter_client.execute_swap(swap_request).await?;
+        let mut transaction: VersionedTransaction = bincode::deserialize(
+            &base64::engine::general_purpose::STANDARD.decode(&swap.transaction)?,
+        )?;
+        sign_transaction(self.signer()?.as_ref(), &mut transaction).await?;
+
+        let opportunity = ArbitrageOpportunity {
+            id: label.clone(),
+            token_pair: label,
+            gas_cost: swap.gas_used,
+            timestamp: self.clock.now_ms(),
+            ..Default::default()
+        };
+        let response = self.executor.execute(&request, &opportunity, Some(&transaction)).await?;
+        self.risk_manager.write().await.record_trade(self.clock.now_ms(), response.actual_profit - response.gas_used);
+        Ok(Some(response))
+    }
+
+    async fn consolidate_dust(&self, mint: &str, amount: f64, value_usd: f64) -> Result<()> {
+        let jupiter_client = self.jupiter_client.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Jupiter client not available"))?;
+        let settings = &self.config.housekeeping;
+
+        let decimals = jupiter_client.get_token_decimals(mint).await?;
+        let raw_amount = to_raw_amount(amount, decimals)?;
+        let slippage_bps = self.config.jupiter.default_slippage_bps;
+        let quote = self.get_jupiter_quote(mint, &settings.base_mint, raw_amount, slippage_bps, 0, None).await?;
+        if quote.price_impact_pct > self.config.jupiter.max_price_impact_pct {
+            return Err(anyhow::anyhow!("price impact {:.2}% is too high", quote.price_impact_pct));
+        }
+
+        let swap_request = SwapRequest {
+            input_mint: mint.to_string(),
+            output_mint: settings.base_mint.clone(),
+            amount: raw_amount,
+            user_public_key: self.config.wallet.public_key.clone(),
+            slippage: slippage_bps as f64 / 100.0,
+            priority_fee: self.config.jupiter.prioritization_fee_lamports,
+            allowed_dexes: None,
+            excluded_dexes: Some(self.config.jupiter.excluded_dexes.clone()),
+            use_jupiter: true,
+            dynamic_slippage: None,
+            compute_unit_price_micro_lamports: None,
+            tracking_account: None,
+            created_token_accounts: self.missing_token_accounts(std::slice::from_ref(&settings.base_mint)).await,
+        };
+        match self.submit_housekeeping_swap("dust", swap_request, value_usd).await? {
+            Some(response) if response.success => info!("🧹 Dust swap landed: {}", response.transaction_id),
+            Some(response) => warn!("⚠️ Dust swap {} failed: {}", response.transaction_id, response.error_message),
+            None => {}
+        }
+        Ok(())
+    }
+
+    async fn poll_api_health(&self, heartbeat: Heartbeat) -> Result<()> {
+        if self.jupiter_client.is_none() {
+            return Ok(());
+        }
+        let mut interval = tokio::time::interval(
+            std::time::Duration::from_millis(self.config.api_health.poll_interval_ms)
+        );
+
+        loop {
+            interval.tick().await;
+            heartbeat.beat();
+            self.refresh_api_health().await;
+        }
+    }
+
+    // Probes Jupiter once and moves the API health state on
+    async fn refresh_api_health(&self) {
+        let Some(jupiter_client) = &self.jupiter_client else {
+            return;
+        };
+        let health = jupiter_client.get_health_status(self.config.api_health.degraded_latency_ms).await;
+        let mut current = self.api_health.write().await;
+        if *current != health {
+            match health {
+                ApiHealth::Healthy => info!("💚 Jupiter API healthy again, resuming normal trading"),
+                ApiHealth::Degraded => warn!("💛 Jupiter API degraded, widening margins and scanning less often"),
+                ApiHealth::Maintenance => error!("🔧 Jupiter API in maintenance, pausing scanning and trading"),
+            }
+            *current = health;
+        }
+    }
+
+    pub async fn get_api_health(&self) -> ApiHealth {
+        *self.api_health.read().await
+    }
+
+    async fn arbitrage_loop(&self, heartbeat: Heartbeat) -> Result<()> {
+        let mut interval = tokio::time::interval(
+            std::time::Duration::from_millis(self.config.trading.scan_interval_ms)
+        );
+        let mut degraded_ticks = 0u32;
+
+        loop {
+            interval.tick().await;
+            heartbeat.beat();
+            
+            let running = *self.is_running.read().await;
+            if !running {
+                break;
+            }
+
+            self.refresh_kill_switch().await;
+            self.blackout.write().await.refresh(self.clock.now_ms(), self.rpc_client.call_counts());
+
+            // No opportunities are emitted until startup warm-up has finished
+            if !self.is_warm().await {
+                continue;
+            }
+
+            let Some(min_profit_threshold) = self.scan_threshold(&mut degraded_ticks).await else {
+                continue;
+            };
+
+            // Scan for opportunities
+            let mut opportunities = self.scan_opportunities(
+                min_profit_threshold,
+                self.get_max_position_size().await,
+            ).await?;
+
+            // Cycles trade alongside the direct opportunities, after them
+            if self.config.multi_hop.enabled && self.jupiter_client.is_some() {
+                match self.scan_cycle_opportunities().await {
+                    Ok(cycles) => {
+                        let executable = self.cycle_opportunities.read().await;
+                        for cycle in cycles {
+                            info!("🔄 Cycle {} -> {} -> {}: {:.2}% return after gas",
+                                  cycle.base_mint, cycle.intermediate_mint, cycle.base_mint, cycle.profit_percentage);
+                            opportunities.extend(executable.get(&cycle.id).cloned());
+                        }
+                    }
+                    Err(e) => warn!("⚠️ Cycle scan failed: {}", e),
+                }
+            }
+
+            // Use idle cycles to drift the portfolio back toward its target weights
+            if opportunities.is_empty()
+                && self.config.trading.enable_auto_trading
+                && self.config.rebalancing.enabled
+            {
+                if let Err(e) = self.rebalance_portfolio().await {
+                    warn!("⚠️ Rebalancing skipped: {}", e);
+                }
+            }
+
+            // Execute profitable trades if auto-trading is enabled
+            if self.config.trading.enable_auto_trading {
+                self.dispatch_opportunities(opportunities).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    // This tick's min profit under the current API health, or None to skip
+    // the scan: degraded scans run every Nth tick with wider margins,
+    // maintenance runs none
+    async fn scan_threshold(&self, degraded_ticks: &mut u32) -> Option<f64> {
+        let min_profit_threshold = self.config.risk_settings.min_profit_threshold;
+        match self.get_api_health().await {
+            ApiHealth::Healthy => {
+                *degraded_ticks = 0;
+                Some(min_profit_threshold)
+            }
+            ApiHealth::Degraded => {
+                // Scan on the first degraded tick, then every Nth
+                *degraded_ticks += 1;
+                if !(*degraded_ticks - 1).is_multiple_of(self.config.api_health.degraded_scan_every) {
+                    return None;
+                }
+                Some(min_profit_threshold * self.config.api_health.degraded_profit_multiplier)
+            }
+            ApiHealth::Maintenance => None,
+        }
+    }
+
+    // Trades the best of a scan's ranked opportunities, up to the per-scan cap
+    async fn dispatch_opportunities(&self, opportunities: Vec<ArbitrageOpportunity>) {
+        let (opportunities, skipped) =
+            cap_opportunities(opportunities, self.config.trading.max_opportunities_per_scan);
+        for opportunity in &skipped {
+            debug!("⏭️ Skipping opportunity {} ({} {:.2}%): per-scan cap of {} reached",
+                   opportunity.id, opportunity.token_pair, opportunity.profit_percentage,
+                   self.config.trading.max_opportunities_per_scan);
+        }
+        if !skipped.is_empty() {
+            info!("⏭️ Skipped {} opportunities due to the per-scan cap", skipped.len());
+        }
+        if self.config.backpressure.enabled {
+            let queue_depth = self.trade_queue.pending().await.len() + skipped.len();
+            self.adaptive_scope.write().await.observe_queue_depth(queue_depth);
+        }
+
+        for opportunity in opportunities {
+            if opportunity.is_profitable {
+                let jito_tip = match self.tip_for(&opportunity).await {
+                    Ok(jito_tip) => jito_tip,
+                    Err(e) => {
+                        warn!("⚠️ Skipping opportunity {}: cannot price its Jito tip: {}", opportunity.id, e);
+                        continue;
+                    }
+                };
+                let trade_request = TradeRequest {
+                    opportunity_id: opportunity.id.clone(),
+                    amount: opportunity.max_amount,
+                    // With a remote signer the key never enters the trade path;
+                    // an empty key means the engine's own signer
+                    private_key: match self.config.wallet.remote_signer {
+                        Some(_) => String::new(),
+                        None => self.config.wallet.private_key.clone(),
+                    },
+                    max_slippage: self.config.risk_settings.max_slippage,
+                    priority_fee: 1000, // Default priority fee
+                    use_jito: self.jito_client.is_some(),
+                    jito_tip: jito_tip.to_string(),
+                };
+
+                match self.execute_trade(trade_request).await {
+                    Ok(response) => {
+                        if response.success {
+                            info!("✅ Trade executed successfully: {}", response.transaction_id);
+                        } else {
+                            warn!("❌ Trade failed: {}", response.error_message);
+                        }
+                    }
+                    Err(e) => {
+                        error!("❌ Trade execution error: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn rebalance_portfolio(&self) -> Result<()> {
+        let jupiter_client = self.jupiter_client.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Jupiter client not available"))?;
+
+        let mut portfolio = self.portfolio_manager.get_portfolio().await?;
+        if self.config.wallet.consolidate_wsol {
+            portfolio = portfolio.consolidate_sol(self.config.wallet.min_sol_balance);
+        }
+        let tokens = jupiter_client.get_tokens().await?;
+        let decimals = tokens.iter().map(|(mint, token)| (mint.clone(), token.decimals)).collect();
+
+        let trades = compute_rebalance_trades(&portfolio, &self.config, &decimals);
+        for mut trade in trades {
+            let slippage_bps = (trade.slippage * 100.0) as u16;
+            // Rebalancing trades on what it sees, so always quote fresh
+            let quote = self.get_jupiter_quote(&trade.input_mint, &trade.output_mint, trade.amount, slippage_bps, 0, None).await?;
+
+            if let Err(e) = self.cross_check_quote(&quote).await {
+                warn!("⚖️ Skipping rebalance {} -> {}: {}", trade.input_mint, trade.output_mint, e);
+                continue;
+            }
+
+            // Only rebalance opportunistically, when the swap is cheap
+            let max_cost = self.config.rebalancing.max_cost_pct.min(self.config.jupiter.max_price_impact_pct);
+            if quote.price_impact_pct > max_cost {
+                debug!("⏭️ Rebalance {} -> {} too expensive ({:.2}% impact)",
+                       trade.input_mint, trade.output_mint, quote.price_impact_pct);
+                continue;
+            }
+
+            // The risk limits weigh the swap by what it sells
+            let value_usd = match decimals.get(&trade.input_mint) {
+                Some(&token_decimals) => match (to_ui_amount(trade.amount, token_decimals), self.price_usd(&trade.input_mint).await) {
+                    (Ok(amount), Ok(price)) => amount * price,
+                    (Err(e), _) | (_, Err(e)) => {
+                        warn!("⚖️ Skipping rebalance {} -> {}: cannot value it: {}", trade.input_mint, trade.output_mint, e);
+                        continue;
+                    }
+                },
+                None => {
+                    warn!("⚖️ Skipping rebalance {} -> {}: unknown decimals", trade.input_mint, trade.output_mint);
+                    continue;
+                }
+            };
+
+            trade.created_token_accounts = self.missing_token_accounts(std::slice::from_ref(&trade.output_mint)).await;
+            match self.submit_housekeeping_swap("rebalance", trade, value_usd).await? {
+                Some(response) if response.success => info!("⚖️ Rebalance swap landed: {}", response.transaction_id),
+                Some(response) => warn!("⚠️ Rebalance swap {} failed: {}", response.transaction_id, response.error_message),
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn check_oracle_deviation(&self, opportunity: &ArbitrageOpportunity) -> Result<()> {
+        let Some(oracle) = &self.oracle else {
+            return Ok(());
+        };
+
+        let (base_mint, quote_mint) = self.trade_mints(opportunity)?;
+        let oracle_price = oracle.price(&base_mint).await? / oracle.price(&quote_mint).await?;
+        // A zero or broken price on either side would turn the deviation into NaN, which passes any threshold
+        if !oracle_price.is_finite() || oracle_price <= 0.0 {
+            return Err(anyhow::anyhow!("oracle price {} for {} is unusable", oracle_price, opportunity.token_pair));
+        }
+
+        let max_deviation = self.config.oracle.max_deviation_pct;
+        for execution_price in [opportunity.buy_price, opportunity.sell_price] {
+            if !execution_price.is_finite() || execution_price <= 0.0 {
+                return Err(anyhow::anyhow!("execution price {} is unusable", execution_price));
+            }
+            let deviation = deviation_pct(execution_price, oracle_price);
+            if deviation > max_deviation {
+                return Err(anyhow::anyhow!(
+                    "price {:.6} deviates {:.2}% from oracle {:.6} (max {:.2}%)",
+                    execution_price, deviation, oracle_price, max_deviation
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Re-quotes the same swap from the secondary source. Disagreement (or an
+    // unavailable secondary) is an error when the cross-check is fatal
+    async fn cross_check_quote(&self, quote: &JupiterQuote) -> Result<()> {
+        let Some(secondary) = &self.secondary_quote_source else {
+            return Ok(());
+        };
+        let settings = &self.config.quote_cross_check;
+
+        let problem = match secondary.quote_out_amount(&quote.input_mint, &quote.output_mint, quote.in_amount).await {
+            Ok(secondary_out) => {
+                let divergence = quote_divergence_pct(quote.out_amount, secondary_out);
+                if divergence <= settings.max_divergence_pct {
+                    return Ok(());
+                }
+                anyhow::anyhow!(
+                    "quoted {} out but {} quotes {} ({:.2}% apart, max {:.2}%)",
+                    quote.out_amount, secondary.name(), secondary_out, divergence, settings.max_divergence_pct
+                )
+            }
+            Err(e) => anyhow::anyhow!("cannot cross-check with {}: {}", secondary.name(), e),
+        };
+
+        if settings.fatal {
+            Err(problem)
+        } else {
+            warn!("⚖️ Quote cross-check {} -> {}: {}", quote.input_mint, quote.output_mint, problem);
+            Ok(())
+        }
+    }
+
+    fn find_blocked_pool(quote: &JupiterQuote, blocked_pools: &HashSet<String>) -> Option<String> {
+        quote.route_plan
+            .iter()
+            .map(|step| &step.swap_info.amm_key)
+            .find(|amm_key| blocked_pools.contains(*amm_key))
+            .cloned()
+    }
+
+    fn is_unconfirmed(error_message: &str) -> bool {
+        error_message == ArbitrageError::ConfirmationTimeout.to_string()
+    }
+
+    fn is_still_profitable(&self, opportunity: &ArbitrageOpportunity, tip_cost: f64) -> bool {
+        opportunity.profit_percentage >= self.config.risk_settings.min_profit_threshold
+            && opportunity.estimated_profit > opportunity.gas_cost
+            && opportunity.estimated_profit > tip_cost
+    }
+
+    // Full transaction cost in the profit denomination: base signature fee,
+    // priority fee, and rent for any token account the swap has to create for `mints`
+    async fn estimate_gas_cost(&self, mints: &[String]) -> Result<f64> {
+        let created_token_accounts = self.missing_token_accounts(mints).await;
+
+        let gas = GasCost {
+            signatures: 1,
+            priority_fee_lamports: self.config.jupiter.prioritization_fee_lamports,
+            created_token_accounts,
+        };
+        let gas_cost = self.denominate(gas.total_sol(), WSOL_MINT).await?;
+
+        debug!("⛽ Gas estimate: {} lamports ({} new token accounts) = {:.4} in profit denomination",
+               gas.total_lamports(), created_token_accounts, gas_cost);
+        Ok(gas_cost)
+    }
+
+    // Creates the wallet's associated token account for every mint that lacks
+    // one, in a single transaction. Returns the mints that needed creating;
+    // afterwards the gas estimate no longer charges their rent.
+    pub async fn ensure_atas(&self, mints: &[String]) -> Result<Vec<String>> {
+        let mut missing = Vec::new();
+        for mint in mints {
+            if !missing.contains(mint) && !self.token_account_exists(mint).await? {
+                missing.push(mint.clone());
+            }
+        }
+        if missing.is_empty() {
+            debug!("🪙 All {} token accounts already exist", mints.len());
+            return Ok(missing);
+        }
+
+        let owner = self.signer()?.pubkey();
+        let instructions = missing.iter()
+            .map(|mint| create_associated_token_account_instruction(&owner, &owner, mint))
+            .collect::<Result<Vec<_>>>()?;
+        let signature = self.send_signed(&instructions).await?;
+
+        let mut known = self.known_token_accounts.write().await;
+        for mint in &missing {
+            known.insert(associated_token_address(&self.config.wallet.public_key, mint)?);
+        }
+        info!("🪙 Created {} token accounts ({}): {}", missing.len(), missing.join(", "), signature);
+        Ok(missing)
+    }
+
+    // Token accounts a swap touching `mints` would have to open. One whose
+    // existence can't be checked is counted as missing, so its rent is
+    // charged rather than silently left out of the gas.
+    async fn missing_token_accounts(&self, mints: &[String]) -> u32 {
+        let mut missing = 0;
+        for mint in mints {
+            match self.token_account_exists(mint).await {
+                Ok(true) => {}
+                Ok(false) => missing += 1,
+                Err(e) => {
+                    warn!("⚠️ Cannot check token account for {}, assuming it must be created: {}", mint, e);
+                    missing += 1;
+                }
+            }
+        }
+        missing
+    }
+
+    async fn token_account_exists(&self, mint: &str) -> Result<bool> {
+        // wSOL accounts are opened and closed within the swap, refunding the rent
+        if mint == WSOL_MINT {
+            return Ok(true);
+        }
+
+        let address = associated_token_address(&self.config.wallet.public_key, mint)?;
+        if self.known_token_accounts.read().await.contains(&address) {
+            return Ok(true);
+        }
+
+        let result = self.rpc_client
+            .call("getAccountInfo", json!([address, { "encoding": "base64" }]))
+            .await?;
+        let exists = !result["value"].is_null();
+        if exists {
+            self.known_token_accounts.write().await.insert(address);
+        }
+        Ok(exists)
+    }
+
+    async fn price_usd(&self, mint: &str) -> Result<f64> {
+        if let Some(jupiter_client) = &self.jupiter_client {
+            match jupiter_client.get_price(&[mint.to_string()]).await {
+                Ok(fetch) => {
+                    if let Some(price) = fetch.prices.get(mint) {
+                        return Ok(*price);
+                    }
+                }
+                Err(e) => warn!("⚠️ Live price for {} unavailable, using cached price: {}", mint, e),
+            }
+        }
+
+        self.warm_cache.read().await.prices.get(mint).copied()
+            .ok_or_else(|| anyhow::anyhow!("No price available for {}", mint))
+    }
+
+    // Quotes `amount`, halving it while the price impact is over the pair's
+    // limit, for at most `impact_size_reductions` halvings and never below the
+    // minimum notional. Returns the last quote, which may still be over the
+    // limit, and the fraction of `amount` it was quoted for.
+    async fn quote_within_impact(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+        pair_settings: &PairSettings,
+    ) -> Result<(JupiterQuote, f64)> {
+        let (slippage_bps, ttl_ms) = (pair_settings.slippage_bps, pair_settings.quote_cache_ttl_ms);
+        let mut size = amount;
+        let mut quote = self.get_jupiter_quote(input_mint, output_mint, size, slippage_bps, ttl_ms, None).await?;
+
+        for _ in 0..self.config.trading.impact_size_reductions {
+            if quote.price_impact_pct <= pair_settings.max_price_impact_pct {
+                break;
+            }
+            let reduced = match self.quantize_trade_size(input_mint, size / 2).await? {
+                Some(reduced) if reduced > 0 && reduced < size => reduced,
+                _ => break,
+            };
+            debug!("📉 {} -> {} impact {:.2}% at {}, re-quoting at {}",
+                   input_mint, output_mint, quote.price_impact_pct, size, reduced);
+            size = reduced;
+            quote = self.get_jupiter_quote(input_mint, output_mint, size, slippage_bps, ttl_ms, None).await?;
+        }
+
+        if size < amount && quote.price_impact_pct <= pair_settings.max_price_impact_pct {
+            info!("📉 Reduced {} -> {} from {} to {} to keep impact at {:.2}%",
+                  input_mint, output_mint, amount, size, quote.price_impact_pct);
+        }
+        Ok((quote, size as f64 / amount as f64))
+    }
+
+    // Rounds a raw trade size down to the mint's configured lot size. None when
+    // the result is worth less than the minimum notional.
+    async fn quantize_trade_size(&self, mint: &str, raw_amount: u64) -> Result<Option<u64>> {
+        let quantization = &self.config.size_quantization;
+        let step = quantization.steps.get(mint).copied();
+        if step.is_none() && quantization.min_notional <= 0.0 {
+            return Ok(Some(raw_amount));
+        }
+
+        let decimals = self.token_decimals(mint).await?;
+        let amount = match step {
+            Some(step) => quantize_raw_amount(raw_amount, step, decimals)?,
+            None => raw_amount,
+        };
+        if amount == 0 {
+            return Ok(None);
+        }
+
+        if quantization.min_notional > 0.0 {
+            let notional = self.denominate(to_ui_amount(amount, decimals)?, mint).await?;
+            if notional < quantization.min_notional {
+                debug!("🪙 {} raw {} is worth {:.4}, below the {:.4} minimum", amount, mint, notional, quantization.min_notional);
+                return Ok(None);
+            }
+        }
+        Ok(Some(amount))
+    }
+
+    async fn token_decimals(&self, mint: &str) -> Result<u8> {
+        if let Some(decimals) = self.warm_cache.read().await.token_decimals.get(mint) {
+            return Ok(*decimals);
+        }
+        match &self.jupiter_client {
+            Some(jupiter_client) => jupiter_client.get_token_decimals(mint).await,
+            None => Err(anyhow::anyhow!("Decimals for {} unknown without Jupiter", mint)),
+        }
+    }
+
+    // The flat default, or the dynamic estimate when enabled. A failed fetch
+    // falls back to the last estimate, then the default.
+    async fn current_tip_lamports(&self) -> u64 {
+        let Some(tip_estimator) = &self.tip_estimator else {
+            return DEFAULT_JITO_TIP_LAMPORTS;
+        };
+        match tip_estimator.estimate(self.clock.now_ms()).await {
+            Ok(tip) => tip,
+            Err(e) => {
+                warn!("⚠️ Jito tip floor unavailable, reusing the last tip: {}", e);
+                tip_estimator.latest().await.unwrap_or(DEFAULT_JITO_TIP_LAMPORTS)
+            }
+        }
+    }
+
+    // Tip to bid on an opportunity. A dynamic tip is capped at
+    // `max_tip_edge_share` of the net profit, so contested slots can't
+    // outbid the trade's own edge.
+    async fn tip_for(&self, opportunity: &ArbitrageOpportunity) -> Result<u64> {
+        if self.jito_client.is_none() {
+            return Ok(0);
+        }
+        let tip = self.current_tip_lamports().await;
+        if self.tip_estimator.is_none() {
+            return Ok(tip);
+        }
+
+        let sol_value = self.denominate(1.0, WSOL_MINT).await?;
+        if sol_value <= 0.0 {
+            return Err(anyhow::anyhow!("Invalid SOL price {}", sol_value));
+        }
+        let edge_lamports = opportunity.estimated_profit.max(0.0) * self.config.jito.max_tip_edge_share
+            / sol_value * LAMPORTS_PER_SOL;
+        let capped = tip.min(edge_lamports as u64);
+        if capped < tip {
+            debug!("💰 Capping Jito tip for {} at {} lamports (estimate {})", opportunity.id, capped, tip);
+        }
+        Ok(capped)
+    }
+
+    // What the current Jito tip costs in the profit denomination; nothing
+    // without Jito
+    async fn tip_cost(&self) -> Result<f64> {
+        if self.jito_client.is_none() {
+            return Ok(0.0);
+        }
+        self.denominate(self.current_tip_lamports().await as f64 / LAMPORTS_PER_SOL, WSOL_MINT).await
+    }
+
+    // Converts an amount of `mint` into the configured profit denomination so
+    // profits across pairs are comparable and summable
+    async fn denominate(&self, amount: f64, mint: &str) -> Result<f64> {
+        let denomination = &self.config.trading.profit_denomination_mint;
+        if mint == denomination {
+            return Ok(amount);
+        }
+
+        let denomination_price = self.price_usd(denomination).await?;
+        if denomination_price <= 0.0 {
+            return Err(anyhow::anyhow!("Invalid price {} for profit denomination {}", denomination_price, denomination));
+        }
+        Ok(amount * self.price_usd(mint).await? / denomination_price)
+    }
+
+    async fn get_opportunity_by_id(&self, id: &str) -> Result<ArbitrageOpportunity> {
+        if let Some(cycle) = self.cycle_opportunities.read().await.get(id) {
+            return Ok(cycle.clone());
+        }
+        // In a real implementation, this would fetch from a database
+        // For now, return a mock opportunity
+        Ok(ArbitrageOpportunity {
+            id: id.to_string(),
+            token_pair: "SOL/USDC".to_string(),
+            buy_dex: "Raydium".to_string(),
+            sell_dex: "Orca".to_string(),
+            buy_price: 100.0,
+            sell_price: 101.0,
+            profit_percentage: 1.0,
+            estimated_profit: 10.0,
+            max_amount: 1000.0,
+            gas_cost: 0.005,
+            timestamp: self.clock.now_ms(),
+            buy_pool: "pool_address_1".to_string(),
+            sell_pool: "pool_address_2".to_string(),
+            slippage: 0.5,
+            is_profitable: true,
+            rejection_reason: None,
+            price_impact: 0.0,
+            price_timestamp: self.clock.now_ms(),
+            expires_at: 0,
+            strategy: StrategyKind::Direct,
+            edge_over_fees_pct: 0.0,
+        })
+    }
+
+    pub async fn usd_to_raw(&self, mint: &str, usd: f64) -> Result<u64> {
+        if let Some(jupiter_client) = &self.jupiter_client {
+            jupiter_client.usd_to_raw(mint, usd).await
+        } else {
+            Err(anyhow::anyhow!("Jupiter client not available for USD price conversion"))
+        }
+    }
+
+    // A later leg passes the slot of the leg before it, so the whole trade is
+    // priced against one state of the chain; pinned quotes skip the cache
+    async fn get_jupiter_quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+        slippage_bps: u16,
+        cache_ttl_ms: u64,
+        context_slot: Option<u64>,
+    ) -> Result<JupiterQuote> {
+        if let Some(jupiter_client) = &self.jupiter_client {
+            let key = QuoteKey {
+                input_mint: input_mint.to_string(),
+                output_mint: output_mint.to_string(),
+                amount,
+                slippage_bps,
+            };
+            if cache_ttl_ms > 0 && context_slot.is_none() {
+                if let Some(quote) = self.quote_cache.read().await.get(&key, cache_ttl_ms, self.clock.now_ms()) {
+                    debug!("♻️ Reusing cached quote {} -> {} ({}ms TTL)", input_mint, output_mint, cache_ttl_ms);
+                    return Ok(quote);
+                }
+            }
+            
+            let request = JupiterQuoteRequest {
+                input_mint: input_mint.to_string(),
+                output_mint: output_mint.to_string(),
+                amount,
+                slippage_bps,
+                swap_mode: Some("ExactIn".to_string()),
+                dexes: Some(self.config.jupiter.preferred_dexes.clone()),
+                exclude_dexes: Some(self.config.jupiter.excluded_dexes.clone()),
+                platform_fee_bps: None,
+                max_accounts: None,
+                context_slot,
+            };
+
+            let quote = jupiter_client.get_quote(request).await?;
+            let max_ttl_ms = self.config.max_quote_cache_ttl_ms();
+            if max_ttl_ms > 0 {
+                self.quote_cache.write().await.insert(key, quote.clone(), max_ttl_ms);
+            }
+            Ok(quote)
+        } else {
+            Err(anyhow::anyhow!("Jupiter client not available"))
+        }
+    }
+
+    fn extract_token_mints(&self, token_pair: &str) -> Result<(String, String)> {
+        // Simplified token mint extraction
+        // In a real implementation, you'd have a mapping from token pairs to mint addresses
+        let parts: Vec<&str> = token_pair.split('/').collect();
+        if parts.len() != 2 {
+            return Err(anyhow::anyhow!("Invalid token pair format: {}", token_pair));
+        }
+
+        Ok((Self::mint_for_symbol(parts[0])?, Self::mint_for_symbol(parts[1])?))
+    }
+
+    // This is a simplified mapping - in reality, you'd have a proper token registry
+    fn mint_for_symbol(symbol: &str) -> Result<String> {
+        match symbol {
+            "SOL" => Ok("So11111111111111111111111111111111111111112".to_string()),
+            "USDC" => Ok("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string()),
+            "USDT" => Ok("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB".to_string()),
+            _ => Err(anyhow::anyhow!("Unknown token: {}", symbol)),
+        }
+    }
+
+    // Measured from the oldest of the trade's quotes
+    fn check_latency_budget(&self, built: &BuiltTrade) -> Result<()> {
+        let Some(received_at) = built.quotes.iter().map(|quote| quote.received_at).min() else {
+            return Ok(());
+        };
+
+        let latency_ms = self.clock.now_ms() - received_at;
+        let budget_ms = self.config.trading.execution_latency_budget_ms;
+        debug!("⏱️ Quote-to-execution latency: {}ms (budget {}ms)", latency_ms, budget_ms);
+
+        if latency_ms > budget_ms as i64 {
+            return Err(anyhow::anyhow!(
+                "Quote-to-execution latency {}ms exceeded budget of {}ms", latency_ms, budget_ms
+            ));
+        }
+        Ok(())
+    }
+
+    // Simulates the built transaction and checks the round trip still pays:
+    // the gain in the traded mint must reach the quoted one, less the
+    // tolerance on the final leg and, for SOL, the fees and tip it pays.
+    async fn verify_simulated_output(&self, built: &BuiltTrade, opportunity: &ArbitrageOpportunity, tip_lamports: u64) -> Result<()> {
+        let (Some(simulator), Some(first), Some(last)) = (&self.simulator, built.quotes.first(), built.quotes.last()) else {
+            return Ok(());
+        };
+        let owner = built.transaction.message.static_account_keys()[0].to_string();
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&built.transaction)?);
+        let simulated_gain = simulator.simulate_balance_change(&encoded, &owner, &last.output_mint).await?;
+
+        let tolerance = self.config.trading.simulation_tolerance_bps as f64 / 10_000.0;
+        let fees = if last.output_mint == WSOL_MINT {
+            built.budget.priority_fee_lamports() as i128
+                + tip_lamports as i128
+                + (built.transaction.signatures.len() as u64 * BASE_FEE_LAMPORTS_PER_SIGNATURE) as i128
+        } else {
+            0
+        };
+        let quoted_gain = last.out_amount as i128 - first.in_amount as i128;
+        let min_acceptable = (last.out_amount as f64 * (1.0 - tolerance)) as i128 - first.in_amount as i128 - fees;
+        if simulated_gain < min_acceptable {
+            warn!("🧪 Simulated gain {} for {} is below quoted {} (tolerance {} bps), aborting",
+                  simulated_gain, opportunity.id, quoted_gain, self.config.trading.simulation_tolerance_bps);
+            self.reject_opportunity(opportunity, RejectionReason::SimulationMismatch).await;
+            return Err(ArbitrageError::SimulationMismatch(format!(
+                "simulated gain {} vs quoted {}", simulated_gain, quoted_gain
+            )).into());
+        }
+
+        debug!("🧪 Simulation verified: gain {} (quoted {})", simulated_gain, quoted_gain);
+        Ok(())
+    }
+
+    // With ExactOut the first leg's output is fixed, so the guardrail is what
+    // its input may cost: the worst case (other_amount_threshold) must fit the
+    // input budget, and the cycle's minimum return must still beat it after gas
+    async fn revalidate_exact_out(&self, quotes: &[JupiterQuote], opportunity: &ArbitrageOpportunity) -> Result<()> {
+        let (Some(first), Some(last)) = (quotes.first().filter(|q| q.swap_mode == SwapMode::ExactOut), quotes.last()) else {
+            return Ok(());
+        };
+        let max_input = first.other_amount_threshold.max(first.in_amount);
+        let input_decimals = self.token_decimals(&first.input_mint).await?;
+
+        if let Some(budget_usd) = self.config.trading.max_exact_out_input_usd {
+            let input_usd = to_ui_amount(max_input, input_decimals)? * self.price_usd(&first.input_mint).await?;
+            if input_usd > budget_usd {
+                warn!("💸 ExactOut leg of {} may spend {} of {} (${:.2}), over the ${:.2} budget",
+                      opportunity.token_pair, max_input, first.input_mint, input_usd, budget_usd);
+                self.record_rejection(&opportunity.token_pair, RejectionReason::InputBudgetExceeded).await;
+                return Err(ArbitrageError::InvalidRequest(format!(
+                    "ExactOut input ${:.2} exceeds budget ${:.2}", input_usd, budget_usd
+                )).into());
+            }
+        }
+
+        let min_return = if last.other_amount_threshold > 0 { last.other_amount_threshold } else { last.out_amount };
+        let output_value = self.denominate(
+            to_ui_amount(min_return, self.token_decimals(&last.output_mint).await?)?,
+            &last.output_mint,
+        ).await?;
+        let input_cost = self.denominate(to_ui_amount(max_input, input_decimals)?, &first.input_mint).await?;
+        let net_profit = output_value - input_cost - opportunity.gas_cost;
+        if net_profit <= 0.0 {
+            warn!("📉 ExactOut trade for {} nets {:.4} at worst-case input {}", opportunity.token_pair, net_profit, max_input);
+            self.record_rejection(&opportunity.token_pair, RejectionReason::BelowGasCost).await;
+            return Err(ArbitrageError::InvalidRequest(format!(
+                "ExactOut trade nets {:.4} after input cost and gas", net_profit
+            )).into());
+        }
+        Ok(())
+    }
+
+    fn clone_for_task(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            price_source: self.price_source.clone(),
+            risk_manager: self.risk_manager.clone(),
+            portfolio_manager: self.portfolio_manager.clone(),
+            jito_client: self.jito_client.clone(),
+            jupiter_client: self.jupiter_client.clone(),
+            monitoring: self.monitoring.clone(),
+            is_running: self.is_running.clone(),
+            blocked_pools: self.blocked_pools.clone(),
+            simulator: self.simulator.clone(),
+            rejection_counts: self.rejection_counts.clone(),
+            strategy_pnl: self.strategy_pnl.clone(),
+            strategy_budgets: self.strategy_budgets.clone(),
+            max_position_size: self.max_position_size.clone(),
+            executor: self.executor.clone(),
+            warm_cache: self.warm_cache.clone(),
+            supervisor: self.supervisor.clone(),
+            oracle: self.oracle.clone(),
+            secondary_quote_source: self.secondary_quote_source.clone(),
+            watchlist: self.watchlist.clone(),
+            rpc_client: self.rpc_client.clone(),
+            known_token_accounts: self.known_token_accounts.clone(),
+            pursued_pairs: self.pursued_pairs.clone(),
+            cycle_opportunities: self.cycle_opportunities.clone(),
+            trade_queue: self.trade_queue.clone(),
+            fee_estimator: self.fee_estimator.clone(),
+            tip_estimator: self.tip_estimator.clone(),
+            impact_tracker: self.impact_tracker.clone(),
+            paused_pairs: self.paused_pairs.clone(),
+            win_rate_monitor: self.win_rate_monitor.clone(),
+            quote_cache: self.quote_cache.clone(),
+            api_health: self.api_health.clone(),
+            dex_fees: self.dex_fees.clone(),
+            adaptive_scope: self.adaptive_scope.clone(),
+            history: self.history.clone(),
+            event_sink: self.event_sink.clone(),
+            slippage_tracker: self.slippage_tracker.clone(),
+            canary_states: self.canary_states.clone(),
+            mint_restrictions: self.mint_restrictions.clone(),
+            opportunity_claims: self.opportunity_claims.clone(),
+            route_availability: self.route_availability.clone(),
+            signer: self.signer.clone(),
+            opportunity_feed: self.opportunity_feed.clone(),
+            price_history: self.price_history.clone(),
+            kill_switch: self.kill_switch.clone(),
+            clock: self.clock.clone(),
+            finality: self.finality.clone(),
+            stats: self.stats.clone(),
+            blackout: self.blackout.clone(),
+        }
+    }
+}
+
+// Orders opportunities best-first by their weighted score
+pub fn rank_opportunities(opportunities: &mut [ArbitrageOpportunity], weights: &ScoringWeights, now: i64) {
+    opportunities.sort_by(|a, b| {
+        opportunity_score(b, weights, now).total_cmp(&opportunity_score(a, weights, now))
+    });
+}
+
+// Profit and the chance of keeping it, minus penalties for stale prices and
+// price impact. Win probability is approximated by how much of the edge is
+// left after the worst tolerated slippage.
+pub fn opportunity_score(opportunity: &ArbitrageOpportunity, weights: &ScoringWeights, now_ms: i64) -> f64 {
+    let edge = opportunity.profit_percentage.max(0.0);
+    let win_probability = if edge + opportunity.slippage > 0.0 {
+        edge / (edge + opportunity.slippage.max(0.0))
+    } else {
+        0.0
+    };
+    let age_secs = (now_ms - opportunity.price_timestamp).max(0) as f64 / 1000.0;
+
+    weights.profit_weight * opportunity.profit_percentage
+        + weights.probability_weight * win_probability
+        - weights.latency_penalty * age_secs
+        - weights.impact_penalty * opportunity.price_impact
+}
+
+// Net edge as a percentage of the trade's notional. Percentage costs
+// (price impact, swap and platform fees) come straight off the gross spread;
+// fixed costs (gas, tip) are spread over the notional.
+pub fn edge_over_fees_pct(gross_spread_pct: f64, percentage_costs_pct: f64, fixed_costs: f64, notional: f64) -> f64 {
+    let fixed_pct = if notional > 0.0 { fixed_costs / notional * 100.0 } else { f64::INFINITY };
+    gross_spread_pct - percentage_costs_pct - fixed_pct
+}
+
+// `equity_percent` of the portfolio, capped at `ceiling`
+pub fn equity_position_size(portfolio_value: f64, equity_percent: f64, ceiling: f64) -> f64 {
+    (portfolio_value.max(0.0) * equity_percent / 100.0).min(ceiling)
+}
+
+// Whether to pursue an opportunity given whether it was pursued last scan.
+// With zero margins this is the plain `profit >= min_profit` threshold.
+// request.amount is in the base token; a direct trade first buys it with the
+// quote token, a cycle starts from its base
+fn first_leg_amount(request: &TradeRequest, opportunity: &ArbitrageOpportunity) -> f64 {
+    match opportunity.strategy {
+        StrategyKind::MultiHop => request.amount,
+        _ => request.amount * opportunity.buy_price,
+    }
+}
+
+// Only a transaction that expired or was dropped is resubmitted, and only
+// while retries remain; a revert is final
+pub fn should_retry_expired(result: &TradeResponse, retries: u32, max_retries: u32) -> bool {
+    !result.success && is_blockhash_expired(&result.error_message) && retries < max_retries
+}
+
+fn is_blockhash_expired(error_message: &str) -> bool {
+    // Expired or dropped transactions are retryable; program errors (reverts) are not
+    let message = error_message.to_lowercase();
+    message.contains("blockhash not found")
+        || message.contains("block height exceeded")
+        || message.contains("blockhash expired")
+        || message.contains("transaction dropped")
+        || message.contains("expired before landing")
+}
+
+// The opportunity at fresh prices: `spent` of the first leg's input bought
+// `held` of the middle token, which sold back for `returned`, all in UI
+// units; `profit` is `returned - spent` in the profit denomination. The id
+// is kept so claims and pending entries still match.
+pub fn reprice_opportunity(
+    opportunity: &ArbitrageOpportunity,
+    spent: f64,
+    held: f64,
+    returned: f64,
+    profit: f64,
+    now_ms: i64,
+) -> Result<ArbitrageOpportunity> {
+    if !(spent > 0.0 && held > 0.0) {
+        return Err(anyhow::anyhow!("Re-quote of {} filled nothing", opportunity.id));
+    }
+    Ok(ArbitrageOpportunity {
+        buy_price: spent / held,
+        sell_price: returned / held,
+        profit_percentage: (returned / spent - 1.0) * 100.0,
+        estimated_profit: profit - opportunity.gas_cost,
+        timestamp: now_ms,
+        price_timestamp: now_ms,
+        ..opportunity.clone()
+    })
+}
+
+pub fn hysteresis_decision(was_pursued: bool, profit_percentage: f64, min_profit: f64, enter_margin: f64, exit_margin: f64) -> bool {
+    if was_pursued {
+        profit_percentage >= min_profit - exit_margin
+    } else {
+        profit_percentage >= min_profit + enter_margin
+    }
+}
+
+// AMM keys used by both legs, and the share of the first leg's price impact
+// that lands on them (weighted by how much of the first leg they carry)
+pub fn route_overlap(first_leg: &JupiterQuote, second_leg: &JupiterQuote) -> (Vec<String>, f64) {
+    let second_pools: HashSet<&str> = second_leg.route_plan.iter()
+        .map(|step| step.swap_info.amm_key.as_str())
+        .collect();
+
+    let mut shared_pools = Vec::new();
+    let mut shared_percent = 0u32;
+    for step in &first_leg.route_plan {
+        if second_pools.contains(step.swap_info.amm_key.as_str()) {
+            if !shared_pools.contains(&step.swap_info.amm_key) {
+                shared_pools.push(step.swap_info.amm_key.clone());
+            }
+            shared_percent += step.percent as u32;
+        }
+    }
+
+    let shared_fraction = (shared_percent as f64 / 100.0).min(1.0);
+    (shared_pools, first_leg.price_impact_pct * shared_fraction)
+}
+
+// Splits ranked opportunities into the top `cap` to act on and the rest
+pub fn cap_opportunities(
+    mut opportunities: Vec<ArbitrageOpportunity>,
+    cap: usize,
+) -> (Vec<ArbitrageOpportunity>, Vec<ArbitrageOpportunity>) {
+    let skipped = opportunities.split_off(cap.min(opportunities.len()));
+    (opportunities, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opportunity() -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            id: "opp".to_string(),
+            token_pair: "SOL/USDC".to_string(),
+            buy_dex: "Raydium".to_string(),
+            sell_dex: "Orca".to_string(),
+            buy_price: 100.0,
+            sell_price: 101.0,
+            profit_percentage: 1.0,
+            estimated_profit: 0.9,
+            max_amount: 1.0,
+            gas_cost: 0.1,
+            timestamp: 0,
+            buy_pool: String::new(),
+            sell_pool: String::new(),
+            slippage: 0.5,
+            is_profitable: true,
+            rejection_reason: None,
+            price_impact: 0.0,
+            price_timestamp: 0,
+            expires_at: 0,
+            strategy: StrategyKind::Direct,
+            edge_over_fees_pct: 0.0,
+        }
+    }
+
+    fn submitted(success: bool, error: Option<ArbitrageError>) -> TradeResponse {
+        TradeResponse {
+            transaction_id: "sig".to_string(),
+            success,
+            ..TradeResponse::rejected(error.map(|e| e.to_string()).unwrap_or_default())
+        }
+    }
+
+    #[test]
+    fn one_expiry_is_requoted_and_resubmitted() {
+        let expired = submitted(false, Some(ArbitrageError::BlockhashExpired));
+        assert!(should_retry_expired(&expired, 0, 2));
+
+        // Prices held while the first attempt was in flight: still worth resubmitting
+        let requoted = reprice_opportunity(&opportunity(), 100.0, 1.0, 100.8, 0.8, 5_000).unwrap();
+        assert_eq!(requoted.id, "opp");
+        assert!((requoted.profit_percentage - 0.8).abs() < 1e-9);
+        assert!((requoted.estimated_profit - 0.7).abs() < 1e-9);
+        assert_eq!(requoted.price_timestamp, 5_000);
+
+        let landed = submitted(true, None);
+        assert!(!should_retry_expired(&landed, 1, 2));
+    }
+
+    #[test]
+    fn reverts_and_exhausted_retries_are_final() {
+        let reverted = submitted(false, Some(ArbitrageError::TransactionFailed("custom program error: 0x1771".into())));
+        assert!(!should_retry_expired(&reverted, 0, 2));
+
+        let expired = submitted(false, Some(ArbitrageError::BlockhashExpired));
+        assert!(!should_retry_expired(&expired, 2, 2));
+    }
+
+    fn test_config() -> Config {
+        let mut config = Config::default();
+        config.jupiter.enabled = false;
+        config.wallet.public_key = solana_sdk::pubkey::Pubkey::new_unique().to_string();
+        config.trading.pending_trades_path = std::env::temp_dir()
+            .join(format!("engine_test_pending_{}.json", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        config.trading.history_path = None;
+        config
+    }
+
+    fn engine() -> ArbitrageEngine {
+        engine_with(test_config())
+    }
+
+    fn engine_with(config: Config) -> ArbitrageEngine {
+        ArbitrageEngine::new(
+            config.clone(),
+            Arc::new(DexMonitor::new(config.dex_endpoints.clone())),
+            Arc::new(RwLock::new(RiskManager::new(config.risk_settings.clone()))),
+            Arc::new(PortfolioManager::new(config.clone())),
+            None,
+            None,
+            Arc::new(MonitoringService::new(config.monitoring.clone())),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn maintenance_halts_every_kind_of_swap() {
+        let engine = engine();
+        assert_eq!(engine.execution_halt_reason().await, None);
+
+        *engine.api_health.write().await = ApiHealth::Maintenance;
+        assert!(engine.execution_halt_reason().await.is_some());
+        // Held before it even looks for a Jupiter client, which this engine lacks
+        let swap = SwapRequest {
+            input_mint: "DUST".to_string(),
+            output_mint: WSOL_MINT.to_string(),
+            amount: 1_000,
+            user_public_key: String::new(),
+            slippage: 1.0,
+            priority_fee: 0,
+            allowed_dexes: None,
+            excluded_dexes: None,
+            use_jupiter: true,
+            dynamic_slippage: None,
+            compute_unit_price_micro_lamports: None,
+            tracking_account: None,
+            created_token_accounts: 0,
+        };
+        assert!(engine.submit_housekeeping_swap("dust", swap.clone(), 1.0).await.unwrap().is_none());
+
+        *engine.api_health.write().await = ApiHealth::Healthy;
+        assert!(engine.submit_housekeeping_swap("dust", swap, 1.0).await.is_err());
+    }
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn leg_quote(input_mint: &str, in_amount: u64, output_mint: &str, out_amount: u64, swap_mode: SwapMode, other_amount_threshold: u64) -> JupiterQuote {
+        JupiterQuote {
+            input_mint: input_mint.to_string(),
+            in_amount,
+            output_mint: output_mint.to_string(),
+            out_amount,
+            platform_fee_amount: 0,
+            swap_mode,
+            other_amount_threshold,
+            price_impact_pct: 0.0,
+            route_plan: Vec::new(),
+            context_slot: 0,
+            time_taken: 0.0,
+            slippage_bps: 50,
+            received_at: 0,
+            request_id: String::new(),
+        }
+    }
+
+    // USDC at $1 and SOL at $100, as the warm cache would hold them
+    async fn price_usdc_and_sol(engine: &ArbitrageEngine) {
+        let mut cache = engine.warm_cache.write().await;
+        cache.token_decimals.insert(USDC.to_string(), 6);
+        cache.token_decimals.insert(WSOL_MINT.to_string(), 9);
+        cache.prices.insert(USDC.to_string(), 1.0);
+        cache.prices.insert(WSOL_MINT.to_string(), 100.0);
+    }
+
+    #[tokio::test]
+    async fn exact_out_leg_over_the_input_budget_is_rejected() {
+        let mut config = test_config();
+        config.trading.max_exact_out_input_usd = Some(150.0);
+        let engine = engine_with(config);
+        price_usdc_and_sol(&engine).await;
+
+        // Buys exactly 1.5 SOL for up to 151 USDC, then sells it for at least 152
+        let quotes = vec![
+            leg_quote(USDC, 150_000_000, WSOL_MINT, 1_500_000_000, SwapMode::ExactOut, 151_000_000),
+            leg_quote(WSOL_MINT, 1_500_000_000, USDC, 152_500_000, SwapMode::ExactIn, 152_000_000),
+        ];
+        assert!(engine.revalidate_exact_out(&quotes, &opportunity()).await.is_err());
+        assert_eq!(engine.get_rejection_counts().await.get(&RejectionReason::InputBudgetExceeded), Some(&1));
+
+        // The same trade within budget nets 152 - 151 - 0.1 gas
+        let mut config = test_config();
+        config.trading.max_exact_out_input_usd = Some(200.0);
+        let engine = engine_with(config);
+        price_usdc_and_sol(&engine).await;
+        assert!(engine.revalidate_exact_out(&quotes, &opportunity()).await.is_ok());
+        assert!(engine.get_rejection_counts().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn daily_loss_limit_resets_by_the_engines_clock_under_skew() {
+        use crate::clock::ManualClock;
+
+        // A month behind the machine's clock: anything still reading system
+        // time would see the loss as a month old, or never age it out
+        let day_ms = 24 * 60 * 60 * 1000;
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now().timestamp_millis() - 30 * day_ms));
+        let mut config = test_config();
+        config.simulation.enabled = true;
+        let engine = engine_with(config).with_clock(clock.clone());
+
+        let loss = TradeResponse { actual_profit: -150.0, ..TradeResponse::rejected("") };
+        engine.account_trade("lost", "SOL/USDC", StrategyKind::Direct, &loss).await;
+        let request = TradeRequest {
+            opportunity_id: "next".to_string(),
+            amount: 1.0,
+            private_key: String::new(),
+            max_slippage: 0.5,
+            priority_fee: 0,
+            use_jito: false,
+            jito_tip: "0".to_string(),
+        };
+        let response = engine.execute_claimed_trade(request.clone()).await.unwrap();
+        assert_eq!(response.error_message, "Risk check failed");
+
+        clock.advance(day_ms - 1);
+        let response = engine.execute_claimed_trade(request.clone()).await.unwrap();
+        assert_eq!(response.error_message, "Risk check failed");
+
+        clock.advance(1);
+        let response = engine.execute_claimed_trade(request).await.unwrap();
+        assert_ne!(response.error_message, "Risk check failed");
+    }
+
+    #[test]
+    fn edge_over_fees_matches_a_worked_example() {
+        // 1.5% gross spread on a $1,000 trade; 0.2% + 0.1% impact and 0.25% +
+        // 0.30% swap fees come off the top, then $0.60 gas and a $0.40 tip
+        // are 0.1% of the notional: 1.5 - 0.85 - 0.1 = 0.55% ($5.50)
+        let percentage_costs = 0.2 + 0.1 + 0.25 + 0.30;
+        let edge = edge_over_fees_pct(1.5, percentage_costs, 0.60 + 0.40, 1_000.0);
+        assert!((edge - 0.55).abs() < 1e-9);
+
+        // Costs over the spread leave a negative edge, and no size can pay fixed costs
+        assert!(edge_over_fees_pct(0.5, percentage_costs, 1.0, 1_000.0) < 0.0);
+        assert_eq!(edge_over_fees_pct(1.5, 0.0, 1.0, 0.0), f64::NEG_INFINITY);
+    }
+
+    #[tokio::test]
+    async fn a_confirmed_trade_rolled_back_by_a_reorg_is_undone_everywhere() {
+        let engine = engine();
+        let won = TradeResponse {
+            transaction_id: solana_sdk::signature::Signature::new_unique().to_string(),
+            success: true,
+            actual_profit: 40.0,
+            ..TradeResponse::rejected("")
+        };
+        let signature = won.transaction_id.parse().unwrap();
+
+        // Accounted at confirmed commitment, then missing from two polls in a row
+        engine.account_trade("won", "SOL/USDC", StrategyKind::Direct, &won).await;
+        assert!(engine.finality.write().await.track("won", "SOL/USDC", StrategyKind::Direct, &won, true));
+        assert_eq!(engine.get_rolling_win_rate().await, Some(1.0));
+        assert_eq!(engine.risk_manager.read().await.daily_pnl(engine.clock.now_ms()), 40.0);
+
+        assert!(engine.finality.write().await.update(&signature, ChainStatus::Missing).is_none());
+        let Some(Finality::RolledBack(trade)) = engine.finality.write().await.update(&signature, ChainStatus::Missing) else {
+            panic!("a trade missing twice is rolled back");
+        };
+        engine.reverse_trade(&trade).await;
+
+        assert_eq!(engine.risk_manager.read().await.daily_pnl(engine.clock.now_ms()), 0.0);
+        assert_eq!(engine.get_rolling_win_rate().await, Some(0.0));
+        assert_eq!(engine.get_strategy_pnl().await[&StrategyKind::Direct], 0.0);
+    }
+
+    // Serves a fixed snapshot so scans never touch the DEX APIs
+    struct StaticPrices(Vec<PriceData>);
+
+    impl PriceSource for StaticPrices {
+        fn get_all_prices(&self) -> futures_util::future::BoxFuture<'_, Result<Vec<PriceData>>> {
+            Box::pin(async move { Ok(self.0.clone()) })
+        }
+    }
+
+    fn sol_price(dex: &str, price: f64, pool: &str) -> PriceData {
+        PriceData {
+            dex_name: dex.to_string(),
+            token_pair: "SOL/USDC".to_string(),
+            base_token: "SOL".to_string(),
+            quote_token: "USDC".to_string(),
+            price,
+            volume_24h: 1_000_000.0,
+            liquidity: 500_000.0,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            pool_address: pool.to_string(),
+            price_impact: 0.0,
+        }
     }
 
-    async fn arbitrage_loop(&self) -> Result<()> {
-        let mut interval = tokio::time::interval(
-            std::time::Duration::from_millis(self.config.trading.scan_interval_ms)
-        );
+    // Scans `prices` with USDC and SOL priced and the wallet's USDC account
+    // already known, so gas estimation needs no RPC
+    async fn scanning(engine: ArbitrageEngine, prices: Vec<PriceData>) -> ArbitrageEngine {
+        let engine = engine.with_price_source(Arc::new(StaticPrices(prices)));
+        price_usdc_and_sol(&engine).await;
+        let usdc_account = associated_token_address(&engine.config.wallet.public_key, USDC).unwrap();
+        engine.known_token_accounts.write().await.insert(usdc_account);
+        engine
+    }
 
-        loop {
-            interval.tick().await;
-            
-            let running = *self.is_running.read().await;
-            if !running {
+    #[tokio::test]
+    async fn a_blocked_pool_never_prices_an_opportunity() {
+        let engine = scanning(engine(), vec![
+            sol_price("Raydium", 100.0, "raydium-pool"),
+            sol_price("Orca", 100.8, "orca-pool"),
+            sol_price("Meteora", 101.5, "drained-pool"),
+        ]).await;
+
+        let opportunities = engine.scan_opportunities(0.1, 10.0).await.unwrap();
+        assert_eq!(opportunities[0].sell_pool, "drained-pool");
+
+        engine.block_pool("drained-pool").await;
+        let opportunities = engine.scan_opportunities(0.1, 10.0).await.unwrap();
+        assert_eq!(opportunities.len(), 1);
+        assert_eq!((opportunities[0].buy_pool.as_str(), opportunities[0].sell_pool.as_str()), ("raydium-pool", "orca-pool"));
+    }
+
+    // The wallet's USDC account before a trade, then the same account as the
+    // simulated trade leaves it
+    fn usdc_simulation(pre_balance: u64, post_balance: u64) -> Vec<crate::mock_http::MockResponse> {
+        let token_account = |amount: u64| json!({ "data": { "parsed": { "info": { "tokenAmount": { "amount": amount.to_string() } } } } });
+        vec![
+            crate::mock_http::reply(200, &json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": token_account(pre_balance) } }).to_string()),
+            crate::mock_http::reply(200, &json!({
+                "jsonrpc": "2.0", "id": 1,
+                "result": { "value": { "err": null, "accounts": [token_account(post_balance)] } },
+            }).to_string()),
+        ]
+    }
+
+    #[tokio::test]
+    async fn a_simulated_output_short_of_the_quote_aborts_the_trade() {
+        let payer = solana_sdk::pubkey::Pubkey::new_unique();
+        let built = BuiltTrade {
+            transaction: VersionedTransaction {
+                signatures: vec![solana_sdk::signature::Signature::default()],
+                message: VersionedMessage::Legacy(Message::new(&[], Some(&payer))),
+            },
+            // 100 USDC round trip quoted to return 101
+            quotes: vec![
+                leg_quote(USDC, 100_000_000, WSOL_MINT, 1_000_000_000, SwapMode::ExactIn, 0),
+                leg_quote(WSOL_MINT, 1_000_000_000, USDC, 101_000_000, SwapMode::ExactIn, 0),
+            ],
+            budget: ComputeBudget::default(),
+        };
+
+        // Within 50 bps of 101 the trade must gain at least 0.495 USDC
+        for (post_balance, verified) in [(500_200_000, false), (500_900_000, true)] {
+            let rpc = crate::mock_http::serve(vec![("/", usdc_simulation(500_000_000, post_balance))]).await;
+            let mut config = test_config();
+            config.trading.simulate_before_execute = true;
+            config.rpc_endpoints.primary = rpc.url.clone();
+            let engine = engine_with(config);
+
+            let result = engine.verify_simulated_output(&built, &opportunity(), 0).await;
+            assert_eq!(result.is_ok(), verified);
+            assert!(rpc.requests_to("/").iter().any(|request| request.contains("simulateTransaction")));
+            let mismatches = engine.get_rejection_counts().await.get(&RejectionReason::SimulationMismatch).copied();
+            assert_eq!(mismatches, if verified { None } else { Some(1) });
+        }
+    }
+
+    #[tokio::test]
+    async fn a_pair_override_sizes_and_slips_only_its_own_pair() {
+        let prices = || vec![sol_price("Raydium", 100.0, "raydium-pool"), sol_price("Orca", 101.0, "orca-pool")];
+
+        let engine = scanning(engine(), prices()).await;
+        let global = engine.scan_opportunities(0.1, 10.0).await.unwrap();
+        assert_eq!((global[0].slippage, global[0].max_amount), (0.5, 10.0));
+
+        let mut config = test_config();
+        config.pair_overrides.insert("SOL/USDC".to_string(), crate::config::PairOverride {
+            slippage_bps: Some(150),
+            max_amount: Some(2.0),
+            ..Default::default()
+        });
+        config.pair_overrides.insert("SOL/USDT".to_string(), crate::config::PairOverride {
+            slippage_bps: Some(5),
+            ..Default::default()
+        });
+        let engine = scanning(engine_with(config), prices()).await;
+        let overridden = engine.scan_opportunities(0.1, 10.0).await.unwrap();
+        assert_eq!((overridden[0].slippage, overridden[0].max_amount), (1.5, 2.0));
+    }
+
+    #[tokio::test]
+    async fn each_scan_gate_counts_its_own_rejection_reason() {
+        let mut lone_usdt = sol_price("Raydium", 100.0, "raydium-usdt-pool");
+        lone_usdt.token_pair = "SOL/USDT".to_string();
+        lone_usdt.quote_token = "USDT".to_string();
+        let engine = scanning(engine(), vec![
+            sol_price("Raydium", 100.0, "raydium-pool"),
+            sol_price("Orca", 100.5, "orca-pool"),
+            lone_usdt,
+        ]).await;
+
+        // A 0.5% spread under a 1% minimum, and a pair only one DEX prices
+        assert!(engine.scan_opportunities(1.0, 10.0).await.unwrap().is_empty());
+        let counts = engine.get_rejection_counts().await;
+        assert_eq!(counts.get(&RejectionReason::BelowMinProfit), Some(&1));
+        assert_eq!(counts.get(&RejectionReason::InsufficientSources), Some(&1));
+
+        // Clears the minimum, but $0.0005 of spread on 0.001 SOL can't pay gas
+        assert!(engine.scan_opportunities(0.1, 0.001).await.unwrap().is_empty());
+        assert_eq!(engine.get_rejection_counts().await.get(&RejectionReason::BelowGasCost), Some(&1));
+    }
+
+    fn engine_with_jupiter(mut config: Config, jupiter_url: &str) -> ArbitrageEngine {
+        config.jupiter.enabled = true;
+        ArbitrageEngine::new(
+            config.clone(),
+            Arc::new(DexMonitor::new(config.dex_endpoints.clone())),
+            Arc::new(RwLock::new(RiskManager::new(config.risk_settings.clone()))),
+            Arc::new(PortfolioManager::new(config.clone())),
+            None,
+            Some(Arc::new(JupiterClient::new(jupiter_url.to_string(), None))),
+            Arc::new(MonitoringService::new(config.monitoring.clone())),
+        )
+        .unwrap()
+    }
+
+    // A quote as Jupiter's API sends it, through a single pool
+    fn jupiter_quote(input_mint: &str, in_amount: u64, output_mint: &str, out_amount: u64) -> crate::mock_http::MockResponse {
+        jupiter_quote_through("pool", 0.001, input_mint, in_amount, output_mint, out_amount)
+    }
+
+    fn jupiter_quote_through(
+        amm_key: &str,
+        price_impact_pct: f64,
+        input_mint: &str,
+        in_amount: u64,
+        output_mint: &str,
+        out_amount: u64,
+    ) -> crate::mock_http::MockResponse {
+        crate::mock_http::reply(200, &json!({
+            "input_mint": input_mint,
+            "in_amount": in_amount.to_string(),
+            "output_mint": output_mint,
+            "out_amount": out_amount.to_string(),
+            "other_amount_threshold": out_amount.to_string(),
+            "swap_mode": "ExactIn",
+            "slippage_bps": 50,
+            "platform_fee": null,
+            "price_impact_pct": price_impact_pct.to_string(),
+            "route_plan": [{
+                "swap_info": {
+                    "amm_key": amm_key,
+                    "label": "Whirlpool",
+                    "input_mint": input_mint,
+                    "in_amount": in_amount.to_string(),
+                    "output_mint": output_mint,
+                    "out_amount": out_amount.to_string(),
+                    "fee_amount": "0",
+                    "fee_mint": input_mint,
+                },
+                "percent": 100,
+            }],
+            "context_slot": 250_000_000u64,
+            "time_taken": 0.002,
+        }).to_string())
+    }
+
+    // A bare Jupiter swap instruction, signed by nobody but the payer
+    fn jupiter_swap_instructions() -> crate::mock_http::MockResponse {
+        crate::mock_http::reply(200, &json!({
+            "swapInstruction": { "programId": "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4", "accounts": [], "data": "" },
+        }).to_string())
+    }
+
+    fn latest_blockhash() -> crate::mock_http::MockResponse {
+        crate::mock_http::reply(200, &json!({
+            "jsonrpc": "2.0", "id": 1,
+            "result": { "value": { "blockhash": solana_sdk::hash::Hash::new_unique().to_string() } },
+        }).to_string())
+    }
+
+    fn trade_request() -> TradeRequest {
+        TradeRequest {
+            opportunity_id: "opp".to_string(),
+            amount: 1.0,
+            private_key: solana_sdk::signature::Keypair::new().to_base58_string(),
+            max_slippage: 0.5,
+            priority_fee: 0,
+            use_jito: false,
+            jito_tip: "0".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn a_build_slower_than_the_latency_budget_is_abandoned_as_stale() {
+        let jupiter = crate::mock_http::serve(vec![
+            ("/quote", vec![
+                jupiter_quote(USDC, 100_000_000, WSOL_MINT, 1_000_000_000),
+                jupiter_quote(WSOL_MINT, 1_000_000_000, USDC, 101_000_000),
+            ]),
+            ("/swap-instructions", vec![jupiter_swap_instructions()]),
+        ]).await;
+        // The blockhash arrives well after the quotes went stale
+        let rpc = crate::mock_http::serve(vec![("/", vec![latest_blockhash().delayed(300)])]).await;
+
+        let mut config = test_config();
+        config.rpc_endpoints.primary = rpc.url.clone();
+        config.trading.execution_latency_budget_ms = 100;
+        config.trading.requote_on_latency_breach = true;
+        let engine = engine_with_jupiter(config, &jupiter.url);
+        price_usdc_and_sol(&engine).await;
+
+        // Re-quoted once, then given up on
+        let stale = engine.build_within_latency_budget(&trade_request(), &opportunity()).await;
+        assert!(stale.is_err_and(|e| e.to_string().contains("exceeded budget")));
+        assert_eq!(jupiter.requests_to("/quote").len(), 4);
+        assert_eq!(engine.get_rejection_counts().await.get(&RejectionReason::StaleQuote), Some(&1));
+    }
+
+    #[tokio::test]
+    async fn no_opportunity_is_emitted_until_warm_up_completes() {
+        let prices = || vec![sol_price("Raydium", 100.0, "raydium-pool"), sol_price("Orca", 101.0, "orca-pool")];
+        let warming = |rpc_url: &str| {
+            let mut config = test_config();
+            config.rpc_endpoints.primary = rpc_url.to_string();
+            config.trading.scan_interval_ms = 10;
+            config.trading.warmup_timeout_ms = 1_000;
+            config
+        };
+
+        // With the RPC node down, warm-up fails loudly and nothing is scanned
+        let down = crate::mock_http::serve(vec![("/", vec![crate::mock_http::reply(503, "")])]).await;
+        let engine = scanning(engine_with(warming(&down.url)), prices()).await;
+        assert!(engine.start().await.is_err());
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!engine.is_warm().await);
+        assert!(engine.get_live_opportunities().await.is_empty());
+
+        let up = crate::mock_http::serve(vec![("/", vec![
+            crate::mock_http::reply(200, &json!({ "jsonrpc": "2.0", "id": 1, "result": "ok" }).to_string()),
+            latest_blockhash(),
+        ])]).await;
+        let engine = scanning(engine_with(warming(&up.url)), prices()).await;
+        engine.start().await.unwrap();
+        assert!(engine.is_warm().await);
+        // Warm-up replaced the cache; without Jupiter it holds no prices
+        price_usdc_and_sol(&engine).await;
+        let mut live = Vec::new();
+        for _ in 0..100 {
+            live = engine.get_live_opportunities().await;
+            if !live.is_empty() {
                 break;
             }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        engine.stop().await.unwrap();
+        assert_eq!(live.len(), 1);
+    }
 
-            // Scan for opportunities
-            let opportunities = self.scan_opportunities(
-                self.config.risk_settings.min_profit_threshold,
-                self.config.risk_settings.max_position_size,
-            ).await?;
+    #[tokio::test]
+    async fn the_same_opportunity_keeps_its_id_across_scans_and_the_feed() {
+        let prices = |sell_price: f64| vec![sol_price("Raydium", 100.0, "raydium-pool"), sol_price("Orca", sell_price, "orca-pool")];
 
-            // Execute profitable trades if auto-trading is enabled
-            if self.config.trading.enable_auto_trading {
-                for opportunity in opportunities {
-                    if opportunity.is_profitable {
-                        let trade_request = TradeRequest {
-                            opportunity_id: opportunity.id.clone(),
-                            amount: opportunity.max_amount,
-                            private_key: self.config.wallet.private_key.clone(),
-                            max_slippage: self.config.risk_settings.max_slippage,
-                            priority_fee: 1000, // Default priority fee
-                            use_jito: self.jito_client.is_some(),
-                            jito_tip: "100000".to_string(), // 0.0001 SOL
-                        };
+        let scanned = scanning(engine(), prices(101.0)).await;
+        let first = scanned.scan_opportunities(0.1, 10.0).await.unwrap();
+        let second = scanned.scan_opportunities(0.1, 10.0).await.unwrap();
+        assert_eq!(first[0].id, second[0].id);
+        assert_eq!(scanned.get_live_opportunities().await[0].id, first[0].id);
 
-                        match self.execute_trade(trade_request).await {
-                            Ok(response) => {
-                                if response.success {
-                                    info!("✅ Trade executed successfully: {}", response.transaction_id);
-                                } else {
-                                    warn!("❌ Trade failed: {}", response.error_message);
-                                }
-                            }
-                            Err(e) => {
-                                error!("❌ Trade execution error: {}", e);
-                            }
-                        }
-                    }
+        let moved = scanning(engine(), prices(101.2)).await;
+        assert_ne!(moved.scan_opportunities(0.1, 10.0).await.unwrap()[0].id, first[0].id);
+    }
+
+    struct FixedOracle(HashMap<String, f64>);
+
+    impl Oracle for FixedOracle {
+        fn price<'a>(&'a self, mint: &'a str) -> futures_util::future::BoxFuture<'a, Result<f64>> {
+            Box::pin(async move { self.0.get(mint).copied().ok_or_else(|| anyhow::anyhow!("no oracle price for {}", mint)) })
+        }
+    }
+
+    #[tokio::test]
+    async fn a_trade_priced_away_from_the_oracle_is_rejected() {
+        // Both legs trade within 2% of a $100 oracle, but not of a $97 one
+        for (oracle_sol_price, deviates) in [(100.0, false), (97.0, true)] {
+            let mut config = test_config();
+            config.simulation.enabled = true;
+            let oracle = FixedOracle(HashMap::from([(WSOL_MINT.to_string(), oracle_sol_price), (USDC.to_string(), 1.0)]));
+            let engine = scanning(engine_with(config), vec![sol_price("Raydium", 100.0, "raydium-pool"), sol_price("Orca", 101.0, "orca-pool")])
+                .await
+                .with_oracle(Arc::new(oracle));
+            let opportunity = engine.scan_opportunities(0.1, 10.0).await.unwrap().remove(0);
+
+            let response = engine.execute_claimed_trade(TradeRequest { opportunity_id: opportunity.id, ..trade_request() }).await.unwrap();
+            assert_eq!(response.success, !deviates, "{}", response.error_message);
+            assert_eq!(response.error_message.contains("from oracle"), deviates);
+            let rejections = engine.get_rejection_counts().await.get(&RejectionReason::OracleDeviation).copied();
+            assert_eq!(rejections, deviates.then_some(1));
+        }
+    }
+
+    const USDT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+
+    // USDT at $1, held in an account the wallet already has
+    async fn hold_usdt(engine: &ArbitrageEngine) {
+        engine.warm_cache.write().await.prices.insert(USDT.to_string(), 1.0);
+        let usdt_account = associated_token_address(&engine.config.wallet.public_key, USDT).unwrap();
+        engine.known_token_accounts.write().await.insert(usdt_account);
+    }
+
+    fn stable_price(dex: &str, price: f64) -> PriceData {
+        PriceData {
+            token_pair: "USDC/USDT".to_string(),
+            base_token: "USDC".to_string(),
+            quote_token: "USDT".to_string(),
+            ..sol_price(dex, price, &format!("{}-stable-pool", dex))
+        }
+    }
+
+    #[tokio::test]
+    async fn only_watchlisted_pairs_are_quoted() {
+        let jupiter = crate::mock_http::serve(vec![
+            ("/quote", vec![jupiter_quote(WSOL_MINT, 10, USDC, 1_000)]),
+        ]).await;
+        let usdt_price = |dex: &str, price: f64| PriceData {
+            token_pair: "SOL/USDT".to_string(),
+            quote_token: "USDT".to_string(),
+            ..sol_price(dex, price, &format!("{}-usdt-pool", dex))
+        };
+        let engine = scanning(engine_with_jupiter(test_config(), &jupiter.url), vec![
+            sol_price("Raydium", 100.0, "raydium-pool"),
+            sol_price("Orca", 101.0, "orca-pool"),
+            usdt_price("Raydium", 100.0),
+            usdt_price("Orca", 101.0),
+        ]).await;
+        hold_usdt(&engine).await;
+        let usdt_quotes = || jupiter.requests_to("/quote").iter().filter(|request| request.contains(USDT)).count();
+
+        assert!(engine.watch_pair(WatchedPair { input_mint: WSOL_MINT.to_string(), output_mint: USDC.to_string() }).await);
+        engine.scan_enhanced_opportunities(0.1, 10.0).await.unwrap();
+        assert!(!jupiter.requests_to("/quote").is_empty());
+        assert_eq!(usdt_quotes(), 0);
+
+        // With the watchlist emptied, every pair is in scope again
+        assert!(engine.unwatch_pair(&WatchedPair { input_mint: WSOL_MINT.to_string(), output_mint: USDC.to_string() }).await);
+        engine.scan_enhanced_opportunities(0.1, 10.0).await.unwrap();
+        assert!(usdt_quotes() > 0);
+    }
+
+    // Opportunity ids the engine has recorded trades for, in order
+    async fn traded_ids(engine: &ArbitrageEngine) -> Vec<String> {
+        let query = HistoryQuery { from_ts: 0, to_ts: i64::MAX, pair_filter: None, limit: 1_000, cursor: None };
+        engine.history.query(&query).await.unwrap().records.into_iter()
+            .filter_map(|record| match record.entry {
+                crate::history::HistoryEntry::Trade { opportunity_id, .. } => Some(opportunity_id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn only_the_top_ranked_opportunities_within_the_cap_are_dispatched() {
+        let mut config = test_config();
+        config.simulation.enabled = true;
+        config.trading.max_opportunities_per_scan = 5;
+        let wallet = solana_sdk::signature::Keypair::new();
+        config.wallet.private_key = wallet.to_base58_string();
+        config.wallet.public_key = solana_sdk::signer::Signer::pubkey(&wallet).to_string();
+        let engine = engine_with(config);
+        price_usdc_and_sol(&engine).await;
+
+        let mut opportunities: Vec<ArbitrageOpportunity> = (0..100)
+            .map(|i| {
+                let mut opportunity = ArbitrageOpportunity {
+                    sell_price: 100.5 + i as f64 * 0.01,
+                    profit_percentage: 0.5 + i as f64 * 0.01,
+                    estimated_profit: 0.5 + i as f64 * 0.1,
+                    ..opportunity()
+                };
+                opportunity.id = opportunity.compute_id();
+                opportunity
+            })
+            .collect();
+        rank_opportunities(&mut opportunities, &engine.config.scoring_weights, engine.clock.now_ms());
+        let best: Vec<String> = opportunities.iter().take(5).map(|o| o.id.clone()).collect();
+
+        engine.dispatch_opportunities(opportunities).await;
+        assert_eq!(traded_ids(&engine).await, best);
+    }
+
+    #[tokio::test]
+    async fn a_stable_pair_trades_near_its_peg_and_is_skipped_off_it() {
+        // A 0.15% spread between stable-swap pools is far under the global
+        // minimum but over the stable one, and clears their 0.05% in fees
+        let pegged = scanning(engine(), vec![stable_price("Saber", 0.9995), stable_price("Mercurial", 1.001)]).await;
+        hold_usdt(&pegged).await;
+        let opportunities = pegged.scan_opportunities(1.0, 10_000.0).await.unwrap();
+        assert_eq!(opportunities.len(), 1);
+        assert_eq!(opportunities[0].slippage, 0.05);
+
+        let depegged = scanning(engine(), vec![stable_price("Saber", 0.97), stable_price("Mercurial", 0.99)]).await;
+        hold_usdt(&depegged).await;
+        assert!(depegged.scan_opportunities(1.0, 10_000.0).await.unwrap().is_empty());
+        assert_eq!(depegged.get_rejection_counts().await.get(&RejectionReason::DepegRisk), Some(&1));
+    }
+
+    #[tokio::test]
+    async fn a_higher_impact_penalty_ranks_the_low_impact_opportunity_first() {
+        let priced = |pair: &str, quote: &str, dex: &str, price: f64, price_impact: f64| PriceData {
+            token_pair: pair.to_string(),
+            quote_token: quote.to_string(),
+            price_impact,
+            ..sol_price(dex, price, &format!("{}-{}-pool", dex, quote))
+        };
+        // SOL/USDC spreads wider but through deeper-impact pools
+        let prices = vec![
+            priced("SOL/USDC", "USDC", "Raydium", 100.0, 0.5),
+            priced("SOL/USDC", "USDC", "Orca", 102.0, 0.5),
+            priced("SOL/USDT", "USDT", "Raydium", 100.0, 0.0),
+            priced("SOL/USDT", "USDT", "Orca", 101.8, 0.0),
+        ];
+        let ranked_pairs = |opportunities: Vec<ArbitrageOpportunity>| {
+            opportunities.into_iter().map(|o| o.token_pair).collect::<Vec<_>>()
+        };
+
+        let greedy = scanning(engine(), prices.clone()).await;
+        hold_usdt(&greedy).await;
+        assert_eq!(ranked_pairs(greedy.scan_opportunities(0.1, 10.0).await.unwrap()), ["SOL/USDC", "SOL/USDT"]);
+
+        let mut config = test_config();
+        config.scoring_weights.impact_penalty = 1.0;
+        let cautious = scanning(engine_with(config), prices).await;
+        hold_usdt(&cautious).await;
+        assert_eq!(ranked_pairs(cautious.scan_opportunities(0.1, 10.0).await.unwrap()), ["SOL/USDT", "SOL/USDC"]);
+    }
+
+    #[tokio::test]
+    async fn a_bonk_cycle_reports_its_profit_in_usdc() {
+        const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+        let cycling = |jupiter_url: &str| {
+            let mut config = test_config();
+            config.multi_hop.base_mints = vec![BONK.to_string()];
+            config.multi_hop.intermediate_mints = vec![USDC.to_string()];
+            // 1M BONK out and 1.02M back
+            config.multi_hop.amount = 100_000_000_000;
+            // The mock routes both legs through the same pool
+            config.multi_hop.route_overlap = RouteOverlapPolicy::Adjust;
+            engine_with_jupiter(config, jupiter_url)
+        };
+        let round_trip = || vec![
+            jupiter_quote(BONK, 100_000_000_000, USDC, 20_000_000),
+            jupiter_quote(USDC, 20_000_000, BONK, 102_000_000_000),
+        ];
+        async fn hold_bonk(engine: &ArbitrageEngine) {
+            price_usdc_and_sol(engine).await;
+            engine.warm_cache.write().await.token_decimals.insert(BONK.to_string(), 5);
+            for mint in [BONK, USDC] {
+                let account = associated_token_address(&engine.config.wallet.public_key, mint).unwrap();
+                engine.known_token_accounts.write().await.insert(account);
+            }
+        }
+
+        let jupiter = crate::mock_http::serve(vec![("/quote", round_trip())]).await;
+        let priced = cycling(&jupiter.url);
+        hold_bonk(&priced).await;
+        priced.warm_cache.write().await.prices.insert(BONK.to_string(), 0.00002);
+        let cycles = priced.scan_cycle_opportunities().await.unwrap();
+        // 20,000 BONK of profit is worth $0.40, less gas
+        assert_eq!(cycles.len(), 1);
+        assert!((0.38..0.4).contains(&cycles[0].estimated_profit), "{}", cycles[0].estimated_profit);
+
+        // Without a BONK price the cycle cannot be valued, and is skipped
+        let jupiter = crate::mock_http::serve(vec![("/quote", round_trip())]).await;
+        let unpriced = cycling(&jupiter.url);
+        hold_bonk(&unpriced).await;
+        assert!(unpriced.scan_cycle_opportunities().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn round_trip_edge_is_the_net_multiplier_of_both_legs() {
+        // 1,000 USDC buys 1,010 USDT in one pool, which buys 1,005 USDC back in another
+        let jupiter = crate::mock_http::serve(vec![("/quote", vec![
+            jupiter_quote_through("pool-a", 0.0, USDC, 1_000_000_000, USDT, 1_010_000_000),
+            jupiter_quote_through("pool-b", 0.0, USDT, 1_010_000_000, USDC, 1_005_000_000),
+            // The same trip, back through the first pool
+            jupiter_quote_through("pool-a", 0.0, USDC, 1_000_000_000, USDT, 1_010_000_000),
+            jupiter_quote_through("pool-a", 0.0, USDT, 1_010_000_000, USDC, 1_005_000_000),
+        ])]).await;
+        let engine = engine_with_jupiter(test_config(), &jupiter.url);
+
+        assert_eq!(engine.round_trip_edge(USDC, USDT, 1_000_000_000).await.unwrap(), 1.005);
+        // The return leg sells exactly what the first leg bought
+        let quotes = jupiter.requests_to("/quote");
+        assert!(quotes[1].contains("amount=1010000000"), "{}", quotes[1]);
+
+        let overlapping = engine.round_trip_edge(USDC, USDT, 1_000_000_000).await.unwrap_err();
+        assert!(overlapping.to_string().contains("pool-a"), "{}", overlapping);
+        assert!(engine.round_trip_edge(USDC, USDT, 0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn creating_the_sentinel_file_halts_execution_within_one_scan() {
+        let sentinel = std::env::temp_dir().join(format!("engine_test_kill_switch_{}", std::process::id()));
+        let _ = std::fs::remove_file(&sentinel);
+        let rpc = crate::mock_http::serve(vec![("/", vec![
+            crate::mock_http::reply(200, &json!({ "jsonrpc": "2.0", "id": 1, "result": "ok" }).to_string()),
+            latest_blockhash(),
+        ])]).await;
+        let mut config = test_config();
+        config.rpc_endpoints.primary = rpc.url.clone();
+        config.simulation.enabled = true;
+        config.trading.scan_interval_ms = 10;
+        config.kill_switch.sentinel_path = Some(sentinel.to_string_lossy().to_string());
+        config.kill_switch.listen_for_signal = false;
+        let engine = scanning(engine_with(config), vec![sol_price("Raydium", 100.0, "raydium-pool"), sol_price("Orca", 101.0, "orca-pool")]).await;
+        engine.start().await.unwrap();
+        price_usdc_and_sol(&engine).await;
+        let scan_cycle = || tokio::time::sleep(std::time::Duration::from_millis(50));
+        for _ in 0..100 {
+            if !engine.get_live_opportunities().await.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        std::fs::write(&sentinel, "").unwrap();
+        scan_cycle().await;
+        assert!(engine.get_kill_switch().await.engaged());
+        let halted = engine.execute_claimed_trade(trade_request()).await.unwrap();
+        assert_eq!(halted.error_message, "Kill switch engaged");
+        // Scanning carries on while execution is halted
+        assert!(!engine.get_live_opportunities().await.is_empty());
+
+        std::fs::remove_file(&sentinel).unwrap();
+        scan_cycle().await;
+        assert!(!engine.get_kill_switch().await.engaged());
+        engine.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn cycle_legs_through_the_same_pool_are_rejected_or_discounted() {
+        for policy in [RouteOverlapPolicy::Reject, RouteOverlapPolicy::Adjust] {
+            // 1 SOL out and 1.02 back, both legs through the mock's one pool
+            let jupiter = crate::mock_http::serve(vec![("/quote", vec![
+                jupiter_quote(WSOL_MINT, 1_000_000_000, USDC, 100_000_000),
+                jupiter_quote(USDC, 100_000_000, WSOL_MINT, 1_020_000_000),
+            ])]).await;
+            let mut config = test_config();
+            config.multi_hop.base_mints = vec![WSOL_MINT.to_string()];
+            config.multi_hop.intermediate_mints = vec![USDC.to_string()];
+            config.multi_hop.route_overlap = policy;
+            let cycling = scanning(engine_with_jupiter(config, &jupiter.url), Vec::new()).await;
+            let cycles = cycling.scan_cycle_opportunities().await.unwrap();
+
+            match policy {
+                RouteOverlapPolicy::Reject => {
+                    assert!(cycles.is_empty());
+                    assert_eq!(cycling.get_rejection_counts().await.get(&RejectionReason::SharedPool), Some(&1));
+                }
+                RouteOverlapPolicy::Adjust => {
+                    assert_eq!(cycles[0].shared_pools, ["pool"]);
+                    // The first leg's 0.001% impact comes off the 2% return, as does gas on $100
+                    let expected = 2.0 - 0.001 - cycles[0].gas_cost / 100.0 * 100.0;
+                    assert!((cycles[0].profit_percentage - expected).abs() < 1e-9, "{}", cycles[0].profit_percentage);
                 }
             }
         }
+    }
 
-        Ok(())
+    #[tokio::test]
+    async fn a_missing_token_account_costs_rent_until_it_is_pre_created() {
+        let wallet = solana_sdk::signature::Keypair::new();
+        let owner = solana_sdk::signer::Signer::pubkey(&wallet);
+        let blockhash = Hash::new_unique();
+        // The node echoes the signature of the account-creating transaction
+        let mut message = Message::new(&[create_associated_token_account_instruction(&owner, &owner, USDC).unwrap()], Some(&owner));
+        message.recent_blockhash = blockhash;
+        let signature = solana_sdk::signer::Signer::sign_message(&wallet, &VersionedMessage::Legacy(message).serialize());
+        let rpc_result = |result: serde_json::Value| crate::mock_http::reply(200, &json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string());
+        let rpc = crate::mock_http::serve(vec![("/", vec![
+            // The scan's and then pre-creation's lookup of the USDC account
+            rpc_result(json!({ "context": { "slot": 1 }, "value": null })),
+            rpc_result(json!({ "context": { "slot": 1 }, "value": null })),
+            rpc_result(json!({ "context": { "slot": 1 }, "value": { "blockhash": blockhash.to_string(), "lastValidBlockHeight": 100 } })),
+            rpc_result(json!(signature.to_string())),
+            rpc_result(json!({ "context": { "slot": 1 }, "value": [{
+                "slot": 1, "confirmations": null, "err": null, "status": { "Ok": null }, "confirmationStatus": "finalized",
+            }] })),
+        ])]).await;
+
+        let mut config = test_config();
+        config.rpc_endpoints.primary = rpc.url.clone();
+        config.wallet.private_key = wallet.to_base58_string();
+        config.wallet.public_key = owner.to_string();
+        let engine = engine_with(config)
+            .with_price_source(Arc::new(StaticPrices(vec![sol_price("Raydium", 100.0, "raydium-pool"), sol_price("Orca", 101.0, "orca-pool")])));
+        price_usdc_and_sol(&engine).await;
+
+        let before = engine.scan_opportunities(0.1, 10.0).await.unwrap().remove(0);
+        assert_eq!(engine.ensure_atas(&[USDC.to_string()]).await.unwrap(), [USDC]);
+        let after = engine.scan_opportunities(0.1, 10.0).await.unwrap().remove(0);
+        // $100 SOL puts the rent at about $0.20
+        let rent = crate::types::TOKEN_ACCOUNT_RENT_LAMPORTS as f64 / LAMPORTS_PER_SOL * 100.0;
+        assert!((before.gas_cost - after.gas_cost - rent).abs() < 1e-9);
+        assert_eq!(rpc.requests().len(), 5);
     }
 
-    async fn estimate_gas_cost(&self) -> Result<f64> {
-        // Estimate gas cost based on current network conditions
-        // This is a simplified estimation
-        Ok(0.005) // $0.005 estimated gas cost
+    #[tokio::test]
+    async fn a_jito_tip_larger_than_the_edge_rejects_the_trade() {
+        let mut config = test_config();
+        config.simulation.enabled = true;
+        let engine = scanning(engine_with(config), vec![sol_price("Raydium", 100.0, "raydium-pool"), sol_price("Orca", 101.0, "orca-pool")]).await;
+        let opportunity = engine.scan_opportunities(0.1, 10.0).await.unwrap().remove(0);
+        let tipping = |jito_tip: &str| TradeRequest {
+            opportunity_id: opportunity.id.clone(),
+            use_jito: true,
+            jito_tip: jito_tip.to_string(),
+            ..trade_request()
+        };
+
+        // 0.001 SOL is $0.10 of a ~$10 edge; 0.2 SOL is twice the edge
+        let tipped = engine.execute_claimed_trade(tipping("1000000")).await.unwrap();
+        assert!(tipped.success, "{}", tipped.error_message);
+        let overtipped = engine.execute_claimed_trade(tipping("200000000")).await.unwrap();
+        assert_eq!(overtipped.error_message, "Jito tip exceeds expected profit");
+        assert_eq!(engine.get_rejection_counts().await.get(&RejectionReason::BelowGasCost), Some(&1));
+
+        let garbled = engine.execute_claimed_trade(tipping("0.001 SOL")).await.unwrap();
+        assert!(!garbled.success);
+        assert!(garbled.error_message.contains("is not a lamport amount"), "{}", garbled.error_message);
     }
 
-    async fn get_opportunity_by_id(&self, id: &str) -> Result<ArbitrageOpportunity> {
-        // In a real implementation, this would fetch from a database
-        // For now, return a mock opportunity
-        Ok(ArbitrageOpportunity {
-            id: id.to_string(),
-            token_pair: "SOL/USDC".to_string(),
-            buy_dex: "Raydium".to_string(),
-            sell_dex: "Orca".to_string(),
-            buy_price: 100.0,
-            sell_price: 101.0,
-            profit_percentage: 1.0,
-            estimated_profit: 10.0,
-            max_amount: 1000.0,
-            gas_cost: 0.005,
-            timestamp: Utc::now().timestamp_millis(),
-            buy_pool: "pool_address_1".to_string(),
-            sell_pool: "pool_address_2".to_string(),
-            slippage: 0.5,
-            is_profitable: true,
-        })
+    // Jito's published landed-tip percentiles, in SOL
+    fn tip_floor(p75: f64) -> crate::mock_http::MockResponse {
+        crate::mock_http::reply(200, &json!([{
+            "landed_tips_25th_percentile": p75 / 4.0,
+            "landed_tips_50th_percentile": p75 / 2.0,
+            "landed_tips_75th_percentile": p75,
+            "landed_tips_95th_percentile": p75 * 2.0,
+            "landed_tips_99th_percentile": p75 * 4.0,
+        }]).to_string())
     }
 
-    async fn execute_jito_trade(
-        &self,
-        request: &TradeRequest,
-        opportunity: &ArbitrageOpportunity,
-    ) -> Result<TradeResponse> {
-        if let Some(jito_client) = &self.jito_client {
-            // Build Jito bundle transaction
-            let bundle_id = jito_client.submit_bundle(&request, opportunity).await?;
-            
-            Ok(TradeResponse {
-                transaction_id: format!("jito_{}", bundle_id),
-                success: true,
-                error_message: "".to_string(),
-                actual_profit: opportunity.estimated_profit,
-                gas_used: opportunity.gas_cost,
-                execution_time: 0,
-                bundle_id,
-            })
-        } else {
-            Err(anyhow::anyhow!("Jito client not available"))
+    #[tokio::test]
+    async fn a_congested_tip_floor_raises_the_tip_to_the_ceiling() {
+        // Quiet slots land at 0.0002 SOL at p75; contested ones at 0.01 SOL
+        let jito = crate::mock_http::serve(vec![("/tip_floor", vec![tip_floor(0.0002), tip_floor(0.01)])]).await;
+        let mut config = test_config();
+        config.jito.enabled = true;
+        config.jito.dynamic_tip = true;
+        config.jito.tip_floor_url = format!("{}/tip_floor", jito.url);
+        config.jito.tip_refresh_ms = 0;
+        let engine = ArbitrageEngine::new(
+            config.clone(),
+            Arc::new(DexMonitor::new(config.dex_endpoints.clone())),
+            Arc::new(RwLock::new(RiskManager::new(config.risk_settings.clone()))),
+            Arc::new(PortfolioManager::new(config.clone())),
+            Some(Arc::new(JitoClient::new(config.jito.clone()))),
+            None,
+            Arc::new(MonitoringService::new(config.monitoring.clone())),
+        )
+        .unwrap();
+        let engine = scanning(engine, vec![sol_price("Raydium", 100.0, "raydium-pool"), sol_price("Orca", 101.0, "orca-pool")]).await;
+        // The scan prices the tip into its gas cost while slots are quiet
+        let opportunity = engine.scan_opportunities(0.1, 10.0).await.unwrap().remove(0);
+        assert_eq!(engine.get_tip_estimate().await, Some(200_000));
+
+        // Congestion bids up to max_tip_lamports and no further
+        assert_eq!(engine.tip_for(&opportunity).await.unwrap(), config.jito.max_tip_lamports);
+        assert_eq!(engine.get_tip_estimate().await, Some(config.jito.max_tip_lamports));
+    }
+
+    // Orca's SOL price, changed between scans
+    struct MovingPrice(std::sync::Mutex<f64>);
+
+    impl PriceSource for MovingPrice {
+        fn get_all_prices(&self) -> futures_util::future::BoxFuture<'_, Result<Vec<PriceData>>> {
+            let orca = *self.0.lock().unwrap();
+            Box::pin(async move { Ok(vec![sol_price("Raydium", 100.0, "raydium-pool"), sol_price("Orca", orca, "orca-pool")]) })
         }
     }
 
-    async fn execute_regular_trade(
-        &self,
-        request: &TradeRequest,
-        opportunity: &ArbitrageOpportunity,
-    ) -> Result<TradeResponse> {
-        // Build and execute regular Solana transaction
-        // This is a simplified implementation
-        let transaction_id = format!("tx_{}", Uuid::new_v4());
-        
-        Ok(TradeResponse {
-            transaction_id,
-            success: true,
-            error_message: "".to_string(),
-            actual_profit: opportunity.estimated_profit,
-            gas_used: opportunity.gas_cost,
-            execution_time: 0,
-            bundle_id: "".to_string(),
-        })
+    #[tokio::test]
+    async fn a_hysteresis_band_pursues_a_hovering_pair_with_fewer_flips() {
+        // Spreads hovering around a 1% threshold, then falling away
+        let spreads = [1.15, 0.95, 1.05, 0.97, 1.03, 0.85];
+        let transitions = |margin: f64| async move {
+            let mut config = test_config();
+            config.trading.hysteresis_enter_margin = margin;
+            config.trading.hysteresis_exit_margin = margin;
+            let price = Arc::new(MovingPrice(std::sync::Mutex::new(100.0)));
+            let hovering = scanning(engine_with(config), Vec::new()).await.with_price_source(price.clone());
+
+            let mut pursued = false;
+            let mut transitions = 0;
+            for spread in spreads {
+                *price.0.lock().unwrap() = 100.0 + spread;
+                let found = !hovering.scan_opportunities(1.0, 10.0).await.unwrap().is_empty();
+                transitions += (found != pursued) as u32;
+                pursued = found;
+            }
+            transitions
+        };
+
+        assert_eq!(transitions(0.0).await, 6);
+        // Entered above 1.1%, held down to 0.9%
+        assert_eq!(transitions(0.1).await, 2);
     }
 
-    async fn get_jupiter_quote(
-        &self,
-        input_mint: &str,
-        output_mint: &str,
-        amount: u64,
-    ) -> Result<JupiterQuote> {
-        if let Some(jupiter_client) = &self.jupiter_client {
-            use crate::jupiter_client::JupiterQuoteRequest;
-            
-            let request = JupiterQuoteRequest {
-                input_mint: input_mint.to_string(),
-                output_mint: output_mint.to_string(),
-                amount,
-                slippage_bps: self.config.jupiter.default_slippage_bps,
-                swap_mode: Some("ExactIn".to_string()),
-                dexes: Some(self.config.jupiter.preferred_dexes.clone()),
-                exclude_dexes: Some(self.config.jupiter.excluded_dexes.clone()),
-                platform_fee_bps: None,
-                max_accounts: Some(64),
-            };
+    #[tokio::test]
+    async fn the_opportunity_stream_recovers_from_a_dropped_server() {
+        use crate::arbitrage::{arbitrage_service_server::ArbitrageServiceServer, OpportunityRequest};
+        use crate::grpc_server::ArbitrageGrpcServer;
+        use crate::opportunity_stream::{ConnectionState, OpportunityStreamClient};
+        use tokio_stream::StreamExt;
+        use tonic::transport::{server::TcpIncoming, Server};
 
-            jupiter_client.get_quote(request).await
-        } else {
-            Err(anyhow::anyhow!("Jupiter client not available"))
+        let mut config = test_config();
+        config.trading.opportunity_ttl_ms = 60_000;
+        let price = Arc::new(MovingPrice(std::sync::Mutex::new(101.0)));
+        let engine = Arc::new(scanning(engine_with(config), Vec::new()).await.with_price_source(price.clone()));
+        // Serves the engine's live opportunities until `shutdown` fires
+        let serve = |listener: tokio::net::TcpListener, shutdown: tokio::sync::oneshot::Receiver<()>| {
+            let server = ArbitrageGrpcServer::new(engine.clone(), engine.portfolio_manager.clone(), engine.risk_manager.clone(), engine.monitoring.clone());
+            tokio::spawn(Server::builder().add_service(ArbitrageServiceServer::new(server)).serve_with_incoming_shutdown(
+                TcpIncoming::from_listener(listener, true, None).unwrap(),
+                async { shutdown.await.ok(); },
+            ))
+        };
+        let patience = std::time::Duration::from_secs(5);
+
+        let first = engine.scan_opportunities(0.1, 10.0).await.unwrap().remove(0);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop, shutdown) = tokio::sync::oneshot::channel();
+        let server = serve(listener, shutdown);
+
+        let client = OpportunityStreamClient::new(format!("http://{}", addr), OpportunityRequest::default())
+            .with_poll_interval(std::time::Duration::from_millis(10))
+            .with_backoff(std::time::Duration::from_millis(20), std::time::Duration::from_millis(20));
+        let mut state = client.connection_state();
+        let mut stream = Box::pin(client.into_stream());
+        assert_eq!(tokio::time::timeout(patience, stream.next()).await.unwrap().unwrap().id, first.id);
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        state.wait_for(|state| matches!(state, ConnectionState::Disconnected { .. })).await.unwrap();
+
+        // Published during the gap, and picked up once the server is back
+        *price.0.lock().unwrap() = 101.2;
+        let second = engine.scan_opportunities(0.1, 10.0).await.unwrap().remove(0);
+        let (_stop, shutdown) = tokio::sync::oneshot::channel();
+        serve(tokio::net::TcpListener::bind(addr).await.unwrap(), shutdown);
+        assert_eq!(tokio::time::timeout(patience, stream.next()).await.unwrap().unwrap().id, second.id);
+        assert_eq!(*state.borrow(), ConnectionState::Connected);
+    }
+
+    #[tokio::test]
+    async fn the_engine_trades_a_run_of_opportunities_against_the_simulator() {
+        let simulated = || async {
+            let mut config = test_config();
+            config.simulation.enabled = true;
+            config.simulation.fill_rate = 0.5;
+            config.simulation.slippage_bps = 10;
+            config.simulation.fee_per_trade = 0.05;
+            let wallet = solana_sdk::signature::Keypair::new();
+            config.wallet.private_key = wallet.to_base58_string();
+            config.wallet.public_key = solana_sdk::signer::Signer::pubkey(&wallet).to_string();
+            let engine = engine_with(config);
+            price_usdc_and_sol(&engine).await;
+
+            let opportunities = (0..4)
+                .map(|i| {
+                    let mut opportunity = ArbitrageOpportunity { sell_price: 101.0 + i as f64 * 0.1, ..opportunity() };
+                    opportunity.id = opportunity.compute_id();
+                    opportunity
+                })
+                .collect();
+            engine.dispatch_opportunities(opportunities).await;
+            engine
+        };
+
+        let engine = simulated().await;
+        let stats = engine.get_trading_stats().await.unwrap();
+        assert_eq!((stats.total_trades, stats.successful_trades), (4, 2));
+        // Each fill nets its $10.005 gross less 10bps of $1000.50 and $0.055
+        // of gas and fees; each miss costs only the $0.055
+        let pnl = engine.get_strategy_pnl().await[&StrategyKind::Direct];
+        assert!((pnl - (2.0 * 8.9495 - 2.0 * 0.055)).abs() < 1e-9, "{}", pnl);
+
+        // The same run reproduces exactly
+        let rerun = simulated().await;
+        assert_eq!(rerun.get_strategy_pnl().await[&StrategyKind::Direct], pnl);
+    }
+
+    #[tokio::test]
+    async fn trading_follows_api_health_from_degraded_through_maintenance_and_back() {
+        let jupiter = crate::mock_http::serve(vec![("/price", vec![
+            crate::mock_http::reply(200, "{}"),
+            crate::mock_http::reply(200, "{}").delayed(100),
+            crate::mock_http::reply(503, ""),
+            crate::mock_http::reply(200, "{}"),
+        ])]).await;
+        let mut config = test_config();
+        config.risk_settings.min_profit_threshold = 0.5;
+        config.api_health.degraded_latency_ms = 50;
+        config.api_health.degraded_scan_every = 2;
+        let engine = engine_with_jupiter(config, &jupiter.url);
+        let mut degraded_ticks = 0;
+
+        engine.refresh_api_health().await;
+        assert_eq!(engine.get_api_health().await, ApiHealth::Healthy);
+        assert_eq!(engine.scan_threshold(&mut degraded_ticks).await, Some(0.5));
+
+        // A slow probe: every other tick scans, at twice the margin
+        engine.refresh_api_health().await;
+        assert_eq!(engine.get_api_health().await, ApiHealth::Degraded);
+        let mut scans = Vec::new();
+        for _ in 0..3 {
+            scans.push(engine.scan_threshold(&mut degraded_ticks).await);
         }
+        assert_eq!(scans, [Some(1.0), None, Some(1.0)]);
+        assert_eq!(engine.execution_halt_reason().await, None);
+
+        engine.refresh_api_health().await;
+        assert_eq!(engine.get_api_health().await, ApiHealth::Maintenance);
+        assert_eq!(engine.scan_threshold(&mut degraded_ticks).await, None);
+        assert!(engine.execution_halt_reason().await.is_some());
+
+        engine.refresh_api_health().await;
+        assert_eq!(engine.get_api_health().await, ApiHealth::Healthy);
+        assert_eq!(engine.scan_threshold(&mut degraded_ticks).await, Some(0.5));
+        assert_eq!(engine.execution_halt_reason().await, None);
     }
 
-    fn extract_token_mints(&self, token_pair: &str) -> Result<(String, String)> {
-        // Simplified token mint extraction
-        // In a real implementation, you'd have a mapping from token pairs to mint addresses
-        let parts: Vec<&str> = token_pair.split('/').collect();
-        if parts.len() != 2 {
-            return Err(anyhow::anyhow!("Invalid token pair format: {}", token_pair));
+    #[tokio::test]
+    async fn a_dex_swap_fee_tips_the_choice_toward_jupiter() {
+        // Lifinity's 1.004 gross beats Jupiter's 1.002, which is already net of fees
+        let opportunities = |lifinity_fee: Option<f64>| async move {
+            let jupiter = crate::mock_http::serve(vec![
+                ("/quote", vec![jupiter_quote(USDC, 1_000_000_000, USDT, 1_002_000_000)]),
+            ]).await;
+            let mut config = test_config();
+            config.dex_endpoints.fee_overrides.extend(lifinity_fee.map(|fee| ("Lifinity".to_string(), fee)));
+            let engine = scanning(
+                engine_with_jupiter(config, &jupiter.url),
+                vec![stable_price("Lifinity", 1.004), stable_price("Saber", 0.995)],
+            ).await;
+            hold_usdt(&engine).await;
+            engine.warm_cache.write().await.token_decimals.insert(USDT.to_string(), 6);
+            engine.scan_enhanced_opportunities(0.1, 1_000_000_000.0).await.unwrap()
+        };
+
+        // Fee-free, the direct route wins and there is nothing to take
+        assert!(opportunities(Some(0.0)).await.is_empty());
+        // Lifinity has no configured fee, so the conservative 0.5% default
+        // nets it down to 0.999 and Jupiter comes out ahead
+        let found = opportunities(None).await;
+        assert_eq!(found.len(), 1);
+        assert!(matches!(found[0].execution_method, ExecutionMethod::Jupiter));
+        assert!((found[0].best_direct_price - 1.004 * 0.995).abs() < 1e-9);
+    }
+
+    struct FixedQuote(u64);
+
+    impl QuoteSource for FixedQuote {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        fn quote_out_amount<'a>(&'a self, _input_mint: &'a str, _output_mint: &'a str, _amount: u64) -> futures_util::future::BoxFuture<'a, Result<u64>> {
+            Box::pin(async move { Ok(self.0) })
         }
+    }
 
-        // This is a simplified mapping - in reality, you'd have a proper token registry
-        let input_mint = match parts[0] {
-            "SOL" => "So11111111111111111111111111111111111111112".to_string(),
-            "USDC" => "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
-            "USDT" => "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB".to_string(),
-            _ => return Err(anyhow::anyhow!("Unknown token: {}", parts[0])),
+    #[tokio::test]
+    async fn a_secondary_quote_far_from_jupiters_blocks_the_route() {
+        // Jupiter quotes 1,002 USDT for 1,000 USDC; the second source agrees, or quotes 990
+        let opportunities = |secondary_out: u64, fatal: bool| async move {
+            let jupiter = crate::mock_http::serve(vec![
+                ("/quote", vec![jupiter_quote(USDC, 1_000_000_000, USDT, 1_002_000_000)]),
+            ]).await;
+            let mut config = test_config();
+            config.quote_cross_check.max_divergence_pct = 0.5;
+            config.quote_cross_check.fatal = fatal;
+            let engine = scanning(
+                engine_with_jupiter(config, &jupiter.url).with_secondary_quote_source(Arc::new(FixedQuote(secondary_out))),
+                vec![stable_price("Lifinity", 1.004), stable_price("Saber", 0.995)],
+            ).await;
+            hold_usdt(&engine).await;
+            engine.warm_cache.write().await.token_decimals.insert(USDT.to_string(), 6);
+            let found = engine.scan_enhanced_opportunities(0.1, 1_000_000_000.0).await.unwrap();
+            (found.len(), engine.get_rejection_counts().await.get(&RejectionReason::QuoteDivergence).copied())
         };
 
-        let output_mint = match parts[1] {
-            "SOL" => "So11111111111111111111111111111111111111112".to_string(),
-            "USDC" => "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
-            "USDT" => "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB".to_string(),
-            _ => return Err(anyhow::anyhow!("Unknown token: {}", parts[1])),
+        assert_eq!(opportunities(1_001_900_000, true).await, (1, None));
+        assert_eq!(opportunities(990_000_000, true).await, (0, Some(1)));
+        // A warning-only cross-check lets the route through
+        assert_eq!(opportunities(990_000_000, false).await, (1, None));
+    }
+
+    #[tokio::test]
+    async fn disabled_strategies_emit_nothing_and_pnl_buckets_by_strategy() {
+        let cycling = |direct: bool| async move {
+            let jupiter = crate::mock_http::serve(vec![("/quote", vec![
+                jupiter_quote(WSOL_MINT, 1_000_000_000, USDC, 100_000_000),
+                jupiter_quote(USDC, 100_000_000, WSOL_MINT, 1_020_000_000),
+            ])]).await;
+            let mut config = test_config();
+            config.simulation.enabled = true;
+            config.strategies.direct = direct;
+            config.strategies.jupiter_route = false;
+            config.multi_hop.base_mints = vec![WSOL_MINT.to_string()];
+            config.multi_hop.intermediate_mints = vec![USDC.to_string()];
+            config.multi_hop.route_overlap = RouteOverlapPolicy::Adjust;
+            let prices = vec![sol_price("Raydium", 100.0, "raydium-pool"), sol_price("Orca", 101.0, "orca-pool")];
+            let engine = scanning(engine_with_jupiter(config, &jupiter.url), prices).await;
+            (engine, jupiter)
         };
 
-        Ok((input_mint, output_mint))
+        let (cycles_only, _jupiter) = cycling(false).await;
+        assert!(cycles_only.scan_opportunities(0.1, 10.0).await.unwrap().is_empty());
+        assert!(cycles_only.scan_enhanced_opportunities(0.1, 10.0).await.unwrap().is_empty());
+        assert_eq!(cycles_only.scan_cycle_opportunities().await.unwrap().len(), 1);
+
+        let (both, _jupiter) = cycling(true).await;
+        let direct = both.scan_opportunities(0.1, 10.0).await.unwrap().remove(0);
+        let cycle = both.scan_cycle_opportunities().await.unwrap().remove(0);
+        let direct_trade = both.execute_claimed_trade(TradeRequest { opportunity_id: direct.id, ..trade_request() }).await.unwrap();
+        let cycle_trade = both.execute_claimed_trade(TradeRequest { opportunity_id: cycle.id, ..trade_request() }).await.unwrap();
+        assert!(direct_trade.success && cycle_trade.success);
+        assert_ne!(direct_trade.actual_profit, cycle_trade.actual_profit);
+        let pnl = both.get_strategy_pnl().await;
+        assert_eq!(pnl[&StrategyKind::Direct], direct_trade.actual_profit);
+        assert_eq!(pnl[&StrategyKind::MultiHop], cycle_trade.actual_profit);
+        assert!(!pnl.contains_key(&StrategyKind::JupiterRoute));
     }
 
-    async fn execute_jupiter_swap(
-        &self,
-        opportunity: &EnhancedArbitrageOpportunity,
-        amount: u64,
-    ) -> Result<SwapResponse> {
-        if let Some(jupiter_client) = &self.jupiter_client {
-            let swap_request = SwapRequest {
-                input_mint: opportunity.input_mint.clone(),
-                output_mint: opportunity.output_mint.clone(),
-                amount,
-                user_public_key: self.config.wallet.public_key.clone(),
-                slippage: self.config.jupiter.default_slippage_bps as f64 / 100.0,
-                priority_fee: self.config.jupiter.prioritization_fee_lamports,
-                allowed_dexes: Some(self.config.jupiter.preferred_dexes.clone()),
-                excluded_dexes: Some(self.config.jupiter.excluded_dexes.clone()),
-                use_jupiter: true,
-            };
+    #[tokio::test]
+    async fn a_grown_portfolio_raises_the_max_position_size() {
+        // 10 SOL, then 20 after a good run, at $100 a SOL
+        let sol_balance = |lamports: u64| crate::mock_http::reply(200, &json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": lamports } }).to_string());
+        let no_token_accounts = || crate::mock_http::reply(200, &json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": [] } }).to_string());
+        let rpc = crate::mock_http::serve(vec![("/", vec![
+            sol_balance(10_000_000_000),
+            no_token_accounts(),
+            sol_balance(20_000_000_000),
+            no_token_accounts(),
+        ])]).await;
+        let jupiter = crate::mock_http::serve(vec![("/price", vec![crate::mock_http::reply(200, &json!({
+            WSOL_MINT: { "id": WSOL_MINT, "mint_symbol": "SOL", "vs_token": USDC, "vs_token_symbol": "USDC", "price": 100.0 },
+        }).to_string())])]).await;
 
-            jupiter_client.execute_swap(swap_request).await
-        } else {
-            Err(anyhow::anyhow!("Jupiter client not available"))
+        let mut config = test_config();
+        config.simulation.enabled = true;
+        config.rpc_endpoints.primary = rpc.url.clone();
+        config.jupiter.api_url = jupiter.url.clone();
+        config.jupiter.token_cache_path = None;
+        config.position_sizing.mode = PositionSizingMode::EquityPercent;
+        config.position_sizing.equity_percent = 10.0;
+        config.position_sizing.max_position_ceiling = 1_000.0;
+        let mut portfolio_config = config.clone();
+        portfolio_config.jupiter.enabled = true;
+        let engine = ArbitrageEngine::new(
+            config.clone(),
+            Arc::new(DexMonitor::new(config.dex_endpoints.clone())),
+            Arc::new(RwLock::new(RiskManager::new(config.risk_settings.clone()))),
+            Arc::new(PortfolioManager::new(portfolio_config)),
+            None,
+            None,
+            Arc::new(MonitoringService::new(config.monitoring.clone())),
+        )
+        .unwrap();
+        price_usdc_and_sol(&engine).await;
+        let sized = |amount: f64| TradeRequest { amount, ..trade_request() };
+
+        engine.refresh_position_size().await;
+        assert_eq!(engine.get_max_position_size().await, 100.0);
+        let oversized = engine.execute_claimed_trade(sized(150.0)).await.unwrap();
+        assert_eq!(oversized.error_message, "Risk check failed");
+
+        // The risk check sizes against the grown portfolio too
+        engine.refresh_position_size().await;
+        assert_eq!(engine.get_max_position_size().await, 200.0);
+        let grown = engine.execute_claimed_trade(sized(150.0)).await.unwrap();
+        assert!(grown.success, "{}", grown.error_message);
+    }
+
+    #[tokio::test]
+    async fn an_oversized_quote_is_halved_until_its_impact_is_within_bounds() {
+        // 1,000 USDC moves the pool 8%; 500 moves it only 0.1%
+        let jupiter = crate::mock_http::serve(vec![("/quote", vec![
+            jupiter_quote_through("pool", 8.0, USDC, 1_000_000_000, USDT, 1_002_000_000),
+            jupiter_quote_through("pool", 0.1, USDC, 500_000_000, USDT, 501_000_000),
+        ])]).await;
+        let engine = scanning(
+            engine_with_jupiter(test_config(), &jupiter.url),
+            vec![stable_price("Lifinity", 1.004), stable_price("Saber", 0.995)],
+        ).await;
+        hold_usdt(&engine).await;
+        engine.warm_cache.write().await.token_decimals.insert(USDT.to_string(), 6);
+
+        let found = engine.scan_enhanced_opportunities(0.1, 1_000_000_000.0).await.unwrap();
+        let quote = found[0].jupiter_quote.as_ref().unwrap();
+        assert_eq!((quote.in_amount, quote.price_impact_pct), (500_000_000, 0.1));
+        let quotes = jupiter.requests_to("/quote");
+        assert_eq!(quotes.len(), 2);
+        assert!(quotes[0].contains("amount=1000000000"), "{}", quotes[0]);
+        assert!(quotes[1].contains("amount=500000000"), "{}", quotes[1]);
+        assert_eq!(engine.get_rejection_counts().await.get(&RejectionReason::PriceImpactTooHigh), None);
+    }
+
+    // Quotes fine but reverts every swap, like a honeypot; records the sizes it was sent
+    struct RevertingExecutor(std::sync::Mutex<Vec<f64>>);
+
+    impl Executor for RevertingExecutor {
+        fn submits_transactions(&self) -> bool {
+            false
+        }
+
+        fn execute<'a>(
+            &'a self,
+            request: &'a TradeRequest,
+            _opportunity: &'a ArbitrageOpportunity,
+            _transaction: Option<&'a VersionedTransaction>,
+        ) -> futures_util::future::BoxFuture<'a, Result<TradeResponse>> {
+            self.0.lock().unwrap().push(request.amount);
+            Box::pin(async { Ok(TradeResponse::rejected("Transaction reverted: sell leg failed")) })
         }
     }
 
-    fn clone_for_task(&self) -> Self {
-        Self {
-            config: self.config.clone(),
-            dex_monitor: self.dex_monitor.clone(),
-            risk_manager: self.risk_manager.clone(),
-            portfolio_manager: self.portfolio_manager.clone(),
-            jito_client: self.jito_client.clone(),
-            jupiter_client: self.jupiter_client.clone(),
-            monitoring: self.monitoring.clone(),
-            is_running: self.is_running.clone(),
+    #[tokio::test]
+    async fn a_failed_canary_blacklists_the_pair_before_any_full_size_trade() {
+        let mut config = test_config();
+        config.canary.enabled = true;
+        config.canary.trade_amount = 0.01;
+        let executor = Arc::new(RevertingExecutor(std::sync::Mutex::new(Vec::new())));
+        let engine = scanning(engine_with(config), vec![sol_price("Raydium", 100.0, "raydium-pool"), sol_price("Orca", 101.0, "orca-pool")])
+            .await
+            .with_executor(executor.clone());
+        let pair = WatchedPair { input_mint: WSOL_MINT.to_string(), output_mint: USDC.to_string() };
+        engine.watch_pair(pair.clone()).await;
+        let opportunity = engine.scan_opportunities(0.1, 10.0).await.unwrap().remove(0);
+
+        for _ in 0..2 {
+            let response = engine.execute_claimed_trade(TradeRequest { opportunity_id: opportunity.id.clone(), ..trade_request() }).await.unwrap();
+            assert!(!response.success);
+            assert!(response.error_message.to_lowercase().contains("canary"), "{}", response.error_message);
         }
+        // Only the canary's minimal size ever reached the executor
+        assert_eq!(*executor.0.lock().unwrap(), [0.01]);
+        assert!(matches!(engine.get_canary_states().await.get(&pair), Some(CanaryStatus::Failed(_))));
+    }
+
+    #[test]
+    fn requote_that_fills_nothing_is_an_error() {
+        assert!(reprice_opportunity(&opportunity(), 100.0, 0.0, 0.0, -100.0, 0).is_err());
     }
 }
@@ -0,0 +1,144 @@
+use std::collections::{HashMap, HashSet};
+use tracing::info;
+
+// Weight of the newest scan in each pair's expected-value average
+const EV_ALPHA: f64 = 0.2;
+
+// Narrows the scan to the highest-EV pairs while the executor can't keep up.
+// Each scan that ends with the backlog above `high_watermark` counts towards
+// narrowing; after `persist_scans` such scans the scope halves (down to
+// `min_pairs`). The full scope returns once the backlog has stayed at or
+// below `low_watermark` for `persist_scans` scans.
+#[derive(Debug)]
+pub struct AdaptiveScope {
+    high_watermark: usize,
+    low_watermark: usize,
+    persist_scans: u32,
+    min_pairs: usize,
+    limit: Option<usize>,
+    high_streak: u32,
+    low_streak: u32,
+    queue_depth: usize,
+    scanned_pairs: usize,
+    pair_ev: HashMap<String, f64>,
+}
+
+impl AdaptiveScope {
+    pub fn new(high_watermark: usize, low_watermark: usize, persist_scans: u32, min_pairs: usize) -> Self {
+        Self {
+            high_watermark,
+            low_watermark: low_watermark.min(high_watermark),
+            persist_scans: persist_scans.max(1),
+            min_pairs: min_pairs.max(1),
+            limit: None,
+            high_streak: 0,
+            low_streak: 0,
+            queue_depth: 0,
+            scanned_pairs: 0,
+            pair_ev: HashMap::new(),
+        }
+    }
+
+    // Folds one scan into each scanned pair's EV; pairs without an
+    // opportunity this scan sample zero
+    pub fn record_scan<'a>(&mut self, scanned: &HashSet<String>, profits: impl IntoIterator<Item = (&'a str, f64)>) {
+        let mut best: HashMap<&str, f64> = HashMap::new();
+        for (token_pair, profit) in profits {
+            let entry = best.entry(token_pair).or_insert(f64::MIN);
+            *entry = entry.max(profit);
+        }
+        for token_pair in scanned {
+            let sample = best.get(token_pair.as_str()).copied().unwrap_or(0.0).max(0.0);
+            let ev = self.pair_ev.entry(token_pair.clone()).or_insert(sample);
+            *ev += EV_ALPHA * (sample - *ev);
+        }
+        self.scanned_pairs = scanned.len();
+    }
+
+    // Returns the new limit when the scope changed
+    pub fn observe_queue_depth(&mut self, depth: usize) -> Option<Option<usize>> {
+        self.queue_depth = depth;
+        if depth > self.high_watermark {
+            self.low_streak = 0;
+            self.high_streak += 1;
+            if self.high_streak < self.persist_scans {
+                return None;
+            }
+            self.high_streak = 0;
+            let current = self.limit.unwrap_or(self.scanned_pairs);
+            let narrowed = (current / 2).max(self.min_pairs);
+            if self.limit == Some(narrowed) || narrowed >= current {
+                return None;
+            }
+            self.limit = Some(narrowed);
+            info!("🚦 Executor backlog at {}, narrowing scan to the top {} pairs by EV", depth, narrowed);
+            Some(self.limit)
+        } else {
+            self.high_streak = 0;
+            if depth > self.low_watermark || self.limit.is_none() {
+                self.low_streak = 0;
+                return None;
+            }
+            self.low_streak += 1;
+            if self.low_streak < self.persist_scans {
+                return None;
+            }
+            self.low_streak = 0;
+            self.limit = None;
+            info!("🚦 Executor backlog cleared ({}), restoring full scan scope", depth);
+            Some(None)
+        }
+    }
+
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth
+    }
+
+    // The `limit` highest-EV of `pairs`, or all of them while unrestricted
+    pub fn retained_pairs<'a>(&self, pairs: impl IntoIterator<Item = &'a String>) -> Option<HashSet<String>> {
+        let limit = self.limit?;
+        let mut ranked: Vec<(&String, f64)> = pairs.into_iter()
+            .map(|token_pair| (token_pair, self.pair_ev.get(token_pair).copied().unwrap_or(0.0)))
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        Some(ranked.into_iter().take(limit).map(|(token_pair, _)| token_pair.clone()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn narrows_after_a_persistent_backlog_and_restores_once_it_clears() {
+        let mut scope = AdaptiveScope::new(10, 2, 2, 1);
+        scope.record_scan(&pairs(&["A", "B", "C", "D"]), [("A", 5.0), ("B", 1.0)]);
+
+        assert_eq!(scope.observe_queue_depth(20), None);
+        assert_eq!(scope.observe_queue_depth(20), Some(Some(2)));
+        assert_eq!(scope.retained_pairs(&pairs(&["A", "B", "C", "D"])), Some(pairs(&["A", "B"])));
+
+        // Between the watermarks nothing changes
+        assert_eq!(scope.observe_queue_depth(5), None);
+        assert_eq!(scope.observe_queue_depth(1), None);
+        assert_eq!(scope.observe_queue_depth(1), Some(None));
+        assert_eq!(scope.retained_pairs(&pairs(&["A"])), None);
+    }
+
+    #[test]
+    fn never_narrows_below_the_minimum() {
+        let mut scope = AdaptiveScope::new(0, 0, 1, 2);
+        scope.record_scan(&pairs(&["A", "B", "C"]), []);
+        assert_eq!(scope.observe_queue_depth(1), Some(Some(2)));
+        assert_eq!(scope.observe_queue_depth(1), None);
+        assert_eq!(scope.limit(), Some(2));
+    }
+}
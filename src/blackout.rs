@@ -0,0 +1,251 @@
+use crate::config::{BlackoutConfig, BlackoutWindow};
+use anyhow::Result;
+use tracing::{error, warn};
+
+const MINUTE_MS: i64 = 60 * 1000;
+const DAY_MS: i64 = 24 * 60 * MINUTE_MS;
+const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+#[derive(Debug, Clone)]
+enum Window {
+    Once { start_ms: i64, end_ms: i64, reason: String },
+    // Minutes into the UTC day; end < start crosses midnight. Weekdays count from Monday = 0
+    Daily { start_min: i64, end_min: i64, weekdays: Vec<i64>, reason: String },
+}
+
+impl Window {
+    fn parse(window: &BlackoutWindow) -> Result<Self> {
+        Ok(match window {
+            BlackoutWindow::Once { start, end, reason } => {
+                let start_ms = parse_rfc3339(start)?;
+                let end_ms = parse_rfc3339(end)?;
+                if end_ms <= start_ms {
+                    return Err(anyhow::anyhow!("Blackout window {} - {} ends before it starts", start, end));
+                }
+                Window::Once { start_ms, end_ms, reason: reason.clone() }
+            }
+            BlackoutWindow::Daily { start, end, weekdays, reason } => Window::Daily {
+                start_min: parse_time_of_day(start)?,
+                end_min: parse_time_of_day(end)?,
+                weekdays: weekdays.iter().map(|day| parse_weekday(day)).collect::<Result<_>>()?,
+                reason: reason.clone(),
+            },
+        })
+    }
+
+    fn contains(&self, now_ms: i64) -> bool {
+        match self {
+            Window::Once { start_ms, end_ms, .. } => (*start_ms..*end_ms).contains(&now_ms),
+            Window::Daily { start_min, end_min, weekdays, .. } => {
+                let minute = now_ms.rem_euclid(DAY_MS) / MINUTE_MS;
+                // 1970-01-01 was a Thursday
+                let weekday = (now_ms.div_euclid(DAY_MS) + 3).rem_euclid(7);
+                let in_hours = if start_min <= end_min {
+                    (*start_min..*end_min).contains(&minute)
+                } else {
+                    minute >= *start_min || minute < *end_min
+                };
+                in_hours && (weekdays.is_empty() || weekdays.contains(&weekday))
+            }
+        }
+    }
+
+    fn reason(&self) -> &str {
+        match self {
+            Window::Once { reason, .. } | Window::Daily { reason, .. } => reason,
+        }
+    }
+}
+
+fn parse_rfc3339(value: &str) -> Result<i64> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|time| time.timestamp_millis())
+        .map_err(|e| anyhow::anyhow!("Blackout time '{}' is not RFC 3339: {}", value, e))
+}
+
+fn parse_time_of_day(value: &str) -> Result<i64> {
+    let (hours, minutes) = value.split_once(':')
+        .and_then(|(hours, minutes)| Some((hours.parse::<i64>().ok()?, minutes.parse::<i64>().ok()?)))
+        .filter(|(hours, minutes)| (0..24).contains(hours) && (0..60).contains(minutes))
+        .ok_or_else(|| anyhow::anyhow!("Blackout time '{}' is not HH:MM", value))?;
+    Ok(hours * 60 + minutes)
+}
+
+fn parse_weekday(value: &str) -> Result<i64> {
+    WEEKDAYS.iter()
+        .position(|day| value.eq_ignore_ascii_case(day))
+        .map(|index| index as i64)
+        .ok_or_else(|| anyhow::anyhow!("Blackout weekday '{}' is not one of {:?}", value, WEEKDAYS))
+}
+
+// Decides whether execution is blacked out: scheduled windows, a pause
+// after a burst of RPC errors, or a manual override that beats both.
+// Scanning is unaffected; only execution stops.
+#[derive(Debug)]
+pub struct Blackout {
+    windows: Vec<Window>,
+    max_rpc_error_rate: Option<f64>,
+    min_rpc_calls: u64,
+    rpc_error_window_ms: i64,
+    rpc_error_pause_ms: i64,
+    // RPC call counts at the start of the current sampling window
+    rpc_sample: Option<(i64, (u64, u64))>,
+    rpc_paused_until: i64,
+    // Some(true) forces a blackout, Some(false) forces trading, None follows the schedule
+    manual: Option<bool>,
+    active: Option<String>,
+}
+
+impl Blackout {
+    pub fn new(config: &BlackoutConfig) -> Result<Self> {
+        let windows = if config.enabled {
+            config.windows.iter().map(Window::parse).collect::<Result<_>>()?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            windows,
+            max_rpc_error_rate: config.max_rpc_error_rate.filter(|_| config.enabled),
+            min_rpc_calls: config.min_rpc_calls,
+            rpc_error_window_ms: config.rpc_error_window_secs as i64 * 1000,
+            rpc_error_pause_ms: config.rpc_error_pause_secs as i64 * 1000,
+            rpc_sample: None,
+            rpc_paused_until: 0,
+            manual: None,
+            active: None,
+        })
+    }
+
+    // Call once per cycle with the RPC client's running call counts
+    pub fn refresh(&mut self, now_ms: i64, rpc_counts: (u64, u64)) {
+        self.sample_rpc_errors(now_ms, rpc_counts);
+
+        let reason = match self.manual {
+            Some(true) => Some("manual override".to_string()),
+            Some(false) => None,
+            None => self.windows.iter()
+                .find(|window| window.contains(now_ms))
+                .map(|window| format!("scheduled window: {}", window.reason()))
+                .or_else(|| (now_ms < self.rpc_paused_until).then(|| "high RPC error rate".to_string())),
+        };
+
+        if reason != self.active {
+            match &reason {
+                Some(reason) => error!("⛔ Execution blacked out ({})", reason),
+                None => warn!("✅ Blackout over, execution re-enabled"),
+            }
+            self.active = reason;
+        }
+    }
+
+    fn sample_rpc_errors(&mut self, now_ms: i64, (calls, failures): (u64, u64)) {
+        let Some(max_error_rate) = self.max_rpc_error_rate else {
+            return;
+        };
+        let Some((started_at, (start_calls, start_failures))) = self.rpc_sample else {
+            self.rpc_sample = Some((now_ms, (calls, failures)));
+            return;
+        };
+        if now_ms - started_at < self.rpc_error_window_ms {
+            return;
+        }
+
+        self.rpc_sample = Some((now_ms, (calls, failures)));
+        let window_calls = calls.saturating_sub(start_calls);
+        let window_failures = failures.saturating_sub(start_failures);
+        if window_calls >= self.min_rpc_calls.max(1)
+            && window_failures as f64 / window_calls as f64 >= max_error_rate
+        {
+            warn!("📡 {} of {} RPC calls failed in the last {}s, pausing execution for {}s",
+                  window_failures, window_calls, self.rpc_error_window_ms / 1000, self.rpc_error_pause_ms / 1000);
+            self.rpc_paused_until = now_ms + self.rpc_error_pause_ms;
+        }
+    }
+
+    // Takes effect on the next refresh
+    pub fn set_manual(&mut self, manual: Option<bool>) {
+        self.manual = manual;
+    }
+
+    pub fn manual(&self) -> Option<bool> {
+        self.manual
+    }
+
+    // Why execution is blacked out; None while trading is allowed
+    pub fn active_reason(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1970-01-05, a Monday
+    const MONDAY_MS: i64 = 4 * DAY_MS;
+
+    fn config(windows: Vec<BlackoutWindow>) -> BlackoutConfig {
+        BlackoutConfig {
+            enabled: true,
+            windows,
+            max_rpc_error_rate: Some(0.5),
+            min_rpc_calls: 10,
+            rpc_error_window_secs: 60,
+            rpc_error_pause_secs: 300,
+        }
+    }
+
+    fn daily(start: &str, end: &str, weekdays: &[&str]) -> BlackoutWindow {
+        BlackoutWindow::Daily {
+            start: start.to_string(),
+            end: end.to_string(),
+            weekdays: weekdays.iter().map(|day| day.to_string()).collect(),
+            reason: "maintenance".to_string(),
+        }
+    }
+
+    #[test]
+    fn daily_windows_cross_midnight_and_respect_weekdays() {
+        let mut blackout = Blackout::new(&config(vec![daily("23:30", "00:30", &["mon"])])).unwrap();
+        blackout.refresh(MONDAY_MS + 23 * 60 * MINUTE_MS + 45 * MINUTE_MS, (0, 0));
+        assert_eq!(blackout.active_reason(), Some("scheduled window: maintenance"));
+
+        blackout.refresh(MONDAY_MS + 12 * 60 * MINUTE_MS, (0, 0));
+        assert_eq!(blackout.active_reason(), None);
+        // Tuesday just after midnight is in hours, but not a listed weekday
+        blackout.refresh(MONDAY_MS + DAY_MS + 10 * MINUTE_MS, (0, 0));
+        assert_eq!(blackout.active_reason(), None);
+    }
+
+    #[test]
+    fn a_burst_of_rpc_errors_pauses_execution_for_a_while() {
+        let mut blackout = Blackout::new(&config(Vec::new())).unwrap();
+        blackout.refresh(0, (0, 0));
+        blackout.refresh(60_000, (20, 12));
+        assert_eq!(blackout.active_reason(), Some("high RPC error rate"));
+        blackout.refresh(360_001, (40, 12));
+        assert_eq!(blackout.active_reason(), None);
+    }
+
+    #[test]
+    fn the_manual_override_beats_the_schedule() {
+        let mut blackout = Blackout::new(&config(vec![daily("00:00", "23:59", &[])])).unwrap();
+        blackout.set_manual(Some(false));
+        blackout.refresh(MONDAY_MS, (0, 0));
+        assert_eq!(blackout.active_reason(), None);
+        blackout.set_manual(Some(true));
+        blackout.refresh(MONDAY_MS, (0, 0));
+        assert_eq!(blackout.active_reason(), Some("manual override"));
+    }
+
+    #[test]
+    fn malformed_windows_are_refused() {
+        assert!(Blackout::new(&config(vec![daily("25:00", "01:00", &[])])).is_err());
+        assert!(Blackout::new(&config(vec![daily("01:00", "02:00", &["someday"])])).is_err());
+        assert!(Blackout::new(&config(vec![BlackoutWindow::Once {
+            start: "2026-01-02T00:00:00Z".to_string(),
+            end: "2026-01-01T00:00:00Z".to_string(),
+            reason: String::new(),
+        }])).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use crate::config::{ClockConfig, FutureTimestampPolicy};
+use chrono::Utc;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, RwLock};
+use tracing::warn;
+
+// Source of the current time (unix ms) for everything the engine times:
+// expiries, hourly and daily windows, cache ages. Swapping in a `ManualClock`
+// lets those windows be driven deterministically.
+pub trait Clock: Send + Sync + std::fmt::Debug {
+    fn now_ms(&self) -> i64;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        Utc::now().timestamp_millis()
+    }
+}
+
+// Stands still until set or advanced
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now_ms: AtomicI64,
+}
+
+impl ManualClock {
+    pub fn new(now_ms: i64) -> Self {
+        Self { now_ms: AtomicI64::new(now_ms) }
+    }
+
+    pub fn set(&self, now_ms: i64) {
+        self.now_ms.store(now_ms, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, delta_ms: i64) {
+        self.now_ms.fetch_add(delta_ms, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ms(&self) -> i64 {
+        self.now_ms.load(Ordering::SeqCst)
+    }
+}
+
+// The one clock the engine hands to every component it builds. Its source
+// can be swapped after construction and every holder follows, so nothing
+// keeps reading the system time behind a `ManualClock`.
+#[derive(Debug)]
+pub struct SharedClock {
+    source: RwLock<Arc<dyn Clock>>,
+}
+
+impl SharedClock {
+    pub fn new(source: Arc<dyn Clock>) -> Self {
+        Self { source: RwLock::new(source) }
+    }
+
+    pub fn set(&self, source: Arc<dyn Clock>) {
+        *self.source.write().unwrap() = source;
+    }
+}
+
+impl Clock for SharedClock {
+    fn now_ms(&self) -> i64 {
+        self.source.read().unwrap().now_ms()
+    }
+}
+
+// Checks a timestamp from outside the bot (a price feed, a file written by
+// an earlier run) against `now_ms`. Up to `max_future_skew_ms` ahead is
+// accepted as clock skew; anything further is clamped to now or, under
+// `Reject`, dropped (None).
+pub fn check_timestamp(
+    timestamp: i64,
+    now_ms: i64,
+    config: &ClockConfig,
+    // Names the record in the warning; only called when there is one
+    source: impl FnOnce() -> String,
+) -> Option<i64> {
+    let ahead_ms = timestamp - now_ms;
+    if ahead_ms <= config.max_future_skew_ms as i64 {
+        return Some(timestamp);
+    }
+    match config.future_timestamps {
+        FutureTimestampPolicy::Clamp => {
+            warn!("🕰️ {} timestamp is {}ms in the future, clamping to now", source(), ahead_ms);
+            Some(now_ms)
+        }
+        FutureTimestampPolicy::Reject => {
+            warn!("🕰️ {} timestamp is {}ms in the future, ignoring it", source(), ahead_ms);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(future_timestamps: FutureTimestampPolicy) -> ClockConfig {
+        ClockConfig { max_future_skew_ms: 1_000, future_timestamps }
+    }
+
+    #[test]
+    fn manual_clock_only_moves_when_told() {
+        let clock = ManualClock::new(1_000);
+        assert_eq!(clock.now_ms(), 1_000);
+        clock.advance(500);
+        assert_eq!(clock.now_ms(), 1_500);
+        clock.set(10);
+        assert_eq!(clock.now_ms(), 10);
+    }
+
+    #[test]
+    fn small_skew_passes_and_the_rest_follows_the_policy() {
+        let clamp = config(FutureTimestampPolicy::Clamp);
+        assert_eq!(check_timestamp(10_500, 10_000, &clamp, String::new), Some(10_500));
+        assert_eq!(check_timestamp(5_000, 10_000, &clamp, String::new), Some(5_000));
+        assert_eq!(check_timestamp(20_000, 10_000, &clamp, String::new), Some(10_000));
+
+        let reject = config(FutureTimestampPolicy::Reject);
+        assert_eq!(check_timestamp(20_000, 10_000, &reject, String::new), None);
+    }
+}
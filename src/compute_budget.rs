@@ -0,0 +1,158 @@
+use anyhow::Result;
+use solana_compute_budget_interface::{self as compute_budget, ComputeBudgetInstruction};
+use solana_sdk::instruction::Instruction;
+
+// Runtime ceiling on compute units for a single transaction
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+// Discriminators of ComputeBudgetInstruction's borsh encoding
+const REQUEST_HEAP_FRAME: u8 = 1;
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+const SET_LOADED_ACCOUNTS_DATA_SIZE_LIMIT: u8 = 4;
+
+// The one compute budget a combined transaction runs under
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComputeBudget {
+    pub unit_limit: Option<u32>,
+    pub unit_price_micro_lamports: Option<u64>,
+    pub heap_frame_bytes: Option<u32>,
+    pub loaded_accounts_data_size_limit: Option<u32>,
+}
+
+impl ComputeBudget {
+    // What the budget bids on top of signature fees
+    pub fn priority_fee_lamports(&self) -> u64 {
+        let units = self.unit_limit.unwrap_or(0) as u128;
+        let price = self.unit_price_micro_lamports.unwrap_or(0) as u128;
+        ((units * price).div_ceil(1_000_000)) as u64
+    }
+
+    fn instructions(&self) -> Vec<Instruction> {
+        let mut instructions = Vec::new();
+        if let Some(bytes) = self.heap_frame_bytes {
+            instructions.push(ComputeBudgetInstruction::request_heap_frame(bytes));
+        }
+        if let Some(units) = self.unit_limit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+        if let Some(price) = self.unit_price_micro_lamports {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        if let Some(bytes) = self.loaded_accounts_data_size_limit {
+            instructions.push(ComputeBudgetInstruction::set_loaded_accounts_data_size_limit(bytes));
+        }
+        instructions
+    }
+}
+
+// Combines the instruction sets of several swap legs into one transaction's
+// instructions. Solana honours only one instruction of each compute-budget
+// kind per transaction (duplicates fail it), so each leg's budget is stripped
+// and replaced by a single merged budget up front: unit limits are summed
+// (capped at `max_unit_limit`), the highest unit price wins, and heap frame
+// and loaded-data limits take the largest request.
+pub fn merge_instruction_sets(sets: &[Vec<Instruction>], max_unit_limit: u32) -> Result<(Vec<Instruction>, ComputeBudget)> {
+    let mut budget = ComputeBudget::default();
+    let mut body = Vec::new();
+
+    for set in sets {
+        let mut leg_units = None;
+        for instruction in set {
+            if instruction.program_id != compute_budget::id() {
+                body.push(instruction.clone());
+                continue;
+            }
+
+            let (&kind, data) = instruction.data.split_first()
+                .ok_or_else(|| anyhow::anyhow!("Empty compute budget instruction"))?;
+            match kind {
+                SET_COMPUTE_UNIT_LIMIT => leg_units = Some(read_u32(data)?),
+                SET_COMPUTE_UNIT_PRICE => {
+                    let price = read_u64(data)?;
+                    budget.unit_price_micro_lamports = budget.unit_price_micro_lamports.max(Some(price));
+                }
+                REQUEST_HEAP_FRAME => {
+                    budget.heap_frame_bytes = budget.heap_frame_bytes.max(Some(read_u32(data)?));
+                }
+                SET_LOADED_ACCOUNTS_DATA_SIZE_LIMIT => {
+                    budget.loaded_accounts_data_size_limit =
+                        budget.loaded_accounts_data_size_limit.max(Some(read_u32(data)?));
+                }
+                other => return Err(anyhow::anyhow!("Unsupported compute budget instruction {}", other)),
+            }
+        }
+
+        // Legs without an explicit limit add nothing to the sum
+        if let Some(units) = leg_units {
+            let total = budget.unit_limit.unwrap_or(0).saturating_add(units);
+            budget.unit_limit = Some(total.min(max_unit_limit));
+        }
+    }
+
+    let mut instructions = budget.instructions();
+    instructions.extend(body);
+    Ok((instructions, budget))
+}
+
+fn read_u32(data: &[u8]) -> Result<u32> {
+    let bytes = data.get(..4).ok_or_else(|| anyhow::anyhow!("Truncated compute budget instruction"))?;
+    Ok(u32::from_le_bytes(bytes.try_into()?))
+}
+
+fn read_u64(data: &[u8]) -> Result<u64> {
+    let bytes = data.get(..8).ok_or_else(|| anyhow::anyhow!("Truncated compute budget instruction"))?;
+    Ok(u64::from_le_bytes(bytes.try_into()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_system_interface::instruction::transfer;
+
+    #[test]
+    fn merges_leg_budgets_into_one() {
+        let swap = transfer(&Pubkey::new_unique(), &Pubkey::new_unique(), 1);
+        let legs = vec![
+            vec![
+                ComputeBudgetInstruction::set_compute_unit_limit(300_000),
+                ComputeBudgetInstruction::set_compute_unit_price(1_000),
+                swap.clone(),
+            ],
+            vec![
+                ComputeBudgetInstruction::set_compute_unit_limit(400_000),
+                ComputeBudgetInstruction::set_compute_unit_price(5_000),
+                ComputeBudgetInstruction::request_heap_frame(64 * 1024),
+                swap.clone(),
+            ],
+        ];
+
+        let (instructions, budget) = merge_instruction_sets(&legs, MAX_COMPUTE_UNIT_LIMIT).unwrap();
+        assert_eq!(budget, ComputeBudget {
+            unit_limit: Some(700_000),
+            unit_price_micro_lamports: Some(5_000),
+            heap_frame_bytes: Some(64 * 1024),
+            loaded_accounts_data_size_limit: None,
+        });
+        assert_eq!(instructions.iter().filter(|i| i.program_id == compute_budget::id()).count(), 3);
+        assert_eq!(&instructions[3..], &[swap.clone(), swap]);
+    }
+
+    #[test]
+    fn caps_the_summed_unit_limit() {
+        let legs = vec![
+            vec![ComputeBudgetInstruction::set_compute_unit_limit(1_000_000)],
+            vec![ComputeBudgetInstruction::set_compute_unit_limit(1_000_000)],
+        ];
+        let (_, budget) = merge_instruction_sets(&legs, MAX_COMPUTE_UNIT_LIMIT).unwrap();
+        assert_eq!(budget.unit_limit, Some(MAX_COMPUTE_UNIT_LIMIT));
+    }
+
+    #[test]
+    fn priority_fee_rounds_up_to_whole_lamports() {
+        let budget = ComputeBudget { unit_limit: Some(200_001), unit_price_micro_lamports: Some(5), ..Default::default() };
+        assert_eq!(budget.priority_fee_lamports(), 2);
+        assert_eq!(ComputeBudget::default().priority_fee_lamports(), 0);
+    }
+}
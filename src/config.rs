@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::str::FromStr;
 use anyhow::Result;
-use crate::types::JupiterConfig;
+use crate::types::{HttpClientConfig, JupiterConfig, PriceConfidence, SlotDriftAction, StrategyKind, SwapMode, TransactionVersion};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -13,6 +14,45 @@ pub struct Config {
     pub risk_settings: RiskSettings,
     pub monitoring: MonitoringConfig,
     pub trading: TradingConfig,
+    #[serde(default)]
+    pub pair_overrides: HashMap<String, PairOverride>,
+    pub rebalancing: RebalancingConfig,
+    pub multi_hop: MultiHopConfig,
+    pub oracle: OracleConfig,
+    pub scan_scope: ScanScopeConfig,
+    pub priority_fee: PriorityFeeConfig,
+    pub impact_alerts: ImpactAlertConfig,
+    pub stable_pairs: StablePairsConfig,
+    pub event_sink: EventSinkConfig,
+    pub scoring_weights: ScoringWeights,
+    pub canary: CanaryConfig,
+    pub price_history: PriceHistoryConfig,
+    pub kill_switch: KillSwitchConfig,
+    pub simulation: SimulationConfig,
+    pub win_rate_alerts: WinRateAlertConfig,
+    pub api_health: ApiHealthConfig,
+    pub size_quantization: SizeQuantizationConfig,
+    pub backpressure: BackpressureConfig,
+    pub history_retention: HistoryRetentionConfig,
+    pub quote_cross_check: QuoteCrossCheckConfig,
+    pub strategies: StrategiesConfig,
+    pub grpc: GrpcConfig,
+    pub position_sizing: PositionSizingConfig,
+    pub mint_safety: MintSafetyConfig,
+    pub display: DisplayConfig,
+    pub housekeeping: HousekeepingConfig,
+    pub clock: ClockConfig,
+    pub price_quorum: PriceQuorumConfig,
+    pub stats: StatsConfig,
+    pub blackout: BlackoutConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcConfig {
+    // When false, a gRPC server that can't bind leaves the bot trading headless
+    pub bind_failure_fatal: bool,
+    // Further ports to try, in order, after the requested one is taken
+    pub fallback_ports: u16,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +71,24 @@ pub struct DexConfig {
     pub aldrin: DexEndpoint,
     pub saber: DexEndpoint,
     pub mercurial: DexEndpoint,
+    pub max_concurrent_requests: usize,
+    pub endpoint_timeout_ms: u64,
+    pub max_backoff_cycles: u32,
+    // Swap fees (%) for DEXes beyond the endpoints above, keyed by DEX name
+    #[serde(default)]
+    pub fee_overrides: HashMap<String, f64>,
+    // Assumed for DEXes with no known fee; deliberately on the high side
+    pub default_fee_percentage: f64,
+}
+
+impl DexConfig {
+    pub fn enabled_endpoints(&self) -> Vec<DexEndpoint> {
+        [&self.raydium, &self.orca, &self.serum, &self.aldrin, &self.saber, &self.mercurial]
+            .into_iter()
+            .filter(|endpoint| endpoint.enabled)
+            .cloned()
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +107,19 @@ pub struct WalletConfig {
     pub public_key: String,
     pub max_sol_balance: f64,
     pub min_sol_balance: f64,
+    pub consolidate_wsol: bool,
+    // Sign through this service instead; private_key may then be left empty
+    pub remote_signer: Option<RemoteSignerConfig>,
+}
+
+// Signing service that holds the wallet key, e.g. in front of a hardware device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSignerConfig {
+    pub url: String,
+    pub timeout_ms: u64,
+    // Sent with every signing request; values may reference ${ENV_VAR}
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +129,16 @@ pub struct JitoConfig {
     pub bundle_endpoint: String,
     pub max_tip_lamports: u64,
     pub min_tip_lamports: u64,
+    pub failover_threshold: u32,
+    pub failover_window_secs: u64,
+    pub recovery_probe_secs: u64,
+    // Bid a percentile of recently landed tips instead of a flat tip
+    pub dynamic_tip: bool,
+    pub tip_floor_url: String,
+    pub tip_percentile: u8,
+    pub tip_refresh_ms: u64,
+    // A dynamic tip never takes more than this share of an opportunity's net profit
+    pub max_tip_edge_share: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,6 +152,469 @@ pub struct RiskSettings {
     pub stop_loss_percentage: f64,
     pub max_gas_price: u64,
     pub min_liquidity: f64,
+    pub blocked_pools: Vec<String>,
+    #[serde(default)]
+    pub exposure_groups: HashMap<String, ExposureGroup>,
+    pub min_price_confidence: PriceConfidence,
+    pub confidence_profit_multipliers: ConfidenceMultipliers,
+    // Cap on the total amount committed to submitted-but-unsettled trades
+    pub max_inflight_capital: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionSizingMode {
+    // risk_settings.max_position_size as configured
+    Fixed,
+    // A share of current portfolio value, so profits compound
+    EquityPercent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionSizingConfig {
+    pub mode: PositionSizingMode,
+    // Percent of portfolio value allowed per position in EquityPercent mode
+    pub equity_percent: f64,
+    // Absolute cap on the computed size, however large the portfolio grows
+    pub max_position_ceiling: f64,
+    pub refresh_interval_ms: u64,
+}
+
+// Scales the minimum profit threshold by the confidence of the underlying prices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfidenceMultipliers {
+    pub low: f64,
+    pub medium: f64,
+    pub high: f64,
+}
+
+impl ConfidenceMultipliers {
+    pub fn for_confidence(&self, confidence: PriceConfidence) -> f64 {
+        match confidence {
+            PriceConfidence::Low => self.low,
+            PriceConfidence::Medium => self.medium,
+            PriceConfidence::High => self.high,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExposureGroup {
+    pub mints: Vec<String>,
+    pub max_exposure_usd: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PairOverride {
+    pub slippage_bps: Option<u16>,
+    pub min_profit_threshold: Option<f64>,
+    pub max_amount: Option<f64>,
+    pub max_price_impact_pct: Option<f64>,
+    pub quote_cache_ttl_ms: Option<u64>,
+}
+
+// Effective per-pair settings after merging any override with the globals
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairSettings {
+    pub slippage_bps: u16,
+    pub min_profit_threshold: f64,
+    pub max_amount: f64,
+    pub max_price_impact_pct: f64,
+    pub quote_cache_ttl_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalancingConfig {
+    pub enabled: bool,
+    pub base_mint: String,
+    pub target_weights: HashMap<String, f64>,
+    pub drift_tolerance_pct: f64,
+    pub max_cost_pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiHopConfig {
+    pub enabled: bool,
+    pub base_mints: Vec<String>,
+    pub intermediate_mints: Vec<String>,
+    pub amount: u64,
+    pub min_profit_percentage: f64,
+    // What to do when both legs of a cycle route through the same pool
+    pub route_overlap: RouteOverlapPolicy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RouteOverlapPolicy {
+    Reject,
+    // Subtract the first leg's impact on the shared pools from the profit
+    Adjust,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OracleConfig {
+    pub enabled: bool,
+    pub max_deviation_pct: f64,
+    // Pyth price account per mint. Both mints of every traded pair need one,
+    // the quote mint (e.g. USDC) included, or their trades are rejected
+    pub price_accounts: HashMap<String, String>,
+    // Slots a Pyth price may lag the slot it is read at; None disables the check
+    #[serde(default)]
+    pub max_staleness_slots: Option<u64>,
+}
+
+// Each scan strategy can be switched off without touching the others
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategiesConfig {
+    pub direct: bool,
+    pub jupiter_route: bool,
+    pub multi_hop: bool,
+    // Share (0-1] of the global position size, trades/hour and daily loss
+    // limits each strategy may use; unlisted strategies only face the global caps
+    #[serde(default)]
+    pub budgets: HashMap<StrategyKind, f64>,
+}
+
+impl StrategiesConfig {
+    pub fn is_enabled(&self, strategy: StrategyKind) -> bool {
+        match strategy {
+            StrategyKind::Direct => self.direct,
+            StrategyKind::JupiterRoute => self.jupiter_route,
+            StrategyKind::MultiHop => self.multi_hop,
+        }
+    }
+}
+
+// Re-quotes Jupiter's routes against an independent source before trusting them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteCrossCheckConfig {
+    pub enabled: bool,
+    // Jupiter-compatible quote API run independently (e.g. self-hosted)
+    pub secondary_api_url: Option<String>,
+    pub max_divergence_pct: f64,
+    // Reject on disagreement; otherwise only warn
+    pub fatal: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WatchedPair {
+    pub input_mint: String,
+    pub output_mint: String,
+}
+
+// Effective set of pairs the scanner considers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ScanScope {
+    All,
+    Watchlist(Vec<WatchedPair>),
+    TopVolume(usize),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanScopeConfig {
+    // Only these pairs are scanned when non-empty
+    pub watchlist: Vec<WatchedPair>,
+    // With an empty watchlist, scan only the top pairs by 24h volume
+    pub auto_discover: bool,
+    pub auto_discover_limit: usize,
+    // Consecutive scans Jupiter may find no route for a pair before it is
+    // dropped from Jupiter scanning; 0 never drops
+    pub route_miss_tolerance: u32,
+    // A dropped pair is re-quoted once every this many scans
+    pub route_recheck_scans: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriorityFeeConfig {
+    pub dynamic: bool,
+    // Percentile of recent prioritization fees to bid at
+    pub percentile: u8,
+    pub floor_micro_lamports: u64,
+    // Further capped by risk_settings.max_gas_price
+    pub ceiling_micro_lamports: u64,
+    // Bid more for more profitable opportunities; None bids the same for all
+    pub profit_scaling: Option<ProfitFeeMapping>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum ProfitFeeMapping {
+    // Scales from floor at zero profit up to ceiling at `full_bid_profit_usd`
+    Linear { full_bid_profit_usd: f64 },
+    // Bids the price of the highest step whose threshold the profit reaches
+    Stepped { steps: Vec<ProfitFeeStep> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfitFeeStep {
+    pub min_profit_usd: f64,
+    pub micro_lamports: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactAlertConfig {
+    pub enabled: bool,
+    // Alert when impact exceeds this multiple of the pool's baseline
+    pub spike_multiple: f64,
+    // EWMA weight of each new sample in the baseline
+    pub baseline_alpha: f64,
+    pub min_samples: u32,
+    pub auto_pause: bool,
+    pub pause_secs: u64,
+}
+
+// Swaps between two of these mints use tighter thresholds and a peg check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StablePairsConfig {
+    pub mints: Vec<String>,
+    pub min_profit_threshold: f64,
+    pub slippage_bps: u16,
+    // Prices further than this from 1.0 signal depeg risk, not arbitrage
+    pub max_peg_deviation_pct: f64,
+}
+
+// How opportunities are ranked: higher weights favour profit or likely wins,
+// higher penalties favour fresh, low-impact opportunities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoringWeights {
+    // Per percentage point of profit
+    pub profit_weight: f64,
+    // Per unit (0-1) of estimated win probability
+    pub probability_weight: f64,
+    // Per second of price age
+    pub latency_penalty: f64,
+    // Per percentage point of combined price impact
+    pub impact_penalty: f64,
+}
+
+// Emergency stop: execution halts while the sentinel file exists or after
+// SIGUSR2 (a second SIGUSR2 re-enables)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillSwitchConfig {
+    pub sentinel_path: Option<String>,
+    pub listen_for_signal: bool,
+}
+
+// Rounds trade sizes to whole lots and skips sub-economical trades
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeQuantizationConfig {
+    // Lot size per input mint, in UI units; mints not listed aren't rounded
+    #[serde(default)]
+    pub steps: HashMap<String, f64>,
+    // Smallest trade worth placing, in the profit denomination; 0 disables
+    pub min_notional: f64,
+}
+
+// Background Jupiter health polling and how trading reacts to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiHealthConfig {
+    pub enabled: bool,
+    pub poll_interval_ms: u64,
+    // Probes slower than this count as Degraded
+    pub degraded_latency_ms: u64,
+    // While Degraded, min profit is scaled by this...
+    pub degraded_profit_multiplier: f64,
+    // ...and only every Nth scan runs
+    pub degraded_scan_every: u32,
+}
+
+// Narrows the scan to the highest-EV pairs while opportunities pile up
+// faster than they can be executed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackpressureConfig {
+    pub enabled: bool,
+    // Backlog (pending trades plus opportunities deferred by the per-scan cap)
+    // above which the executor counts as saturated...
+    pub high_watermark: usize,
+    // ...and at or below which it counts as cleared
+    pub low_watermark: usize,
+    // Consecutive scans a condition must hold before the scope changes
+    pub persist_scans: u32,
+    pub min_pairs: usize,
+}
+
+// Alerts when too few of the most recent trades were profitable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WinRateAlertConfig {
+    pub enabled: bool,
+    // Number of most recent trades the win rate is computed over
+    pub window: usize,
+    // Alert when the rolling win rate falls below this fraction
+    pub min_win_rate: f64,
+    // Also engage the kill switch (release with SIGUSR2, so this requires
+    // kill_switch.listen_for_signal)
+    pub auto_pause: bool,
+}
+
+// Replaces chain execution with a deterministic simulator for strategy testing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationConfig {
+    pub enabled: bool,
+    // Fraction of trades that fill; the rest fail and only cost gas
+    pub fill_rate: f64,
+    // Slippage taken on every fill, against the traded notional
+    pub slippage_bps: u16,
+    // Flat fee per trade on top of the estimated gas, in the profit denomination
+    pub fee_per_trade: f64,
+    pub latency_ms: u64,
+}
+
+// Ages records out of the opportunity/trade history. Trades are kept longer
+// (or forever) since they matter for accounting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRetentionConfig {
+    pub enabled: bool,
+    pub opportunity_retention_days: u64,
+    // None never prunes trades
+    pub trade_retention_days: Option<u64>,
+    pub vacuum_interval_secs: u64,
+}
+
+// Rolling per-pair price samples kept for analytics and backtesting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceHistoryConfig {
+    pub enabled: bool,
+    // None keeps the history in memory only
+    pub path: Option<String>,
+    // Minimum gap between samples of the same pair on the same DEX
+    pub sample_interval_ms: u64,
+    pub retention_secs: u64,
+    pub flush_interval_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MintRestrictionPolicy {
+    // Trade restricted mints like any other
+    Allow,
+    // Never trade pairs involving a restricted mint
+    Skip,
+    // Require the pair to land a canary trade first
+    Canary,
+}
+
+// Mints with a freeze authority or the non-transferable extension can leave
+// a bought leg impossible to sell
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintSafetyConfig {
+    pub policy: MintRestrictionPolicy,
+    // Exempt from the policy despite a freeze authority (e.g. USDC, USDT)
+    pub trusted_mints: Vec<String>,
+}
+
+// Periodic cleanup of leftover wSOL accounts and dust balances
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HousekeepingConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    // Carry out the cleanup; otherwise it is only logged
+    pub auto_execute: bool,
+    // Balances worth less than this count as dust
+    pub dust_threshold_usd: f64,
+    // Dust worth less than this wouldn't cover a swap and is only reported
+    pub min_swap_value_usd: f64,
+    // Token dust is consolidated into
+    pub base_mint: String,
+    // Never treated as dust
+    pub keep_mints: Vec<String>,
+}
+
+// Periods when execution is paused: protocol upgrades, congestion, maintenance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlackoutConfig {
+    pub enabled: bool,
+    #[serde(default)]
+    pub windows: Vec<BlackoutWindow>,
+    // Pause once this share of RPC calls within `rpc_error_window_secs` failed; None disables
+    pub max_rpc_error_rate: Option<f64>,
+    // Fewer calls than this in a window say nothing about the error rate
+    pub min_rpc_calls: u64,
+    pub rpc_error_window_secs: u64,
+    pub rpc_error_pause_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum BlackoutWindow {
+    // RFC 3339 start and end, e.g. around an announced upgrade
+    Once {
+        start: String,
+        end: String,
+        #[serde(default)]
+        reason: String,
+    },
+    // "HH:MM" UTC every day, or only on `weekdays` ("mon" to "sun"); may cross midnight
+    Daily {
+        start: String,
+        end: String,
+        #[serde(default)]
+        weekdays: Vec<String>,
+        #[serde(default)]
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsConfig {
+    // Trade recordings queued for the stats aggregator; when full, recording
+    // waits instead of dropping
+    pub channel_capacity: usize,
+}
+
+// A pair's price is only trusted once enough independent DEXes agree on it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceQuorumConfig {
+    // Distinct DEXes that must agree before the pair is scanned
+    pub min_sources: usize,
+    // Largest distance from the median price, in percent, that still counts as agreeing
+    pub agreement_tolerance_pct: f64,
+}
+
+// Tolerance for timestamps from outside the bot that are ahead of the local clock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockConfig {
+    // Further ahead than this is treated as a bad timestamp, not clock skew
+    pub max_future_skew_ms: u64,
+    pub future_timestamps: FutureTimestampPolicy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FutureTimestampPolicy {
+    // Treat the record as observed now
+    Clamp,
+    // Drop the record
+    Reject,
+}
+
+// How monetary values are written to logs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisplayConfig {
+    // Used for every denomination without a fixed precision
+    pub significant_figures: usize,
+    pub thousands_separator: String,
+    pub decimal_separator: String,
+    // Fixed decimals per denomination ("USD" for dollar values, else the token symbol)
+    #[serde(default)]
+    pub precision: HashMap<String, usize>,
+}
+
+// Minimal-size trade a newly watched pair must land before full-size trading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryConfig {
+    pub enabled: bool,
+    // Same units as TradeRequest::amount
+    pub trade_amount: f64,
+}
+
+// Message-bus publishing of opportunities and trades; disabled without a URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSinkConfig {
+    pub nats_url: Option<String>,
+    pub opportunity_subject: String,
+    pub trade_subject: String,
+    // Events queued before new ones are dropped
+    pub buffer_size: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,16 +633,326 @@ pub struct TradingConfig {
     pub enable_auto_trading: bool,
     pub min_opportunity_duration_ms: u64,
     pub price_update_threshold: f64,
+    pub trade_size_usd: Option<f64>,
+    pub max_blockhash_retries: u32,
+    pub simulate_before_execute: bool,
+    pub simulation_tolerance_bps: u16,
+    // ExactOut fixes what the first leg buys at the trade size and bounds
+    // what it may spend instead; multi-hop cycles always quote ExactIn
+    #[serde(default)]
+    pub swap_mode: SwapMode,
+    // Most an ExactOut swap may spend, in USD of the input mint; None is unbounded
+    pub max_exact_out_input_usd: Option<f64>,
+    pub execution_latency_budget_ms: u64,
+    pub requote_on_latency_breach: bool,
+    pub skip_warmup: bool,
+    pub warmup_timeout_ms: u64,
+    pub heartbeat_timeout_ms: u64,
+    pub task_restart_delay_ms: u64,
+    pub pending_trades_path: String,
+    pub history_path: Option<String>,
+    pub max_opportunities_per_scan: usize,
+    // How long a detected opportunity stays valid for streaming and execution
+    pub opportunity_ttl_ms: u64,
+    // Profit move, in percentage points, before an opportunity seen in
+    // consecutive scans is re-sent to stream subscribers
+    pub feed_change_threshold_pct: f64,
+    // Hysteresis around the profit threshold, in percentage points: a pair
+    // starts being pursued above min + enter and stops below min - exit
+    pub hysteresis_enter_margin: f64,
+    pub hysteresis_exit_margin: f64,
+    // Opportunities must net at least this % of trade size after all costs
+    pub min_edge_over_fees_pct: f64,
+    // How long an opportunity stays with the wallet that claimed it; other
+    // wallets only get it sooner if that wallet declines. 0 disables claims
+    pub opportunity_claim_ttl_ms: u64,
+    // Times a Jupiter quote over the price impact limit is re-quoted at half
+    // size before the route is rejected; 0 rejects straight away
+    pub impact_size_reductions: u32,
+    // Every profit and gas figure is expressed in this token
+    pub profit_denomination_mint: String,
+    // Commitment a trade must reach before its profit is recorded
+    pub profit_commitment: ProfitCommitment,
+    // How often unfinalized trades are checked for finalization or a rollback
+    pub finality_poll_ms: u64,
+    // Create missing token accounts for watchlisted output mints at startup,
+    // so swaps don't pay the rent (or the extra instruction) on the hot path
+    pub precreate_token_accounts: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfitCommitment {
+    // Record at once; reversed if a reorg rolls the trade back before finalization
+    Confirmed,
+    // Record only once finalized, ~13s later, so recorded profit never rolls back
+    Finalized,
 }
 
 impl Config {
     pub fn load(path: &str) -> Result<Self> {
         let content = std::fs::read_to_string(path)?;
         let config: Config = toml::from_str(&content)?;
+        config.validate()?;
         Ok(config)
     }
-    
-    pub fn default() -> Self {
+
+    // Loads a base file plus overlays (e.g. config.toml, config.prod.toml).
+    // Later files win: tables merge key by key at every depth, while scalars
+    // and arrays replace the earlier value outright (an overlay's
+    // `secondary = [...]` is the whole list, not an addition to it). Only the
+    // merged result has to be a complete config.
+    pub fn load_layered(paths: &[String]) -> Result<Self> {
+        let mut merged = toml::Value::Table(toml::value::Table::new());
+        for path in paths {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("Failed to read config {}: {}", path, e))?;
+            let layer: toml::Value = toml::from_str(&content)
+                .map_err(|e| anyhow::anyhow!("Failed to parse config {}: {}", path, e))?;
+            merge_toml(&mut merged, layer);
+        }
+
+        let config: Config = merged.try_into()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let total_weight: f64 = self.rebalancing.target_weights.values().sum();
+        if self.rebalancing.target_weights.values().any(|w| *w < 0.0) || total_weight > 1.0 + 1e-9 {
+            return Err(anyhow::anyhow!("Rebalancing target weights must be non-negative and sum to at most 1.0"));
+        }
+
+        for (api_type, url) in &self.jupiter.base_urls {
+            let parsed = reqwest::Url::parse(url)
+                .map_err(|e| anyhow::anyhow!("Invalid Jupiter base URL for {:?} '{}': {}", api_type, url, e))?;
+            if parsed.scheme() != "http" && parsed.scheme() != "https" {
+                return Err(anyhow::anyhow!("Jupiter base URL for {:?} must be http(s): {}", api_type, url));
+            }
+        }
+
+        let weights = &self.scoring_weights;
+        if [weights.profit_weight, weights.probability_weight, weights.latency_penalty, weights.impact_penalty]
+            .iter()
+            .any(|w| !w.is_finite() || *w < 0.0)
+        {
+            return Err(anyhow::anyhow!("Scoring weights must be non-negative"));
+        }
+
+        if self.oracle.enabled && self.oracle.price_accounts.is_empty() {
+            return Err(anyhow::anyhow!(
+                "oracle.enabled needs oracle.price_accounts for both mints of every traded pair"
+            ));
+        }
+
+        // SIGUSR2 is the only way to release an auto-pause short of a restart
+        let auto_pause = self.win_rate_alerts.enabled && self.win_rate_alerts.auto_pause;
+        if auto_pause && !(self.kill_switch.listen_for_signal && cfg!(unix)) {
+            return Err(anyhow::anyhow!(
+                "win_rate_alerts.auto_pause needs kill_switch.listen_for_signal (on unix) to resume"
+            ));
+        }
+
+        if self.multi_hop.amount == 0 {
+            return Err(anyhow::anyhow!("multi_hop.amount must be positive"));
+        }
+
+        if let Some(slippage) = &self.jupiter.dynamic_slippage {
+            if slippage.min_bps > slippage.max_bps || slippage.max_bps > 10_000 {
+                return Err(anyhow::anyhow!(
+                    "Jupiter dynamic slippage needs min_bps <= max_bps <= 10000 (got {} and {})",
+                    slippage.min_bps, slippage.max_bps
+                ));
+            }
+        }
+
+        if let Some(platform_fee) = &self.jupiter.platform_fee {
+            if platform_fee.fee_bps > 10_000 || platform_fee.fee_account.is_empty() {
+                return Err(anyhow::anyhow!("Jupiter platform fee needs fee_bps <= 10000 and a fee_account"));
+            }
+        }
+
+        crate::blackout::Blackout::new(&self.blackout)?;
+        if self.blackout.max_rpc_error_rate.is_some_and(|rate| !(rate > 0.0 && rate <= 1.0)) {
+            return Err(anyhow::anyhow!("blackout.max_rpc_error_rate must be in (0, 1]"));
+        }
+
+        if self.trading.finality_poll_ms == 0 {
+            return Err(anyhow::anyhow!("trading.finality_poll_ms must be positive"));
+        }
+
+        if self.mint_safety.policy == MintRestrictionPolicy::Canary && !self.canary.enabled {
+            return Err(anyhow::anyhow!("mint_safety.policy = \"canary\" needs [canary] enabled"));
+        }
+
+        if self.position_sizing.mode == PositionSizingMode::EquityPercent
+            && (!(0.0..=100.0).contains(&self.position_sizing.equity_percent)
+                || self.position_sizing.max_position_ceiling <= 0.0
+                || self.position_sizing.refresh_interval_ms == 0)
+        {
+            return Err(anyhow::anyhow!("Equity position sizing needs equity_percent in 0-100, a positive ceiling and refresh interval"));
+        }
+
+        if self.wallet.remote_signer.is_some()
+            && solana_sdk::pubkey::Pubkey::from_str(&self.wallet.public_key).is_err()
+        {
+            return Err(anyhow::anyhow!("A remote signer needs wallet.public_key set to the key it signs for"));
+        }
+
+        let budgets = &self.strategies.budgets;
+        if budgets.values().any(|share| !(*share > 0.0 && *share <= 1.0))
+            || budgets.values().sum::<f64>() > 1.0 + 1e-9
+        {
+            return Err(anyhow::anyhow!("Strategy budgets must each be in (0, 1] and sum to at most 1"));
+        }
+
+        if self.jito.dynamic_tip
+            && (self.jito.tip_percentile > 100
+                || !(self.jito.max_tip_edge_share > 0.0 && self.jito.max_tip_edge_share <= 1.0)
+                || self.jito.min_tip_lamports > self.jito.max_tip_lamports)
+        {
+            return Err(anyhow::anyhow!("Dynamic Jito tips need tip_percentile <= 100, max_tip_edge_share in (0, 1] and min_tip_lamports <= max_tip_lamports"));
+        }
+
+        if self.price_quorum.min_sources == 0 || self.price_quorum.agreement_tolerance_pct <= 0.0 {
+            return Err(anyhow::anyhow!("Price quorum needs at least one source and a positive agreement tolerance"));
+        }
+
+        let housekeeping = &self.housekeeping;
+        if housekeeping.enabled
+            && (housekeeping.interval_secs == 0 || housekeeping.min_swap_value_usd > housekeeping.dust_threshold_usd)
+        {
+            return Err(anyhow::anyhow!("Housekeeping needs a positive interval and min_swap_value_usd <= dust_threshold_usd"));
+        }
+
+        if self.display.significant_figures == 0 || self.display.decimal_separator.is_empty()
+            || self.display.thousands_separator == self.display.decimal_separator
+        {
+            return Err(anyhow::anyhow!("display needs significant_figures > 0 and distinct, non-empty separators"));
+        }
+
+        if !(0.0..=1.0).contains(&self.jupiter.trace_sample_rate) {
+            return Err(anyhow::anyhow!("jupiter.trace_sample_rate must be between 0 and 1"));
+        }
+
+        for (strategy, account) in &self.jupiter.tracking_accounts {
+            if solana_sdk::pubkey::Pubkey::from_str(account).is_err() {
+                return Err(anyhow::anyhow!("Jupiter tracking account '{}' for {:?} is not a valid pubkey", account, strategy));
+            }
+        }
+
+        if self.size_quantization.steps.values().any(|step| !step.is_finite() || *step < 0.0)
+            || self.size_quantization.min_notional < 0.0
+        {
+            return Err(anyhow::anyhow!("Size quantization steps and min_notional must be non-negative"));
+        }
+
+        if self.api_health.degraded_profit_multiplier < 1.0 || self.api_health.degraded_scan_every == 0 {
+            return Err(anyhow::anyhow!("Degraded API handling must not loosen margins or stop scanning (multiplier >= 1, scan_every >= 1)"));
+        }
+
+        if self.quote_cross_check.max_divergence_pct <= 0.0 {
+            return Err(anyhow::anyhow!("quote_cross_check.max_divergence_pct must be positive"));
+        }
+
+        if self.history_retention.opportunity_retention_days == 0
+            || self.history_retention.trade_retention_days == Some(0)
+            || self.history_retention.vacuum_interval_secs == 0
+        {
+            return Err(anyhow::anyhow!("History retention periods and vacuum_interval_secs must be non-zero"));
+        }
+
+        if self.backpressure.low_watermark > self.backpressure.high_watermark
+            || self.backpressure.persist_scans == 0
+            || self.backpressure.min_pairs == 0
+        {
+            return Err(anyhow::anyhow!("Backpressure needs low_watermark <= high_watermark and non-zero persist_scans and min_pairs"));
+        }
+
+        if !(0.0..=1.0).contains(&self.win_rate_alerts.min_win_rate) || self.win_rate_alerts.window == 0 {
+            return Err(anyhow::anyhow!("Win rate alerts need a non-empty window and min_win_rate between 0 and 1"));
+        }
+
+        if !(0.0..=1.0).contains(&self.simulation.fill_rate) {
+            return Err(anyhow::anyhow!("Simulation fill_rate must be between 0 and 1"));
+        }
+
+        if !self.jupiter.price_impact_clamp_pct.is_finite() || self.jupiter.price_impact_clamp_pct <= 0.0 {
+            return Err(anyhow::anyhow!("price_impact_clamp_pct must be positive"));
+        }
+
+        if self.jupiter.max_route_legs == 0 {
+            return Err(anyhow::anyhow!("jupiter.max_route_legs must be positive"));
+        }
+
+        let trading = &self.trading;
+        if [trading.hysteresis_enter_margin, trading.hysteresis_exit_margin]
+            .iter()
+            .any(|margin| !margin.is_finite() || *margin < 0.0)
+        {
+            return Err(anyhow::anyhow!("Hysteresis margins must be non-negative"));
+        }
+
+        if let Some(max_inflight) = self.risk_settings.max_inflight_capital {
+            if !max_inflight.is_finite() || max_inflight <= 0.0 {
+                return Err(anyhow::anyhow!("max_inflight_capital must be positive"));
+            }
+        }
+
+        if self.priority_fee.percentile > 100 {
+            return Err(anyhow::anyhow!("Priority fee percentile must be between 0 and 100"));
+        }
+        if self.priority_fee.floor_micro_lamports > self.priority_fee.ceiling_micro_lamports {
+            return Err(anyhow::anyhow!("Priority fee floor must not exceed its ceiling"));
+        }
+        if let Some(ProfitFeeMapping::Stepped { steps }) = &self.priority_fee.profit_scaling {
+            if steps.is_empty() {
+                return Err(anyhow::anyhow!("Stepped priority fee scaling needs at least one step"));
+            }
+        }
+
+        for (pair, pair_override) in &self.pair_overrides {
+            let tokens: Vec<&str> = pair.split('/').collect();
+            if tokens.len() != 2 || tokens.iter().any(|t| t.trim().is_empty()) || tokens[0] == tokens[1] {
+                return Err(anyhow::anyhow!("Invalid pair override key '{}': expected BASE/QUOTE", pair));
+            }
+            if pair_override.min_profit_threshold.is_some_and(|v| v < 0.0)
+                || pair_override.max_amount.is_some_and(|v| v <= 0.0)
+                || pair_override.max_price_impact_pct.is_some_and(|v| v < 0.0)
+            {
+                return Err(anyhow::anyhow!("Invalid values in pair override '{}'", pair));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_stable_pair(&self, input_mint: &str, output_mint: &str) -> bool {
+        input_mint != output_mint
+            && self.stable_pairs.mints.iter().any(|m| m == input_mint)
+            && self.stable_pairs.mints.iter().any(|m| m == output_mint)
+    }
+
+    pub fn pair_settings(&self, token_pair: &str, min_profit_threshold: f64, max_amount: f64) -> PairSettings {
+        let pair_override = self.pair_overrides.get(token_pair).cloned().unwrap_or_default();
+        PairSettings {
+            slippage_bps: pair_override.slippage_bps.unwrap_or(self.jupiter.default_slippage_bps),
+            min_profit_threshold: pair_override.min_profit_threshold.unwrap_or(min_profit_threshold),
+            max_amount: pair_override.max_amount.unwrap_or(max_amount),
+            max_price_impact_pct: pair_override.max_price_impact_pct.unwrap_or(self.jupiter.max_price_impact_pct),
+            quote_cache_ttl_ms: pair_override.quote_cache_ttl_ms.unwrap_or(self.jupiter.quote_cache_ttl_ms),
+        }
+    }
+
+    // Longest TTL any pair may use; cached quotes older than this are dead
+    pub fn max_quote_cache_ttl_ms(&self) -> u64 {
+        self.pair_overrides.values()
+            .filter_map(|pair_override| pair_override.quote_cache_ttl_ms)
+            .fold(self.jupiter.quote_cache_ttl_ms, u64::max)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
         Self {
             rpc_endpoints: RpcConfig {
                 primary: "https://api.mainnet-beta.solana.com".to_string(),
@@ -158,12 +1012,19 @@ impl Config {
                     priority: 6,
                     fee_percentage: 0.01,
                 },
+                max_concurrent_requests: 4,
+                endpoint_timeout_ms: 3000,
+                max_backoff_cycles: 16,
+                fee_overrides: HashMap::new(),
+                default_fee_percentage: 0.5,
             },
             wallet: WalletConfig {
                 private_key: "".to_string(),
                 public_key: "".to_string(),
                 max_sol_balance: 10.0,
                 min_sol_balance: 0.1,
+                consolidate_wsol: true,
+                remote_signer: None,
             },
             jito: JitoConfig {
                 enabled: true,
@@ -171,10 +1032,19 @@ impl Config {
                 bundle_endpoint: "https://mainnet.block-engine.jito.wtf".to_string(),
                 max_tip_lamports: 1_000_000, // 0.001 SOL
                 min_tip_lamports: 100_000,   // 0.0001 SOL
+                failover_threshold: 3,
+                failover_window_secs: 60,
+                recovery_probe_secs: 30,
+                dynamic_tip: false,
+                tip_floor_url: "https://bundles.jito.wtf/api/v1/bundles/tip_floor".to_string(),
+                tip_percentile: 75,
+                tip_refresh_ms: 10_000,
+                max_tip_edge_share: 0.5,
             },
             jupiter: JupiterConfig {
                 enabled: true,
                 api_url: "https://quote-api.jup.ag/v6".to_string(),
+                api_type: None,
                 api_key: None,
                 timeout_ms: 10000,
                 retry_attempts: 3,
@@ -193,6 +1063,36 @@ impl Config {
                 use_shared_accounts: true,
                 dynamic_compute_unit_limit: true,
                 prioritization_fee_lamports: 100_000, // 0.0001 SOL
+                base_urls: HashMap::new(),
+                token_cache_path: Some("token_list_cache.json".to_string()),
+                token_list_ttl_ms: 300_000,
+                dynamic_slippage: None,
+                send_correlation_id: true,
+                platform_fee: None,
+                requote_on_swap_failure: true,
+                rate_limit_max_wait_ms: Some(10_000),
+                max_accounts: 64,
+                adaptive_max_accounts_start: None,
+                price_impact_clamp_pct: 100.0,
+                max_route_legs: 16,
+                quote_cache_ttl_ms: 0,
+                price_batch_size: 100,
+                transaction_version: TransactionVersion::Versioned,
+                trace_sample_rate: 0.01,
+                trace_slow_request_ms: 1000,
+                tracking_accounts: HashMap::new(),
+                custom_headers: HashMap::new(),
+                max_swap_slot_drift: Some(32),
+                slot_drift_action: SlotDriftAction::Requote,
+                http: HttpClientConfig {
+                    proxy_url: None,
+                    pool_max_idle_per_host: 32,
+                    tcp_keepalive_secs: Some(60),
+                    pool_idle_timeout_secs: Some(90),
+                    compression: true,
+                    max_response_bytes: 4 * 1024 * 1024,
+                    max_token_list_bytes: 64 * 1024 * 1024,
+                },
             },
             risk_settings: RiskSettings {
                 max_position_size: 1000.0,
@@ -204,6 +1104,15 @@ impl Config {
                 stop_loss_percentage: 5.0,
                 max_gas_price: 1_000_000,
                 min_liquidity: 10_000.0,
+                blocked_pools: vec![],
+                exposure_groups: HashMap::new(),
+                min_price_confidence: PriceConfidence::Medium,
+                confidence_profit_multipliers: ConfidenceMultipliers {
+                    low: 2.0,
+                    medium: 1.5,
+                    high: 1.0,
+                },
+                max_inflight_capital: None,
             },
             monitoring: MonitoringConfig {
                 prometheus_port: 9090,
@@ -218,7 +1127,346 @@ impl Config {
                 enable_auto_trading: false,
                 min_opportunity_duration_ms: 500,
                 price_update_threshold: 0.1,
+                trade_size_usd: None,
+                max_blockhash_retries: 2,
+                simulate_before_execute: false,
+                simulation_tolerance_bps: 50,
+                swap_mode: SwapMode::ExactIn,
+                max_exact_out_input_usd: None,
+                execution_latency_budget_ms: 2000,
+                requote_on_latency_breach: true,
+                skip_warmup: false,
+                warmup_timeout_ms: 30000,
+                heartbeat_timeout_ms: 60000,
+                task_restart_delay_ms: 1000,
+                pending_trades_path: "pending_trades.json".to_string(),
+                history_path: Some("history.jsonl".to_string()),
+                max_opportunities_per_scan: 5,
+                opportunity_ttl_ms: 2000,
+                feed_change_threshold_pct: 0.05,
+                hysteresis_enter_margin: 0.0,
+                hysteresis_exit_margin: 0.0,
+                min_edge_over_fees_pct: 0.0,
+                opportunity_claim_ttl_ms: 30000,
+                impact_size_reductions: 3,
+                profit_denomination_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(), // USDC
+                profit_commitment: ProfitCommitment::Confirmed,
+                finality_poll_ms: 2000,
+                precreate_token_accounts: false,
+            },
+            pair_overrides: HashMap::new(),
+            rebalancing: RebalancingConfig {
+                enabled: false,
+                base_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+                target_weights: HashMap::new(),
+                drift_tolerance_pct: 5.0,
+                max_cost_pct: 0.3,
+            },
+            multi_hop: MultiHopConfig {
+                enabled: false,
+                base_mints: vec!["So11111111111111111111111111111111111111112".to_string()],
+                intermediate_mints: vec![
+                    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+                    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB".to_string(),
+                ],
+                amount: 1_000_000_000, // 1 SOL
+                min_profit_percentage: 0.3,
+                route_overlap: RouteOverlapPolicy::Reject,
+            },
+            oracle: OracleConfig {
+                enabled: false,
+                max_deviation_pct: 2.0,
+                price_accounts: HashMap::new(),
+                max_staleness_slots: Some(25),
+            },
+            scan_scope: ScanScopeConfig {
+                watchlist: Vec::new(),
+                auto_discover: false,
+                auto_discover_limit: 20,
+                route_miss_tolerance: 5,
+                route_recheck_scans: 20,
+            },
+            priority_fee: PriorityFeeConfig {
+                dynamic: false,
+                percentile: 75,
+                floor_micro_lamports: 1_000,
+                ceiling_micro_lamports: 1_000_000,
+                profit_scaling: None,
+            },
+            impact_alerts: ImpactAlertConfig {
+                enabled: true,
+                spike_multiple: 3.0,
+                baseline_alpha: 0.1,
+                min_samples: 20,
+                auto_pause: true,
+                pause_secs: 300,
+            },
+            stable_pairs: StablePairsConfig {
+                mints: vec![
+                    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(), // USDC
+                    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB".to_string(), // USDT
+                ],
+                min_profit_threshold: 0.05,
+                slippage_bps: 5,
+                max_peg_deviation_pct: 1.0,
+            },
+            event_sink: EventSinkConfig {
+                nats_url: None,
+                opportunity_subject: "arbitrage.opportunities".to_string(),
+                trade_subject: "arbitrage.trades".to_string(),
+                buffer_size: 1024,
+            },
+            scoring_weights: ScoringWeights {
+                profit_weight: 1.0,
+                probability_weight: 0.0,
+                latency_penalty: 0.0,
+                impact_penalty: 0.0,
+            },
+            canary: CanaryConfig {
+                enabled: true,
+                trade_amount: 1.0,
+            },
+            kill_switch: KillSwitchConfig {
+                sentinel_path: Some("KILL_SWITCH".to_string()),
+                listen_for_signal: true,
+            },
+            price_history: PriceHistoryConfig {
+                enabled: false,
+                path: Some("price_history.jsonl".to_string()),
+                sample_interval_ms: 5000,
+                retention_secs: 86_400,
+                flush_interval_ms: 10_000,
+            },
+            simulation: SimulationConfig {
+                enabled: false,
+                fill_rate: 1.0,
+                slippage_bps: 10,
+                fee_per_trade: 0.0,
+                latency_ms: 0,
+            },
+            win_rate_alerts: WinRateAlertConfig {
+                enabled: true,
+                window: 20,
+                min_win_rate: 0.4,
+                auto_pause: false,
+            },
+            size_quantization: SizeQuantizationConfig {
+                steps: HashMap::new(),
+                min_notional: 0.0,
+            },
+            api_health: ApiHealthConfig {
+                enabled: true,
+                poll_interval_ms: 5000,
+                degraded_latency_ms: 2000,
+                degraded_profit_multiplier: 2.0,
+                degraded_scan_every: 4,
+            },
+            mint_safety: MintSafetyConfig {
+                policy: MintRestrictionPolicy::Allow,
+                trusted_mints: vec![
+                    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(), // USDC
+                    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB".to_string(), // USDT
+                ],
+            },
+            position_sizing: PositionSizingConfig {
+                mode: PositionSizingMode::Fixed,
+                equity_percent: 5.0,
+                max_position_ceiling: 10_000.0,
+                refresh_interval_ms: 60_000,
+            },
+            grpc: GrpcConfig {
+                bind_failure_fatal: true,
+                fallback_ports: 0,
+            },
+            strategies: StrategiesConfig {
+                direct: true,
+                jupiter_route: true,
+                multi_hop: true,
+                budgets: HashMap::new(),
+            },
+            quote_cross_check: QuoteCrossCheckConfig {
+                enabled: false,
+                secondary_api_url: None,
+                max_divergence_pct: 1.0,
+                fatal: true,
+            },
+            history_retention: HistoryRetentionConfig {
+                enabled: true,
+                opportunity_retention_days: 7,
+                trade_retention_days: None,
+                vacuum_interval_secs: 3600,
+            },
+            backpressure: BackpressureConfig {
+                enabled: true,
+                high_watermark: 10,
+                low_watermark: 2,
+                persist_scans: 3,
+                min_pairs: 3,
+            },
+            display: DisplayConfig {
+                significant_figures: 4,
+                thousands_separator: ",".to_string(),
+                decimal_separator: ".".to_string(),
+                precision: HashMap::new(),
+            },
+            housekeeping: HousekeepingConfig {
+                enabled: false,
+                interval_secs: 3600,
+                auto_execute: false,
+                dust_threshold_usd: 1.0,
+                min_swap_value_usd: 0.25,
+                base_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+                keep_mints: Vec::new(),
+            },
+            clock: ClockConfig {
+                max_future_skew_ms: 2000,
+                future_timestamps: FutureTimestampPolicy::Clamp,
+            },
+            price_quorum: PriceQuorumConfig {
+                min_sources: 2,
+                agreement_tolerance_pct: 2.0,
+            },
+            stats: StatsConfig {
+                channel_capacity: 1024,
+            },
+            blackout: BlackoutConfig {
+                enabled: false,
+                windows: Vec::new(),
+                max_rpc_error_rate: Some(0.5),
+                min_rpc_calls: 10,
+                rpc_error_window_secs: 60,
+                rpc_error_pause_secs: 300,
             },
         }
     }
 }
+
+// Replaces each ${NAME} with the environment variable NAME, so secrets can
+// stay out of the config file. An unset variable is an error rather than
+// silently becoming an empty string.
+pub fn expand_env_vars(value: &str) -> Result<String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let end = rest[start..].find('}')
+            .ok_or_else(|| anyhow::anyhow!("Unterminated ${{...}} reference"))?;
+        let name = &rest[start + 2..start + end];
+        let var = std::env::var(name)
+            .map_err(|_| anyhow::anyhow!("Environment variable {} is not set", name))?;
+        expanded.push_str(&var);
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+// Builds request headers from config, expanding ${ENV} references and
+// rejecting invalid names or values up front. Errors name the header but
+// never echo its value, which may be a secret.
+pub fn secret_header_map(headers: &HashMap<String, String>) -> Result<reqwest::header::HeaderMap> {
+    let mut map = reqwest::header::HeaderMap::new();
+    for (name, value) in headers {
+        let header_name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| anyhow::anyhow!("header name '{}' is invalid", name))?;
+        let value = expand_env_vars(value)
+            .map_err(|e| anyhow::anyhow!("header '{}': {}", name, e))?;
+        let mut header_value = reqwest::header::HeaderValue::from_str(&value)
+            .map_err(|_| anyhow::anyhow!("header '{}' has an invalid value", name))?;
+        header_value.set_sensitive(true);
+        map.insert(header_name, header_value);
+    }
+    Ok(map)
+}
+
+fn merge_toml(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DynamicSlippageConfig, WSOL_MINT};
+
+    #[test]
+    fn header_errors_never_echo_the_value() {
+        let headers = HashMap::from([("x-api-key".to_string(), "hunter2${UNCLOSED".to_string())]);
+        let error = secret_header_map(&headers).unwrap_err().to_string();
+        assert!(error.contains("x-api-key"));
+        assert!(!error.contains("hunter2"));
+    }
+
+    #[test]
+    fn rejects_inverted_dynamic_slippage_bounds() {
+        let mut config = Config::default();
+        config.jupiter.dynamic_slippage = Some(DynamicSlippageConfig { min_bps: 300, max_bps: 50 });
+        assert!(config.validate().is_err());
+        config.jupiter.dynamic_slippage = Some(DynamicSlippageConfig { min_bps: 50, max_bps: 300 });
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn an_enabled_oracle_needs_price_accounts() {
+        let mut config = Config::default();
+        config.oracle.enabled = true;
+        assert!(config.validate().is_err());
+        config.oracle.price_accounts.insert(WSOL_MINT.to_string(), "H6ARHf6YXhGYeQfUzQNGk6rDNnLBQKrenN712K4AQJEG".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn auto_pause_needs_a_way_to_resume() {
+        let mut config = Config::default();
+        config.win_rate_alerts.auto_pause = true;
+        assert!(config.validate().is_ok());
+        config.kill_switch.listen_for_signal = false;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn a_prod_overlay_merges_over_the_shipped_config() {
+        let overlay = std::env::temp_dir().join(format!("config_test_overlay_{}.toml", std::process::id()));
+        std::fs::write(&overlay, r#"
+            [rpc_endpoints]
+            secondary = ["https://prod-rpc.example"]
+
+            [dex_endpoints.orca]
+            enabled = false
+
+            [trading]
+            scan_interval_ms = 250
+        "#).unwrap();
+        let base = format!("{}/config.toml", env!("CARGO_MANIFEST_DIR"));
+
+        let config = Config::load_layered(&[base, overlay.to_string_lossy().to_string()]);
+        std::fs::remove_file(&overlay).unwrap();
+        let config = config.unwrap();
+        // Arrays replace, nested tables merge key by key
+        assert_eq!(config.rpc_endpoints.secondary, ["https://prod-rpc.example"]);
+        assert_eq!(config.rpc_endpoints.primary, "https://api.mainnet-beta.solana.com");
+        assert!(!config.dex_endpoints.orca.enabled);
+        assert_eq!(config.dex_endpoints.orca.fee_percentage, 0.3);
+        assert_eq!(config.trading.scan_interval_ms, 250);
+        assert!(config.dex_endpoints.raydium.enabled);
+    }
+
+    #[test]
+    fn rejects_a_zero_cycle_amount() {
+        let mut config = Config::default();
+        config.multi_hop.amount = 0;
+        assert!(config.validate().is_err());
+    }
+}
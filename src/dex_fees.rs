@@ -0,0 +1,66 @@
+use crate::config::DexConfig;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tracing::warn;
+
+// Swap fee per DEX name, used to net direct-DEX prices before comparing them
+// with Jupiter quotes (which already include fees)
+#[derive(Debug)]
+pub struct DexFeeModel {
+    fees: HashMap<String, f64>,
+    default_fee_percentage: f64,
+    warned: Mutex<HashSet<String>>,
+}
+
+impl DexFeeModel {
+    pub fn from_config(config: &DexConfig) -> Self {
+        let mut fees: HashMap<String, f64> = config.fee_overrides.iter()
+            .map(|(name, fee)| (name.to_lowercase(), *fee))
+            .collect();
+        for endpoint in [&config.raydium, &config.orca, &config.serum, &config.aldrin, &config.saber, &config.mercurial] {
+            fees.entry(endpoint.name.to_lowercase()).or_insert(endpoint.fee_percentage);
+        }
+
+        Self {
+            fees,
+            default_fee_percentage: config.default_fee_percentage,
+            warned: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn fee_percentage(&self, dex_name: &str) -> f64 {
+        let key = dex_name.to_lowercase();
+        if let Some(fee) = self.fees.get(&key) {
+            return *fee;
+        }
+        if self.warned.lock().unwrap().insert(key) {
+            warn!("⚠️ No fee configured for DEX {}, assuming {}%", dex_name, self.default_fee_percentage);
+        }
+        self.default_fee_percentage
+    }
+
+    // What one unit of input actually yields on this DEX after its swap fee
+    pub fn net_price(&self, dex_name: &str, price: f64) -> f64 {
+        price * (1.0 - self.fee_percentage(dex_name) / 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn overrides_win_and_unknown_dexes_get_the_default() {
+        let mut config = Config::default().dex_endpoints;
+        config.raydium.fee_percentage = 0.25;
+        config.fee_overrides.insert("Orca".to_string(), 0.3);
+        config.orca.fee_percentage = 0.9;
+        let fees = DexFeeModel::from_config(&config);
+
+        assert_eq!(fees.fee_percentage("RAYDIUM"), 0.25);
+        assert_eq!(fees.fee_percentage("orca"), 0.3);
+        assert_eq!(fees.fee_percentage("Unheard"), config.default_fee_percentage);
+        assert!((fees.net_price("Raydium", 100.0) - 99.75).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{
+    config::{DexConfig, DexEndpoint},
+    endpoint_poller::{EndpointPoller, EndpointStats},
+    types::PriceData,
+};
+use anyhow::Result;
+use chrono::Utc;
+use reqwest::Client;
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::{debug, info, warn};
+
+// Polls the pool lists of every enabled DEX endpoint through an
+// `EndpointPoller`, so a slow or failing DEX is timed out and backed off
+// instead of stalling the scan.
+pub struct DexMonitor {
+    config: DexConfig,
+    client: Client,
+    poller: EndpointPoller,
+}
+
+impl DexMonitor {
+    pub fn new(config: DexConfig) -> Self {
+        let poller = EndpointPoller::new(
+            config.max_concurrent_requests,
+            config.endpoint_timeout_ms,
+            config.max_backoff_cycles,
+        );
+        Self {
+            config,
+            client: Client::new(),
+            poller,
+        }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        let endpoints = self.config.enabled_endpoints();
+        if endpoints.is_empty() {
+            warn!("⚠️ No DEX endpoints enabled, direct scans will find nothing");
+            return Ok(());
+        }
+        let names: Vec<&str> = endpoints.iter().map(|endpoint| endpoint.name.as_str()).collect();
+        info!("📡 Monitoring {} DEX endpoints: {}", endpoints.len(), names.join(", "));
+        Ok(())
+    }
+
+    // One polling cycle: prices from every endpoint that answered in time
+    pub async fn get_all_prices(&self) -> Result<Vec<PriceData>> {
+        let client = self.client.clone();
+        let results = self.poller
+            .poll_all(self.config.enabled_endpoints(), move |endpoint| {
+                let client = client.clone();
+                async move { fetch_pool_prices(&client, &endpoint).await }
+            })
+            .await;
+
+        let prices: Vec<PriceData> = results.into_iter().flat_map(|(_, prices)| prices).collect();
+        debug!("📡 Collected {} DEX prices", prices.len());
+        Ok(prices)
+    }
+
+    pub async fn get_endpoint_stats(&self) -> HashMap<String, EndpointStats> {
+        self.poller.get_stats().await
+    }
+}
+
+async fn fetch_pool_prices(client: &Client, endpoint: &DexEndpoint) -> Result<Vec<PriceData>> {
+    let response = client.get(&endpoint.rpc_url).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("{} returned {}", endpoint.name, response.status()));
+    }
+    let body: Value = response.json().await?;
+    Ok(parse_pools(&endpoint.name, &body, Utc::now().timestamp_millis()))
+}
+
+// Pool lists differ per DEX; this accepts a bare array or one nested under a
+// common key, and pools that name their pair, price and address under any of
+// the usual field names. Pools missing any of those are skipped.
+fn parse_pools(dex_name: &str, body: &Value, timestamp: i64) -> Vec<PriceData> {
+    let pools = ["data", "pools", "whirlpools", "official", "result"].iter()
+        .find_map(|key| body[key].as_array())
+        .or_else(|| body.as_array());
+    let Some(pools) = pools else {
+        warn!("⚠️ {} returned no recognizable pool list", dex_name);
+        return Vec::new();
+    };
+
+    pools.iter()
+        .filter_map(|pool| {
+            let base = first_str(pool, &["baseSymbol", "base", "tokenA"])
+                .or_else(|| pool["tokenA"]["symbol"].as_str())?;
+            let quote = first_str(pool, &["quoteSymbol", "quote", "tokenB"])
+                .or_else(|| pool["tokenB"]["symbol"].as_str())?;
+            let price = first_f64(pool, &["price", "lastPrice"]).filter(|price| *price > 0.0 && price.is_finite())?;
+            let pool_address = first_str(pool, &["id", "address", "ammId", "poolAddress"])?;
+            Some(PriceData {
+                dex_name: dex_name.to_string(),
+                token_pair: format!("{}/{}", base, quote),
+                base_token: base.to_string(),
+                quote_token: quote.to_string(),
+                price,
+                volume_24h: first_f64(pool, &["volume24h", "volume_24h", "volume"]).unwrap_or(0.0),
+                liquidity: first_f64(pool, &["liquidity", "tvl"]).unwrap_or(0.0),
+                timestamp,
+                pool_address: pool_address.to_string(),
+                price_impact: 0.0,
+            })
+        })
+        .collect()
+}
+
+fn first_str<'a>(value: &'a Value, keys: &[&str]) -> Option<&'a str> {
+    keys.iter().find_map(|key| value[key].as_str())
+}
+
+// Numbers may come as JSON numbers or numeric strings
+fn first_f64(value: &Value, keys: &[&str]) -> Option<f64> {
+    keys.iter().find_map(|key| {
+        let field = &value[key];
+        field.as_f64().or_else(|| field.as_str().and_then(|s| s.parse().ok()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_pools_from_the_common_response_shapes() {
+        let body = json!({ "data": [
+            { "baseSymbol": "SOL", "quoteSymbol": "USDC", "price": "101.5", "id": "pool1", "tvl": 5000 },
+            { "tokenA": { "symbol": "RAY" }, "tokenB": { "symbol": "USDC" }, "lastPrice": 2.0, "address": "pool2" },
+        ] });
+        let prices = parse_pools("Raydium", &body, 42);
+        assert_eq!(prices.len(), 2);
+        assert_eq!(prices[0].token_pair, "SOL/USDC");
+        assert_eq!((prices[0].price, prices[0].liquidity, prices[0].timestamp), (101.5, 5000.0, 42));
+        assert_eq!(prices[1].token_pair, "RAY/USDC");
+        assert_eq!(prices[1].pool_address, "pool2");
+
+        let bare = json!([{ "base": "SOL", "quote": "USDT", "price": 100.0, "poolAddress": "pool3" }]);
+        assert_eq!(parse_pools("Orca", &bare, 0)[0].token_pair, "SOL/USDT");
+    }
+
+    #[test]
+    fn skips_pools_without_a_usable_price_or_address() {
+        let body = json!({ "pools": [
+            { "base": "SOL", "quote": "USDC", "price": 0.0, "id": "pool1" },
+            { "base": "SOL", "quote": "USDC", "price": 100.0 },
+        ] });
+        assert!(parse_pools("Orca", &body, 0).is_empty());
+        assert!(parse_pools("Orca", &json!({ "message": "down" }), 0).is_empty());
+    }
+}
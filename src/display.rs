@@ -0,0 +1,119 @@
+use crate::config::DisplayConfig;
+
+// Most decimals shown for a tiny value; anything smaller prints as zero
+const MAX_DECIMALS: usize = 12;
+
+// Formats monetary values for logs: significant figures by default so both
+// BONK prices and portfolio totals read sensibly, or fixed decimals for any
+// denomination listed in `[display.precision]`
+#[derive(Debug, Clone)]
+pub struct MoneyFormat {
+    config: DisplayConfig,
+}
+
+impl MoneyFormat {
+    pub fn new(config: DisplayConfig) -> Self {
+        Self { config }
+    }
+
+    // A dollar value, e.g. `$1,234.57` or `-$0.00001234`
+    pub fn usd(&self, value: f64) -> String {
+        let formatted = self.number(value.abs(), "USD");
+        if value < 0.0 {
+            format!("-${}", formatted)
+        } else {
+            format!("${}", formatted)
+        }
+    }
+
+    // A token amount, e.g. `1,250,000 BONK`
+    pub fn amount(&self, value: f64, denomination: &str) -> String {
+        format!("{} {}", self.number(value, denomination), denomination)
+    }
+
+    fn number(&self, value: f64, denomination: &str) -> String {
+        let plain = match self.config.precision.get(denomination) {
+            Some(decimals) => format!("{:.*}", (*decimals).min(MAX_DECIMALS), value),
+            None => format_significant(value, self.config.significant_figures),
+        };
+        localize(&plain, &self.config.thousands_separator, &self.config.decimal_separator)
+    }
+}
+
+// Rounds to `figures` significant digits without switching to scientific
+// notation: 0.0000123456 -> "0.00001235", 1234567.8 -> "1234568"
+pub fn format_significant(value: f64, figures: usize) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    let figures = figures.max(1) as i32;
+    let magnitude = value.abs().log10().floor() as i32;
+    let decimals = (figures - 1 - magnitude).clamp(0, MAX_DECIMALS as i32) as usize;
+    format!("{:.*}", decimals, value)
+}
+
+// Groups the integer digits and swaps in the configured decimal separator
+fn localize(plain: &str, thousands_separator: &str, decimal_separator: &str) -> String {
+    let (sign, unsigned) = match plain.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", plain),
+    };
+    let (integer, fraction) = match unsigned.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (unsigned, None),
+    };
+
+    let mut grouped = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push_str(thousands_separator);
+        }
+        grouped.push(digit);
+    }
+
+    match fraction {
+        Some(fraction) => format!("{}{}{}{}", sign, grouped, decimal_separator, fraction),
+        None => format!("{}{}", sign, grouped),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn format(precision: HashMap<String, usize>) -> MoneyFormat {
+        MoneyFormat::new(DisplayConfig {
+            significant_figures: 4,
+            thousands_separator: ",".to_string(),
+            decimal_separator: ".".to_string(),
+            precision,
+        })
+    }
+
+    #[test]
+    fn rounds_to_significant_figures_without_scientific_notation() {
+        assert_eq!(format_significant(0.0000123456, 4), "0.00001235");
+        assert_eq!(format_significant(1234567.8, 4), "1234568");
+        assert_eq!(format_significant(0.0, 4), "0");
+        assert_eq!(format_significant(f64::NAN, 4), "NaN");
+    }
+
+    #[test]
+    fn groups_thousands_and_signs_dollar_values() {
+        let money = format(HashMap::new());
+        assert_eq!(money.usd(1234567.8), "$1,234,568");
+        assert_eq!(money.usd(-0.00001234), "-$0.00001234");
+        assert_eq!(money.amount(1_250_000.0, "BONK"), "1,250,000 BONK");
+    }
+
+    #[test]
+    fn fixed_precision_and_separators_are_configurable() {
+        let money = format(HashMap::from([("USD".to_string(), 2)]));
+        assert_eq!(money.usd(1234.567), "$1,234.57");
+        assert_eq!(localize("-1234567.5", ".", ","), "-1.234.567,5");
+    }
+}
@@ -0,0 +1,166 @@
+use crate::config::DexEndpoint;
+use anyhow::Result;
+use futures_util::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EndpointStats {
+    pub successes: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    pub avg_latency_ms: f64,
+    pub skip_cycles_remaining: u32,
+}
+
+impl EndpointStats {
+    pub fn success_rate(&self) -> f64 {
+        let total = self.successes + self.failures;
+        if total == 0 {
+            1.0
+        } else {
+            self.successes as f64 / total as f64
+        }
+    }
+}
+
+// Polls DEX endpoints with bounded concurrency and a per-endpoint timeout.
+// Endpoints that keep failing or timing out are skipped for an exponentially
+// growing number of cycles (capped by max_backoff_cycles).
+#[derive(Debug, Clone)]
+pub struct EndpointPoller {
+    max_concurrent_requests: usize,
+    endpoint_timeout: Duration,
+    max_backoff_cycles: u32,
+    stats: Arc<RwLock<HashMap<String, EndpointStats>>>,
+}
+
+impl EndpointPoller {
+    pub fn new(max_concurrent_requests: usize, endpoint_timeout_ms: u64, max_backoff_cycles: u32) -> Self {
+        Self {
+            max_concurrent_requests: max_concurrent_requests.max(1),
+            endpoint_timeout: Duration::from_millis(endpoint_timeout_ms),
+            max_backoff_cycles,
+            stats: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub async fn poll_all<T, F, Fut>(&self, endpoints: Vec<DexEndpoint>, fetch: F) -> Vec<(String, T)>
+    where
+        F: Fn(DexEndpoint) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let due = self.due_endpoints(endpoints).await;
+
+        let results: Vec<(String, Result<T>, Duration)> = stream::iter(due)
+            .map(|endpoint| {
+                let name = endpoint.name.clone();
+                let request = fetch(endpoint);
+                async move {
+                    let started = Instant::now();
+                    let result = match tokio::time::timeout(self.endpoint_timeout, request).await {
+                        Ok(result) => result,
+                        Err(_) => Err(anyhow::anyhow!("timed out after {:?}", self.endpoint_timeout)),
+                    };
+                    (name, result, started.elapsed())
+                }
+            })
+            .buffer_unordered(self.max_concurrent_requests)
+            .collect()
+            .await;
+
+        let mut successes = Vec::new();
+        let mut stats = self.stats.write().await;
+        for (name, result, latency) in results {
+            let entry = stats.entry(name.clone()).or_default();
+            let samples = (entry.successes + entry.failures) as f64;
+            entry.avg_latency_ms = (entry.avg_latency_ms * samples + latency.as_millis() as f64) / (samples + 1.0);
+
+            match result {
+                Ok(value) => {
+                    entry.successes += 1;
+                    entry.consecutive_failures = 0;
+                    successes.push((name, value));
+                }
+                Err(e) => {
+                    entry.failures += 1;
+                    entry.consecutive_failures += 1;
+                    entry.skip_cycles_remaining = 2u32
+                        .saturating_pow(entry.consecutive_failures - 1)
+                        .min(self.max_backoff_cycles);
+                    warn!("⚠️ DEX endpoint {} failed ({} in a row), backing off {} cycles: {}",
+                          name, entry.consecutive_failures, entry.skip_cycles_remaining, e);
+                }
+            }
+        }
+
+        successes
+    }
+
+    pub async fn get_stats(&self) -> HashMap<String, EndpointStats> {
+        self.stats.read().await.clone()
+    }
+
+    async fn due_endpoints(&self, endpoints: Vec<DexEndpoint>) -> Vec<DexEndpoint> {
+        let mut stats = self.stats.write().await;
+        endpoints
+            .into_iter()
+            .filter(|endpoint| {
+                let entry = stats.entry(endpoint.name.clone()).or_default();
+                if entry.skip_cycles_remaining > 0 {
+                    entry.skip_cycles_remaining -= 1;
+                    debug!("⏭️ Skipping backed-off DEX endpoint {}", endpoint.name);
+                    false
+                } else {
+                    true
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(name: &str) -> DexEndpoint {
+        DexEndpoint {
+            name: name.to_string(),
+            rpc_url: String::new(),
+            api_url: None,
+            enabled: true,
+            priority: 1,
+            fee_percentage: 0.25,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_hanging_endpoint_times_out_without_holding_up_the_rest() {
+        let poller = EndpointPoller::new(4, 500, 8);
+        let endpoints = vec![endpoint("Hangs"), endpoint("Answers")];
+        let fetch = |endpoint: DexEndpoint| async move {
+            if endpoint.name == "Hangs" {
+                std::future::pending::<()>().await;
+            }
+            Ok(endpoint.name)
+        };
+
+        let results = poller.poll_all(endpoints.clone(), fetch).await;
+        assert_eq!(results, vec![("Answers".to_string(), "Answers".to_string())]);
+
+        let stats = poller.get_stats().await;
+        assert_eq!(stats["Hangs"].failures, 1);
+        assert_eq!(stats["Hangs"].skip_cycles_remaining, 1);
+        assert_eq!(stats["Answers"].successes, 1);
+
+        // Backed off for the next cycle, so it isn't even asked
+        let results = poller.poll_all(endpoints, fetch).await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(poller.get_stats().await["Hangs"].failures, 1);
+    }
+}
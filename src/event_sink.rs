@@ -0,0 +1,163 @@
+use crate::config::EventSinkConfig;
+use crate::types::{ArbitrageOpportunity, TradeResponse};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+// Only read by the NATS publisher
+#[derive(Debug)]
+#[cfg_attr(not(feature = "nats"), allow(dead_code))]
+struct SinkMessage {
+    subject: String,
+    payload: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeEvent<'a> {
+    pub opportunity_id: &'a str,
+    pub token_pair: &'a str,
+    pub timestamp: i64,
+    pub response: &'a TradeResponse,
+}
+
+// Publishes opportunities and trades to a message bus for downstream
+// consumers. Publishing only enqueues onto a bounded channel; when the
+// publisher falls behind, events are dropped and counted rather than
+// slowing down the trading path.
+pub struct EventSink {
+    sender: Option<mpsc::Sender<SinkMessage>>,
+    opportunity_subject: String,
+    trade_subject: String,
+    dropped: Arc<AtomicU64>,
+}
+
+impl EventSink {
+    pub fn disabled() -> Self {
+        Self {
+            sender: None,
+            opportunity_subject: String::new(),
+            trade_subject: String::new(),
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    // Must be called inside a tokio runtime when a NATS URL is configured
+    pub fn start(config: &EventSinkConfig) -> Self {
+        let Some(url) = &config.nats_url else {
+            return Self::disabled();
+        };
+
+        let Some(sender) = spawn_nats_publisher(url.clone(), config.buffer_size.max(1)) else {
+            return Self::disabled();
+        };
+
+        Self {
+            sender: Some(sender),
+            opportunity_subject: config.opportunity_subject.clone(),
+            trade_subject: config.trade_subject.clone(),
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    pub fn publish_opportunity(&self, opportunity: &ArbitrageOpportunity) {
+        self.publish(&self.opportunity_subject, opportunity);
+    }
+
+    pub fn publish_trade(&self, event: &TradeEvent<'_>) {
+        self.publish(&self.trade_subject, event);
+    }
+
+    // Events lost because the publisher was behind or disconnected
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn publish<T: Serialize>(&self, subject: &str, event: &T) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+
+        let payload = match serde_json::to_vec(event) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("⚠️ Failed to serialize event for {}: {}", subject, e);
+                return;
+            }
+        };
+
+        let message = SinkMessage { subject: subject.to_string(), payload };
+        if sender.try_send(message).is_err() {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            debug!("📭 Event sink dropped an event for {} ({} dropped so far)", subject, dropped);
+        }
+    }
+}
+
+#[cfg(feature = "nats")]
+fn spawn_nats_publisher(url: String, buffer_size: usize) -> Option<mpsc::Sender<SinkMessage>> {
+    let (sender, mut receiver) = mpsc::channel::<SinkMessage>(buffer_size);
+
+    tokio::spawn(async move {
+        let client = match async_nats::ConnectOptions::new()
+            .retry_on_initial_connect()
+            .connect(url.as_str())
+            .await
+        {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("⚠️ Event sink could not connect to NATS at {}: {}", url, e);
+                return;
+            }
+        };
+        tracing::info!("📡 Event sink publishing to NATS at {}", url);
+
+        while let Some(message) = receiver.recv().await {
+            if let Err(e) = client.publish(message.subject, message.payload.into()).await {
+                warn!("⚠️ Event sink publish failed: {}", e);
+            }
+        }
+    });
+
+    Some(sender)
+}
+
+#[cfg(not(feature = "nats"))]
+fn spawn_nats_publisher(url: String, _buffer_size: usize) -> Option<mpsc::Sender<SinkMessage>> {
+    warn!("⚠️ event_sink.nats_url is set to {} but this build lacks the `nats` feature; events will not be published", url);
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_disabled_sink_ignores_events() {
+        let sink = EventSink::disabled();
+        sink.publish_opportunity(&ArbitrageOpportunity::default());
+        assert!(!sink.is_enabled());
+        assert_eq!(sink.dropped_count(), 0);
+    }
+
+    #[test]
+    fn drops_and_counts_events_once_the_buffer_is_full() {
+        let (sender, mut receiver) = mpsc::channel(1);
+        let sink = EventSink {
+            sender: Some(sender),
+            opportunity_subject: "opportunities".to_string(),
+            trade_subject: "trades".to_string(),
+            dropped: Arc::new(AtomicU64::new(0)),
+        };
+
+        sink.publish_opportunity(&ArbitrageOpportunity::default());
+        sink.publish_opportunity(&ArbitrageOpportunity::default());
+        assert_eq!(sink.dropped_count(), 1);
+        assert_eq!(receiver.try_recv().unwrap().subject, "opportunities");
+    }
+}
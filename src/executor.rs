@@ -0,0 +1,320 @@
+use crate::{
+    config::SimulationConfig,
+    jito_client::JitoClient,
+    jito_failover::JitoFailover,
+    rpc_client::SolanaRpcClient,
+    types::{ArbitrageError, ArbitrageOpportunity, SubmissionPath, TradeRequest, TradeResponse},
+};
+use anyhow::Result;
+use base64::Engine;
+use futures_util::future::BoxFuture;
+use serde_json::json;
+use solana_sdk::transaction::VersionedTransaction;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+// How often a submitted signature is polled for confirmation
+const CONFIRMATION_POLL_MS: u64 = 500;
+
+// Submits a vetted trade. `ChainExecutor` is the live Jito/RPC path;
+// `SimExecutor` lets the whole engine run without touching chain or APIs.
+pub trait Executor: Send + Sync {
+    // False for executors that never reach chain; the engine then skips
+    // building and signing a transaction for them
+    fn submits_transactions(&self) -> bool {
+        true
+    }
+
+    fn execute<'a>(
+        &'a self,
+        request: &'a TradeRequest,
+        opportunity: &'a ArbitrageOpportunity,
+        transaction: Option<&'a VersionedTransaction>,
+    ) -> BoxFuture<'a, Result<TradeResponse>>;
+}
+
+// Jito bundle when requested and healthy, otherwise a regular transaction.
+// Either way the trade's id is the transaction signature, and the response
+// reports whether it confirmed before its blockhash expired.
+pub struct ChainExecutor {
+    jito_client: Option<Arc<JitoClient>>,
+    jito_failover: Arc<JitoFailover>,
+    rpc: Arc<SolanaRpcClient>,
+    confirmation_timeout: Duration,
+}
+
+impl ChainExecutor {
+    pub fn new(
+        jito_client: Option<Arc<JitoClient>>,
+        jito_failover: Arc<JitoFailover>,
+        rpc: Arc<SolanaRpcClient>,
+        confirmation_timeout_ms: u64,
+    ) -> Self {
+        Self {
+            jito_client,
+            jito_failover,
+            rpc,
+            confirmation_timeout: Duration::from_millis(confirmation_timeout_ms),
+        }
+    }
+
+    async fn submit_trade(
+        &self,
+        request: &TradeRequest,
+        opportunity: &ArbitrageOpportunity,
+        transaction: Option<&VersionedTransaction>,
+    ) -> Result<TradeResponse> {
+        let transaction = transaction
+            .ok_or_else(|| anyhow::anyhow!("No signed transaction to submit for {}", request.opportunity_id))?;
+        let signature = transaction.signatures.first()
+            .ok_or_else(|| anyhow::anyhow!("Transaction for {} is unsigned", request.opportunity_id))?
+            .to_string();
+
+        let mut bundle_id = String::new();
+        let mut submission_path = SubmissionPath::NotSubmitted;
+        if request.use_jito && self.jito_failover.should_use_jito().await {
+            if let Some(jito_client) = &self.jito_client {
+                match jito_client.send_bundle(std::slice::from_ref(transaction)).await {
+                    Ok(id) => {
+                        info!("📦 Trade {} submitted via Jito bundle {}", request.opportunity_id, id);
+                        bundle_id = id;
+                        submission_path = SubmissionPath::Jito;
+                    }
+                    Err(e) => {
+                        self.jito_failover.record_failure().await;
+                        warn!("⚠️ Jito bundle for {} failed, falling back to RPC: {}", request.opportunity_id, e);
+                    }
+                }
+            }
+        }
+
+        if submission_path == SubmissionPath::NotSubmitted {
+            // The same signed transaction, so it can't land twice if the bundle did too
+            self.send_transaction(transaction).await?;
+            submission_path = SubmissionPath::Rpc;
+            info!("📨 Trade {} submitted via RPC", request.opportunity_id);
+        }
+
+        let outcome = self.await_confirmation(&signature, transaction).await?;
+        // An accepted bundle only counts as healthy once it lands; one that
+        // didn't was dropped and counts toward failover like a refusal
+        if submission_path == SubmissionPath::Jito {
+            match &outcome {
+                Ok(()) => self.jito_failover.record_success().await,
+                Err(e) => {
+                    warn!("⚠️ Jito bundle {} for {} did not land: {}", bundle_id, request.opportunity_id, e);
+                    self.jito_failover.record_failure().await;
+                }
+            }
+        }
+        let error_message = match outcome {
+            Ok(()) => String::new(),
+            Err(e) => e.to_string(),
+        };
+        Ok(TradeResponse {
+            transaction_id: signature,
+            success: error_message.is_empty(),
+            error_message,
+            actual_profit: opportunity.estimated_profit,
+            gas_used: opportunity.gas_cost,
+            execution_time: 0,
+            bundle_id,
+            submission_path,
+        })
+    }
+
+    async fn send_transaction(&self, transaction: &VersionedTransaction) -> Result<()> {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bincode::serialize(transaction)?);
+        // Preflight already ran when the engine simulated the transaction
+        self.rpc.call("sendTransaction", json!([
+            encoded,
+            { "encoding": "base64", "skipPreflight": true, "maxRetries": 0 }
+        ])).await?;
+        Ok(())
+    }
+
+    // Polls until the signature confirms, fails on chain, or can no longer
+    // land. The outer error is an RPC failure; the inner one is the trade's.
+    async fn await_confirmation(
+        &self,
+        signature: &str,
+        transaction: &VersionedTransaction,
+    ) -> Result<Result<(), ArbitrageError>> {
+        let blockhash = transaction.message.recent_blockhash().to_string();
+        let started = Instant::now();
+
+        loop {
+            tokio::time::sleep(Duration::from_millis(CONFIRMATION_POLL_MS)).await;
+
+            let result = self.rpc.call("getSignatureStatuses", json!([[signature]])).await?;
+            let status = &result["value"][0];
+            if !status.is_null() {
+                if !status["err"].is_null() {
+                    return Ok(Err(ArbitrageError::TransactionFailed(status["err"].to_string())));
+                }
+                if matches!(status["confirmationStatus"].as_str(), Some("confirmed" | "finalized")) {
+                    debug!("✅ Transaction {} confirmed", signature);
+                    return Ok(Ok(()));
+                }
+                continue;
+            }
+
+            let valid = self.rpc.call("isBlockhashValid", json!([blockhash, { "commitment": "processed" }])).await?;
+            if valid["value"] == false {
+                return Ok(Err(ArbitrageError::BlockhashExpired));
+            }
+            if started.elapsed() >= self.confirmation_timeout {
+                warn!("⏳ Transaction {} not confirmed within {:?}", signature, self.confirmation_timeout);
+                return Ok(Err(ArbitrageError::ConfirmationTimeout));
+            }
+        }
+    }
+}
+
+impl Executor for ChainExecutor {
+    fn execute<'a>(
+        &'a self,
+        request: &'a TradeRequest,
+        opportunity: &'a ArbitrageOpportunity,
+        transaction: Option<&'a VersionedTransaction>,
+    ) -> BoxFuture<'a, Result<TradeResponse>> {
+        Box::pin(self.submit_trade(request, opportunity, transaction))
+    }
+}
+
+// Deterministic stand-in for the chain: the same config and sequence of
+// opportunities always produce the same fills, profits and ids
+pub struct SimExecutor {
+    config: SimulationConfig,
+    trades: AtomicU64,
+    // Carries the fractional part of fill_rate between trades, so exactly
+    // fill_rate of all trades fill, spread evenly
+    fill_credit: Mutex<f64>,
+}
+
+impl SimExecutor {
+    pub fn new(config: SimulationConfig) -> Self {
+        Self {
+            config,
+            trades: AtomicU64::new(0),
+            fill_credit: Mutex::new(0.0),
+        }
+    }
+
+    fn simulate(&self, opportunity: &ArbitrageOpportunity) -> TradeResponse {
+        let trade = self.trades.fetch_add(1, Ordering::SeqCst) + 1;
+        let filled = {
+            let mut credit = self.fill_credit.lock().unwrap();
+            *credit += self.config.fill_rate.clamp(0.0, 1.0);
+            if *credit >= 1.0 - 1e-9 {
+                *credit -= 1.0;
+                true
+            } else {
+                false
+            }
+        };
+
+        let gas_used = opportunity.gas_cost + self.config.fee_per_trade;
+        if !filled {
+            return TradeResponse {
+                transaction_id: format!("sim_{}", trade),
+                success: false,
+                error_message: "Simulated trade was not filled".to_string(),
+                actual_profit: -gas_used,
+                gas_used,
+                execution_time: self.config.latency_ms as i64,
+                bundle_id: String::new(),
+                submission_path: SubmissionPath::NotSubmitted,
+            };
+        }
+
+        // Slippage applies to the traded notional, recovered from the gross edge
+        let gross_profit = opportunity.estimated_profit + opportunity.gas_cost;
+        let notional = if opportunity.profit_percentage > 0.0 {
+            gross_profit / (opportunity.profit_percentage / 100.0)
+        } else {
+            0.0
+        };
+        let slippage_cost = notional * self.config.slippage_bps as f64 / 10_000.0;
+
+        TradeResponse {
+            transaction_id: format!("sim_{}", trade),
+            success: true,
+            error_message: String::new(),
+            actual_profit: gross_profit - slippage_cost - gas_used,
+            gas_used,
+            execution_time: self.config.latency_ms as i64,
+            bundle_id: String::new(),
+            submission_path: SubmissionPath::NotSubmitted,
+        }
+    }
+}
+
+impl Executor for SimExecutor {
+    fn submits_transactions(&self) -> bool {
+        false
+    }
+
+    fn execute<'a>(
+        &'a self,
+        request: &'a TradeRequest,
+        opportunity: &'a ArbitrageOpportunity,
+        _transaction: Option<&'a VersionedTransaction>,
+    ) -> BoxFuture<'a, Result<TradeResponse>> {
+        Box::pin(async move {
+            if self.config.latency_ms > 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(self.config.latency_ms)).await;
+            }
+            let response = self.simulate(opportunity);
+            info!("🧪 Simulated trade {}: filled {}, profit {:.4}",
+                  request.opportunity_id, response.success, response.actual_profit);
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simulator(fill_rate: f64) -> SimExecutor {
+        SimExecutor::new(SimulationConfig {
+            enabled: true,
+            fill_rate,
+            slippage_bps: 10,
+            fee_per_trade: 0.5,
+            latency_ms: 0,
+        })
+    }
+
+    fn opportunity() -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            profit_percentage: 1.0,
+            estimated_profit: 9.0,
+            gas_cost: 1.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fills_exactly_the_configured_share_of_trades() {
+        let executor = simulator(0.5);
+        let fills: Vec<bool> = (0..4).map(|_| executor.simulate(&opportunity()).success).collect();
+        assert_eq!(fills, vec![false, true, false, true]);
+    }
+
+    #[test]
+    fn fills_pay_slippage_on_the_notional_and_misses_pay_gas() {
+        let filled = simulator(1.0).simulate(&opportunity());
+        // Gross 10 on a 1,000 notional: 1 slippage and 1.5 gas
+        assert!((filled.actual_profit - 7.5).abs() < 1e-9);
+        assert_eq!(filled.submission_path, SubmissionPath::NotSubmitted);
+
+        let missed = simulator(0.0).simulate(&opportunity());
+        assert!(!missed.success);
+        assert_eq!(missed.actual_profit, -1.5);
+    }
+}
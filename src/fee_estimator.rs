@@ -0,0 +1,130 @@
+use crate::{config::ProfitFeeMapping, rpc_client::SolanaRpcClient};
+use anyhow::Result;
+use serde_json::json;
+use tracing::{debug, warn};
+
+// Picks a compute-unit price from recent prioritization fees, bounded so we
+// never bid below `floor` or above `ceiling` (micro-lamports per CU)
+pub struct FeeEstimator {
+    rpc: SolanaRpcClient,
+    percentile: u8,
+    floor: u64,
+    ceiling: u64,
+}
+
+impl FeeEstimator {
+    pub fn new(rpc_url: String, timeout_ms: u64, percentile: u8, floor: u64, ceiling: u64) -> Self {
+        Self {
+            rpc: SolanaRpcClient::new(rpc_url, timeout_ms),
+            percentile: percentile.min(100),
+            floor,
+            ceiling: ceiling.max(floor),
+        }
+    }
+
+    pub async fn estimate_cu_price(&self) -> Result<u64> {
+        let result = self.rpc.call("getRecentPrioritizationFees", json!([])).await?;
+        let mut fees: Vec<u64> = result
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("Malformed getRecentPrioritizationFees response"))?
+            .iter()
+            .filter_map(|entry| entry["prioritizationFee"].as_u64())
+            .collect();
+
+        let estimate = percentile(&mut fees, self.percentile);
+        let price = self.clamp(estimate);
+        debug!("⛽ CU price p{} = {} micro-lamports, bidding {}", self.percentile, estimate, price);
+        Ok(price)
+    }
+
+    pub fn clamp(&self, estimate: u64) -> u64 {
+        if estimate < self.floor {
+            debug!("⛽ CU price estimate {} below floor, raising to {}", estimate, self.floor);
+            self.floor
+        } else if estimate > self.ceiling {
+            warn!("⛽ CU price estimate {} capped at ceiling {}; landing rate may suffer", estimate, self.ceiling);
+            self.ceiling
+        } else {
+            estimate
+        }
+    }
+}
+
+// Maps an opportunity's net profit to a compute-unit price in [floor, ceiling]
+// so fatter opportunities outbid marginal ones for the same block
+pub fn cu_price_for_profit(net_profit_usd: f64, mapping: &ProfitFeeMapping, floor: u64, ceiling: u64) -> u64 {
+    let ceiling = ceiling.max(floor);
+    let profit = if net_profit_usd.is_finite() { net_profit_usd.max(0.0) } else { 0.0 };
+
+    let price = match mapping {
+        ProfitFeeMapping::Linear { full_bid_profit_usd } => {
+            let fraction = if *full_bid_profit_usd > 0.0 {
+                (profit / full_bid_profit_usd).min(1.0)
+            } else {
+                1.0
+            };
+            floor + ((ceiling - floor) as f64 * fraction) as u64
+        }
+        ProfitFeeMapping::Stepped { steps } => steps.iter()
+            .filter(|step| profit >= step.min_profit_usd)
+            .map(|step| step.micro_lamports)
+            .max()
+            .unwrap_or(floor),
+    };
+    price.clamp(floor, ceiling)
+}
+
+fn percentile(values: &mut [u64], percentile: u8) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+    values.sort_unstable();
+    let rank = (percentile as f64 / 100.0 * values.len() as f64).ceil() as usize;
+    values[rank.clamp(1, values.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProfitFeeStep;
+
+    #[test]
+    fn percentile_picks_the_ranked_fee() {
+        assert_eq!(percentile(&mut [], 75), 0);
+        assert_eq!(percentile(&mut [40, 10, 30, 20], 50), 20);
+        assert_eq!(percentile(&mut [40, 10, 30, 20], 75), 30);
+        assert_eq!(percentile(&mut [40, 10, 30, 20], 0), 10);
+        assert_eq!(percentile(&mut [40, 10, 30, 20], 100), 40);
+    }
+
+    #[test]
+    fn estimates_are_clamped_between_floor_and_ceiling() {
+        let estimator = FeeEstimator::new("http://localhost:8899".to_string(), 1_000, 75, 100, 10);
+        assert_eq!(estimator.clamp(5), 100);
+        assert_eq!(estimator.clamp(500), 100);
+
+        let estimator = FeeEstimator::new("http://localhost:8899".to_string(), 1_000, 75, 100, 1_000);
+        assert_eq!(estimator.clamp(500), 500);
+        assert_eq!(estimator.clamp(5_000), 1_000);
+    }
+
+    #[test]
+    fn linear_mapping_scales_with_profit() {
+        let mapping = ProfitFeeMapping::Linear { full_bid_profit_usd: 10.0 };
+        assert_eq!(cu_price_for_profit(0.0, &mapping, 100, 1_100), 100);
+        assert_eq!(cu_price_for_profit(5.0, &mapping, 100, 1_100), 600);
+        assert_eq!(cu_price_for_profit(50.0, &mapping, 100, 1_100), 1_100);
+        assert_eq!(cu_price_for_profit(f64::NAN, &mapping, 100, 1_100), 100);
+    }
+
+    #[test]
+    fn stepped_mapping_bids_the_highest_reached_step() {
+        let mapping = ProfitFeeMapping::Stepped { steps: vec![
+            ProfitFeeStep { min_profit_usd: 1.0, micro_lamports: 500 },
+            ProfitFeeStep { min_profit_usd: 10.0, micro_lamports: 5_000 },
+        ] };
+        assert_eq!(cu_price_for_profit(0.5, &mapping, 100, 2_000), 100);
+        assert_eq!(cu_price_for_profit(2.0, &mapping, 100, 2_000), 500);
+        assert_eq!(cu_price_for_profit(20.0, &mapping, 100, 2_000), 2_000);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::types::{StrategyKind, TradeResponse};
+use serde_json::Value;
+use solana_sdk::signature::Signature;
+use std::str::FromStr;
+
+// Polls in a row a recorded signature may go missing before it counts as
+// rolled back, so one lagging RPC node can't reverse a trade
+const MISSES_BEFORE_ROLLBACK: u32 = 2;
+
+// One signature's entry in a getSignatureStatuses response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainStatus {
+    Missing,
+    Confirmed,
+    Finalized,
+    // Landed with an error, e.g. re-executed differently on the surviving fork
+    Failed,
+}
+
+impl ChainStatus {
+    pub fn from_json(status: &Value) -> Self {
+        if status.is_null() {
+            ChainStatus::Missing
+        } else if !status["err"].is_null() {
+            ChainStatus::Failed
+        } else if status["confirmationStatus"] == "finalized" {
+            ChainStatus::Finalized
+        } else {
+            ChainStatus::Confirmed
+        }
+    }
+}
+
+// A successful trade whose transaction isn't finalized yet
+#[derive(Debug, Clone)]
+pub struct UnfinalizedTrade {
+    pub signature: Signature,
+    pub opportunity_id: String,
+    pub token_pair: String,
+    pub strategy: StrategyKind,
+    pub response: TradeResponse,
+    // Already counted in PnL and history (recorded at confirmed commitment)
+    pub accounted: bool,
+    misses: u32,
+}
+
+#[derive(Debug, Clone)]
+pub enum Finality {
+    Finalized(UnfinalizedTrade),
+    // Gone after a reorg, or landed with an error on the surviving fork
+    RolledBack(UnfinalizedTrade),
+}
+
+// Follows successful trades from confirmation to finalization. Trades are
+// either already accounted (and reversed if rolled back) or held back until
+// finalized, depending on the configured profit commitment.
+#[derive(Debug, Default)]
+pub struct FinalityTracker {
+    trades: Vec<UnfinalizedTrade>,
+}
+
+impl FinalityTracker {
+    // Only trades with a real transaction signature can be followed; false
+    // leaves the trade for the caller to account straight away
+    pub fn track(
+        &mut self,
+        opportunity_id: &str,
+        token_pair: &str,
+        strategy: StrategyKind,
+        response: &TradeResponse,
+        accounted: bool,
+    ) -> bool {
+        let Ok(signature) = Signature::from_str(&response.transaction_id) else {
+            return false;
+        };
+        self.trades.push(UnfinalizedTrade {
+            signature,
+            opportunity_id: opportunity_id.to_string(),
+            token_pair: token_pair.to_string(),
+            strategy,
+            response: response.clone(),
+            accounted,
+            misses: 0,
+        });
+        true
+    }
+
+    pub fn signatures(&self) -> Vec<Signature> {
+        self.trades.iter().map(|trade| trade.signature).collect()
+    }
+
+    // Applies one poll's status; finalized and rolled-back trades stop being tracked
+    pub fn update(&mut self, signature: &Signature, status: ChainStatus) -> Option<Finality> {
+        let index = self.trades.iter().position(|trade| &trade.signature == signature)?;
+        let trade = &mut self.trades[index];
+        match status {
+            ChainStatus::Confirmed => {
+                trade.misses = 0;
+                None
+            }
+            ChainStatus::Missing => {
+                trade.misses += 1;
+                if trade.misses < MISSES_BEFORE_ROLLBACK {
+                    return None;
+                }
+                Some(Finality::RolledBack(self.trades.remove(index)))
+            }
+            ChainStatus::Failed => Some(Finality::RolledBack(self.trades.remove(index))),
+            ChainStatus::Finalized => Some(Finality::Finalized(self.trades.remove(index))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn response(transaction_id: &str) -> TradeResponse {
+        TradeResponse {
+            transaction_id: transaction_id.to_string(),
+            success: true,
+            error_message: String::new(),
+            actual_profit: 1.0,
+            gas_used: 0.0,
+            execution_time: 0,
+            bundle_id: String::new(),
+            submission_path: Default::default(),
+        }
+    }
+
+    #[test]
+    fn reads_signature_statuses() {
+        assert_eq!(ChainStatus::from_json(&Value::Null), ChainStatus::Missing);
+        assert_eq!(ChainStatus::from_json(&json!({ "err": { "InstructionError": [0, "Custom"] } })), ChainStatus::Failed);
+        assert_eq!(ChainStatus::from_json(&json!({ "err": null, "confirmationStatus": "finalized" })), ChainStatus::Finalized);
+        assert_eq!(ChainStatus::from_json(&json!({ "err": null, "confirmationStatus": "confirmed" })), ChainStatus::Confirmed);
+    }
+
+    #[test]
+    fn only_trades_with_a_real_signature_are_tracked() {
+        let mut tracker = FinalityTracker::default();
+        assert!(!tracker.track("opp", "SOL/USDC", StrategyKind::Direct, &response("mock_tx"), true));
+        let signature = Signature::new_unique();
+        assert!(tracker.track("opp", "SOL/USDC", StrategyKind::Direct, &response(&signature.to_string()), true));
+        assert_eq!(tracker.signatures(), vec![signature]);
+
+        assert!(tracker.update(&signature, ChainStatus::Confirmed).is_none());
+        assert!(matches!(tracker.update(&signature, ChainStatus::Finalized), Some(Finality::Finalized(_))));
+        assert!(tracker.signatures().is_empty());
+    }
+
+    #[test]
+    fn a_signature_must_go_missing_repeatedly_to_roll_back() {
+        let mut tracker = FinalityTracker::default();
+        let signature = Signature::new_unique();
+        tracker.track("opp", "SOL/USDC", StrategyKind::Direct, &response(&signature.to_string()), false);
+
+        assert!(tracker.update(&signature, ChainStatus::Missing).is_none());
+        assert!(tracker.update(&signature, ChainStatus::Confirmed).is_none());
+        assert!(tracker.update(&signature, ChainStatus::Missing).is_none());
+        assert!(matches!(tracker.update(&signature, ChainStatus::Missing), Some(Finality::RolledBack(trade)) if !trade.accounted));
+    }
+}
@@ -0,0 +1,38 @@
+use anyhow::Result;
+use std::net::TcpListener;
+
+// First port in `port..=port + fallback_ports` that can be bound right now.
+// The probe listener is dropped before returning, so the caller binds it
+// again; losing that race surfaces as an ordinary server start error.
+pub fn bindable_port(port: u16, fallback_ports: u16) -> Result<u16> {
+    let last = port.saturating_add(fallback_ports);
+    let mut last_error = None;
+    for candidate in port..=last {
+        match TcpListener::bind(("0.0.0.0", candidate)) {
+            Ok(_) => return Ok(candidate),
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    let error = last_error.map(|e| e.to_string()).unwrap_or_default();
+    Err(if last == port {
+        anyhow::anyhow!("cannot bind gRPC port {}: {}", port, error)
+    } else {
+        anyhow::anyhow!("cannot bind any gRPC port in {}..={}: {}", port, last, error)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_past_a_taken_port() {
+        let taken = TcpListener::bind(("0.0.0.0", 0)).unwrap();
+        let port = taken.local_addr().unwrap().port();
+
+        let error = bindable_port(port, 0).unwrap_err().to_string();
+        assert!(error.contains(&format!("gRPC port {}", port)));
+        assert_ne!(bindable_port(port, 5).ok(), Some(port));
+    }
+}
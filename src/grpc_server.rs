@@ -0,0 +1,436 @@
+use crate::{
+    arbitrage::{
+        self,
+        arbitrage_service_server::{ArbitrageService, ArbitrageServiceServer},
+        history_record, scan_scope_response, HistoryRequest, HistoryResponse, OpportunityRequest, OpportunityResponse, PortfolioRequest,
+        PriceStreamRequest, RiskSettingsResponse, ScanScopeRequest, ScanScopeResponse, StatsRequest, StatsResponse, WatchPairResponse,
+    },
+    config::{ScanScope, WatchedPair},
+    arbitrage_engine::ArbitrageEngine,
+    history::{HistoryEntry, HistoryQuery},
+    monitoring::MonitoringService,
+    portfolio_manager::PortfolioManager,
+    risk_manager::RiskManager,
+    types,
+};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use tonic::{transport::Server, Request, Response, Status};
+use tracing::{debug, info, warn};
+
+const DEFAULT_PRICE_UPDATE_MS: u64 = 1000;
+// Polling any faster only repeats the DEX monitor's own cycle
+const MIN_PRICE_UPDATE_MS: u64 = 100;
+const DEFAULT_HISTORY_PAGE: usize = 100;
+const MAX_HISTORY_PAGE: usize = 1000;
+
+pub struct ArbitrageGrpcServer {
+    engine: Arc<ArbitrageEngine>,
+    portfolio_manager: Arc<PortfolioManager>,
+    risk_manager: Arc<RwLock<RiskManager>>,
+    // Kept so the server holds the same services the engine reports through
+    _monitoring: Arc<MonitoringService>,
+}
+
+impl ArbitrageGrpcServer {
+    pub fn new(
+        engine: Arc<ArbitrageEngine>,
+        portfolio_manager: Arc<PortfolioManager>,
+        risk_manager: Arc<RwLock<RiskManager>>,
+        monitoring: Arc<MonitoringService>,
+    ) -> Self {
+        Self {
+            engine,
+            portfolio_manager,
+            risk_manager,
+            _monitoring: monitoring,
+        }
+    }
+
+    pub async fn start(self, port: u16) -> anyhow::Result<()> {
+        let addr = format!("0.0.0.0:{}", port).parse()?;
+        Server::builder()
+            .add_service(ArbitrageServiceServer::new(self))
+            .serve(addr)
+            .await?;
+        Ok(())
+    }
+}
+
+fn internal(e: anyhow::Error) -> Status {
+    Status::internal(e.to_string())
+}
+
+#[tonic::async_trait]
+impl ArbitrageService for ArbitrageGrpcServer {
+    type StreamPricesStream = Pin<Box<dyn Stream<Item = Result<arbitrage::PriceData, Status>> + Send>>;
+
+    async fn stream_prices(
+        &self,
+        request: Request<PriceStreamRequest>,
+    ) -> Result<Response<Self::StreamPricesStream>, Status> {
+        let request = request.into_inner();
+        let interval_ms = match request.update_interval_ms {
+            ms if ms > 0 => (ms as u64).max(MIN_PRICE_UPDATE_MS),
+            _ => DEFAULT_PRICE_UPDATE_MS,
+        };
+        let engine = self.engine.clone();
+        let (sender, receiver) = mpsc::channel(256);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(interval_ms));
+            loop {
+                interval.tick().await;
+                let prices = match engine.get_current_prices().await {
+                    Ok(prices) => prices,
+                    Err(e) => {
+                        warn!("⚠️ Price stream poll failed: {}", e);
+                        continue;
+                    }
+                };
+                let wanted = prices.into_iter().filter(|price| {
+                    (request.dex_names.is_empty() || request.dex_names.contains(&price.dex_name))
+                        && (request.token_pairs.is_empty() || request.token_pairs.contains(&price.token_pair))
+                });
+                for price in wanted {
+                    if sender.send(Ok(price_to_proto(price))).await.is_err() {
+                        debug!("📴 Price stream subscriber went away");
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(receiver))))
+    }
+
+    async fn get_opportunities(
+        &self,
+        request: Request<OpportunityRequest>,
+    ) -> Result<Response<OpportunityResponse>, Status> {
+        let request = request.into_inner();
+        let opportunities: Vec<arbitrage::ArbitrageOpportunity> = self.engine.get_live_opportunities().await
+            .into_iter()
+            .filter(|opportunity| {
+                opportunity.profit_percentage >= request.min_profit_percentage
+                    && opportunity.max_amount >= request.min_amount
+                    && (request.token_pairs.is_empty() || request.token_pairs.contains(&opportunity.token_pair))
+            })
+            .map(opportunity_to_proto)
+            .collect();
+
+        Ok(Response::new(OpportunityResponse {
+            total_count: opportunities.len() as i64,
+            opportunities,
+        }))
+    }
+
+    async fn execute_trade(
+        &self,
+        request: Request<arbitrage::TradeRequest>,
+    ) -> Result<Response<arbitrage::TradeResponse>, Status> {
+        let request = request.into_inner();
+        info!("🌐 ExecuteTrade for opportunity {}", request.opportunity_id);
+        let response = self.engine.execute_trade(types::TradeRequest {
+            opportunity_id: request.opportunity_id,
+            amount: request.amount,
+            private_key: request.private_key,
+            max_slippage: request.max_slippage,
+            priority_fee: request.priority_fee,
+            use_jito: request.use_jito,
+            jito_tip: request.jito_tip,
+        }).await.map_err(internal)?;
+
+        Ok(Response::new(trade_response_to_proto(response)))
+    }
+
+    async fn get_portfolio(
+        &self,
+        _request: Request<PortfolioRequest>,
+    ) -> Result<Response<arbitrage::Portfolio>, Status> {
+        let portfolio = self.portfolio_manager.get_portfolio().await.map_err(internal)?;
+        Ok(Response::new(arbitrage::Portfolio {
+            wallet_address: portfolio.wallet_address,
+            balances: portfolio.balances.into_iter()
+                .map(|balance| arbitrage::TokenBalance {
+                    token_mint: balance.token_mint,
+                    symbol: balance.symbol,
+                    amount: balance.amount,
+                    value_usd: balance.value_usd,
+                    price: balance.price,
+                })
+                .collect(),
+            total_value_usd: portfolio.total_value_usd,
+            available_balance: portfolio.available_balance,
+            last_updated: portfolio.last_updated,
+        }))
+    }
+
+    async fn update_risk_settings(
+        &self,
+        request: Request<arbitrage::RiskSettings>,
+    ) -> Result<Response<RiskSettingsResponse>, Status> {
+        let settings = request.into_inner();
+        if settings.max_trades_per_hour < 0 {
+            return Ok(Response::new(RiskSettingsResponse {
+                success: false,
+                message: "max_trades_per_hour must not be negative".to_string(),
+            }));
+        }
+
+        let mut risk_manager = self.risk_manager.write().await;
+        risk_manager.update_max_position_size(settings.max_position_size);
+        risk_manager.update_max_daily_loss(settings.max_daily_loss);
+        risk_manager.update_max_slippage(settings.max_slippage);
+        risk_manager.update_min_profit_threshold(settings.min_profit_threshold);
+        risk_manager.update_max_trades_per_hour(settings.max_trades_per_hour as u32);
+        risk_manager.update_stop_loss(settings.enable_stop_loss, settings.stop_loss_percentage);
+        info!("📊 Risk settings updated over gRPC");
+
+        Ok(Response::new(RiskSettingsResponse {
+            success: true,
+            message: "Risk settings updated".to_string(),
+        }))
+    }
+
+    async fn get_stats(&self, request: Request<StatsRequest>) -> Result<Response<StatsResponse>, Status> {
+        let time_period = request.into_inner().time_period;
+        if !time_period.is_empty() {
+            debug!("📊 Stats are kept since start; ignoring time_period {}", time_period);
+        }
+        let stats = self.engine.get_trading_stats().await.map_err(internal)?;
+        Ok(Response::new(StatsResponse {
+            total_profit: stats.total_profit,
+            total_trades: stats.total_trades as i32,
+            win_rate: stats.win_rate,
+            avg_profit_per_trade: stats.avg_profit_per_trade,
+            max_drawdown: stats.max_drawdown,
+            sharpe_ratio: stats.sharpe_ratio,
+        }))
+    }
+
+    async fn query_history(&self, request: Request<HistoryRequest>) -> Result<Response<HistoryResponse>, Status> {
+        let request = request.into_inner();
+        let query = HistoryQuery {
+            from_ts: request.from_ts,
+            to_ts: if request.to_ts > 0 { request.to_ts } else { i64::MAX },
+            pair_filter: Some(request.token_pair).filter(|pair| !pair.is_empty()),
+            limit: match request.limit {
+                limit if limit > 0 => (limit as usize).min(MAX_HISTORY_PAGE),
+                _ => DEFAULT_HISTORY_PAGE,
+            },
+            cursor: Some(request.cursor).filter(|cursor| !cursor.is_empty()),
+        };
+        let page = self.engine.query_history(&query).await
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        Ok(Response::new(HistoryResponse {
+            records: page.records.into_iter()
+                .map(|record| arbitrage::HistoryRecord {
+                    seq: record.seq,
+                    timestamp: record.timestamp,
+                    token_pair: record.token_pair,
+                    entry: Some(match record.entry {
+                        HistoryEntry::Opportunity(opportunity) => {
+                            history_record::Entry::Opportunity(opportunity_to_proto(opportunity))
+                        }
+                        HistoryEntry::Trade { opportunity_id, response } => {
+                            history_record::Entry::Trade(arbitrage::TradeRecord {
+                                opportunity_id,
+                                response: Some(trade_response_to_proto(response)),
+                            })
+                        }
+                    }),
+                })
+                .collect(),
+            next_cursor: page.next_cursor.unwrap_or_default(),
+            total_count: page.total_count as i64,
+        }))
+    }
+
+    async fn watch_pair(&self, request: Request<arbitrage::WatchedPair>) -> Result<Response<WatchPairResponse>, Status> {
+        let pair = watched_pair_from_proto(request.into_inner())
+            .ok_or_else(|| Status::invalid_argument("input_mint and output_mint must be two different mints"))?;
+        let changed = self.engine.watch_pair(pair).await;
+        Ok(Response::new(WatchPairResponse { changed }))
+    }
+
+    async fn unwatch_pair(&self, request: Request<arbitrage::WatchedPair>) -> Result<Response<WatchPairResponse>, Status> {
+        let pair = watched_pair_from_proto(request.into_inner())
+            .ok_or_else(|| Status::invalid_argument("input_mint and output_mint must be two different mints"))?;
+        let changed = self.engine.unwatch_pair(&pair).await;
+        Ok(Response::new(WatchPairResponse { changed }))
+    }
+
+    async fn get_scan_scope(&self, _request: Request<ScanScopeRequest>) -> Result<Response<ScanScopeResponse>, Status> {
+        let response = match self.engine.get_scan_scope().await {
+            ScanScope::All => ScanScopeResponse {
+                mode: scan_scope_response::Mode::All as i32,
+                ..Default::default()
+            },
+            ScanScope::Watchlist(watchlist) => ScanScopeResponse {
+                mode: scan_scope_response::Mode::Watchlist as i32,
+                watchlist: watchlist.into_iter()
+                    .map(|pair| arbitrage::WatchedPair { input_mint: pair.input_mint, output_mint: pair.output_mint })
+                    .collect(),
+                ..Default::default()
+            },
+            ScanScope::TopVolume(limit) => ScanScopeResponse {
+                mode: scan_scope_response::Mode::TopVolume as i32,
+                top_volume_limit: limit.min(u32::MAX as usize) as u32,
+                ..Default::default()
+            },
+        };
+        Ok(Response::new(response))
+    }
+}
+
+// None unless both mints are set and differ
+fn watched_pair_from_proto(pair: arbitrage::WatchedPair) -> Option<WatchedPair> {
+    if pair.input_mint.is_empty() || pair.output_mint.is_empty() || pair.input_mint == pair.output_mint {
+        return None;
+    }
+    Some(WatchedPair { input_mint: pair.input_mint, output_mint: pair.output_mint })
+}
+
+fn trade_response_to_proto(response: types::TradeResponse) -> arbitrage::TradeResponse {
+    arbitrage::TradeResponse {
+        transaction_id: response.transaction_id,
+        success: response.success,
+        error_message: response.error_message,
+        actual_profit: response.actual_profit,
+        gas_used: response.gas_used,
+        execution_time: response.execution_time,
+        bundle_id: response.bundle_id,
+    }
+}
+
+fn price_to_proto(price: types::PriceData) -> arbitrage::PriceData {
+    arbitrage::PriceData {
+        dex_name: price.dex_name,
+        token_pair: price.token_pair,
+        base_token: price.base_token,
+        quote_token: price.quote_token,
+        price: price.price,
+        volume_24h: price.volume_24h,
+        liquidity: price.liquidity,
+        timestamp: price.timestamp,
+        pool_address: price.pool_address,
+        price_impact: price.price_impact,
+    }
+}
+
+fn opportunity_to_proto(opportunity: types::ArbitrageOpportunity) -> arbitrage::ArbitrageOpportunity {
+    arbitrage::ArbitrageOpportunity {
+        id: opportunity.id,
+        token_pair: opportunity.token_pair,
+        buy_dex: opportunity.buy_dex,
+        sell_dex: opportunity.sell_dex,
+        buy_price: opportunity.buy_price,
+        sell_price: opportunity.sell_price,
+        profit_percentage: opportunity.profit_percentage,
+        estimated_profit: opportunity.estimated_profit,
+        max_amount: opportunity.max_amount,
+        gas_cost: opportunity.gas_cost,
+        timestamp: opportunity.timestamp,
+        buy_pool: opportunity.buy_pool,
+        sell_pool: opportunity.sell_pool,
+        slippage: opportunity.slippage,
+        is_profitable: opportunity.is_profitable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{arbitrage::arbitrage_service_client::ArbitrageServiceClient, config::Config, dex_monitor::DexMonitor, history::HistoryStore};
+    use tonic::transport::{server::TcpIncoming, Channel};
+
+    // A server over an engine whose trade history is `history_path`, reached
+    // through a real gRPC client on a local port
+    async fn client(history_path: &std::path::Path) -> ArbitrageServiceClient<Channel> {
+        let mut config = Config::default();
+        config.jupiter.enabled = false;
+        config.trading.pending_trades_path = std::env::temp_dir()
+            .join(format!("grpc_test_pending_{}.json", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        config.trading.history_path = Some(history_path.to_string_lossy().to_string());
+
+        let risk_manager = Arc::new(RwLock::new(RiskManager::new(config.risk_settings.clone())));
+        let portfolio_manager = Arc::new(PortfolioManager::new(config.clone()));
+        let monitoring = Arc::new(MonitoringService::new(config.monitoring.clone()));
+        let engine = ArbitrageEngine::new(
+            config.clone(),
+            Arc::new(DexMonitor::new(config.dex_endpoints.clone())),
+            risk_manager.clone(),
+            portfolio_manager.clone(),
+            None,
+            None,
+            monitoring.clone(),
+        )
+        .unwrap();
+        let server = ArbitrageGrpcServer::new(Arc::new(engine), portfolio_manager, risk_manager, monitoring);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(Server::builder().add_service(ArbitrageServiceServer::new(server)).serve_with_incoming(incoming));
+        ArbitrageServiceClient::connect(format!("http://{}", addr)).await.unwrap()
+    }
+
+    fn trade() -> types::TradeResponse {
+        types::TradeResponse {
+            transaction_id: "sig".to_string(),
+            success: true,
+            actual_profit: 1.0,
+            ..types::TradeResponse::rejected("")
+        }
+    }
+
+    #[tokio::test]
+    async fn query_history_pages_through_a_range_with_its_cursor() {
+        let path = std::env::temp_dir().join(format!("grpc_history_test_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = HistoryStore::open(Some(path.clone())).unwrap();
+        for (i, timestamp) in [1_000, 2_000, 3_000, 4_000, 5_000].into_iter().enumerate() {
+            store.record_trade(timestamp, "SOL/USDC", &format!("trade-{}", i), &trade()).await.unwrap();
+        }
+        store.record_trade(3_500, "BONK/SOL", "other", &trade()).await.unwrap();
+        let mut client = client(&path).await;
+
+        let mut seen = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let page = client.query_history(HistoryRequest {
+                from_ts: 1_500,
+                to_ts: 0,
+                token_pair: "SOL/USDC".to_string(),
+                limit: 2,
+                cursor: cursor.clone(),
+            }).await.unwrap().into_inner();
+            assert_eq!(page.total_count, 4);
+            assert!(page.records.len() <= 2);
+            seen.extend(page.records.iter().map(|record| record.timestamp));
+            if page.next_cursor.is_empty() {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+        assert_eq!(seen, vec![2_000, 3_000, 4_000, 5_000]);
+
+        let empty = client.query_history(HistoryRequest {
+            from_ts: 6_000,
+            to_ts: 7_000,
+            ..Default::default()
+        }).await.unwrap().into_inner();
+        assert!(empty.records.is_empty());
+        assert_eq!(empty.total_count, 0);
+        assert!(empty.next_cursor.is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
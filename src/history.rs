@@ -0,0 +1,309 @@
+use crate::{
+    clock::Clock,
+    supervisor::Heartbeat,
+    types::{ArbitrageOpportunity, TradeResponse},
+};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+const MS_PER_DAY: i64 = 86_400_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HistoryEntry {
+    Opportunity(ArbitrageOpportunity),
+    Trade {
+        opportunity_id: String,
+        response: TradeResponse,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub seq: u64,
+    pub timestamp: i64,
+    pub token_pair: String,
+    pub entry: HistoryEntry,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryQuery {
+    pub from_ts: i64,
+    pub to_ts: i64,
+    pub pair_filter: Option<String>,
+    pub limit: usize,
+    // `next_cursor` from the previous page; None starts from the beginning
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneCounts {
+    pub opportunities: usize,
+    pub trades: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub records: Vec<HistoryRecord>,
+    pub next_cursor: Option<String>,
+    pub total_count: usize,
+}
+
+// Append-only log of opportunities and trades (JSON lines on disk), queryable
+// by time range and pair with cursor pagination
+pub struct HistoryStore {
+    path: Option<PathBuf>,
+    records: RwLock<Vec<HistoryRecord>>,
+}
+
+impl HistoryStore {
+    pub fn open(path: Option<PathBuf>) -> Result<Self> {
+        let mut records = Vec::new();
+        if let Some(path) = &path {
+            match std::fs::read_to_string(path) {
+                Ok(content) => {
+                    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+                        records.push(serde_json::from_str(line)?);
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(Self {
+            path,
+            records: RwLock::new(records),
+        })
+    }
+
+    pub async fn record_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<()> {
+        self.append(opportunity.timestamp, &opportunity.token_pair, HistoryEntry::Opportunity(opportunity.clone())).await
+    }
+
+    pub async fn record_trade(&self, timestamp: i64, token_pair: &str, opportunity_id: &str, response: &TradeResponse) -> Result<()> {
+        self.append(timestamp, token_pair, HistoryEntry::Trade {
+            opportunity_id: opportunity_id.to_string(),
+            response: response.clone(),
+        }).await
+    }
+
+    async fn append(&self, timestamp: i64, token_pair: &str, entry: HistoryEntry) -> Result<()> {
+        let mut records = self.records.write().await;
+        let record = HistoryRecord {
+            seq: records.last().map_or(0, |r| r.seq + 1),
+            timestamp,
+            token_pair: token_pair.to_string(),
+            entry,
+        };
+
+        if let Some(path) = &self.path {
+            let mut line = serde_json::to_string(&record)?;
+            line.push('\n');
+            let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(path).await?;
+            file.write_all(line.as_bytes()).await?;
+        }
+        records.push(record);
+        Ok(())
+    }
+
+    // Drops opportunities older than `opportunity_cutoff` and trades older than
+    // `trade_cutoff` (None keeps every trade), rewriting the file if any went
+    pub async fn prune(&self, opportunity_cutoff: i64, trade_cutoff: Option<i64>) -> Result<PruneCounts> {
+        let mut records = self.records.write().await;
+        let mut counts = PruneCounts::default();
+        records.retain(|record| match record.entry {
+            HistoryEntry::Opportunity(_) if record.timestamp < opportunity_cutoff => {
+                counts.opportunities += 1;
+                false
+            }
+            HistoryEntry::Trade { .. } if trade_cutoff.is_some_and(|cutoff| record.timestamp < cutoff) => {
+                counts.trades += 1;
+                false
+            }
+            _ => true,
+        });
+
+        if counts == PruneCounts::default() {
+            return Ok(counts);
+        }
+        if let Some(path) = &self.path {
+            // Still holding the lock, so no append can land between snapshot and rename
+            let mut content = String::new();
+            for record in records.iter() {
+                content.push_str(&serde_json::to_string(record)?);
+                content.push('\n');
+            }
+            let tmp_path = path.with_extension("tmp");
+            tokio::fs::write(&tmp_path, content).await?;
+            tokio::fs::rename(&tmp_path, path).await?;
+        }
+        Ok(counts)
+    }
+
+    pub async fn run_vacuum(
+        &self,
+        opportunity_retention_days: u64,
+        trade_retention_days: Option<u64>,
+        interval_secs: u64,
+        clock: Arc<dyn Clock>,
+        heartbeat: Heartbeat,
+    ) -> Result<()> {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            heartbeat.beat();
+
+            let now = clock.now_ms();
+            let opportunity_cutoff = now - opportunity_retention_days as i64 * MS_PER_DAY;
+            let trade_cutoff = trade_retention_days.map(|days| now - days as i64 * MS_PER_DAY);
+            // A failed rewrite only leaves pruned records on disk until a later rewrite
+            match self.prune(opportunity_cutoff, trade_cutoff).await {
+                Ok(counts) if counts != PruneCounts::default() => {
+                    info!("🧹 History vacuum removed {} opportunities and {} trades", counts.opportunities, counts.trades);
+                }
+                Ok(_) => {}
+                Err(e) => warn!("⚠️ History vacuum failed: {}", e),
+            }
+        }
+    }
+
+    pub async fn query(&self, query: &HistoryQuery) -> Result<HistoryPage> {
+        let limit = query.limit.max(1);
+        let after = match &query.cursor {
+            Some(cursor) => Some(decode_cursor(cursor)?),
+            None => None,
+        };
+
+        let records = self.records.read().await;
+        let mut matching: Vec<&HistoryRecord> = records.iter()
+            .filter(|r| r.timestamp >= query.from_ts && r.timestamp <= query.to_ts)
+            .filter(|r| query.pair_filter.as_ref().is_none_or(|pair| &r.token_pair == pair))
+            .collect();
+        matching.sort_by_key(|r| (r.timestamp, r.seq));
+
+        let total_count = matching.len();
+        let page: Vec<HistoryRecord> = matching.into_iter()
+            .filter(|r| after.is_none_or(|key| (r.timestamp, r.seq) > key))
+            .take(limit + 1)
+            .cloned()
+            .collect();
+
+        // One extra record was fetched to learn whether another page exists
+        let (records, next_cursor) = if page.len() > limit {
+            let records = page[..limit].to_vec();
+            let last = records.last().unwrap();
+            let cursor = encode_cursor(last.timestamp, last.seq);
+            (records, Some(cursor))
+        } else {
+            (page, None)
+        };
+
+        Ok(HistoryPage { records, next_cursor, total_count })
+    }
+}
+
+fn encode_cursor(timestamp: i64, seq: u64) -> String {
+    format!("{}:{}", timestamp, seq)
+}
+
+fn decode_cursor(cursor: &str) -> Result<(i64, u64)> {
+    let (timestamp, seq) = cursor
+        .split_once(':')
+        .ok_or_else(|| anyhow::anyhow!("Invalid history cursor: {}", cursor))?;
+    Ok((timestamp.parse()?, seq.parse()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opportunity(token_pair: &str, timestamp: i64) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            id: format!("{}@{}", token_pair, timestamp),
+            token_pair: token_pair.to_string(),
+            timestamp,
+            ..Default::default()
+        }
+    }
+
+    fn response() -> TradeResponse {
+        TradeResponse {
+            transaction_id: "tx".to_string(),
+            success: true,
+            error_message: String::new(),
+            actual_profit: 1.0,
+            gas_used: 0.0,
+            execution_time: 0,
+            bundle_id: String::new(),
+            submission_path: Default::default(),
+        }
+    }
+
+    fn query(cursor: Option<String>) -> HistoryQuery {
+        HistoryQuery { from_ts: 0, to_ts: i64::MAX, pair_filter: None, limit: 2, cursor }
+    }
+
+    #[tokio::test]
+    async fn pages_through_records_in_time_order() {
+        let store = HistoryStore::open(None).unwrap();
+        for timestamp in [30, 10, 20, 40, 50] {
+            store.record_opportunity(&opportunity("SOL/USDC", timestamp)).await.unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = store.query(&query(cursor)).await.unwrap();
+            assert_eq!(page.total_count, 5);
+            seen.extend(page.records.iter().map(|r| r.timestamp));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(seen, vec![10, 20, 30, 40, 50]);
+        assert!(store.query(&query(Some("garbage".to_string()))).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn filters_by_pair_and_time_range() {
+        let store = HistoryStore::open(None).unwrap();
+        store.record_opportunity(&opportunity("SOL/USDC", 10)).await.unwrap();
+        store.record_opportunity(&opportunity("RAY/USDC", 20)).await.unwrap();
+        store.record_trade(30, "SOL/USDC", "SOL/USDC@10", &response()).await.unwrap();
+
+        let page = store.query(&HistoryQuery {
+            from_ts: 15,
+            to_ts: 100,
+            pair_filter: Some("SOL/USDC".to_string()),
+            limit: 10,
+            cursor: None,
+        }).await.unwrap();
+        assert_eq!(page.records.len(), 1);
+        assert!(matches!(page.records[0].entry, HistoryEntry::Trade { .. }));
+    }
+
+    #[tokio::test]
+    async fn prunes_opportunities_and_trades_separately_and_persists_it() {
+        let path = std::env::temp_dir().join(format!("history_test_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let store = HistoryStore::open(Some(path.clone())).unwrap();
+        store.record_opportunity(&opportunity("SOL/USDC", 10)).await.unwrap();
+        store.record_trade(10, "SOL/USDC", "SOL/USDC@10", &response()).await.unwrap();
+        store.record_opportunity(&opportunity("SOL/USDC", 100)).await.unwrap();
+
+        let counts = store.prune(50, None).await.unwrap();
+        assert_eq!(counts, PruneCounts { opportunities: 1, trades: 0 });
+
+        let reopened = HistoryStore::open(Some(path.clone())).unwrap();
+        assert_eq!(reopened.query(&query(None)).await.unwrap().total_count, 2);
+        assert_eq!(reopened.prune(50, Some(50)).await.unwrap(), PruneCounts { opportunities: 0, trades: 1 });
+        let _ = std::fs::remove_file(&path);
+    }
+}
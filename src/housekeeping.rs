@@ -0,0 +1,105 @@
+use crate::{
+    config::HousekeepingConfig,
+    types::{Portfolio, NATIVE_SOL_MINT, WSOL_MINT},
+};
+
+// Rent held by an SPL token account, returned when it is closed
+pub const TOKEN_ACCOUNT_RENT_SOL: f64 = 0.00203928;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HousekeepingAction {
+    // Closing the wSOL account unwraps its balance to native SOL and
+    // reclaims the rent
+    CloseWsol { amount: f64 },
+    // Swap a dust balance into the base token
+    ConsolidateDust { mint: String, symbol: String, amount: f64, value_usd: f64 },
+    // Dust worth less than a swap would cost; only reported
+    ReportDust { mint: String, symbol: String, value_usd: f64 },
+}
+
+// Works on the raw portfolio: after `consolidate_sol` wSOL is no longer
+// distinguishable from native SOL
+pub fn plan_housekeeping(portfolio: &Portfolio, config: &HousekeepingConfig) -> Vec<HousekeepingAction> {
+    let mut actions = Vec::new();
+    for balance in &portfolio.balances {
+        if balance.token_mint == WSOL_MINT {
+            actions.push(HousekeepingAction::CloseWsol { amount: balance.amount });
+            continue;
+        }
+
+        let is_dust = balance.amount > 0.0 && balance.value_usd < config.dust_threshold_usd;
+        if !is_dust
+            || balance.token_mint == NATIVE_SOL_MINT
+            || balance.token_mint == config.base_mint
+            || config.keep_mints.contains(&balance.token_mint)
+        {
+            continue;
+        }
+
+        actions.push(if balance.value_usd >= config.min_swap_value_usd {
+            HousekeepingAction::ConsolidateDust {
+                mint: balance.token_mint.clone(),
+                symbol: balance.symbol.clone(),
+                amount: balance.amount,
+                value_usd: balance.value_usd,
+            }
+        } else {
+            HousekeepingAction::ReportDust {
+                mint: balance.token_mint.clone(),
+                symbol: balance.symbol.clone(),
+                value_usd: balance.value_usd,
+            }
+        });
+    }
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::types::TokenBalance;
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn balance(mint: &str, amount: f64, value_usd: f64) -> TokenBalance {
+        TokenBalance {
+            token_mint: mint.to_string(),
+            symbol: mint.chars().take(4).collect(),
+            amount,
+            value_usd,
+            price: 0.0,
+        }
+    }
+
+    #[test]
+    fn plans_wsol_close_and_sorts_dust_by_whether_a_swap_pays() {
+        let mut config = Config::default().housekeeping;
+        config.dust_threshold_usd = 5.0;
+        config.min_swap_value_usd = 1.0;
+        config.base_mint = USDC.to_string();
+        config.keep_mints = vec!["KEEP".to_string()];
+
+        let portfolio = Portfolio {
+            wallet_address: String::new(),
+            balances: vec![
+                balance(WSOL_MINT, 0.5, 75.0),
+                balance(NATIVE_SOL_MINT, 0.001, 0.15),
+                balance(USDC, 0.5, 0.5),
+                balance("KEEP", 1.0, 0.2),
+                balance("SWAP", 10.0, 3.0),
+                balance("TINY", 10.0, 0.1),
+                balance("BIG", 10.0, 50.0),
+            ],
+            total_value_usd: 0.0,
+            available_balance: 0.0,
+            last_updated: 0,
+        };
+
+        assert_eq!(plan_housekeeping(&portfolio, &config), vec![
+            HousekeepingAction::CloseWsol { amount: 0.5 },
+            HousekeepingAction::ConsolidateDust { mint: "SWAP".into(), symbol: "SWAP".into(), amount: 10.0, value_usd: 3.0 },
+            HousekeepingAction::ReportDust { mint: "TINY".into(), symbol: "TINY".into(), value_usd: 0.1 },
+        ]);
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy)]
+struct ImpactBaseline {
+    mean: f64,
+    samples: u32,
+}
+
+// Tracks an exponentially weighted baseline of price impact per pool and flags
+// observations that exceed `spike_multiple` times that baseline
+#[derive(Debug)]
+pub struct ImpactTracker {
+    alpha: f64,
+    spike_multiple: f64,
+    min_samples: u32,
+    baselines: HashMap<String, ImpactBaseline>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImpactSpike {
+    pub observed: f64,
+    pub baseline: f64,
+}
+
+impl ImpactTracker {
+    pub fn new(alpha: f64, spike_multiple: f64, min_samples: u32) -> Self {
+        Self {
+            alpha: alpha.clamp(0.0, 1.0),
+            spike_multiple,
+            min_samples,
+            baselines: HashMap::new(),
+        }
+    }
+
+    pub fn observe(&mut self, pool: &str, impact: f64) -> Option<ImpactSpike> {
+        if !impact.is_finite() || impact < 0.0 {
+            return None;
+        }
+
+        let baseline = self.baselines.entry(pool.to_string()).or_insert(ImpactBaseline {
+            mean: impact,
+            samples: 0,
+        });
+
+        let is_spike = baseline.samples >= self.min_samples
+            && baseline.mean > 0.0
+            && impact > baseline.mean * self.spike_multiple;
+        if is_spike {
+            // Spikes are kept out of the baseline so a sustained spike keeps alerting
+            return Some(ImpactSpike { observed: impact, baseline: baseline.mean });
+        }
+
+        baseline.mean += self.alpha * (impact - baseline.mean);
+        baseline.samples += 1;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_spikes_only_once_the_baseline_is_established() {
+        let mut tracker = ImpactTracker::new(0.5, 3.0, 2);
+        assert_eq!(tracker.observe("pool", 0.1), None);
+        // Too few samples to call anything a spike yet
+        assert_eq!(tracker.observe("pool", 1.0), None);
+        assert_eq!(tracker.observe("pool", 0.1), None);
+
+        let spike = tracker.observe("pool", 5.0).unwrap();
+        assert_eq!(spike.observed, 5.0);
+        // Spikes stay out of the baseline, so a sustained one keeps alerting
+        assert_eq!(tracker.observe("pool", 5.0).map(|s| s.baseline), Some(spike.baseline));
+        assert_eq!(tracker.observe("other", 5.0), None);
+        assert_eq!(tracker.observe("pool", f64::NAN), None);
+    }
+}
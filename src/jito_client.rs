@@ -0,0 +1,98 @@
+use crate::config::JitoConfig;
+use anyhow::Result;
+use base64::Engine;
+use reqwest::Client;
+use serde_json::{json, Value};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    transaction::VersionedTransaction,
+};
+use solana_system_interface::instruction as system_instruction;
+use std::str::FromStr;
+use tracing::{debug, error};
+
+// Sends bundles to a Jito block engine. The tip is an ordinary transfer to
+// the tip account inside the bundled transaction; see `tip_instruction`.
+pub struct JitoClient {
+    config: JitoConfig,
+    client: Client,
+}
+
+impl JitoClient {
+    pub fn new(config: JitoConfig) -> Self {
+        Self {
+            config,
+            client: Client::new(),
+        }
+    }
+
+    pub fn tip_instruction(&self, payer: &Pubkey, lamports: u64) -> Result<Instruction> {
+        let tip_account = Pubkey::from_str(&self.config.tip_account)
+            .map_err(|e| anyhow::anyhow!("Jito tip account '{}' is invalid: {}", self.config.tip_account, e))?;
+        Ok(system_instruction::transfer(payer, &tip_account, lamports))
+    }
+
+    // Submits signed transactions as one bundle and returns its bundle id.
+    // Acceptance only means the block engine queued it; whether it landed is
+    // read from the transactions' signatures.
+    pub async fn send_bundle(&self, transactions: &[VersionedTransaction]) -> Result<String> {
+        let encoded = transactions.iter()
+            .map(|transaction| Ok(base64::engine::general_purpose::STANDARD.encode(bincode::serialize(transaction)?)))
+            .collect::<Result<Vec<String>>>()?;
+
+        let result = self.call("sendBundle", json!([encoded, { "encoding": "base64" }])).await?;
+        let bundle_id = result.as_str()
+            .ok_or_else(|| anyhow::anyhow!("Malformed sendBundle response: {}", result))?;
+        debug!("📦 Jito accepted bundle {}", bundle_id);
+        Ok(bundle_id.to_string())
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let url = format!("{}/api/v1/bundles", self.config.bundle_endpoint.trim_end_matches('/'));
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let response = self.client.post(&url).json(&body).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            error!("❌ Jito {} failed ({}): {}", method, status, error_text);
+            return Err(anyhow::anyhow!("Jito {} failed ({}): {}", method, status, error_text));
+        }
+
+        let response: Value = response.json().await?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow::anyhow!("Jito {} error: {}", method, error));
+        }
+        Ok(response["result"].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn tip_is_a_transfer_to_the_tip_account() {
+        let tip_account = Pubkey::new_unique();
+        let mut config = Config::default().jito;
+        config.tip_account = tip_account.to_string();
+        let payer = Pubkey::new_unique();
+
+        let instruction = JitoClient::new(config).tip_instruction(&payer, 10_000).unwrap();
+        assert_eq!(instruction, system_instruction::transfer(&payer, &tip_account, 10_000));
+    }
+
+    #[test]
+    fn refuses_an_invalid_tip_account() {
+        let mut config = Config::default().jito;
+        config.tip_account = "not-a-key".to_string();
+        assert!(JitoClient::new(config).tip_instruction(&Pubkey::new_unique(), 1).is_err());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::clock::Clock;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+#[derive(Debug, Default)]
+struct FailoverState {
+    // Unix ms, from the failover's clock
+    recent_failures: VecDeque<i64>,
+    failed_over_at: Option<i64>,
+    last_probe: Option<i64>,
+}
+
+// Decides whether trades go through Jito bundles or plain RPC submission.
+// After `threshold` failed bundles within `window`, submissions fall back to
+// RPC; every `probe_interval` one trade is sent through Jito again to check
+// whether it has recovered.
+#[derive(Debug)]
+pub struct JitoFailover {
+    threshold: u32,
+    window: Duration,
+    probe_interval: Duration,
+    state: Mutex<FailoverState>,
+    clock: Arc<dyn Clock>,
+}
+
+impl JitoFailover {
+    pub fn new(threshold: u32, window_secs: u64, probe_interval_secs: u64, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            window: Duration::from_secs(window_secs),
+            probe_interval: Duration::from_secs(probe_interval_secs),
+            state: Mutex::new(FailoverState::default()),
+            clock,
+        }
+    }
+
+    pub async fn should_use_jito(&self) -> bool {
+        let mut state = self.state.lock().await;
+        let Some(failed_over_at) = state.failed_over_at else {
+            return true;
+        };
+
+        let now = self.clock.now_ms();
+        let last_attempt = state.last_probe.unwrap_or(failed_over_at);
+        if now - last_attempt >= self.probe_interval.as_millis() as i64 {
+            state.last_probe = Some(now);
+            info!("🩺 Probing Jito health with the next bundle");
+            return true;
+        }
+        false
+    }
+
+    pub async fn is_failed_over(&self) -> bool {
+        self.state.lock().await.failed_over_at.is_some()
+    }
+
+    pub async fn record_success(&self) {
+        let mut state = self.state.lock().await;
+        if state.failed_over_at.is_some() {
+            info!("✅ Jito bundle landed, leaving RPC failover");
+        }
+        *state = FailoverState::default();
+    }
+
+    pub async fn record_failure(&self) {
+        let mut state = self.state.lock().await;
+        let now = self.clock.now_ms();
+        state.recent_failures.push_back(now);
+        while let Some(oldest) = state.recent_failures.front() {
+            if now - oldest > self.window.as_millis() as i64 {
+                state.recent_failures.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if state.failed_over_at.is_none() && state.recent_failures.len() as u32 >= self.threshold {
+            warn!("⚠️ {} Jito bundles failed within {:?}, failing over to RPC submission",
+                  state.recent_failures.len(), self.window);
+            state.failed_over_at = Some(now);
+            state.last_probe = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    #[tokio::test]
+    async fn fails_over_after_repeated_failures_and_recovers_on_success() {
+        let failover = JitoFailover::new(2, 60, 3_600, Arc::new(ManualClock::new(0)));
+        failover.record_failure().await;
+        assert!(failover.should_use_jito().await);
+
+        failover.record_failure().await;
+        assert!(failover.is_failed_over().await);
+        assert!(!failover.should_use_jito().await);
+
+        failover.record_success().await;
+        assert!(!failover.is_failed_over().await);
+        assert!(failover.should_use_jito().await);
+    }
+
+    #[tokio::test]
+    async fn probes_jito_once_per_interval_while_failed_over() {
+        let clock = Arc::new(ManualClock::new(0));
+        let failover = JitoFailover::new(1, 60, 30, clock.clone());
+        failover.record_failure().await;
+        assert!(failover.is_failed_over().await);
+        assert!(!failover.should_use_jito().await);
+
+        clock.advance(30_000);
+        assert!(failover.should_use_jito().await);
+        assert!(!failover.should_use_jito().await);
+        assert!(failover.is_failed_over().await);
+    }
+}
@@ -1,28 +1,113 @@
-use crate::types::{JupiterQuote, JupiterSwap, SwapRequest, SwapResponse};
+use crate::config::secret_header_map;
+use crate::amounts::to_raw_amount;
+use crate::clock::{Clock, SystemClock};
+use crate::rate_limit::{RateLimitGovernor, RateLimitStatus, RequestPriority};
+use crate::transaction_simulator::{ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID};
+use crate::types::{
+    ApiHealth, ArbitrageError, DynamicSlippageConfig, GasCost, JupiterApiType, JupiterConfig, JupiterQuote, JupiterSwap,
+    PlatformFeeConfig, PriceConfidence, PriceWithConfidence, RoutePlan, SlotDriftAction, SwapMode, SwapRequest, SwapResponse,
+    TransactionVersion, WSOL_MINT,
+};
 use anyhow::Result;
+use futures_util::future::join_all;
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{DeserializeOwned, IgnoredAny, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use base64::Engine;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    transaction::VersionedTransaction,
+};
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+const REQUEST_ID_HEADER: &str = "x-request-id";
+const JUPITER_PROGRAM_ID: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+// wSOL wrapping transfers lamports and creates accounts through the system program
+const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+const ALLOWED_SWAP_PROGRAMS: [&str; 5] = [
+    JUPITER_PROGRAM_ID,
+    TOKEN_PROGRAM_ID,
+    ASSOCIATED_TOKEN_PROGRAM_ID,
+    COMPUTE_BUDGET_PROGRAM_ID,
+    SYSTEM_PROGRAM_ID,
+];
+const RETRY_BASE_DELAY_MS: u64 = 200;
+const DEFAULT_MAX_ACCOUNTS: u8 = 64;
+const DEFAULT_MAX_RESPONSE_BYTES: usize = 4 * 1024 * 1024;
+const DEFAULT_MAX_TOKEN_LIST_BYTES: usize = 64 * 1024 * 1024;
+const DEFAULT_PRICE_IMPACT_CLAMP_PCT: f64 = 100.0;
+const DEFAULT_MAX_ROUTE_LEGS: usize = 16;
+const DEFAULT_PRICE_BATCH_SIZE: usize = 100;
+const DEFAULT_TRACE_SLOW_REQUEST_MS: u64 = 1000;
+const DEFAULT_TOKEN_LIST_TTL_MS: u64 = 300_000;
+
+type TokenList = Arc<HashMap<String, TokenInfo>>;
+// Slots a quote may drift from the pinned context before we warn
+const CONTEXT_SLOT_TOLERANCE: u64 = 2;
 
 #[derive(Debug, Clone)]
 pub struct JupiterClient {
     client: Client,
     base_url: String,
-    api_key: Option<String>,
+    token_cache_path: Option<PathBuf>,
+    // The token list as last loaded, and when (unix ms); shared by clones of the client
+    token_list: Arc<RwLock<Option<(i64, TokenList)>>>,
+    token_list_ttl: Duration,
+    send_correlation_id: bool,
+    platform_fee: Option<PlatformFeeConfig>,
+    retry_attempts: u32,
+    requote_on_swap_failure: bool,
+    rate_limit: Option<Arc<RateLimitGovernor>>,
+    // Stamps quotes and ages the token list, so both agree with the engine's clock
+    clock: Arc<dyn Clock>,
+    max_accounts: u8,
+    adaptive_max_accounts_start: Option<u8>,
+    max_response_bytes: usize,
+    max_token_list_bytes: usize,
+    price_impact_clamp_pct: f64,
+    max_route_legs: usize,
+    price_batch_size: usize,
+    transaction_version: TransactionVersion,
+    trace_sample_rate: f64,
+    trace_slow_request_ms: u64,
+    max_swap_slot_drift: Option<u64>,
+    slot_drift_action: SlotDriftAction,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JupiterQuoteRequest {
     pub input_mint: String,
     pub output_mint: String,
     pub amount: u64,
     pub slippage_bps: u16,
     pub swap_mode: Option<String>,
+    #[serde(serialize_with = "comma_separated")]
     pub dexes: Option<Vec<String>>,
+    #[serde(serialize_with = "comma_separated")]
     pub exclude_dexes: Option<Vec<String>>,
     pub platform_fee_bps: Option<u16>,
+    // None uses the client's configured cap (adaptive if enabled)
     pub max_accounts: Option<u8>,
+    // Pins the quote to a slot, for reproducible simulation and replay
+    pub context_slot: Option<u64>,
+}
+
+// Prices that were fetched, plus the ids whose batch failed
+#[derive(Debug, Clone, Default)]
+pub struct PriceFetch {
+    pub prices: HashMap<String, f64>,
+    pub failed_ids: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -47,24 +132,6 @@ pub struct PlatformFee {
     pub fee_bps: u16,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct RoutePlan {
-    pub swap_info: SwapInfo,
-    pub percent: u8,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SwapInfo {
-    pub amm_key: String,
-    pub label: String,
-    pub input_mint: String,
-    pub in_amount: String,
-    pub output_mint: String,
-    pub out_amount: String,
-    pub fee_amount: String,
-    pub fee_mint: String,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JupiterSwapRequest {
     pub quote_response: JupiterQuoteResponse,
@@ -77,6 +144,7 @@ pub struct JupiterSwapRequest {
     pub tracking_account: Option<String>,
     pub compute_unit_price_micro_lamports: Option<u64>,
     pub as_versioned_transaction: Option<bool>,
+    pub dynamic_slippage: Option<DynamicSlippageConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -86,105 +154,608 @@ pub struct JupiterSwapResponse {
     pub prioritization_fee_lamports: u64,
     pub compute_unit_limit: u32,
     pub prioritization_fee_lamports_per_cu: u64,
+    pub dynamic_slippage_report: Option<DynamicSlippageReport>,
+    pub simulation_slot: Option<u64>,
+}
+
+// A swap as loose instructions, so several legs can share one transaction
+#[derive(Debug, Clone)]
+pub struct SwapInstructions {
+    // Compute budget, setup, swap and cleanup, in execution order
+    pub instructions: Vec<Instruction>,
+    pub address_lookup_tables: Vec<Pubkey>,
+    pub request_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SwapInstructionsResponse {
+    #[serde(default)]
+    compute_budget_instructions: Vec<JupiterInstruction>,
+    #[serde(default)]
+    setup_instructions: Vec<JupiterInstruction>,
+    swap_instruction: JupiterInstruction,
+    cleanup_instruction: Option<JupiterInstruction>,
+    #[serde(default)]
+    address_lookup_table_addresses: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterInstruction {
+    program_id: String,
+    accounts: Vec<JupiterAccountMeta>,
+    // base64
+    data: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterAccountMeta {
+    pubkey: String,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+impl JupiterInstruction {
+    fn into_instruction(self) -> Result<Instruction> {
+        let accounts = self.accounts.into_iter()
+            .map(|account| {
+                let pubkey = Pubkey::from_str(&account.pubkey)?;
+                Ok(if account.is_writable {
+                    AccountMeta::new(pubkey, account.is_signer)
+                } else {
+                    AccountMeta::new_readonly(pubkey, account.is_signer)
+                })
+            })
+            .collect::<Result<Vec<AccountMeta>>>()?;
+        Ok(Instruction {
+            program_id: Pubkey::from_str(&self.program_id)?,
+            accounts,
+            data: base64::engine::general_purpose::STANDARD.decode(&self.data)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DynamicSlippageReport {
+    pub slippage_bps: u16,
+    pub other_amount: Option<u64>,
+    pub simulated_incurred_slippage_bps: Option<i32>,
+    pub amplification_ratio: Option<String>,
+    pub category_name: Option<String>,
+    pub heuristic_max_slippage_bps: Option<u16>,
 }
 
 impl JupiterClient {
     pub fn new(base_url: String, api_key: Option<String>) -> Self {
+        let client = Self::client_builder(&api_key, reqwest::header::HeaderMap::new())
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .expect("Failed to create HTTP client");
+        Self::with_client(client, base_url)
+    }
+
+    // Builds a fully configured client: HTTP tuning, proxy and every
+    // optional behaviour from the [jupiter] section
+    pub fn new_with_config(config: &JupiterConfig) -> Result<Self> {
+        let http = &config.http;
+        let custom_headers = secret_header_map(&config.custom_headers)
+            .map_err(|e| anyhow::anyhow!("Jupiter custom {}", e))?;
+        let mut builder = Self::client_builder(&config.api_key, custom_headers)
+            .timeout(std::time::Duration::from_millis(config.timeout_ms))
+            .pool_max_idle_per_host(http.pool_max_idle_per_host)
+            .tcp_keepalive(http.tcp_keepalive_secs.map(std::time::Duration::from_secs))
+            .pool_idle_timeout(http.pool_idle_timeout_secs.map(std::time::Duration::from_secs))
+            .gzip(http.compression)
+            .brotli(http.compression);
+        if let Some(proxy_url) = &http.proxy_url {
+            let proxy = reqwest::Proxy::all(proxy_url)
+                .map_err(|e| anyhow::anyhow!("Invalid Jupiter proxy URL '{}': {}", proxy_url, e))?;
+            builder = builder.proxy(proxy);
+        }
+        let client = builder.build()?;
+
+        let mut jupiter_client = Self::with_client(client, config.base_url())
+            .with_correlation_id(config.send_correlation_id)
+            .with_platform_fee(config.platform_fee.clone())
+            .with_retries(config.retry_attempts)
+            .with_requote_on_swap_failure(config.requote_on_swap_failure)
+            .with_max_accounts(config.max_accounts, config.adaptive_max_accounts_start)
+            .with_response_limits(http.max_response_bytes, http.max_token_list_bytes)
+            .with_price_impact_clamp(config.price_impact_clamp_pct)
+            .with_max_route_legs(config.max_route_legs)
+            .with_price_batch_size(config.price_batch_size)
+            .with_transaction_version(config.transaction_version)
+            .with_quote_tracing(config.trace_sample_rate, config.trace_slow_request_ms)
+            .with_slot_drift_check(config.max_swap_slot_drift, config.slot_drift_action)
+            .with_token_list_ttl(config.token_list_ttl_ms);
+        if let Some(max_wait_ms) = config.rate_limit_max_wait_ms {
+            jupiter_client = jupiter_client.with_rate_limit_gating(max_wait_ms);
+        }
+        if let Some(path) = &config.token_cache_path {
+            jupiter_client = jupiter_client.with_token_cache(path);
+        }
+        Ok(jupiter_client)
+    }
+
+    // Custom headers go in last, so they can replace the Bearer scheme
+    fn client_builder(api_key: &Option<String>, custom_headers: reqwest::header::HeaderMap) -> reqwest::ClientBuilder {
         let mut headers = reqwest::header::HeaderMap::new();
-        if let Some(key) = &api_key {
+        if let Some(key) = api_key {
             headers.insert(
                 "Authorization",
                 format!("Bearer {}", key).parse().unwrap(),
             );
         }
         headers.insert("Content-Type", "application/json".parse().unwrap());
+        headers.extend(custom_headers);
+        Client::builder().default_headers(headers)
+    }
 
-        let client = Client::builder()
-            .default_headers(headers)
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .expect("Failed to create HTTP client");
 
+    // The API key, if any, is already in the client's default headers
+    fn with_client(client: Client, base_url: String) -> Self {
         Self {
             client,
             base_url,
-            api_key,
+            token_cache_path: None,
+            token_list: Arc::new(RwLock::new(None)),
+            token_list_ttl: Duration::from_millis(DEFAULT_TOKEN_LIST_TTL_MS),
+            send_correlation_id: true,
+            platform_fee: None,
+            retry_attempts: 0,
+            requote_on_swap_failure: false,
+            rate_limit: None,
+            clock: Arc::new(SystemClock),
+            max_accounts: DEFAULT_MAX_ACCOUNTS,
+            adaptive_max_accounts_start: None,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            max_token_list_bytes: DEFAULT_MAX_TOKEN_LIST_BYTES,
+            price_impact_clamp_pct: DEFAULT_PRICE_IMPACT_CLAMP_PCT,
+            max_route_legs: DEFAULT_MAX_ROUTE_LEGS,
+            price_batch_size: DEFAULT_PRICE_BATCH_SIZE,
+            transaction_version: TransactionVersion::Versioned,
+            trace_sample_rate: 0.0,
+            trace_slow_request_ms: DEFAULT_TRACE_SLOW_REQUEST_MS,
+            max_swap_slot_drift: None,
+            slot_drift_action: SlotDriftAction::Requote,
+        }
+    }
+
+    pub fn with_response_limits(mut self, max_response_bytes: usize, max_token_list_bytes: usize) -> Self {
+        self.max_response_bytes = max_response_bytes;
+        self.max_token_list_bytes = max_token_list_bytes;
+        self
+    }
+
+    pub fn with_price_impact_clamp(mut self, clamp_pct: f64) -> Self {
+        self.price_impact_clamp_pct = clamp_pct;
+        self
+    }
+
+    pub fn with_max_route_legs(mut self, max_route_legs: usize) -> Self {
+        self.max_route_legs = max_route_legs.max(1);
+        self
+    }
+
+    pub fn with_price_batch_size(mut self, batch_size: usize) -> Self {
+        self.price_batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_transaction_version(mut self, version: TransactionVersion) -> Self {
+        self.transaction_version = version;
+        self
+    }
+
+    pub fn with_slot_drift_check(mut self, max_drift: Option<u64>, action: SlotDriftAction) -> Self {
+        self.max_swap_slot_drift = max_drift;
+        self.slot_drift_action = action;
+        self
+    }
+
+    pub fn with_quote_tracing(mut self, sample_rate: f64, slow_request_ms: u64) -> Self {
+        self.trace_sample_rate = sample_rate.clamp(0.0, 1.0);
+        self.trace_slow_request_ms = slow_request_ms;
+        self
+    }
+
+    pub fn with_token_cache(mut self, path: impl Into<PathBuf>) -> Self {
+        self.token_cache_path = Some(path.into());
+        self
+    }
+
+    pub fn with_token_list_ttl(mut self, ttl_ms: u64) -> Self {
+        self.token_list_ttl = Duration::from_millis(ttl_ms);
+        self
+    }
+
+    pub fn with_correlation_id(mut self, enabled: bool) -> Self {
+        self.send_correlation_id = enabled;
+        self
+    }
+
+    pub fn with_platform_fee(mut self, platform_fee: Option<PlatformFeeConfig>) -> Self {
+        self.platform_fee = platform_fee;
+        self
+    }
+
+    // Extra attempts for transient failures (network errors, 429, 5xx)
+    pub fn with_retries(mut self, retry_attempts: u32) -> Self {
+        self.retry_attempts = retry_attempts;
+        self
+    }
+
+    // Fetch a fresh quote and rebuild once if the swap build keeps failing
+    pub fn with_requote_on_swap_failure(mut self, enabled: bool) -> Self {
+        self.requote_on_swap_failure = enabled;
+        self
+    }
+
+    // Hold normal requests while the reported quota is exhausted, waiting at
+    // most `max_wait_ms` for the reset
+    pub fn with_rate_limit_gating(mut self, max_wait_ms: u64) -> Self {
+        self.rate_limit = Some(Arc::new(RateLimitGovernor::new(max_wait_ms, self.clock.clone())));
+        self
+    }
+
+    // Replaces the system clock, for the quota too when rate limits are gated
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.rate_limit = self.rate_limit.map(|governor| Arc::new(governor.with_clock(clock.clone())));
+        self.clock = clock;
+        self
+    }
+
+    // Quotes start at `adaptive_start` accounts (if set) and retry at
+    // `max_accounts` only when no route fits the lower cap
+    pub fn with_max_accounts(mut self, max_accounts: u8, adaptive_start: Option<u8>) -> Self {
+        self.max_accounts = max_accounts;
+        self.adaptive_max_accounts_start = adaptive_start.filter(|start| *start < max_accounts);
+        self
+    }
+
+    pub fn rate_limit_status(&self) -> Option<RateLimitStatus> {
+        self.rate_limit.as_ref().and_then(|governor| governor.status())
+    }
+
+    fn new_correlation_id(&self) -> Option<String> {
+        self.send_correlation_id.then(|| Uuid::new_v4().to_string())
+    }
+
+    // Inverse of `net_out_amount`: the outAmount the swap endpoint expects back
+    fn quoted_out_amount(&self, quote: &JupiterQuote) -> u64 {
+        match &self.platform_fee {
+            Some(fee) if !fee.deducted_from_out_amount => quote.out_amount + quote.platform_fee_amount,
+            _ => quote.out_amount,
         }
     }
 
-    pub async fn get_quote(&self, request: JupiterQuoteRequest) -> Result<JupiterQuote> {
+    pub fn new_public() -> Self {
+        Self::new(JupiterApiType::Public.default_base_url().to_string(), None)
+    }
+
+    pub fn new_lite() -> Self {
+        Self::new(JupiterApiType::Lite.default_base_url().to_string(), None)
+    }
+
+    pub fn new_pro(api_key: String) -> Self {
+        Self::new(JupiterApiType::Pro.default_base_url().to_string(), Some(api_key))
+    }
+
+    pub fn new_ultra(api_key: String) -> Self {
+        Self::new(JupiterApiType::Ultra.default_base_url().to_string(), Some(api_key))
+    }
+
+    // Builds a client for the given tier, honouring any base URL override in config
+    pub fn new_for_tier(api_type: JupiterApiType, config: &JupiterConfig) -> Result<Self> {
+        Self::new_with_config(&JupiterConfig { api_type: Some(api_type), ..config.clone() })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub async fn get_quote(&self, mut request: JupiterQuoteRequest) -> Result<JupiterQuote> {
+        validate_swap_params(&request.input_mint, &request.output_mint, request.amount)?;
+        if request.platform_fee_bps.is_none() {
+            request.platform_fee_bps = self.platform_fee.as_ref().map(|fee| fee.fee_bps);
+        }
+        if request.max_accounts.is_some() {
+            return self.fetch_quote(request).await;
+        }
+
+        if let Some(start) = self.adaptive_max_accounts_start {
+            let mut limited = request.clone();
+            limited.max_accounts = Some(start);
+            match self.fetch_quote(limited).await {
+                Err(e) if is_no_route_error(&e) => {
+                    debug!("🧮 No route within {} accounts, retrying with {}", start, self.max_accounts);
+                }
+                result => return result,
+            }
+        }
+
+        request.max_accounts = Some(self.max_accounts);
+        self.fetch_quote(request).await
+    }
+
+    async fn fetch_quote(&self, request: JupiterQuoteRequest) -> Result<JupiterQuote> {
+        let started = std::time::Instant::now();
+        let result = self.request_quote(&request).await;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+
+        match &result {
+            Err(e) => warn!("🔬 Quote failed after {}ms: {:?} -> {}", elapsed_ms, request, e),
+            Ok(quote) if should_trace_quote(elapsed_ms, self.trace_slow_request_ms, self.trace_sample_rate, rand::random()) => {
+                info!(request_id = %quote.request_id, "🔬 Quote took {}ms: {:?} -> {:?}", elapsed_ms, request, quote);
+            }
+            Ok(_) => {}
+        }
+        result
+    }
+
+    async fn request_quote(&self, request: &JupiterQuoteRequest) -> Result<JupiterQuote> {
         debug!("🔍 Getting Jupiter quote for {} -> {}", request.input_mint, request.output_mint);
         
         let url = format!("{}/quote", self.base_url);
-        let response = self.client
-            .get(&url)
-            .query(&request)
-            .send()
-            .await?;
+        let (response, request_id) = self.send_with_retry("quote", RequestPriority::Normal, || {
+            self.client.get(&url).query(request)
+        }).await?;
 
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            error!("❌ Jupiter quote request failed: {}", error_text);
-            return Err(anyhow::anyhow!("Jupiter quote request failed: {}", error_text));
+        let body = read_body(response, self.max_response_bytes).await?;
+        // Counted without building the legs, so a pathological route costs no allocations
+        let legs = serde_json::from_slice::<RoutePlanLength>(&body)?.route_plan;
+        if legs > self.max_route_legs {
+            warn!(request_id = %request_id, "🧵 Rejecting quote {} -> {}: route plan has {} legs, over the {} limit",
+                  request.input_mint, request.output_mint, legs, self.max_route_legs);
+            return Err(anyhow::anyhow!("Quote route plan has {} legs, over the {} leg limit", legs, self.max_route_legs));
         }
-
-        let quote_response: JupiterQuoteResponse = response.json().await?;
+        let quote_response: JupiterQuoteResponse = serde_json::from_slice(&body)?;
+        let platform_fee_amount = match &quote_response.platform_fee {
+            Some(fee) => fee.amount.parse()?,
+            None => 0,
+        };
+        let fee_deducted = self.platform_fee.as_ref().is_none_or(|fee| fee.deducted_from_out_amount);
         
         let quote = JupiterQuote {
             input_mint: quote_response.input_mint,
             in_amount: quote_response.in_amount.parse()?,
             output_mint: quote_response.output_mint,
-            out_amount: quote_response.out_amount.parse()?,
-            price_impact_pct: quote_response.price_impact_pct.parse()?,
+            out_amount: net_out_amount(quote_response.out_amount.parse()?, platform_fee_amount, fee_deducted),
+            platform_fee_amount,
+            swap_mode: if quote_response.swap_mode == SwapMode::ExactOut.as_str() { SwapMode::ExactOut } else { SwapMode::ExactIn },
+            other_amount_threshold: quote_response.other_amount_threshold.parse()?,
+            price_impact_pct: sanitize_price_impact(&quote_response.price_impact_pct, self.price_impact_clamp_pct)?,
             route_plan: quote_response.route_plan,
             context_slot: quote_response.context_slot,
             time_taken: quote_response.time_taken,
             slippage_bps: quote_response.slippage_bps,
+            received_at: self.clock.now_ms(),
+            request_id,
         };
 
-        debug!("✅ Jupiter quote received: {} -> {} ({} tokens)", 
+        if let Some(requested) = request.context_slot {
+            if quote.context_slot.abs_diff(requested) > CONTEXT_SLOT_TOLERANCE {
+                warn!(request_id = %quote.request_id, "⚠️ Quote for slot {} came back at slot {}",
+                      requested, quote.context_slot);
+            }
+        }
+
+        debug!(request_id = %quote.request_id, "✅ Jupiter quote received: {} -> {} ({} tokens)",
                quote.input_mint, quote.output_mint, quote.out_amount);
         
         Ok(quote)
     }
 
     pub async fn get_swap_transaction(&self, request: JupiterSwapRequest) -> Result<JupiterSwap> {
+        let quote_response = &request.quote_response;
+        validate_swap_params(&quote_response.input_mint, &quote_response.output_mint, quote_response.in_amount.parse()?)?;
         debug!("🔄 Getting Jupiter swap transaction");
         
         let url = format!("{}/swap", self.base_url);
-        let response = self.client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await?;
+        // Only built for opportunities that already passed every check
+        let (response, request_id) = self.send_with_retry("swap", RequestPriority::Critical, || {
+            self.client.post(&url).json(&request)
+        }).await?;
 
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            error!("❌ Jupiter swap request failed: {}", error_text);
-            return Err(anyhow::anyhow!("Jupiter swap request failed: {}", error_text));
-        }
+        let swap_response: JupiterSwapResponse = read_json(response, self.max_response_bytes).await?;
 
-        let swap_response: JupiterSwapResponse = response.json().await?;
+        // Prefer Jupiter's recommended slippage when dynamic slippage was requested
+        let out_amount: u64 = request.quote_response.out_amount.parse()?;
+        let (slippage_bps, other_amount_threshold) = match &swap_response.dynamic_slippage_report {
+            Some(report) => {
+                debug!("🎚️ Dynamic slippage: {} bps", report.slippage_bps);
+                let threshold = report.other_amount
+                    .unwrap_or_else(|| min_amount_out(out_amount, report.slippage_bps));
+                (report.slippage_bps, threshold)
+            }
+            None => {
+                let slippage_bps = request.quote_response.slippage_bps;
+                (slippage_bps, min_amount_out(out_amount, slippage_bps))
+            }
+        };
         
         let swap = JupiterSwap {
             swap_transaction: swap_response.swap_transaction,
             last_valid_block_height: swap_response.last_valid_block_height,
             prioritization_fee_lamports: swap_response.prioritization_fee_lamports,
             compute_unit_limit: swap_response.compute_unit_limit,
+            slippage_bps,
+            other_amount_threshold,
+            request_id,
+            simulation_slot: swap_response.simulation_slot,
         };
 
-        debug!("✅ Jupiter swap transaction received");
+        debug!(request_id = %swap.request_id, "✅ Jupiter swap transaction received");
         Ok(swap)
     }
 
+    // The swap for `quote` as instructions rather than a finished transaction.
+    // Held to the same rules as `validate_swap_transaction`: only allow-listed
+    // programs, and no signer besides the user.
+    pub async fn get_swap_instructions(&self, quote: &JupiterQuote, swap_request: &SwapRequest) -> Result<SwapInstructions> {
+        validate_swap_params(&quote.input_mint, &quote.output_mint, quote.in_amount)?;
+        debug!("🔄 Getting Jupiter swap instructions for {} -> {}", quote.input_mint, quote.output_mint);
+
+        let request = self.swap_request_for(quote, swap_request, false);
+        let url = format!("{}/swap-instructions", self.base_url);
+        let (response, request_id) = self.send_with_retry("swap-instructions", RequestPriority::Critical, || {
+            self.client.post(&url).json(&request)
+        }).await?;
+        let response: SwapInstructionsResponse = read_json(response, self.max_response_bytes).await?;
+
+        let user = Pubkey::from_str(&swap_request.user_public_key)?;
+        let instructions = response.compute_budget_instructions.into_iter()
+            .chain(response.setup_instructions)
+            .chain(std::iter::once(response.swap_instruction))
+            .chain(response.cleanup_instruction)
+            .map(JupiterInstruction::into_instruction)
+            .collect::<Result<Vec<Instruction>>>()?;
+        for instruction in &instructions {
+            if !ALLOWED_SWAP_PROGRAMS.contains(&instruction.program_id.to_string().as_str()) {
+                return Err(ArbitrageError::InvalidRequest(format!(
+                    "swap instructions call unexpected program {}", instruction.program_id
+                )).into());
+            }
+            if let Some(signer) = instruction.accounts.iter().find(|account| account.is_signer && account.pubkey != user) {
+                return Err(ArbitrageError::InvalidRequest(format!(
+                    "swap instructions require signer {}, expected only {}", signer.pubkey, user
+                )).into());
+            }
+        }
+        let address_lookup_tables = response.address_lookup_table_addresses.iter()
+            .map(|address| Pubkey::from_str(address))
+            .collect::<Result<Vec<Pubkey>, _>>()?;
+
+        debug!(request_id = %request_id, "✅ Jupiter swap instructions received");
+        Ok(SwapInstructions { instructions, address_lookup_tables, request_id })
+    }
+
+    // Sends a request built fresh for every attempt, retrying transient
+    // failures with exponential backoff. Returns the response and its request id.
+    async fn send_with_retry<F>(
+        &self,
+        operation: &str,
+        priority: RequestPriority,
+        build: F,
+    ) -> Result<(reqwest::Response, String)>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        let mut attempt = 0;
+        loop {
+            if let Some(governor) = &self.rate_limit {
+                governor.acquire(priority).await?;
+            }
+            let correlation_id = self.new_correlation_id();
+            let mut builder = build();
+            if let Some(id) = &correlation_id {
+                builder = builder.header(REQUEST_ID_HEADER, id);
+            }
+
+            let result = builder.send().await;
+            if let (Some(governor), Ok(response)) = (&self.rate_limit, &result) {
+                governor.observe(response.headers(), response.status());
+            }
+            match result {
+                Ok(response) if response.status().is_success() => {
+                    let request_id = response_request_id(&response, correlation_id);
+                    return Ok((response, request_id));
+                }
+                Ok(response) => {
+                    let retryable = is_retryable_status(response.status());
+                    let request_id = response_request_id(&response, correlation_id);
+                    let error = handle_error_response(operation, &request_id, response).await;
+                    if !retryable || attempt >= self.retry_attempts {
+                        return Err(error);
+                    }
+                    warn!(request_id = %request_id, "🔁 Retrying Jupiter {} request ({}/{}): {}",
+                          operation, attempt + 1, self.retry_attempts, error);
+                }
+                Err(e) if attempt < self.retry_attempts => {
+                    warn!("🔁 Retrying Jupiter {} request ({}/{}): {}",
+                          operation, attempt + 1, self.retry_attempts, e);
+                }
+                Err(e) => return Err(e.into()),
+            }
+
+            attempt += 1;
+            let delay = RETRY_BASE_DELAY_MS << (attempt - 1).min(5);
+            tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+        }
+    }
+
     pub async fn get_tokens(&self) -> Result<HashMap<String, TokenInfo>> {
+        Ok((*self.token_list().await?).clone())
+    }
+
+    // The token list from memory while it is younger than the TTL; after
+    // that it is revalidated against Jupiter and the disk cache
+    pub async fn token_list(&self) -> Result<TokenList> {
+        if let Some((loaded_at, tokens)) = &*self.token_list.read().await {
+            if self.clock.now_ms() - loaded_at < self.token_list_ttl.as_millis() as i64 {
+                return Ok(tokens.clone());
+            }
+        }
+
+        let mut token_list = self.token_list.write().await;
+        // Another caller may have refreshed it while this one waited for the lock
+        if let Some((loaded_at, tokens)) = &*token_list {
+            if self.clock.now_ms() - loaded_at < self.token_list_ttl.as_millis() as i64 {
+                return Ok(tokens.clone());
+            }
+        }
+        let tokens = Arc::new(self.load_tokens().await?);
+        *token_list = Some((self.clock.now_ms(), tokens.clone()));
+        Ok(tokens)
+    }
+
+    async fn load_tokens(&self) -> Result<HashMap<String, TokenInfo>> {
         debug!("🪙 Fetching Jupiter token list");
-        
+
+        let cached = self.load_token_cache();
+        match self.fetch_tokens(cached.as_ref()).await {
+            Ok(Some(fresh)) => {
+                if let Err(e) = self.store_token_cache(&fresh) {
+                    warn!("⚠️ Failed to write token list cache: {}", e);
+                }
+                debug!("✅ Fetched {} tokens from Jupiter", fresh.tokens.len());
+                Ok(fresh.tokens)
+            }
+            Ok(None) => {
+                debug!("✅ Token list unchanged, using cached copy");
+                cached
+                    .map(|cache| cache.tokens)
+                    .ok_or_else(|| anyhow::anyhow!("Token list not modified but no cache is available"))
+            }
+            Err(e) => match cached {
+                Some(cache) => {
+                    warn!("⚠️ Jupiter tokens request failed, using cached token list: {}", e);
+                    Ok(cache.tokens)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    // Returns None when the server reports the cached copy is still current
+    async fn fetch_tokens(&self, cached: Option<&TokenListCache>) -> Result<Option<TokenListCache>> {
         let url = format!("{}/tokens", self.base_url);
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
+        let mut request = self.client.get(&url);
+        if let Some(cache) = cached {
+            if let Some(etag) = &cache.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cache.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request.send().await?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
 
         if !response.status().is_success() {
             let error_text = response.text().await?;
@@ -192,14 +763,108 @@ impl JupiterClient {
             return Err(anyhow::anyhow!("Jupiter tokens request failed: {}", error_text));
         }
 
-        let tokens: HashMap<String, TokenInfo> = response.json().await?;
-        debug!("✅ Fetched {} tokens from Jupiter", tokens.len());
-        Ok(tokens)
+        let header = |name: reqwest::header::HeaderName| {
+            response.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+
+        let tokens: HashMap<String, TokenInfo> = read_json(response, self.max_token_list_bytes).await?;
+        Ok(Some(TokenListCache { etag, last_modified, tokens }))
+    }
+
+    fn load_token_cache(&self) -> Option<TokenListCache> {
+        let path = self.token_cache_path.as_ref()?;
+        let content = std::fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                warn!("⚠️ Ignoring unreadable token list cache {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    fn store_token_cache(&self, cache: &TokenListCache) -> Result<()> {
+        if let Some(path) = &self.token_cache_path {
+            std::fs::write(path, serde_json::to_vec(cache)?)?;
+        }
+        Ok(())
+    }
+
+    // Probes the price endpoint with a single mint. 503 or a maintenance
+    // message means Maintenance; failures, responses slower than
+    // `degraded_latency_ms` or an exhausted rate limit mean Degraded.
+    pub async fn get_health_status(&self, degraded_latency_ms: u64) -> ApiHealth {
+        if self.rate_limit.as_ref().is_some_and(|governor| governor.is_exhausted()) {
+            return ApiHealth::Degraded;
+        }
+
+        let url = format!("{}/price", self.base_url);
+        let started = std::time::Instant::now();
+        let response = match self.client.get(&url).query(&[("ids", WSOL_MINT)]).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                debug!("🩺 Jupiter health probe failed: {}", e);
+                return ApiHealth::Degraded;
+            }
+        };
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::SERVICE_UNAVAILABLE {
+            return ApiHealth::Maintenance;
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return if body.to_lowercase().contains("maintenance") {
+                ApiHealth::Maintenance
+            } else {
+                ApiHealth::Degraded
+            };
+        }
+        if latency_ms > degraded_latency_ms {
+            debug!("🩺 Jupiter health probe took {}ms", latency_ms);
+            return ApiHealth::Degraded;
+        }
+        ApiHealth::Healthy
     }
 
-    pub async fn get_price(&self, ids: &[String]) -> Result<HashMap<String, f64>> {
+    // Fetches prices in batches of `price_batch_size` ids, concurrently. A
+    // failing batch doesn't sink the rest: its ids are reported in
+    // `failed_ids`. Only errors when every batch failed.
+    pub async fn get_price(&self, ids: &[String]) -> Result<PriceFetch> {
         debug!("💰 Getting Jupiter prices for {} tokens", ids.len());
-        
+
+        let batches: Vec<&[String]> = ids.chunks(self.price_batch_size.max(1)).collect();
+        let results = join_all(batches.iter().map(|batch| self.fetch_price_batch(batch))).await;
+
+        let mut fetch = PriceFetch::default();
+        let mut last_error = None;
+        for (batch, result) in batches.iter().zip(results) {
+            match result {
+                Ok(prices) => fetch.prices.extend(prices),
+                Err(e) => {
+                    warn!("⚠️ Jupiter price batch of {} ids failed ({}): {}", batch.len(), e, batch.join(","));
+                    fetch.failed_ids.extend(batch.iter().cloned());
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) if fetch.prices.is_empty() => Err(e),
+            _ => {
+                debug!("✅ Fetched prices for {} tokens ({} failed)", fetch.prices.len(), fetch.failed_ids.len());
+                Ok(fetch)
+            }
+        }
+    }
+
+    async fn fetch_price_batch(&self, ids: &[String]) -> Result<HashMap<String, f64>> {
         let url = format!("{}/price", self.base_url);
         let response = self.client
             .get(&url)
@@ -213,78 +878,493 @@ impl JupiterClient {
             return Err(anyhow::anyhow!("Jupiter price request failed: {}", error_text));
         }
 
-        let prices: HashMap<String, PriceData> = response.json().await?;
+        let prices: HashMap<String, PriceData> = read_json(response, self.max_response_bytes).await?;
         let price_map: HashMap<String, f64> = prices
             .into_iter()
             .map(|(k, v)| (k, v.price))
             .collect();
+        Ok(price_map)
+    }
+
+    pub async fn get_price_v2(&self, ids: &[String]) -> Result<HashMap<String, PriceWithConfidence>> {
+        debug!("💰 Getting Jupiter v2 prices for {} tokens", ids.len());
+
+        let url = format!("{}/price/v2", self.base_url);
+        let response = self.client
+            .get(&url)
+            .query(&[("ids", ids.join(",")), ("showExtraInfo", "true".to_string())])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            error!("❌ Jupiter price v2 request failed: {}", error_text);
+            return Err(anyhow::anyhow!("Jupiter price v2 request failed: {}", error_text));
+        }
+
+        let body: PriceV2Response = read_json(response, self.max_response_bytes).await?;
+        let price_map: HashMap<String, PriceWithConfidence> = body.data
+            .into_iter()
+            .filter_map(|(mint, entry)| {
+                let entry = entry?;
+                let price = entry.price.parse().ok()?;
+                // Prices without confidence info are treated as the least reliable
+                let confidence = entry.extra_info
+                    .and_then(|info| info.confidence_level)
+                    .unwrap_or(PriceConfidence::Low);
+                Some((mint, PriceWithConfidence { price, confidence }))
+            })
+            .collect();
 
-        debug!("✅ Fetched prices for {} tokens", price_map.len());
+        debug!("✅ Fetched v2 prices for {} tokens", price_map.len());
         Ok(price_map)
     }
 
+    pub async fn get_token_decimals(&self, mint: &str) -> Result<u8> {
+        let tokens = self.token_list().await?;
+        tokens
+            .get(mint)
+            .map(|token| token.decimals)
+            .ok_or_else(|| anyhow::anyhow!("Token {} not found in Jupiter token list", mint))
+    }
+
+    pub async fn usd_to_raw(&self, mint: &str, usd: f64) -> Result<u64> {
+        if !usd.is_finite() || usd <= 0.0 {
+            return Err(anyhow::anyhow!("Invalid USD trade size: {}", usd));
+        }
+
+        let fetch = self.get_price(&[mint.to_string()]).await?;
+        let price = match fetch.prices.get(mint) {
+            Some(price) if price.is_finite() && *price > 0.0 => *price,
+            _ => return Err(anyhow::anyhow!("No USD price available for {}", mint)),
+        };
+
+        let decimals = self.get_token_decimals(mint).await?;
+        let raw = to_raw_amount(usd / price, decimals).map_err(|e| anyhow::anyhow!(
+            "USD trade size ${:.2} converts to an invalid raw amount for {}: {}",
+            usd, mint, e
+        ))?;
+        if raw == 0 {
+            return Err(anyhow::anyhow!(
+                "USD trade size ${:.2} is smaller than one raw unit of {}",
+                usd, mint
+            ));
+        }
+
+        debug!("💵 ${:.2} of {} = {} raw units (price ${}, {} decimals)",
+               usd, mint, raw, price, decimals);
+        Ok(raw)
+    }
+
+    // Renders the quote's route with token symbols, e.g. `SOL →(Orca)→ USDC →(Raydium)→ BONK`
+    pub async fn describe_route(&self, quote: &JupiterQuote) -> String {
+        let symbols = match self.token_list().await {
+            Ok(tokens) => tokens.iter().map(|(mint, token)| (mint.clone(), token.symbol.clone())).collect(),
+            Err(e) => {
+                warn!("⚠️ Token list unavailable, showing raw mints in route: {}", e);
+                HashMap::new()
+            }
+        };
+        format_route(quote, &symbols)
+    }
+
     pub async fn execute_swap(&self, swap_request: SwapRequest) -> Result<SwapResponse> {
+        validate_swap_params(&swap_request.input_mint, &swap_request.output_mint, swap_request.amount)?;
+        if let Some(account) = &swap_request.tracking_account {
+            if Pubkey::from_str(account).is_err() {
+                return Err(ArbitrageError::InvalidRequest(format!("tracking account '{}' is not a valid pubkey", account)).into());
+            }
+        }
         info!("🚀 Executing Jupiter swap: {} -> {}", 
               swap_request.input_mint, swap_request.output_mint);
 
-        // Get quote first
-        let quote_request = JupiterQuoteRequest {
+        let mut quote = self.get_quote(Self::quote_request_for(&swap_request)).await?;
+        let mut legacy = self.transaction_version == TransactionVersion::Legacy;
+        let mut swap = match self.build_swap(&mut quote, &swap_request, legacy).await {
+            Ok(swap) => swap,
+            Err(e) if self.transaction_version == TransactionVersion::Auto && is_version_incompatibility(&e.to_string()) => {
+                warn!("🔁 Versioned swap transaction unsupported ({}), retrying as legacy", e);
+                legacy = true;
+                self.build_swap(&mut quote, &swap_request, true).await?
+            }
+            Err(e) => return Err(e),
+        };
+        if let Err(e) = self.check_slot_drift(&quote, &swap) {
+            if self.slot_drift_action == SlotDriftAction::Abort {
+                return Err(e);
+            }
+            warn!("🕰️ {}, re-quoting and rebuilding", e);
+            quote = self.get_quote(Self::quote_request_for(&swap_request)).await?;
+            swap = self.get_swap_transaction(self.swap_request_for(&quote, &swap_request, legacy)).await?;
+            self.check_slot_drift(&quote, &swap)?;
+        }
+        self.validate_swap_transaction(&swap.swap_transaction, &swap_request.user_public_key)?;
+
+        let gas = GasCost {
+            signatures: 1,
+            priority_fee_lamports: swap.prioritization_fee_lamports,
+            created_token_accounts: swap_request.created_token_accounts,
+        };
+        let sol_price = match self.get_price(&[WSOL_MINT.to_string()]).await?.prices.get(WSOL_MINT) {
+            Some(price) if price.is_finite() && *price > 0.0 => *price,
+            _ => return Err(anyhow::anyhow!("No USD price for SOL to cost the swap's gas")),
+        };
+
+        Ok(SwapResponse {
+            transaction: swap.swap_transaction,
+            success: true,
+            error_message: String::new(),
+            actual_profit: 0.0, // Will be calculated after execution
+            gas_used: gas.total_usd(sol_price),
+            execution_time: 0,
+            bundle_id: String::new(),
+            quote: Some(quote),
+            swap_request_id: Some(swap.request_id),
+        })
+    }
+
+    async fn build_swap(&self, quote: &mut JupiterQuote, swap_request: &SwapRequest, legacy: bool) -> Result<JupiterSwap> {
+        match self.get_swap_transaction(self.swap_request_for(quote, swap_request, legacy)).await {
+            Ok(swap) => Ok(swap),
+            Err(e) if self.requote_on_swap_failure && !is_version_incompatibility(&e.to_string()) => {
+                // Prices may have moved while the build was failing, so quote afresh
+                warn!("🔄 Swap build failed ({}), re-quoting once before giving up", e);
+                *quote = self.get_quote(Self::quote_request_for(swap_request)).await?;
+                self.get_swap_transaction(self.swap_request_for(quote, swap_request, legacy)).await
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    // A swap simulated well past its quote's slot was priced against a market
+    // the quote never saw, so its economics can't be trusted
+    fn check_slot_drift(&self, quote: &JupiterQuote, swap: &JupiterSwap) -> Result<()> {
+        let (Some(max_drift), Some(simulation_slot)) = (self.max_swap_slot_drift, swap.simulation_slot) else {
+            return Ok(());
+        };
+        let drift = slot_drift(quote.context_slot, simulation_slot);
+        if drift > max_drift {
+            return Err(ArbitrageError::SimulationMismatch(format!(
+                "swap built at slot {} is {} slots past its quote's slot {} (max {})",
+                simulation_slot, drift, quote.context_slot, max_drift
+            )).into());
+        }
+        Ok(())
+    }
+
+    // Refuses a transaction that needs any signer besides our wallet or calls a
+    // program outside the swap allow-list, so a compromised or misconfigured
+    // endpoint can't get us to sign something else
+    pub fn validate_swap_transaction(&self, tx_base64: &str, expected_signer: &str) -> Result<()> {
+        let bytes = base64::engine::general_purpose::STANDARD.decode(tx_base64)
+            .map_err(|e| anyhow::anyhow!("Swap transaction is not valid base64: {}", e))?;
+        let transaction: VersionedTransaction = bincode::deserialize(&bytes)
+            .map_err(|e| anyhow::anyhow!("Swap transaction could not be decoded: {}", e))?;
+
+        let message = &transaction.message;
+        let account_keys = message.static_account_keys();
+        let signers = message.header().num_required_signatures as usize;
+        let expected_signer = Pubkey::from_str(expected_signer)?;
+        if signers != 1 || account_keys.first() != Some(&expected_signer) {
+            let required: Vec<String> = account_keys.iter().take(signers).map(|key| key.to_string()).collect();
+            return Err(ArbitrageError::InvalidRequest(format!(
+                "swap transaction requires signers [{}], expected only {}",
+                required.join(", "), expected_signer
+            )).into());
+        }
+
+        for instruction in message.instructions() {
+            // Program ids can't come from lookup tables, so they are always static keys
+            let program_id = account_keys
+                .get(instruction.program_id_index as usize)
+                .ok_or_else(|| anyhow::anyhow!("Swap transaction references a missing program account"))?;
+            if !ALLOWED_SWAP_PROGRAMS.contains(&program_id.to_string().as_str()) {
+                return Err(ArbitrageError::InvalidRequest(format!(
+                    "swap transaction calls unexpected program {}", program_id
+                )).into());
+            }
+        }
+        Ok(())
+    }
+
+    fn quote_request_for(swap_request: &SwapRequest) -> JupiterQuoteRequest {
+        JupiterQuoteRequest {
             input_mint: swap_request.input_mint.clone(),
             output_mint: swap_request.output_mint.clone(),
             amount: swap_request.amount,
             slippage_bps: (swap_request.slippage * 100.0) as u16,
             swap_mode: Some("ExactIn".to_string()),
-            dexes: swap_request.allowed_dexes,
-            exclude_dexes: swap_request.excluded_dexes,
+            dexes: swap_request.allowed_dexes.clone(),
+            exclude_dexes: swap_request.excluded_dexes.clone(),
             platform_fee_bps: None,
-            max_accounts: Some(64),
-        };
-
-        let quote = self.get_quote(quote_request).await?;
+            max_accounts: None,
+            context_slot: None,
+        }
+    }
 
-        // Create swap transaction
-        let swap_request_jupiter = JupiterSwapRequest {
+    // Exactly one of as_legacy_transaction / as_versioned_transaction is set
+    fn swap_request_for(&self, quote: &JupiterQuote, swap_request: &SwapRequest, legacy: bool) -> JupiterSwapRequest {
+        JupiterSwapRequest {
             quote_response: JupiterQuoteResponse {
                 input_mint: quote.input_mint.clone(),
                 in_amount: quote.in_amount.to_string(),
                 output_mint: quote.output_mint.clone(),
-                out_amount: quote.out_amount.to_string(),
-                other_amount_threshold: "0".to_string(),
-                swap_mode: "ExactIn".to_string(),
+                out_amount: self.quoted_out_amount(quote).to_string(),
+                other_amount_threshold: quote.other_amount_threshold.to_string(),
+                swap_mode: quote.swap_mode.as_str().to_string(),
                 slippage_bps: quote.slippage_bps,
-                platform_fee: None,
+                platform_fee: self.platform_fee.as_ref().map(|fee| PlatformFee {
+                    amount: quote.platform_fee_amount.to_string(),
+                    fee_bps: fee.fee_bps,
+                }),
                 price_impact_pct: quote.price_impact_pct.to_string(),
                 route_plan: quote.route_plan.clone(),
                 context_slot: quote.context_slot,
                 time_taken: quote.time_taken,
             },
-            user_public_key: swap_request.user_public_key,
+            user_public_key: swap_request.user_public_key.clone(),
             dynamic_compute_unit_limit: Some(true),
-            prioritization_fee_lamports: Some(swap_request.priority_fee),
-            as_legacy_transaction: Some(false),
+            prioritization_fee_lamports: match swap_request.compute_unit_price_micro_lamports {
+                Some(_) => None,
+                None => Some(swap_request.priority_fee),
+            },
+            as_legacy_transaction: legacy.then_some(true),
             use_shared_accounts: Some(true),
-            fee_account: None,
-            tracking_account: None,
-            compute_unit_price_micro_lamports: None,
-            as_versioned_transaction: Some(true),
-        };
+            fee_account: self.platform_fee.as_ref().map(|fee| fee.fee_account.clone()),
+            tracking_account: swap_request.tracking_account.clone(),
+            compute_unit_price_micro_lamports: swap_request.compute_unit_price_micro_lamports,
+            as_versioned_transaction: (!legacy).then_some(true),
+            dynamic_slippage: swap_request.dynamic_slippage.clone(),
+        }
+    }
+}
 
-        let swap = self.get_swap_transaction(swap_request_jupiter).await?;
+// Slots the swap was built past the quote; a swap at or before the quote's
+// slot hasn't drifted
+pub fn slot_drift(quote_slot: u64, swap_slot: u64) -> u64 {
+    swap_slot.saturating_sub(quote_slot)
+}
 
-        Ok(SwapResponse {
-            transaction: swap.swap_transaction,
-            success: true,
-            error_message: String::new(),
-            actual_profit: 0.0, // Will be calculated after execution
-            gas_used: swap.prioritization_fee_lamports as f64 / 1_000_000_000.0, // Convert lamports to SOL
-            execution_time: 0,
-            bundle_id: String::new(),
-            quote: Some(quote),
-        })
+// Slow quotes are always traced; the rest only when `roll` (uniform in [0, 1))
+// falls within the sample rate
+pub fn should_trace_quote(elapsed_ms: u64, slow_request_ms: u64, sample_rate: f64, roll: f64) -> bool {
+    elapsed_ms > slow_request_ms || roll < sample_rate
+}
+
+// Errors that mean the versioned format itself was refused (by Jupiter, the
+// RPC or the wallet), as opposed to a failure a legacy build would share
+pub fn is_version_incompatibility(error: &str) -> bool {
+    let error = error.to_lowercase();
+    ["transaction version", "unsupported version", "versioned transaction", "address lookup table"]
+        .iter()
+        .any(|marker| error.contains(marker))
+}
+
+// (input mint, output mint, [(AMM label, percent)])
+type RouteHop<'a> = (&'a str, &'a str, Vec<(&'a str, u8)>);
+
+// Consecutive route steps between the same two mints are one hop split across
+// AMMs, shown as `(Orca 60% | Raydium 40%)`
+pub fn format_route(quote: &JupiterQuote, symbols: &HashMap<String, String>) -> String {
+    let symbol = |mint: &str| match symbols.get(mint) {
+        Some(symbol) => symbol.clone(),
+        None => format!("{}…", &mint[..mint.len().min(4)]),
+    };
+
+    let mut hops: Vec<RouteHop> = Vec::new();
+    for step in &quote.route_plan {
+        let info = &step.swap_info;
+        match hops.last_mut() {
+            Some((input, output, venues)) if *input == info.input_mint && *output == info.output_mint => {
+                venues.push((info.label.as_str(), step.percent));
+            }
+            _ => hops.push((
+                info.input_mint.as_str(),
+                info.output_mint.as_str(),
+                vec![(info.label.as_str(), step.percent)],
+            )),
+        }
+    }
+
+    let Some((first_input, _, _)) = hops.first() else {
+        return format!("{} → {} (no route)", symbol(&quote.input_mint), symbol(&quote.output_mint));
+    };
+
+    let mut description = symbol(first_input);
+    for (_, output, venues) in &hops {
+        let venues: Vec<String> = venues.iter()
+            .map(|(label, percent)| if *percent < 100 {
+                format!("{} {}%", label, percent)
+            } else {
+                label.to_string()
+            })
+            .collect();
+        description.push_str(&format!(" →({})→ {}", venues.join(" | "), symbol(output)));
+    }
+    description
+}
+
+// The amount that reaches our wallet. Depending on the endpoint, Jupiter quotes
+// outAmount either after the platform fee or before it.
+pub fn net_out_amount(quoted_out_amount: u64, platform_fee_amount: u64, fee_already_deducted: bool) -> u64 {
+    if fee_already_deducted {
+        quoted_out_amount
+    } else {
+        quoted_out_amount.saturating_sub(platform_fee_amount)
+    }
+}
+
+// Jupiter occasionally quotes odd impacts. Negative values are rounding in
+// our favour and count as 0; values past `clamp_pct` are clamped so they
+// still fail the max impact gate without skewing averages; anything that
+// isn't a finite number is an error.
+pub fn sanitize_price_impact(raw: &str, clamp_pct: f64) -> Result<f64> {
+    let impact: f64 = raw.trim().parse()
+        .map_err(|_| anyhow::anyhow!("Jupiter returned an unparseable price impact '{}'", raw))?;
+    if !impact.is_finite() {
+        return Err(anyhow::anyhow!("Jupiter returned a non-finite price impact '{}'", raw));
+    }
+
+    if impact < 0.0 {
+        debug!("📐 Treating negative price impact {} as 0", impact);
+        Ok(0.0)
+    } else if impact > clamp_pct {
+        warn!("📐 Clamping implausible price impact {}% to {}%", impact, clamp_pct);
+        Ok(clamp_pct)
+    } else {
+        Ok(impact)
+    }
+}
+
+// Cheap checks that stop requests Jupiter would reject or answer with a no-op
+pub fn validate_swap_params(input_mint: &str, output_mint: &str, amount: u64) -> Result<()> {
+    for (side, mint) in [("input", input_mint), ("output", output_mint)] {
+        // Pubkey parsing checks both the base58 alphabet and the 32-byte length
+        if Pubkey::from_str(mint).is_err() {
+            return Err(ArbitrageError::InvalidRequest(format!("{} mint '{}' is not a valid pubkey", side, mint)).into());
+        }
+    }
+    if input_mint == output_mint {
+        return Err(ArbitrageError::InvalidRequest(format!("input and output mint are both {}", input_mint)).into());
+    }
+    if amount == 0 {
+        return Err(ArbitrageError::InvalidRequest("amount must be greater than zero".to_string()).into());
+    }
+    Ok(())
+}
+
+pub fn is_no_route_error(error: &anyhow::Error) -> bool {
+    let message = error.to_string().to_lowercase();
+    message.contains("no route") || message.contains("could not find any route") || message.contains("no_routes_found")
+}
+
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+#[derive(Debug, Deserialize)]
+struct JupiterErrorBody {
+    error: String,
+}
+
+// Turns a failed response into an error carrying the status, Jupiter's error
+// message when the body has one, and the request id for support
+async fn handle_error_response(operation: &str, request_id: &str, response: reqwest::Response) -> anyhow::Error {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<JupiterErrorBody>(&body)
+        .map(|parsed| parsed.error)
+        .unwrap_or(body);
+
+    error!(request_id = %request_id, "❌ Jupiter {} request failed with {}: {}", operation, status, message);
+    anyhow::anyhow!("Jupiter {} request {} failed with {}: {}", operation, request_id, status, message)
+}
+
+// Reads and parses a JSON body, aborting as soon as it grows past `limit`
+// bytes so a misbehaving endpoint cannot exhaust memory
+async fn read_json<T: DeserializeOwned>(response: reqwest::Response, limit: usize) -> Result<T> {
+    Ok(serde_json::from_slice(&read_body(response, limit).await?)?)
+}
+
+async fn read_body(mut response: reqwest::Response, limit: usize) -> Result<Vec<u8>> {
+    let url = response.url().clone();
+    if let Some(length) = response.content_length() {
+        if length > limit as u64 {
+            return Err(anyhow::anyhow!("Response from {} is {} bytes, over the {} byte limit", url, length, limit));
+        }
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > limit {
+            return Err(anyhow::anyhow!("Response from {} exceeded the {} byte limit, aborted", url, limit));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+// A quote response reduced to its route plan's length; every other field is skipped
+#[derive(Deserialize)]
+struct RoutePlanLength {
+    // Missing is left for the full parse to report
+    #[serde(default, deserialize_with = "count_elements")]
+    route_plan: usize,
+}
+
+fn count_elements<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<usize, D::Error> {
+    struct ElementCounter;
+
+    impl<'de> Visitor<'de> for ElementCounter {
+        type Value = usize;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a sequence")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<usize, A::Error> {
+            let mut count = 0;
+            while seq.next_element::<IgnoredAny>()?.is_some() {
+                count += 1;
+            }
+            Ok(count)
+        }
+    }
+
+    deserializer.deserialize_seq(ElementCounter)
+}
+
+// A query string can't carry a sequence: Jupiter takes DEX filters as one
+// comma-separated value, and an empty filter is left out
+fn comma_separated<S: Serializer>(values: &Option<Vec<String>>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    match values {
+        Some(values) if !values.is_empty() => serializer.serialize_some(&values.join(",")),
+        _ => serializer.serialize_none(),
     }
 }
 
+// Prefers the id Jupiter assigned; falls back to the one we sent
+fn response_request_id(response: &reqwest::Response, correlation_id: Option<String>) -> String {
+    response.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+        .or(correlation_id)
+        .unwrap_or_default()
+}
+
+fn min_amount_out(out_amount: u64, slippage_bps: u16) -> u64 {
+    (out_amount as u128 * (10_000 - slippage_bps.min(10_000)) as u128 / 10_000) as u64
+}
+
 #[derive(Debug, Serialize, Deserialize)]
+struct TokenListCache {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    tokens: HashMap<String, TokenInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenInfo {
     pub address: String,
     pub chain_id: u16,
@@ -296,6 +1376,24 @@ pub struct TokenInfo {
     pub extensions: Option<serde_json::Value>,
 }
 
+#[derive(Debug, Deserialize)]
+struct PriceV2Response {
+    data: HashMap<String, Option<PriceV2Entry>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PriceV2Entry {
+    price: String,
+    extra_info: Option<PriceV2ExtraInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PriceV2ExtraInfo {
+    confidence_level: Option<PriceConfidence>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PriceData {
     pub id: String,
@@ -304,3 +1402,692 @@ pub struct PriceData {
     pub vs_token_symbol: String,
     pub price: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_http::{reply, serve};
+
+    fn quote_request(context_slot: Option<u64>) -> JupiterQuoteRequest {
+        JupiterQuoteRequest {
+            input_mint: "So11111111111111111111111111111111111111112".to_string(),
+            output_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            amount: 1_000_000,
+            slippage_bps: 50,
+            swap_mode: Some("ExactIn".to_string()),
+            dexes: None,
+            exclude_dexes: None,
+            platform_fee_bps: None,
+            max_accounts: None,
+            context_slot,
+        }
+    }
+
+    fn query_of(request: &JupiterQuoteRequest) -> String {
+        Client::new().get("http://localhost/quote").query(request).build().unwrap()
+            .url().query().unwrap_or_default().to_string()
+    }
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn route_step(amm_key: &str, input_mint: &str, output_mint: &str, percent: u8) -> serde_json::Value {
+        serde_json::json!({
+            "swap_info": {
+                "amm_key": amm_key,
+                "label": "Whirlpool",
+                "input_mint": input_mint,
+                "in_amount": "1000000000",
+                "output_mint": output_mint,
+                "out_amount": "150000000",
+                "fee_amount": "30000",
+                "fee_mint": input_mint,
+            },
+            "percent": percent,
+        })
+    }
+
+    // 1 SOL quoted at 150 USDC over `route_plan`
+    fn quote_body(route_plan: Vec<serde_json::Value>) -> String {
+        serde_json::json!({
+            "input_mint": WSOL_MINT,
+            "in_amount": "1000000000",
+            "output_mint": USDC,
+            "out_amount": "150000000",
+            "other_amount_threshold": "149250000",
+            "swap_mode": "ExactIn",
+            "slippage_bps": 50,
+            "platform_fee": null,
+            "price_impact_pct": "0.0012",
+            "route_plan": route_plan,
+            "context_slot": 250_000_000u64,
+            "time_taken": 0.002,
+        }).to_string()
+    }
+
+    fn swap_request(user: &str) -> SwapRequest {
+        SwapRequest {
+            input_mint: WSOL_MINT.to_string(),
+            output_mint: USDC.to_string(),
+            amount: 1_000_000_000,
+            user_public_key: user.to_string(),
+            slippage: 0.5,
+            priority_fee: 10_000,
+            allowed_dexes: None,
+            excluded_dexes: None,
+            use_jupiter: true,
+            dynamic_slippage: None,
+            compute_unit_price_micro_lamports: None,
+            tracking_account: None,
+            created_token_accounts: 0,
+        }
+    }
+
+    // A /swap response carrying `swap_transaction`, as Jupiter sends it
+    fn swap_body(swap_transaction: &str, simulation_slot: Option<u64>, dynamic_slippage_report: serde_json::Value) -> String {
+        serde_json::json!({
+            "swap_transaction": swap_transaction,
+            "last_valid_block_height": 280_000_000u64,
+            "prioritization_fee_lamports": 10_000,
+            "compute_unit_limit": 200_000,
+            "prioritization_fee_lamports_per_cu": 50,
+            "dynamic_slippage_report": dynamic_slippage_report,
+            "simulation_slot": simulation_slot,
+        }).to_string()
+    }
+
+    // An unsigned transaction from `user` calling `program`, base64 as /swap returns it
+    fn swap_transaction(user: &Pubkey, program: &str) -> String {
+        let instruction = Instruction::new_with_bytes(Pubkey::from_str(program).unwrap(), &[1], vec![AccountMeta::new(*user, true)]);
+        let message = solana_sdk::message::Message::new(&[instruction], Some(user));
+        let transaction = VersionedTransaction {
+            signatures: vec![solana_sdk::signature::Signature::default()],
+            message: solana_sdk::message::VersionedMessage::Legacy(message),
+        };
+        base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&transaction).unwrap())
+    }
+
+    // SOL at $150, for costing a swap's gas
+    fn sol_price_body() -> String {
+        serde_json::json!({
+            WSOL_MINT: { "id": WSOL_MINT, "mint_symbol": "SOL", "vs_token": USDC, "vs_token_symbol": "USDC", "price": 150.0 },
+        }).to_string()
+    }
+
+    #[test]
+    fn forwards_a_pinned_context_slot() {
+        assert!(query_of(&quote_request(Some(123_456))).contains("context_slot=123456"));
+        assert!(!query_of(&quote_request(None)).contains("context_slot"));
+    }
+
+    #[test]
+    fn dex_filters_are_sent_as_comma_separated_values() {
+        let mut request = quote_request(None);
+        request.dexes = Some(vec!["Raydium".to_string(), "Orca V2".to_string()]);
+        request.exclude_dexes = Some(Vec::new());
+        let query = query_of(&request);
+        assert!(query.contains("dexes=Raydium%2COrca+V2"));
+        assert!(!query.contains("exclude_dexes"));
+    }
+
+    fn wsol_token() -> TokenInfo {
+        token_info(WSOL_MINT, "SOL", 9)
+    }
+
+    fn token_info(mint: &str, symbol: &str, decimals: u8) -> TokenInfo {
+        TokenInfo {
+            address: mint.to_string(),
+            chain_id: 101,
+            decimals,
+            name: symbol.to_string(),
+            symbol: symbol.to_string(),
+            logo_uri: None,
+            tags: Vec::new(),
+            extensions: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn token_list_is_served_from_memory_until_the_ttl() {
+        let path = std::env::temp_dir().join(format!("token_list_ttl_test_{}.json", std::process::id()));
+        let cache = TokenListCache { etag: None, last_modified: None, tokens: HashMap::from([(WSOL_MINT.to_string(), wsol_token())]) };
+        std::fs::write(&path, serde_json::to_vec(&cache).unwrap()).unwrap();
+
+        // Nothing listens here, so the first load falls back to the disk cache
+        let client = JupiterClient::new("http://127.0.0.1:9".to_string(), None)
+            .with_token_cache(&path)
+            .with_token_list_ttl(60_000);
+        assert_eq!(client.get_token_decimals(WSOL_MINT).await.unwrap(), 9);
+
+        // Neither the network nor the disk is consulted again within the TTL
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(client.get_token_decimals(WSOL_MINT).await.unwrap(), 9);
+        assert_eq!(client.clone().get_tokens().await.unwrap().len(), 1);
+
+        let expired = client.with_token_list_ttl(0);
+        assert!(expired.get_tokens().await.is_err());
+    }
+
+    #[test]
+    fn configured_tier_uses_its_base_url_override() {
+        let mut config = crate::config::Config::default().jupiter;
+        config.base_urls.insert(JupiterApiType::Lite, "https://mirror.example/swap/v1".to_string());
+
+        assert_eq!(JupiterClient::new_with_config(&config).unwrap().base_url(), config.api_url);
+        config.api_type = Some(JupiterApiType::Lite);
+        assert_eq!(JupiterClient::new_with_config(&config).unwrap().base_url(), "https://mirror.example/swap/v1");
+        assert_eq!(
+            JupiterClient::new_for_tier(JupiterApiType::Pro, &config).unwrap().base_url(),
+            JupiterApiType::Pro.default_base_url()
+        );
+    }
+
+    #[tokio::test]
+    async fn price_confidence_is_read_from_jupiters_camel_case_fields() {
+        let body = r#"{
+            "data": {
+                "So11111111111111111111111111111111111111112": {
+                    "id": "So11111111111111111111111111111111111111112",
+                    "type": "derivedPrice",
+                    "price": "151.2034",
+                    "extraInfo": { "confidenceLevel": "high", "lastSwappedPrice": { "lastJupiterSellPrice": "151.19" } }
+                },
+                "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263": {
+                    "id": "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263",
+                    "type": "derivedPrice",
+                    "price": "0.0000213",
+                    "extraInfo": { "confidenceLevel": "low" }
+                }
+            },
+            "timeTaken": 0.0031
+        }"#;
+        let server = serve(vec![("/price/v2", vec![reply(200, body)])]).await;
+        let client = JupiterClient::new(server.url.clone(), None);
+        let prices = client.get_price_v2(&[WSOL_MINT.to_string(), "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263".to_string()]).await.unwrap();
+
+        let min_confidence = crate::config::Config::default().risk_settings.min_price_confidence;
+        let sol = &prices[WSOL_MINT];
+        assert_eq!(sol.confidence, PriceConfidence::High);
+        assert!(sol.confidence >= min_confidence);
+        let bonk = &prices["DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263"];
+        assert_eq!(bonk.confidence, PriceConfidence::Low);
+        assert!(bonk.confidence < min_confidence);
+    }
+
+    #[tokio::test]
+    async fn quotes_are_stamped_by_the_injected_clock() {
+        let server = serve(vec![("/quote", vec![reply(200, &quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)]))])]).await;
+        let clock = Arc::new(crate::clock::ManualClock::new(1_000_000));
+        let client = JupiterClient::new(server.url.clone(), None).with_clock(clock);
+
+        let quote = client.get_quote(quote_request(None)).await.unwrap();
+        assert_eq!(quote.received_at, 1_000_000);
+    }
+
+    #[tokio::test]
+    async fn a_not_modified_token_list_is_served_from_the_disk_cache() {
+        let path = std::env::temp_dir().join(format!("token_list_etag_test_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let tokens = serde_json::to_string(&HashMap::from([(WSOL_MINT.to_string(), wsol_token())])).unwrap();
+        let server = serve(vec![("/tokens", vec![
+            reply(200, &tokens).header("etag", "\"v1\""),
+            reply(304, ""),
+        ])]).await;
+        let client = JupiterClient::new(server.url.clone(), None)
+            .with_token_cache(&path)
+            .with_token_list_ttl(0);
+
+        assert_eq!(client.get_token_decimals(WSOL_MINT).await.unwrap(), 9);
+        // Revalidated with the stored ETag, and the 304 reuses the cached list
+        assert_eq!(client.get_token_decimals(WSOL_MINT).await.unwrap(), 9);
+        let requests = server.requests_to("/tokens");
+        assert!(!requests[0].to_lowercase().contains("if-none-match"));
+        assert!(requests[1].to_lowercase().contains("if-none-match: \"v1\""), "{}", requests[1]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_dynamic_slippage_report_sets_the_minimum_out() {
+        let server = serve(vec![
+            ("/quote", vec![reply(200, &quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)]))]),
+            ("/swap", vec![
+                reply(200, &swap_body("", None, serde_json::json!({
+                    "slippage_bps": 120,
+                    "other_amount": null,
+                    "simulated_incurred_slippage_bps": -8,
+                    "amplification_ratio": "1.5",
+                    "category_name": "bluechip",
+                    "heuristic_max_slippage_bps": 300,
+                }))),
+                reply(200, &swap_body("", None, serde_json::json!({ "slippage_bps": 120, "other_amount": 149_000_000 }))),
+                reply(200, &swap_body("", None, serde_json::Value::Null)),
+            ]),
+        ]).await;
+        let client = JupiterClient::new(server.url.clone(), None);
+        let quote = client.get_quote(quote_request(None)).await.unwrap();
+        let mut dynamic = swap_request(&Pubkey::new_unique().to_string());
+        dynamic.dynamic_slippage = Some(DynamicSlippageConfig { min_bps: 10, max_bps: 300 });
+
+        // 120 bps off the 150 USDC quote, overriding the static 50
+        let swap = client.get_swap_transaction(client.swap_request_for(&quote, &dynamic, false)).await.unwrap();
+        assert_eq!((swap.slippage_bps, swap.other_amount_threshold), (120, 148_200_000));
+        assert!(server.requests_to("/swap")[0].contains(r#""dynamic_slippage":{"min_bps":10,"max_bps":300}"#));
+
+        // Jupiter's own minimum, when it reports one
+        let swap = client.get_swap_transaction(client.swap_request_for(&quote, &dynamic, false)).await.unwrap();
+        assert_eq!(swap.other_amount_threshold, 149_000_000);
+
+        // No report leaves the quote's static slippage in place
+        let swap = client.get_swap_transaction(client.swap_request_for(&quote, &swap_request(&Pubkey::new_unique().to_string()), false)).await.unwrap();
+        assert_eq!((swap.slippage_bps, swap.other_amount_threshold), (50, 149_250_000));
+    }
+
+    #[tokio::test]
+    async fn describes_a_two_hop_route_with_a_split_first_hop() {
+        const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+        let labelled = |mut step: serde_json::Value, label: &str| {
+            step["swap_info"]["label"] = serde_json::json!(label);
+            step
+        };
+        let route = vec![
+            labelled(route_step("orca-pool", WSOL_MINT, USDC, 60), "Orca"),
+            labelled(route_step("raydium-pool", WSOL_MINT, USDC, 40), "Raydium"),
+            labelled(route_step("bonk-pool", USDC, BONK, 100), "Meteora"),
+        ];
+        let tokens = HashMap::from([
+            (WSOL_MINT.to_string(), wsol_token()),
+            (USDC.to_string(), token_info(USDC, "USDC", 6)),
+        ]);
+        let server = serve(vec![
+            ("/quote", vec![reply(200, &quote_body(route))]),
+            ("/tokens", vec![reply(200, &serde_json::to_string(&tokens).unwrap())]),
+        ]).await;
+        let client = JupiterClient::new(server.url.clone(), None);
+
+        let quote = client.get_quote(quote_request(None)).await.unwrap();
+        // BONK isn't in the token list, so it shows as its shortened mint
+        assert_eq!(client.describe_route(&quote).await, "SOL →(Orca 60% | Raydium 40%)→ USDC →(Meteora)→ DezX…");
+    }
+
+    #[tokio::test]
+    async fn quotes_carry_jupiters_request_id_or_our_own() {
+        let body = quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)]);
+        let server = serve(vec![("/quote", vec![
+            reply(200, &body).header(REQUEST_ID_HEADER, "jup-req-42"),
+            reply(200, &body),
+        ])]).await;
+        let client = JupiterClient::new(server.url.clone(), None);
+
+        assert_eq!(client.get_quote(quote_request(None)).await.unwrap().request_id, "jup-req-42");
+        // Without one from Jupiter, the correlation id we sent stands in
+        let quote = client.get_quote(quote_request(None)).await.unwrap();
+        let sent = server.requests_to("/quote")[1].lines()
+            .find_map(|line| line.strip_prefix("x-request-id: "))
+            .unwrap()
+            .to_string();
+        assert_eq!(quote.request_id, sent);
+
+        let uncorrelated = client.with_correlation_id(false);
+        assert_eq!(uncorrelated.get_quote(quote_request(None)).await.unwrap().request_id, "");
+        assert!(!server.requests_to("/quote")[2].contains("x-request-id"));
+    }
+
+    #[tokio::test]
+    async fn a_platform_fee_comes_out_of_the_quoted_amount_once() {
+        let mut body: serde_json::Value = serde_json::from_str(&quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)])).unwrap();
+        body["platform_fee"] = serde_json::json!({ "amount": "300000", "fee_bps": 20 });
+        let server = serve(vec![
+            ("/quote", vec![reply(200, &body.to_string())]),
+            ("/swap", vec![reply(200, &swap_body("", None, serde_json::Value::Null))]),
+        ]).await;
+        let fee = |deducted_from_out_amount| Some(PlatformFeeConfig {
+            fee_bps: 20,
+            fee_account: Pubkey::new_unique().to_string(),
+            deducted_from_out_amount,
+        });
+
+        // An endpoint quoting before the fee: 0.3 USDC never reaches the wallet
+        let gross = JupiterClient::new(server.url.clone(), None).with_platform_fee(fee(false));
+        let quote = gross.get_quote(quote_request(None)).await.unwrap();
+        assert_eq!((quote.out_amount, quote.platform_fee_amount), (149_700_000, 300_000));
+        assert!(server.requests_to("/quote")[0].contains("platform_fee_bps=20"));
+        // Jupiter gets its own outAmount back when the swap is built
+        gross.get_swap_transaction(gross.swap_request_for(&quote, &swap_request(&Pubkey::new_unique().to_string()), false)).await.unwrap();
+        assert!(server.requests_to("/swap")[0].contains(r#""out_amount":"150000000""#));
+
+        // One quoting after the fee is taken at its word
+        let net = JupiterClient::new(server.url.clone(), None).with_platform_fee(fee(true));
+        assert_eq!(net.get_quote(quote_request(None)).await.unwrap().out_amount, 150_000_000);
+    }
+
+    #[tokio::test]
+    async fn a_swap_build_that_503s_once_is_retried() {
+        let unavailable = || reply(503, r#"{"error":"upstream busy"}"#);
+        let server = serve(vec![
+            ("/quote", vec![reply(200, &quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)]))]),
+            ("/swap", vec![unavailable(), reply(200, &swap_body("", None, serde_json::Value::Null)), unavailable()]),
+        ]).await;
+        let client = JupiterClient::new(server.url.clone(), None).with_retries(1);
+        let quote = client.get_quote(quote_request(None)).await.unwrap();
+        let request = || client.swap_request_for(&quote, &swap_request(&Pubkey::new_unique().to_string()), false);
+
+        let swap = client.get_swap_transaction(request()).await.unwrap();
+        assert_eq!(swap.last_valid_block_height, 280_000_000);
+        assert_eq!(server.requests_to("/swap").len(), 2);
+
+        // Without retries the 503 surfaces with Jupiter's message
+        let error = client.clone().with_retries(0).get_swap_transaction(request()).await.unwrap_err().to_string();
+        assert!(error.contains("503") && error.contains("upstream busy"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn requests_go_through_the_configured_proxy() {
+        // A proxy sees the absolute URL; the host itself never resolves
+        let proxy = serve(vec![("http://jupiter.invalid/swap/v1/quote", vec![
+            reply(200, &quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)])),
+        ])]).await;
+        let mut config = crate::config::Config::default().jupiter;
+        config.api_type = None;
+        config.api_url = "http://jupiter.invalid/swap/v1".to_string();
+        config.http.proxy_url = Some(proxy.url.clone());
+        config.http.pool_max_idle_per_host = 4;
+        config.http.tcp_keepalive_secs = Some(30);
+
+        let client = JupiterClient::new_with_config(&config).unwrap();
+        assert_eq!(client.get_quote(quote_request(None)).await.unwrap().out_amount, 150_000_000);
+        assert_eq!(proxy.requests().len(), 1);
+
+        config.http.proxy_url = Some("not a proxy".to_string());
+        let error = JupiterClient::new_with_config(&config).unwrap_err();
+        assert!(error.to_string().contains("Invalid Jupiter proxy URL"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn invalid_swap_inputs_are_refused_before_any_request() {
+        let server = serve(vec![("/quote", vec![reply(200, &quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)]))])]).await;
+        let client = JupiterClient::new(server.url.clone(), None);
+        let invalid = [
+            (WSOL_MINT, WSOL_MINT, 1_000_000, "both"),
+            ("not-a-mint", USDC, 1_000_000, "input mint"),
+            (WSOL_MINT, "0OIl", 1_000_000, "output mint"),
+            // Valid base58, but 31 bytes
+            (WSOL_MINT, "1111111111111111111111111111111", 1_000_000, "output mint"),
+            (WSOL_MINT, USDC, 0, "greater than zero"),
+        ];
+
+        for (input_mint, output_mint, amount, reason) in invalid {
+            let request = JupiterQuoteRequest {
+                input_mint: input_mint.to_string(),
+                output_mint: output_mint.to_string(),
+                amount,
+                ..quote_request(None)
+            };
+            let error = client.get_quote(request).await.unwrap_err().to_string();
+            assert!(error.contains(reason), "{}", error);
+
+            let swap = SwapRequest {
+                input_mint: input_mint.to_string(),
+                output_mint: output_mint.to_string(),
+                amount,
+                ..swap_request(&Pubkey::new_unique().to_string())
+            };
+            let error = client.execute_swap(swap).await.unwrap_err().to_string();
+            assert!(error.contains(reason), "{}", error);
+        }
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn max_accounts_is_sent_and_bumped_when_no_route_fits() {
+        let quote = || reply(200, &quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)]));
+        let no_route = reply(400, r#"{"error":"Could not find any route"}"#);
+        let server = serve(vec![("/quote", vec![quote(), no_route, quote()])]).await;
+
+        let fixed = JupiterClient::new(server.url.clone(), None).with_max_accounts(48, None);
+        fixed.get_quote(quote_request(None)).await.unwrap();
+        // Starts at 20 accounts and retries at the full 48 only after "no route"
+        let adaptive = JupiterClient::new(server.url.clone(), None).with_max_accounts(48, Some(20));
+        adaptive.get_quote(quote_request(None)).await.unwrap();
+
+        let caps: Vec<String> = server.requests_to("/quote").iter()
+            .filter_map(|request| request.split(['?', '&', ' ']).find_map(|param| param.strip_prefix("max_accounts=")).map(str::to_string))
+            .collect();
+        assert_eq!(caps, ["48", "20", "48"]);
+    }
+
+    #[tokio::test]
+    async fn an_oversized_response_is_rejected_but_the_token_list_has_more_room() {
+        let quote = quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)]);
+        let tokens = serde_json::to_string(&HashMap::from([(WSOL_MINT.to_string(), wsol_token())])).unwrap();
+        assert!(quote.len() > 200 && tokens.len() > 200);
+        let server = serve(vec![
+            ("/quote", vec![reply(200, &quote)]),
+            ("/tokens", vec![reply(200, &tokens)]),
+        ]).await;
+        let client = JupiterClient::new(server.url.clone(), None).with_response_limits(200, 64 * 1024);
+
+        let error = client.get_quote(quote_request(None)).await.unwrap_err().to_string();
+        assert!(error.contains(&format!("is {} bytes, over the 200 byte limit", quote.len())), "{}", error);
+        assert_eq!(client.get_token_decimals(WSOL_MINT).await.unwrap(), 9);
+    }
+
+    #[tokio::test]
+    async fn odd_price_impacts_are_sanitized_as_quotes_arrive() {
+        let impact = |raw: &str| {
+            let mut body: serde_json::Value = serde_json::from_str(&quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)])).unwrap();
+            body["price_impact_pct"] = serde_json::json!(raw);
+            reply(200, &body.to_string())
+        };
+        let server = serve(vec![("/quote", vec![impact("-0.02"), impact("250000"), impact(""), impact("NaN")])]).await;
+        let client = JupiterClient::new(server.url.clone(), None).with_price_impact_clamp(50.0);
+
+        assert_eq!(client.get_quote(quote_request(None)).await.unwrap().price_impact_pct, 0.0);
+        // Still fails any max impact gate, without a six-figure outlier
+        assert_eq!(client.get_quote(quote_request(None)).await.unwrap().price_impact_pct, 50.0);
+        let empty = client.get_quote(quote_request(None)).await.unwrap_err();
+        assert!(empty.to_string().contains("unparseable price impact ''"), "{}", empty);
+        let nan = client.get_quote(quote_request(None)).await.unwrap_err();
+        assert!(nan.to_string().contains("non-finite price impact 'NaN'"), "{}", nan);
+    }
+
+    #[tokio::test]
+    async fn a_swap_calling_an_unexpected_program_is_never_returned() {
+        let user = Pubkey::new_unique();
+        let server = serve(vec![
+            ("/quote", vec![reply(200, &quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)]))]),
+            ("/swap", vec![
+                reply(200, &swap_body(&swap_transaction(&user, JUPITER_PROGRAM_ID), None, serde_json::Value::Null)),
+                reply(200, &swap_body(&swap_transaction(&user, &Pubkey::new_unique().to_string()), None, serde_json::Value::Null)),
+            ]),
+            ("/price", vec![reply(200, &sol_price_body())]),
+        ]).await;
+        let client = JupiterClient::new(server.url.clone(), None);
+
+        let benign = client.execute_swap(swap_request(&user.to_string())).await.unwrap();
+        assert_eq!(benign.transaction, swap_transaction(&user, JUPITER_PROGRAM_ID));
+
+        let error = client.execute_swap(swap_request(&user.to_string())).await.unwrap_err();
+        assert!(error.to_string().contains("calls unexpected program"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn a_failed_price_batch_leaves_the_others_standing() {
+        const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+        let price = |mint: &str, price: f64| reply(200, &serde_json::json!({
+            mint: { "id": mint, "mint_symbol": "", "vs_token": USDC, "vs_token_symbol": "USDC", "price": price },
+        }).to_string());
+        let sol = format!("/price?ids={}", WSOL_MINT);
+        let usdc = format!("/price?ids={}", USDC);
+        let bonk = format!("/price?ids={}", BONK);
+        let server = serve(vec![
+            (sol.as_str(), vec![price(WSOL_MINT, 150.0)]),
+            (usdc.as_str(), vec![reply(500, r#"{"error":"internal"}"#)]),
+            (bonk.as_str(), vec![price(BONK, 0.00002)]),
+        ]).await;
+        let client = JupiterClient::new(server.url.clone(), None).with_price_batch_size(1);
+
+        let fetch = client.get_price(&[WSOL_MINT.to_string(), USDC.to_string(), BONK.to_string()]).await.unwrap();
+        assert_eq!(fetch.prices, HashMap::from([(WSOL_MINT.to_string(), 150.0), (BONK.to_string(), 0.00002)]));
+        assert_eq!(fetch.failed_ids, [USDC]);
+
+        // Only when every batch fails is it an error
+        assert!(client.get_price(&[USDC.to_string()]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn an_auto_swap_falls_back_to_legacy_when_versioned_is_refused() {
+        let user = Pubkey::new_unique();
+        let refused = || reply(400, r#"{"error":"Versioned transaction is not supported by this route"}"#);
+        let built = reply(200, &swap_body(&swap_transaction(&user, JUPITER_PROGRAM_ID), None, serde_json::Value::Null));
+        let server = serve(vec![
+            ("/quote", vec![reply(200, &quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)]))]),
+            ("/swap", vec![refused(), built, refused()]),
+            ("/price", vec![reply(200, &sol_price_body())]),
+        ]).await;
+
+        let auto = JupiterClient::new(server.url.clone(), None).with_transaction_version(TransactionVersion::Auto);
+        assert!(auto.execute_swap(swap_request(&user.to_string())).await.unwrap().success);
+        let builds = server.requests_to("/swap");
+        assert!(builds[0].contains(r#""as_legacy_transaction":null"#) && builds[0].contains(r#""as_versioned_transaction":true"#));
+        assert!(builds[1].contains(r#""as_legacy_transaction":true"#) && builds[1].contains(r#""as_versioned_transaction":null"#));
+
+        // Versioned-only gives up on the same refusal
+        let versioned = JupiterClient::new(server.url.clone(), None).with_transaction_version(TransactionVersion::Versioned);
+        let error = versioned.execute_swap(swap_request(&user.to_string())).await.unwrap_err();
+        assert!(error.to_string().contains("Versioned transaction is not supported"), "{}", error);
+        assert_eq!(server.requests_to("/swap").len(), 3);
+    }
+
+    #[tokio::test]
+    async fn a_tracking_account_is_sent_with_the_swap() {
+        let user = Pubkey::new_unique();
+        let tracking = Pubkey::new_unique().to_string();
+        let server = serve(vec![
+            ("/quote", vec![reply(200, &quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)]))]),
+            ("/swap", vec![reply(200, &swap_body(&swap_transaction(&user, JUPITER_PROGRAM_ID), None, serde_json::Value::Null))]),
+            ("/price", vec![reply(200, &sol_price_body())]),
+        ]).await;
+        let client = JupiterClient::new(server.url.clone(), None);
+
+        let tracked = SwapRequest { tracking_account: Some(tracking.clone()), ..swap_request(&user.to_string()) };
+        client.execute_swap(tracked).await.unwrap();
+        client.execute_swap(swap_request(&user.to_string())).await.unwrap();
+        let builds = server.requests_to("/swap");
+        assert!(builds[0].contains(&format!(r#""tracking_account":"{}""#, tracking)), "{}", builds[0]);
+        assert!(builds[1].contains(r#""tracking_account":null"#), "{}", builds[1]);
+
+        // A malformed one is refused before anything is quoted
+        let garbled = SwapRequest { tracking_account: Some("not-a-pubkey".to_string()), ..swap_request(&user.to_string()) };
+        let error = client.execute_swap(garbled).await.unwrap_err();
+        assert!(error.to_string().contains("tracking account 'not-a-pubkey'"), "{}", error);
+        assert_eq!(server.requests_to("/quote").len(), 2);
+    }
+
+    // Log output collected by a test subscriber
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl CapturedLogs {
+        fn traced_quotes(&self) -> usize {
+            String::from_utf8_lossy(&self.0.lock().unwrap()).matches("🔬").count()
+        }
+    }
+
+    #[tokio::test]
+    async fn with_no_sampling_only_failed_and_slow_quotes_are_traced() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::INFO)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let quote = || reply(200, &quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)]));
+        let server = serve(vec![("/quote", vec![
+            quote(),
+            reply(500, r#"{"error":"internal"}"#),
+            quote().delayed(150),
+        ])]).await;
+        let client = JupiterClient::new(server.url.clone(), None).with_quote_tracing(0.0, 100);
+
+        client.get_quote(quote_request(None)).await.unwrap();
+        assert_eq!(logs.traced_quotes(), 0);
+        client.get_quote(quote_request(None)).await.unwrap_err();
+        assert_eq!(logs.traced_quotes(), 1);
+        client.get_quote(quote_request(None)).await.unwrap();
+        assert_eq!(logs.traced_quotes(), 2);
+    }
+
+    #[tokio::test]
+    async fn custom_headers_replace_the_bearer_scheme_on_every_request() {
+        std::env::set_var("JUPITER_CLIENT_TEST_GATEWAY_TOKEN", "s3cret");
+        let server = serve(vec![("/quote", vec![reply(200, &quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)]))])]).await;
+        let mut config = crate::config::Config::default().jupiter;
+        config.api_type = None;
+        config.api_url = server.url.clone();
+        config.api_key = Some("jupiter-key".to_string());
+        config.custom_headers = HashMap::from([
+            ("Authorization".to_string(), "Basic ${JUPITER_CLIENT_TEST_GATEWAY_TOKEN}".to_string()),
+            ("X-Gateway-Tenant".to_string(), "arb-desk".to_string()),
+        ]);
+
+        let client = JupiterClient::new_with_config(&config).unwrap();
+        client.get_quote(quote_request(None)).await.unwrap();
+        let request = server.requests_to("/quote")[0].to_lowercase();
+        assert!(request.contains("authorization: basic s3cret"), "{}", request);
+        assert!(!request.contains("bearer"), "{}", request);
+        assert!(request.contains("x-gateway-tenant: arb-desk"), "{}", request);
+
+        config.custom_headers = HashMap::from([("Bad Header".to_string(), "x".to_string())]);
+        let error = JupiterClient::new_with_config(&config).unwrap_err();
+        assert!(error.to_string().contains("header name 'Bad Header' is invalid"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn a_swap_built_far_past_its_quotes_slot_is_aborted() {
+        let user = Pubkey::new_unique();
+        let transaction = swap_transaction(&user, JUPITER_PROGRAM_ID);
+        // The quote is at slot 250,000,000
+        let server = serve(vec![
+            ("/quote", vec![reply(200, &quote_body(vec![route_step("pool", WSOL_MINT, USDC, 100)]))]),
+            ("/swap", vec![
+                reply(200, &swap_body(&transaction, Some(250_000_050), serde_json::Value::Null)),
+                reply(200, &swap_body(&transaction, Some(250_000_050), serde_json::Value::Null)),
+                reply(200, &swap_body(&transaction, Some(250_000_004), serde_json::Value::Null)),
+            ]),
+            ("/price", vec![reply(200, &sol_price_body())]),
+        ]).await;
+
+        let aborting = JupiterClient::new(server.url.clone(), None).with_slot_drift_check(Some(10), SlotDriftAction::Abort);
+        let error = aborting.execute_swap(swap_request(&user.to_string())).await.unwrap_err();
+        assert!(error.to_string().contains("50 slots past its quote's slot 250000000 (max 10)"), "{}", error);
+        assert_eq!(server.requests_to("/quote").len(), 1);
+
+        // Re-quoting instead rebuilds once, and the rebuilt swap is close enough
+        let requoting = JupiterClient::new(server.url.clone(), None).with_slot_drift_check(Some(10), SlotDriftAction::Requote);
+        assert!(requoting.execute_swap(swap_request(&user.to_string())).await.unwrap().success);
+        assert_eq!(server.requests_to("/quote").len(), 3);
+    }
+
+    #[tokio::test]
+    async fn an_oversized_route_plan_is_rejected_by_its_leg_count() {
+        let legs = |count: usize| (0..count).map(|leg| route_step(&format!("pool-{}", leg), WSOL_MINT, USDC, 100)).collect();
+        let server = serve(vec![("/quote", vec![reply(200, &quote_body(legs(300))), reply(200, &quote_body(legs(16)))])]).await;
+        let client = JupiterClient::new(server.url.clone(), None).with_max_route_legs(16);
+
+        let error = client.get_quote(quote_request(None)).await.unwrap_err();
+        assert_eq!(error.to_string(), "Quote route plan has 300 legs, over the 16 leg limit");
+        assert_eq!(client.get_quote(quote_request(None)).await.unwrap().route_plan.len(), 16);
+    }
+}
@@ -1,13 +1,59 @@
+pub mod amounts;
+pub mod clock;
+pub mod compute_budget;
 pub mod config;
 pub mod arbitrage_engine;
+pub mod backpressure;
+pub mod blackout;
+pub mod dex_fees;
+pub mod display;
 pub mod dex_monitor;
+pub mod endpoint_poller;
+pub mod event_sink;
+pub mod executor;
+pub mod fee_estimator;
+pub mod finality;
+pub mod grpc_bind;
 pub mod grpc_server;
+pub mod history;
+pub mod housekeeping;
+pub mod impact_monitor;
 pub mod jito_client;
+pub mod jito_failover;
 pub mod jupiter_client;
+#[cfg(test)]
+mod mock_http;
 pub mod risk_manager;
 pub mod portfolio_manager;
+pub mod preflight;
+pub mod mint_safety;
 pub mod monitoring;
-pub mod utils;
+pub mod observe;
+pub mod opportunity_claims;
+pub mod opportunity_feed;
+pub mod opportunity_stream;
+pub mod oracle;
+pub mod price_history;
+pub mod price_quorum;
+pub mod price_source;
+pub mod quote_cache;
+pub mod quote_source;
+pub mod rate_limit;
+pub mod rebalancer;
+pub mod reconciler;
+pub mod route_availability;
+pub mod rpc_client;
+pub mod signer;
+pub mod slippage;
+pub mod stats;
+pub mod strategy_budget;
+pub mod supervisor;
+pub mod tip_estimator;
+pub mod trade_queue;
+pub mod transaction_simulator;
+pub mod transaction_builder;
+pub mod warm_cache;
+pub mod win_rate;
 pub mod types;
 
 pub use config::Config;
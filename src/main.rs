@@ -2,17 +2,24 @@ use clap::{Parser, Subcommand};
 use solana_arbitrage_bot::{
     config::Config,
     arbitrage_engine::ArbitrageEngine,
+    display::MoneyFormat,
     dex_monitor::DexMonitor,
+    arbitrage::{arbitrage_service_client::ArbitrageServiceClient, StatsRequest},
+    grpc_bind::bindable_port,
     grpc_server::ArbitrageGrpcServer,
     jito_client::JitoClient,
     jupiter_client::JupiterClient,
     risk_manager::RiskManager,
     portfolio_manager::PortfolioManager,
     monitoring::MonitoringService,
+    observe::serve_read_only,
+    preflight::run_preflight,
+    reconciler::{apply_corrections, ProfitCheck, Reconciler},
+    types::TradeResponse,
 };
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{info, error};
+use tracing::{info, error, warn};
 
 #[derive(Parser)]
 #[command(name = "solana-arbitrage-bot")]
@@ -22,9 +29,9 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
     
-    /// Configuration file path
+    /// Configuration file path; repeat to layer overlays on top (later files win)
     #[arg(short, long, default_value = "config.toml")]
-    config: String,
+    config: Vec<String>,
     
     /// Enable debug logging
     #[arg(short, long)]
@@ -43,6 +50,10 @@ enum Commands {
         #[arg(long, default_value = "50051")]
         grpc_port: u16,
         
+        /// Serve only the read RPCs (no trading or settings changes)
+        #[arg(long)]
+        observe: bool,
+        
         /// Enable Jito bundle submission
         #[arg(long)]
         jito: bool,
@@ -73,6 +84,28 @@ enum Commands {
         #[arg(long)]
         max_slippage: Option<f64>,
     },
+    /// Reconcile recorded trades against on-chain transactions
+    Reconcile {
+        /// JSON file of recorded trades
+        #[arg(long)]
+        trades_file: String,
+        
+        /// Write corrected outcomes back to the trades file
+        #[arg(long)]
+        fix: bool,
+
+        /// How far on-chain profit may differ from the recorded profit, in the profit denomination
+        #[arg(long, default_value = "0.01")]
+        profit_tolerance: f64,
+    },
+    /// Check config, RPC, wallet, Jupiter, Jito and persistence before going live
+    Preflight,
+    /// Report whether a running bot's gRPC server is reachable
+    Status {
+        /// gRPC server port (fallback ports from config are tried too)
+        #[arg(long, default_value = "50051")]
+        grpc_port: u16,
+    },
     /// Test Jupiter integration
     TestJupiter {
         /// Input token mint
@@ -86,6 +119,14 @@ enum Commands {
         /// Amount to swap
         #[arg(long, default_value = "1000000")]
         amount: u64,
+        
+        /// Trade size in USD, converted to the input mint's raw amount (overrides --amount)
+        #[arg(long)]
+        usd_amount: Option<f64>,
+
+        /// Pin the quote to this slot, to reproduce an earlier quote
+        #[arg(long)]
+        context_slot: Option<u64>,
     },
 }
 
@@ -102,11 +143,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("🚀 Starting Solana Arbitrage Bot v{}", env!("CARGO_PKG_VERSION"));
     
     // Load configuration
-    let config = Config::load(&cli.config)?;
-    info!("📋 Configuration loaded from {}", cli.config);
+    let config = Config::load_layered(&cli.config)?;
+    info!("📋 Configuration loaded from {}", cli.config.join(" + "));
+    let money = MoneyFormat::new(config.display.clone());
     
     // Initialize services
-    let monitoring = Arc::new(MonitoringService::new());
+    let monitoring = Arc::new(MonitoringService::new(config.monitoring.clone()));
     let risk_manager = Arc::new(RwLock::new(RiskManager::new(config.risk_settings.clone())));
     let portfolio_manager = Arc::new(PortfolioManager::new(config.clone()));
     let jito_client = if cli.command.is_jito_enabled() {
@@ -116,16 +158,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     let jupiter_client = if config.jupiter.enabled {
-        Some(Arc::new(JupiterClient::new(
-            config.jupiter.api_url.clone(),
-            config.jupiter.api_key.clone(),
-        )))
+        Some(Arc::new(JupiterClient::new_with_config(&config.jupiter)?))
     } else {
         None
     };
     
     let dex_monitor = Arc::new(DexMonitor::new(config.dex_endpoints.clone()));
-    let arbitrage_engine = Arc::new(ArbitrageEngine::new(
+    // Only the commands that trade or scan open the engine's stores, so a
+    // bad path surfaces as a preflight failure rather than a startup abort
+    let build_engine = || ArbitrageEngine::new(
         config.clone(),
         dex_monitor.clone(),
         risk_manager.clone(),
@@ -133,10 +174,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         jito_client.clone(),
         jupiter_client.clone(),
         monitoring.clone(),
-    ));
+    ).map(Arc::new);
     
     match cli.command {
-        Commands::Start { grpc, grpc_port, jito } => {
+        Commands::Start { grpc, grpc_port, observe, jito } => {
             info!("🎯 Starting arbitrage bot with gRPC: {}, Jito: {}", grpc, jito);
             
             // Start monitoring
@@ -146,6 +187,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             dex_monitor.start().await?;
             
             // Start arbitrage engine
+            let arbitrage_engine = build_engine()?;
             arbitrage_engine.start().await?;
             
             if grpc {
@@ -156,8 +198,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     monitoring.clone(),
                 );
                 
-                info!("🌐 Starting gRPC server on port {}", grpc_port);
-                grpc_server.start(grpc_port).await?;
+                let served = match bindable_port(grpc_port, config.grpc.fallback_ports) {
+                    Ok(port) => {
+                        if port != grpc_port {
+                            warn!("⚠️ gRPC port {} is taken, using {}", grpc_port, port);
+                        }
+                        if observe {
+                            serve_read_only(grpc_server, port).await
+                        } else {
+                            info!("🌐 Starting gRPC server on port {}", port);
+                            grpc_server.start(port).await.map_err(|e| anyhow::anyhow!("{}", e))
+                        }
+                    }
+                    Err(e) => Err(e),
+                };
+
+                if let Err(e) = served {
+                    if config.grpc.bind_failure_fatal {
+                        return Err(e.into());
+                    }
+                    warn!("⚠️ gRPC unavailable ({}), continuing headless", e);
+                    tokio::signal::ctrl_c().await?;
+                    info!("🛑 Shutting down arbitrage bot");
+                }
             } else {
                 // Keep the main thread alive
                 tokio::signal::ctrl_c().await?;
@@ -166,46 +229,121 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
         Commands::Scan { min_profit, max_amount } => {
             info!("🔍 Scanning for arbitrage opportunities...");
-            let opportunities = arbitrage_engine.scan_opportunities(min_profit, max_amount).await?;
+            let opportunities = build_engine()?.scan_opportunities(min_profit, max_amount).await?;
             
             if opportunities.is_empty() {
                 info!("❌ No profitable opportunities found");
             } else {
                 info!("✅ Found {} opportunities:", opportunities.len());
                 for (i, opp) in opportunities.iter().enumerate() {
-                    info!("  {}. {}: {:.2}% profit, ${:.2} estimated", 
-                          i + 1, opp.token_pair, opp.profit_percentage, opp.estimated_profit);
+                    info!("  {}. {}: {:.3}% edge over fees ({:.2}% gross spread), {} estimated",
+                          i + 1, opp.token_pair, opp.edge_over_fees_pct, opp.profit_percentage, money.usd(opp.estimated_profit));
                 }
             }
         }
         Commands::Portfolio => {
-            let portfolio = portfolio_manager.get_portfolio().await?;
-            info!("💰 Portfolio Value: ${:.2}", portfolio.total_value_usd);
+            let mut portfolio = portfolio_manager.get_portfolio().await?;
+            if config.wallet.consolidate_wsol {
+                portfolio = portfolio.consolidate_sol(config.wallet.min_sol_balance);
+            }
+            info!("💰 Portfolio Value: {}", money.usd(portfolio.total_value_usd));
             for balance in portfolio.balances {
-                info!("  {}: {:.4} (${:.2})", balance.symbol, balance.amount, balance.value_usd);
+                info!("  {} ({})", money.amount(balance.amount, &balance.symbol), money.usd(balance.value_usd));
             }
         }
         Commands::Risk { max_position, max_daily_loss, max_slippage } => {
             let mut risk = risk_manager.write().await;
             if let Some(pos) = max_position {
                 risk.update_max_position_size(pos);
-                info!("📊 Updated max position size to ${:.2}", pos);
+                info!("📊 Updated max position size to {}", money.usd(pos));
             }
             if let Some(loss) = max_daily_loss {
                 risk.update_max_daily_loss(loss);
-                info!("📊 Updated max daily loss to ${:.2}", loss);
+                info!("📊 Updated max daily loss to {}", money.usd(loss));
             }
             if let Some(slip) = max_slippage {
                 risk.update_max_slippage(slip);
                 info!("📊 Updated max slippage to {:.2}%", slip);
             }
         }
-        Commands::TestJupiter { input_mint, output_mint, amount } => {
+        Commands::Reconcile { trades_file, fix, profit_tolerance } => {
+            let mut trades: Vec<TradeResponse> =
+                serde_json::from_str(&std::fs::read_to_string(&trades_file)?)?;
+            info!("🔎 Reconciling {} recorded trades against {}", trades.len(), config.rpc_endpoints.primary);
+
+            let reconciler = Reconciler::new(
+                config.rpc_endpoints.primary.clone(),
+                config.rpc_endpoints.timeout_ms,
+            ).with_profit_check(ProfitCheck {
+                denomination_mint: config.trading.profit_denomination_mint.clone(),
+                tolerance: profit_tolerance,
+            });
+            let report = reconciler.reconcile(&trades).await?;
+
+            info!("📋 Checked: {}, matched: {}, discrepancies: {}",
+                  report.checked, report.matched, report.discrepancies.len());
+            for entry in &report.discrepancies {
+                info!("  {}: {:?}", entry.transaction_id, entry.discrepancy);
+            }
+
+            if fix && !report.discrepancies.is_empty() {
+                apply_corrections(&mut trades, &report);
+                std::fs::write(&trades_file, serde_json::to_string_pretty(&trades)?)?;
+                info!("✏️ Corrected trades written to {}", trades_file);
+            }
+        }
+        Commands::Preflight => {
+            info!("🛫 Running preflight checks");
+            let results = run_preflight(&config, jupiter_client.as_deref()).await;
+
+            for result in &results {
+                let status = match (result.passed, result.critical) {
+                    (true, _) => "PASS",
+                    (false, true) => "FAIL",
+                    (false, false) => "WARN",
+                };
+                info!("  {:<4} {:<14} {}", status, result.name, result.detail);
+            }
+
+            let critical_failures = results.iter().filter(|r| r.critical && !r.passed).count();
+            if critical_failures > 0 {
+                error!("❌ {} critical preflight checks failed", critical_failures);
+                std::process::exit(1);
+            }
+            info!("✅ Preflight passed");
+        }
+        Commands::Status { grpc_port } => {
+            let last = grpc_port.saturating_add(config.grpc.fallback_ports);
+            let mut reachable = None;
+            for port in grpc_port..=last {
+                let Ok(mut client) = ArbitrageServiceClient::connect(format!("http://127.0.0.1:{}", port)).await else {
+                    continue;
+                };
+                if client.get_stats(StatsRequest { time_period: "24h".to_string() }).await.is_ok() {
+                    reachable = Some(port);
+                    break;
+                }
+            }
+
+            match reachable {
+                Some(port) => info!("🌐 gRPC: available on port {}", port),
+                None => info!("🌐 gRPC: unavailable (not running, or running headless)"),
+            }
+        }
+        Commands::TestJupiter { input_mint, output_mint, amount, usd_amount, context_slot } => {
             if let Some(jupiter_client) = jupiter_client {
+                let amount = match usd_amount {
+                    Some(usd) => {
+                        let raw = jupiter_client.usd_to_raw(&input_mint, usd).await?;
+                        info!("💵 {} of {} = {} raw units", money.usd(usd), input_mint, raw);
+                        raw
+                    }
+                    None => amount,
+                };
                 info!("🧪 Testing Jupiter integration: {} -> {} (amount: {})", 
                       input_mint, output_mint, amount);
                 
-                use crate::jupiter_client::JupiterQuoteRequest;
+                use solana_arbitrage_bot::jupiter_client::JupiterQuoteRequest;
                 let request = JupiterQuoteRequest {
                     input_mint: input_mint.clone(),
                     output_mint: output_mint.clone(),
@@ -215,7 +353,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     dexes: None,
                     exclude_dexes: None,
                     platform_fee_bps: None,
-                    max_accounts: Some(64),
+                    max_accounts: None,
+                    context_slot,
                 };
 
                 match jupiter_client.get_quote(request).await {
@@ -225,7 +364,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         info!("  Output: {} {} tokens", quote.out_amount, output_mint);
                         info!("  Price impact: {:.2}%", quote.price_impact_pct);
                         info!("  Time taken: {:.2}ms", quote.time_taken);
-                        info!("  Route: {} steps", quote.route_plan.len());
+                        info!("  Context slot: {}", quote.context_slot);
+                        info!("  Route: {}", jupiter_client.describe_route(&quote).await);
                     }
                     Err(e) => {
                         error!("❌ Jupiter quote failed: {}", e);
@@ -0,0 +1,119 @@
+use crate::{rpc_client::SolanaRpcClient, transaction_simulator::TOKEN_PROGRAM_ID};
+use anyhow::Result;
+use base64::Engine;
+use serde_json::json;
+
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+
+// SPL mint layout: freeze authority COption<Pubkey> after mint authority,
+// supply, decimals and is_initialized
+const FREEZE_AUTHORITY_OFFSET: usize = 46;
+const MINT_LEN: usize = 82;
+// Token-2022 pads mints to the account length, then an account-type byte,
+// then TLV extensions (u16 type, u16 length)
+const EXTENSIONS_OFFSET: usize = 166;
+const NON_TRANSFERABLE_EXTENSION: u16 = 9;
+
+// Properties of a mint that can leave bought inventory unsellable
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MintRestrictions {
+    pub freeze_authority: Option<String>,
+    pub non_transferable: bool,
+}
+
+impl MintRestrictions {
+    pub fn is_restricted(&self) -> bool {
+        self.freeze_authority.is_some() || self.non_transferable
+    }
+}
+
+pub async fn fetch_mint_restrictions(rpc: &SolanaRpcClient, mint: &str) -> Result<MintRestrictions> {
+    let result = rpc.call("getAccountInfo", json!([mint, { "encoding": "base64" }])).await?;
+    let value = &result["value"];
+    let owner = value["owner"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Mint account {} not found", mint))?;
+    let encoded = value["data"][0]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Mint account {} has no data", mint))?;
+    let data = base64::engine::general_purpose::STANDARD.decode(encoded)?;
+
+    parse_mint_restrictions(owner, &data)
+}
+
+pub fn parse_mint_restrictions(owner: &str, data: &[u8]) -> Result<MintRestrictions> {
+    if owner != TOKEN_PROGRAM_ID && owner != TOKEN_2022_PROGRAM_ID {
+        return Err(anyhow::anyhow!("Account owned by {} is not a token mint", owner));
+    }
+    if data.len() < MINT_LEN {
+        return Err(anyhow::anyhow!("Mint account too short: {} bytes", data.len()));
+    }
+
+    let freeze_authority = match data[FREEZE_AUTHORITY_OFFSET..FREEZE_AUTHORITY_OFFSET + 4] {
+        [0, 0, 0, 0] => None,
+        _ => {
+            let key = &data[FREEZE_AUTHORITY_OFFSET + 4..FREEZE_AUTHORITY_OFFSET + 36];
+            Some(bs58::encode(key).into_string())
+        }
+    };
+
+    let mut non_transferable = false;
+    if owner == TOKEN_2022_PROGRAM_ID {
+        let mut offset = EXTENSIONS_OFFSET;
+        while offset + 4 <= data.len() {
+            let extension = u16::from_le_bytes([data[offset], data[offset + 1]]);
+            let length = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+            if extension == NON_TRANSFERABLE_EXTENSION {
+                non_transferable = true;
+                break;
+            }
+            offset += 4 + length;
+        }
+    }
+
+    Ok(MintRestrictions { freeze_authority, non_transferable })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn mint_data(freeze_authority: Option<&Pubkey>) -> Vec<u8> {
+        let mut data = vec![0u8; MINT_LEN];
+        if let Some(authority) = freeze_authority {
+            data[FREEZE_AUTHORITY_OFFSET] = 1;
+            data[FREEZE_AUTHORITY_OFFSET + 4..FREEZE_AUTHORITY_OFFSET + 36].copy_from_slice(authority.as_ref());
+        }
+        data
+    }
+
+    #[test]
+    fn reports_a_freeze_authority() {
+        let authority = Pubkey::new_unique();
+        let restrictions = parse_mint_restrictions(TOKEN_PROGRAM_ID, &mint_data(Some(&authority))).unwrap();
+        assert_eq!(restrictions.freeze_authority, Some(authority.to_string()));
+        assert!(restrictions.is_restricted());
+        assert!(!parse_mint_restrictions(TOKEN_PROGRAM_ID, &mint_data(None)).unwrap().is_restricted());
+    }
+
+    #[test]
+    fn finds_the_non_transferable_extension_among_others() {
+        let mut data = mint_data(None);
+        data.resize(EXTENSIONS_OFFSET, 0);
+        // An unrelated 2-byte extension first
+        data.extend_from_slice(&[1, 0, 2, 0, 0xff, 0xff]);
+        data.extend_from_slice(&NON_TRANSFERABLE_EXTENSION.to_le_bytes());
+        data.extend_from_slice(&[0, 0]);
+
+        assert!(parse_mint_restrictions(TOKEN_2022_PROGRAM_ID, &data).unwrap().non_transferable);
+        // The classic token program has no extensions
+        assert!(!parse_mint_restrictions(TOKEN_PROGRAM_ID, &data).unwrap().non_transferable);
+    }
+
+    #[test]
+    fn refuses_accounts_that_are_not_mints() {
+        assert!(parse_mint_restrictions("11111111111111111111111111111111", &mint_data(None)).is_err());
+        assert!(parse_mint_restrictions(TOKEN_PROGRAM_ID, &[0u8; 10]).is_err());
+    }
+}
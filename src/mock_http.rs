@@ -0,0 +1,119 @@
+// A local HTTP server for tests of code that talks to Jupiter, Jito or an
+// RPC node. Each path answers with its responses in order, repeating the
+// last; unknown paths get a 404. A route with a query string matches only
+// that exact target, ahead of its bare path. One request per connection, so retries and
+// sequences are easy to reason about.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: String,
+    pub delay_ms: u64,
+}
+
+pub fn reply(status: u16, body: &str) -> MockResponse {
+    MockResponse { status, headers: Vec::new(), body: body.to_string(), delay_ms: 0 }
+}
+
+impl MockResponse {
+    // Holds the response back, as a slow upstream would
+    pub fn delayed(mut self, delay_ms: u64) -> Self {
+        self.delay_ms = delay_ms;
+        self
+    }
+
+    pub fn header(mut self, name: &'static str, value: &str) -> Self {
+        self.headers.push((name, value.to_string()));
+        self
+    }
+}
+
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    // Every request received so far, raw: request line, headers and body
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn requests_to(&self, path: &str) -> Vec<String> {
+        self.requests().into_iter().filter(|request| request_path(request) == path).collect()
+    }
+}
+
+pub async fn serve(routes: Vec<(&str, Vec<MockResponse>)>) -> MockServer {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let seen = requests.clone();
+    let mut routes: HashMap<String, (Vec<MockResponse>, usize)> = routes.into_iter()
+        .map(|(path, responses)| (path.to_string(), (responses, 0)))
+        .collect();
+
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else { return };
+            let request = read_request(&mut socket).await;
+            seen.lock().unwrap().push(request.clone());
+
+            let not_found = reply(404, "{\"error\":\"not found\"}");
+            let target = request.split(' ').nth(1).unwrap_or_default();
+            let route = if routes.contains_key(target) { target } else { request_path(&request) };
+            let response = match routes.get_mut(route) {
+                Some((responses, served)) if !responses.is_empty() => {
+                    let index = (*served).min(responses.len() - 1);
+                    *served += 1;
+                    &responses[index]
+                }
+                _ => &not_found,
+            };
+            tokio::time::sleep(std::time::Duration::from_millis(response.delay_ms)).await;
+            let mut head = format!(
+                "HTTP/1.1 {} MOCK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n",
+                response.status, response.body.len()
+            );
+            for (name, value) in &response.headers {
+                head.push_str(&format!("{}: {}\r\n", name, value));
+            }
+            head.push_str("\r\n");
+            let _ = socket.write_all(head.as_bytes()).await;
+            let _ = socket.write_all(response.body.as_bytes()).await;
+            let _ = socket.shutdown().await;
+        }
+    });
+    MockServer { url, requests }
+}
+
+async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        let read = socket.read(&mut buf).await.unwrap_or(0);
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+        let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") else { continue };
+        let head = String::from_utf8_lossy(&request[..end]).to_lowercase();
+        let length: usize = head.lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(0);
+        if request.len() >= end + 4 + length {
+            break;
+        }
+    }
+    String::from_utf8_lossy(&request).to_string()
+}
+
+// The path of a raw request, without its query string
+pub fn request_path(request: &str) -> &str {
+    let target = request.split(' ').nth(1).unwrap_or_default();
+    target.split('?').next().unwrap_or_default()
+}
@@ -0,0 +1,43 @@
+use crate::{config::MonitoringConfig, types::RejectionReason};
+use anyhow::Result;
+use metrics_exporter_prometheus::PrometheusBuilder;
+use std::net::SocketAddr;
+use tracing::info;
+
+// Exports trade metrics on a Prometheus /metrics endpoint. Recording is a
+// no-op until `start` installs the exporter, and with metrics disabled.
+pub struct MonitoringService {
+    config: MonitoringConfig,
+}
+
+impl MonitoringService {
+    pub fn new(config: MonitoringConfig) -> Self {
+        Self { config }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        if !self.config.enable_metrics {
+            info!("📈 Metrics disabled");
+            return Ok(());
+        }
+
+        let addr = SocketAddr::from(([0, 0, 0, 0], self.config.prometheus_port));
+        PrometheusBuilder::new()
+            .with_http_listener(addr)
+            .install()
+            .map_err(|e| anyhow::anyhow!("Failed to start Prometheus exporter on {}: {}", addr, e))?;
+        info!("📈 Serving Prometheus metrics on {}", addr);
+        Ok(())
+    }
+
+    pub async fn record_trade_execution(&self, success: bool, profit: f64, execution_time_ms: i64) {
+        let outcome = if success { "success" } else { "failure" };
+        metrics::counter!("arbitrage_trades_total", "outcome" => outcome).increment(1);
+        metrics::gauge!("arbitrage_last_trade_profit").set(profit);
+        metrics::histogram!("arbitrage_trade_execution_ms").record(execution_time_ms as f64);
+    }
+
+    pub fn record_rejection(&self, reason: RejectionReason) {
+        metrics::counter!("arbitrage_rejections_total", "reason" => format!("{:?}", reason)).increment(1);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::arbitrage::{
+    arbitrage_service_server::{ArbitrageService, ArbitrageServiceServer},
+    HistoryRequest, HistoryResponse, OpportunityRequest, OpportunityResponse, Portfolio, PortfolioRequest, PriceStreamRequest,
+    RiskSettings, RiskSettingsResponse, ScanScopeRequest, ScanScopeResponse, StatsRequest, StatsResponse, TradeRequest,
+    TradeResponse, WatchPairResponse, WatchedPair,
+};
+use tonic::{transport::Server, Request, Response, Status};
+use tracing::{info, warn};
+
+// Exposes only the read RPCs of an ArbitrageService. Trading and settings
+// RPCs are never forwarded to the wrapped service, so they cannot be reached
+// no matter what credentials a caller has.
+pub struct ReadOnlyService<S> {
+    inner: S,
+}
+
+impl<S> ReadOnlyService<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+#[tonic::async_trait]
+impl<S: ArbitrageService> ArbitrageService for ReadOnlyService<S> {
+    type StreamPricesStream = S::StreamPricesStream;
+
+    async fn stream_prices(
+        &self,
+        request: Request<PriceStreamRequest>,
+    ) -> Result<Response<Self::StreamPricesStream>, Status> {
+        self.inner.stream_prices(request).await
+    }
+
+    async fn get_opportunities(
+        &self,
+        request: Request<OpportunityRequest>,
+    ) -> Result<Response<OpportunityResponse>, Status> {
+        self.inner.get_opportunities(request).await
+    }
+
+    async fn execute_trade(&self, _request: Request<TradeRequest>) -> Result<Response<TradeResponse>, Status> {
+        warn!("👁️ Rejected ExecuteTrade in observe mode");
+        Err(Status::unimplemented("ExecuteTrade is not available in observe mode"))
+    }
+
+    async fn get_portfolio(&self, request: Request<PortfolioRequest>) -> Result<Response<Portfolio>, Status> {
+        self.inner.get_portfolio(request).await
+    }
+
+    async fn update_risk_settings(
+        &self,
+        _request: Request<RiskSettings>,
+    ) -> Result<Response<RiskSettingsResponse>, Status> {
+        warn!("👁️ Rejected UpdateRiskSettings in observe mode");
+        Err(Status::unimplemented("UpdateRiskSettings is not available in observe mode"))
+    }
+
+    async fn get_stats(&self, request: Request<StatsRequest>) -> Result<Response<StatsResponse>, Status> {
+        self.inner.get_stats(request).await
+    }
+
+    async fn query_history(&self, request: Request<HistoryRequest>) -> Result<Response<HistoryResponse>, Status> {
+        self.inner.query_history(request).await
+    }
+
+    async fn watch_pair(&self, _request: Request<WatchedPair>) -> Result<Response<WatchPairResponse>, Status> {
+        warn!("👁️ Rejected WatchPair in observe mode");
+        Err(Status::unimplemented("WatchPair is not available in observe mode"))
+    }
+
+    async fn unwatch_pair(&self, _request: Request<WatchedPair>) -> Result<Response<WatchPairResponse>, Status> {
+        warn!("👁️ Rejected UnwatchPair in observe mode");
+        Err(Status::unimplemented("UnwatchPair is not available in observe mode"))
+    }
+
+    async fn get_scan_scope(&self, request: Request<ScanScopeRequest>) -> Result<Response<ScanScopeResponse>, Status> {
+        self.inner.get_scan_scope(request).await
+    }
+}
+
+pub async fn serve_read_only<S: ArbitrageService>(service: S, port: u16) -> anyhow::Result<()> {
+    let addr = format!("0.0.0.0:{}", port).parse()?;
+    info!("👁️ Starting read-only gRPC server on {}", addr);
+
+    Server::builder()
+        .add_service(ArbitrageServiceServer::new(ReadOnlyService::new(service)))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        arbitrage_engine::ArbitrageEngine, config::Config, dex_monitor::DexMonitor, grpc_server::ArbitrageGrpcServer,
+        monitoring::MonitoringService, portfolio_manager::PortfolioManager, risk_manager::RiskManager,
+    };
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+    use tonic::Code;
+
+    fn read_only() -> ReadOnlyService<ArbitrageGrpcServer> {
+        let mut config = Config::default();
+        config.jupiter.enabled = false;
+        config.trading.pending_trades_path = std::env::temp_dir()
+            .join(format!("observe_test_pending_{}.json", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        config.trading.history_path = None;
+
+        let risk_manager = Arc::new(RwLock::new(RiskManager::new(config.risk_settings.clone())));
+        let portfolio_manager = Arc::new(PortfolioManager::new(config.clone()));
+        let monitoring = Arc::new(MonitoringService::new(config.monitoring.clone()));
+        let engine = ArbitrageEngine::new(
+            config.clone(),
+            Arc::new(DexMonitor::new(config.dex_endpoints.clone())),
+            risk_manager.clone(),
+            portfolio_manager.clone(),
+            None,
+            None,
+            monitoring.clone(),
+        )
+        .unwrap();
+        ReadOnlyService::new(ArbitrageGrpcServer::new(Arc::new(engine), portfolio_manager, risk_manager, monitoring))
+    }
+
+    #[tokio::test]
+    async fn trading_and_settings_rpcs_are_unimplemented() {
+        let service = read_only();
+
+        let trade = service.execute_trade(Request::new(TradeRequest::default())).await.unwrap_err();
+        assert_eq!(trade.code(), Code::Unimplemented);
+        let settings = service.update_risk_settings(Request::new(RiskSettings::default())).await.unwrap_err();
+        assert_eq!(settings.code(), Code::Unimplemented);
+        let watch = service.watch_pair(Request::new(WatchedPair::default())).await.unwrap_err();
+        assert_eq!(watch.code(), Code::Unimplemented);
+
+        // Reads still reach the wrapped service
+        assert!(service.get_scan_scope(Request::new(ScanScopeRequest::default())).await.is_ok());
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use tracing::debug;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClaimOutcome {
+    // This wallet now owns the opportunity (or already did)
+    Claimed,
+    // Another wallet is working it; trading too would compete with ourselves
+    HeldBy(String),
+}
+
+#[derive(Debug, Clone)]
+struct Claim {
+    wallet: String,
+    claimed_at_ms: i64,
+}
+
+// Gives each opportunity to exactly one wallet at a time. A wallet that
+// declines before submitting (e.g. insufficient balance) releases its claim so
+// another wallet may take the opportunity; otherwise the claim stands until
+// `ttl_ms` has passed, by which time the mispricing is long gone.
+#[derive(Debug)]
+pub struct OpportunityClaims {
+    ttl_ms: i64,
+    claims: HashMap<String, Claim>,
+}
+
+impl OpportunityClaims {
+    pub fn new(ttl_ms: u64) -> Self {
+        Self {
+            ttl_ms: ttl_ms as i64,
+            claims: HashMap::new(),
+        }
+    }
+
+    pub fn try_claim(&mut self, opportunity_id: &str, wallet: &str, now_ms: i64) -> ClaimOutcome {
+        let ttl_ms = self.ttl_ms;
+        self.claims.retain(|_, claim| now_ms - claim.claimed_at_ms < ttl_ms);
+
+        match self.claims.get(opportunity_id) {
+            Some(claim) if claim.wallet != wallet => ClaimOutcome::HeldBy(claim.wallet.clone()),
+            Some(_) => ClaimOutcome::Claimed,
+            None => {
+                self.claims.insert(opportunity_id.to_string(), Claim {
+                    wallet: wallet.to_string(),
+                    claimed_at_ms: now_ms,
+                });
+                ClaimOutcome::Claimed
+            }
+        }
+    }
+
+    // Only the owning wallet can hand an opportunity back
+    pub fn release(&mut self, opportunity_id: &str, wallet: &str) {
+        if self.claims.get(opportunity_id).is_some_and(|claim| claim.wallet == wallet) {
+            self.claims.remove(opportunity_id);
+            debug!("🤝 {} released opportunity {} for other wallets", wallet, opportunity_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_wallet_holds_an_opportunity_until_it_releases_or_the_claim_expires() {
+        let mut claims = OpportunityClaims::new(1_000);
+        assert_eq!(claims.try_claim("opp", "alice", 0), ClaimOutcome::Claimed);
+        assert_eq!(claims.try_claim("opp", "alice", 10), ClaimOutcome::Claimed);
+        assert_eq!(claims.try_claim("opp", "bob", 10), ClaimOutcome::HeldBy("alice".to_string()));
+
+        // Only the owner can hand it back
+        claims.release("opp", "bob");
+        assert_eq!(claims.try_claim("opp", "bob", 20), ClaimOutcome::HeldBy("alice".to_string()));
+        claims.release("opp", "alice");
+        assert_eq!(claims.try_claim("opp", "bob", 30), ClaimOutcome::Claimed);
+
+        assert_eq!(claims.try_claim("opp", "alice", 1_030), ClaimOutcome::Claimed);
+    }
+}
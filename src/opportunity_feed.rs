@@ -0,0 +1,207 @@
+use crate::{
+    clock::Clock,
+    types::{ArbitrageOpportunity, RejectionReason, StrategyKind},
+};
+use std::collections::HashMap;
+use tokio::sync::{broadcast, RwLock};
+use tracing::debug;
+
+const FEED_CAPACITY: usize = 256;
+
+// What changed since the previous scan. Opportunity ids hash the prices, so a
+// repriced opportunity arrives under a new id and names the one it replaces.
+#[derive(Debug, Clone)]
+pub enum OpportunityEvent {
+    New(ArbitrageOpportunity),
+    // Profit moved by more than the change threshold
+    Changed { opportunity: ArbitrageOpportunity, replaces: String },
+    // Gone from the latest scan, or past its expiry
+    Expired { id: String },
+}
+
+// The same trade across scans, whatever its prices
+type OpportunityKey = (String, String, String, StrategyKind);
+
+fn key_of(opportunity: &ArbitrageOpportunity) -> OpportunityKey {
+    (
+        opportunity.token_pair.clone(),
+        opportunity.buy_dex.clone(),
+        opportunity.sell_dex.clone(),
+        opportunity.strategy,
+    )
+}
+
+// Fan-out of scan results to stream subscribers as deltas: each scan emits
+// only new opportunities, ones whose profit moved by more than
+// `change_threshold_pct` percentage points, and expirations. Expired
+// opportunities are never sent, replayed on (re)subscribe, or handed out by
+// `recv_live`, even if a slow subscriber only reads them later.
+pub struct OpportunityFeed {
+    sender: broadcast::Sender<OpportunityEvent>,
+    live: RwLock<HashMap<OpportunityKey, ArbitrageOpportunity>>,
+    change_threshold_pct: f64,
+}
+
+impl OpportunityFeed {
+    pub fn new(change_threshold_pct: f64) -> Self {
+        let (sender, _) = broadcast::channel(FEED_CAPACITY);
+        Self {
+            sender,
+            live: RwLock::new(HashMap::new()),
+            change_threshold_pct,
+        }
+    }
+
+    // Takes a full scan's results and emits what changed since the last one
+    pub async fn publish(&self, opportunities: &[ArbitrageOpportunity], now: i64) {
+        let mut live = self.live.write().await;
+        let mut previous = std::mem::take(&mut *live);
+
+        for opportunity in opportunities.iter().filter(|o| !o.is_expired(now)) {
+            let key = key_of(opportunity);
+            let event = match previous.remove(&key) {
+                None => OpportunityEvent::New(opportunity.clone()),
+                Some(prior) if (opportunity.profit_percentage - prior.profit_percentage).abs() > self.change_threshold_pct => {
+                    OpportunityEvent::Changed { opportunity: opportunity.clone(), replaces: prior.id }
+                }
+                Some(mut prior) => {
+                    // Unchanged: subscribers keep the id they have, valid for as long as the new sighting
+                    prior.expires_at = prior.expires_at.max(opportunity.expires_at);
+                    live.insert(key, prior);
+                    continue;
+                }
+            };
+            live.insert(key, opportunity.clone());
+            // No subscribers is fine; the snapshot still serves the next one
+            let _ = self.sender.send(event);
+        }
+
+        for (_, gone) in previous {
+            debug!("⌛ Opportunity {} ({}) expired", gone.id, gone.token_pair);
+            let _ = self.sender.send(OpportunityEvent::Expired { id: gone.id });
+        }
+    }
+
+    // Still-valid opportunities to replay, plus a receiver for what changes next
+    pub async fn subscribe(&self, now: i64) -> (Vec<ArbitrageOpportunity>, broadcast::Receiver<OpportunityEvent>) {
+        let receiver = self.sender.subscribe();
+        (self.live(now).await, receiver)
+    }
+
+    pub async fn live(&self, now: i64) -> Vec<ArbitrageOpportunity> {
+        self.live.read().await.values()
+            .filter(|opportunity| !opportunity.is_expired(now))
+            .cloned()
+            .collect()
+    }
+
+    pub async fn find(&self, id: &str) -> Option<ArbitrageOpportunity> {
+        self.live.read().await.values().find(|opportunity| opportunity.id == id).cloned()
+    }
+
+    // Keeps the opportunity listed, but as unprofitable and with the reason
+    // execution turned it down, until the next scan replaces it
+    pub async fn mark_rejected(&self, id: &str, reason: RejectionReason) {
+        let mut live = self.live.write().await;
+        if let Some(opportunity) = live.values_mut().find(|opportunity| opportunity.id == id) {
+            opportunity.is_profitable = false;
+            opportunity.rejection_reason = Some(reason);
+        }
+    }
+}
+
+// Next event that is still relevant; None once the feed is closed. New and
+// changed opportunities that expired while queued are skipped.
+pub async fn recv_live(
+    receiver: &mut broadcast::Receiver<OpportunityEvent>,
+    clock: &dyn Clock,
+) -> Option<OpportunityEvent> {
+    loop {
+        match receiver.recv().await {
+            Ok(OpportunityEvent::New(opportunity) | OpportunityEvent::Changed { opportunity, .. })
+                if opportunity.is_expired(clock.now_ms()) =>
+            {
+                debug!("⌛ Dropping expired opportunity {} from stream", opportunity.id);
+            }
+            Ok(event) => return Some(event),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                debug!("⌛ Opportunity subscriber lagged, skipped {}", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    fn opportunity(id: &str, profit_percentage: f64, expires_at: i64) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            id: id.to_string(),
+            token_pair: "SOL/USDC".to_string(),
+            buy_dex: "Raydium".to_string(),
+            sell_dex: "Orca".to_string(),
+            profit_percentage,
+            expires_at,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn emits_only_deltas_between_scans() {
+        let feed = OpportunityFeed::new(0.1);
+        let (_, mut receiver) = feed.subscribe(0).await;
+
+        feed.publish(&[opportunity("a", 1.0, 0)], 0).await;
+        assert!(matches!(receiver.try_recv(), Ok(OpportunityEvent::New(o)) if o.id == "a"));
+
+        // A small move keeps the original id and sends nothing
+        feed.publish(&[opportunity("b", 1.05, 0)], 0).await;
+        assert!(receiver.try_recv().is_err());
+        assert!(feed.find("a").await.is_some());
+
+        feed.publish(&[opportunity("c", 1.5, 0)], 0).await;
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(OpportunityEvent::Changed { opportunity, replaces }) if opportunity.id == "c" && replaces == "a"
+        ));
+
+        feed.publish(&[], 0).await;
+        assert!(matches!(receiver.try_recv(), Ok(OpportunityEvent::Expired { id }) if id == "c"));
+        assert!(feed.live(0).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn expired_opportunities_are_neither_replayed_nor_streamed() {
+        let feed = OpportunityFeed::new(0.1);
+        let (_, mut receiver) = feed.subscribe(0).await;
+        feed.publish(&[opportunity("a", 1.0, 100)], 0).await;
+        feed.publish(&[], 50).await;
+        feed.publish(&[opportunity("b", 1.0, 1_000)], 50).await;
+
+        assert!(feed.live(200).await.iter().all(|o| o.id == "b"));
+        let clock = ManualClock::new(200);
+        assert!(matches!(recv_live(&mut receiver, &clock).await, Some(OpportunityEvent::Expired { id }) if id == "a"));
+        assert!(matches!(recv_live(&mut receiver, &clock).await, Some(OpportunityEvent::New(o)) if o.id == "b"));
+
+        clock.set(2_000);
+        feed.publish(&[opportunity("c", 1.0, 1_500)], 0).await;
+        drop(feed);
+        assert!(recv_live(&mut receiver, &clock).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn rejection_marks_the_listed_opportunity_unprofitable() {
+        let feed = OpportunityFeed::new(0.1);
+        let mut listed = opportunity("a", 1.0, 0);
+        listed.is_profitable = true;
+        feed.publish(&[listed], 0).await;
+        feed.mark_rejected("a", RejectionReason::Cooldown).await;
+
+        let rejected = feed.find("a").await.unwrap();
+        assert!(!rejected.is_profitable);
+        assert_eq!(rejected.rejection_reason, Some(RejectionReason::Cooldown));
+    }
+}
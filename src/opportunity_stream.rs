@@ -0,0 +1,153 @@
+use crate::arbitrage::{
+    arbitrage_service_client::ArbitrageServiceClient, ArbitrageOpportunity, OpportunityRequest,
+};
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use tonic::transport::Channel;
+use tracing::{debug, info, warn};
+
+// Opportunity ids remembered for de-duplication across polls and reconnects
+const SEEN_IDS_CAPACITY: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    // In a gap: opportunities published until the reconnect are missed
+    Disconnected { error: String, retry_in: Duration },
+}
+
+// Client-side consumer of a bot's opportunities that survives dropped
+// connections: it reconnects with exponential backoff, re-applies the same
+// filter, and never yields the same opportunity twice. The service has no
+// server-streaming opportunities RPC, so this polls GetOpportunities.
+pub struct OpportunityStreamClient {
+    endpoint: String,
+    request: OpportunityRequest,
+    poll_interval: Duration,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    state: watch::Sender<ConnectionState>,
+}
+
+impl OpportunityStreamClient {
+    pub fn new(endpoint: impl Into<String>, request: OpportunityRequest) -> Self {
+        let (state, _) = watch::channel(ConnectionState::Connecting);
+        Self {
+            endpoint: endpoint.into(),
+            request,
+            poll_interval: Duration::from_millis(500),
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(30),
+            state,
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    // Take this before `into_stream` to learn when the stream is in a gap
+    pub fn connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.state.subscribe()
+    }
+
+    // Runs until the returned stream is dropped
+    pub fn into_stream(self) -> impl Stream<Item = ArbitrageOpportunity> {
+        let (sender, receiver) = mpsc::channel(256);
+        tokio::spawn(self.run(sender));
+        ReceiverStream::new(receiver)
+    }
+
+    async fn run(self, sender: mpsc::Sender<ArbitrageOpportunity>) {
+        let mut seen = SeenIds::default();
+        let mut backoff = self.initial_backoff;
+
+        loop {
+            self.state.send_replace(ConnectionState::Connecting);
+            let error = match ArbitrageServiceClient::connect(self.endpoint.clone()).await {
+                Ok(mut client) => {
+                    info!("🔌 Connected to opportunity stream at {}", self.endpoint);
+                    self.state.send_replace(ConnectionState::Connected);
+                    backoff = self.initial_backoff;
+                    match self.poll(&mut client, &sender, &mut seen).await {
+                        Some(error) => error,
+                        // The consumer dropped the stream
+                        None => return,
+                    }
+                }
+                Err(e) => e.to_string(),
+            };
+
+            warn!("🔌 Opportunity stream from {} lost ({}), reconnecting in {:?}", self.endpoint, error, backoff);
+            self.state.send_replace(ConnectionState::Disconnected { error, retry_in: backoff });
+            tokio::select! {
+                _ = tokio::time::sleep(backoff) => {}
+                _ = sender.closed() => return,
+            }
+            backoff = (backoff * 2).min(self.max_backoff);
+        }
+    }
+
+    // Returns the error that ended the connection, or None once the consumer is gone
+    async fn poll(
+        &self,
+        client: &mut ArbitrageServiceClient<Channel>,
+        sender: &mpsc::Sender<ArbitrageOpportunity>,
+        seen: &mut SeenIds,
+    ) -> Option<String> {
+        let mut interval = tokio::time::interval(self.poll_interval);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = sender.closed() => return None,
+            }
+
+            let response = match client.get_opportunities(self.request.clone()).await {
+                Ok(response) => response.into_inner(),
+                Err(status) => return Some(status.to_string()),
+            };
+
+            for opportunity in response.opportunities {
+                if !seen.insert(&opportunity.id) {
+                    continue;
+                }
+                debug!("📨 Streamed opportunity {} ({})", opportunity.id, opportunity.token_pair);
+                if sender.send(opportunity).await.is_err() {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct SeenIds {
+    ids: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl SeenIds {
+    // Returns false if the id was already seen
+    fn insert(&mut self, id: &str) -> bool {
+        if !self.ids.insert(id.to_string()) {
+            return false;
+        }
+        self.order.push_back(id.to_string());
+        if self.order.len() > SEEN_IDS_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,145 @@
+use crate::rpc_client::SolanaRpcClient;
+use anyhow::Result;
+use base64::Engine;
+use futures_util::future::BoxFuture;
+use serde_json::json;
+use std::collections::HashMap;
+
+// Independent USD price source used to sanity-check DEX/Jupiter prices
+pub trait Oracle: Send + Sync {
+    fn price<'a>(&'a self, mint: &'a str) -> BoxFuture<'a, Result<f64>>;
+}
+
+const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+const PYTH_STATUS_TRADING: u32 = 1;
+const EXPO_OFFSET: usize = 20;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_STATUS_OFFSET: usize = 224;
+const AGG_PUB_SLOT_OFFSET: usize = 232;
+
+// The aggregate price and the slot it was published in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PythPrice {
+    pub price: f64,
+    pub pub_slot: u64,
+}
+
+// Reads Pyth v2 price accounts directly from chain
+pub struct PythOracle {
+    rpc: SolanaRpcClient,
+    price_accounts: HashMap<String, String>,
+    max_staleness_slots: Option<u64>,
+}
+
+impl PythOracle {
+    pub fn new(rpc_url: String, timeout_ms: u64, price_accounts: HashMap<String, String>) -> Self {
+        Self {
+            rpc: SolanaRpcClient::new(rpc_url, timeout_ms),
+            price_accounts,
+            max_staleness_slots: None,
+        }
+    }
+
+    // Prices published more than `max_slots` before the slot they're read at are refused
+    pub fn with_max_staleness_slots(mut self, max_slots: Option<u64>) -> Self {
+        self.max_staleness_slots = max_slots;
+        self
+    }
+
+    async fn fetch_price(&self, mint: &str) -> Result<f64> {
+        let account = self.price_accounts
+            .get(mint)
+            .ok_or_else(|| anyhow::anyhow!("No Pyth price account configured for {}", mint))?;
+
+        let result = self.rpc.call("getAccountInfo", json!([account, { "encoding": "base64" }])).await?;
+        let encoded = result["value"]["data"][0]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Pyth price account {} not found", account))?;
+        let data = base64::engine::general_purpose::STANDARD.decode(encoded)?;
+
+        let price = parse_pyth_price(&data)?;
+        if let (Some(max_slots), Some(slot)) = (self.max_staleness_slots, result["context"]["slot"].as_u64()) {
+            check_staleness(price.pub_slot, slot, max_slots)?;
+        }
+        Ok(price.price)
+    }
+}
+
+impl Oracle for PythOracle {
+    fn price<'a>(&'a self, mint: &'a str) -> BoxFuture<'a, Result<f64>> {
+        Box::pin(self.fetch_price(mint))
+    }
+}
+
+pub fn parse_pyth_price(data: &[u8]) -> Result<PythPrice> {
+    if data.len() < AGG_PUB_SLOT_OFFSET + 8 {
+        return Err(anyhow::anyhow!("Pyth price account too short: {} bytes", data.len()));
+    }
+
+    let read_u32 = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+    if read_u32(0) != PYTH_MAGIC {
+        return Err(anyhow::anyhow!("Not a Pyth price account"));
+    }
+    if read_u32(AGG_STATUS_OFFSET) != PYTH_STATUS_TRADING {
+        return Err(anyhow::anyhow!("Pyth price is not currently trading"));
+    }
+
+    let expo = i32::from_le_bytes(data[EXPO_OFFSET..EXPO_OFFSET + 4].try_into().unwrap());
+    let price = i64::from_le_bytes(data[AGG_PRICE_OFFSET..AGG_PRICE_OFFSET + 8].try_into().unwrap());
+    let pub_slot = u64::from_le_bytes(data[AGG_PUB_SLOT_OFFSET..AGG_PUB_SLOT_OFFSET + 8].try_into().unwrap());
+
+    let price = price as f64 * 10f64.powi(expo);
+    if !price.is_finite() || price <= 0.0 {
+        return Err(anyhow::anyhow!("Pyth price {} is not positive", price));
+    }
+    Ok(PythPrice { price, pub_slot })
+}
+
+pub fn check_staleness(pub_slot: u64, current_slot: u64, max_slots: u64) -> Result<()> {
+    let age = current_slot.saturating_sub(pub_slot);
+    if age > max_slots {
+        return Err(anyhow::anyhow!("Pyth price is {} slots old (max {})", age, max_slots));
+    }
+    Ok(())
+}
+
+// Percentage deviation of an execution price from the oracle-implied price
+pub fn deviation_pct(execution_price: f64, oracle_price: f64) -> f64 {
+    ((execution_price - oracle_price) / oracle_price).abs() * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price_account(price: i64, expo: i32, pub_slot: u64) -> Vec<u8> {
+        let mut data = vec![0u8; AGG_PUB_SLOT_OFFSET + 8];
+        data[0..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+        data[EXPO_OFFSET..EXPO_OFFSET + 4].copy_from_slice(&expo.to_le_bytes());
+        data[AGG_PRICE_OFFSET..AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+        data[AGG_STATUS_OFFSET..AGG_STATUS_OFFSET + 4].copy_from_slice(&PYTH_STATUS_TRADING.to_le_bytes());
+        data[AGG_PUB_SLOT_OFFSET..AGG_PUB_SLOT_OFFSET + 8].copy_from_slice(&pub_slot.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn parses_the_aggregate_price_and_its_slot() {
+        let price = parse_pyth_price(&price_account(15_025_000_000, -8, 900)).unwrap();
+        assert!((price.price - 150.25).abs() < 1e-9);
+        assert_eq!(price.pub_slot, 900);
+    }
+
+    #[test]
+    fn rejects_prices_that_cannot_anchor_a_deviation() {
+        assert!(parse_pyth_price(&price_account(0, -8, 900)).is_err());
+        assert!(parse_pyth_price(&price_account(-5, -8, 900)).is_err());
+    }
+
+    #[test]
+    fn rejects_stale_prices() {
+        assert!(check_staleness(900, 925, 25).is_ok());
+        assert!(check_staleness(900, 926, 25).is_err());
+        // A read from a lagging node is not "from the future"
+        assert!(check_staleness(900, 899, 25).is_ok());
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{
+    config::Config,
+    jupiter_client::JupiterClient,
+    rpc_client::SolanaRpcClient,
+    transaction_simulator::TOKEN_PROGRAM_ID,
+    types::{Portfolio, TokenBalance, LAMPORTS_PER_SOL, NATIVE_SOL_MINT, WSOL_MINT},
+};
+use anyhow::Result;
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tracing::{debug, warn};
+
+// Values the configured wallet: native SOL plus every SPL token account,
+// priced through Jupiter. Balances without a price are kept at $0.
+pub struct PortfolioManager {
+    wallet_address: String,
+    rpc: SolanaRpcClient,
+    jupiter_client: Option<JupiterClient>,
+}
+
+impl PortfolioManager {
+    pub fn new(config: Config) -> Self {
+        let jupiter_client = if config.jupiter.enabled {
+            match JupiterClient::new_with_config(&config.jupiter) {
+                Ok(client) => Some(client),
+                Err(e) => {
+                    warn!("⚠️ Portfolio balances won't be priced: cannot build Jupiter client: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        Self {
+            wallet_address: config.wallet.public_key.clone(),
+            rpc: SolanaRpcClient::new(config.rpc_endpoints.primary.clone(), config.rpc_endpoints.timeout_ms),
+            jupiter_client,
+        }
+    }
+
+    pub async fn get_portfolio(&self) -> Result<Portfolio> {
+        let mut amounts = vec![(NATIVE_SOL_MINT.to_string(), self.get_sol_balance().await?)];
+        amounts.extend(self.get_token_balances().await?);
+
+        let prices = self.get_prices(&amounts).await;
+        let symbols = self.get_symbols().await;
+
+        let balances: Vec<TokenBalance> = amounts.into_iter()
+            .map(|(mint, amount)| {
+                // Native SOL is priced as wSOL
+                let price_mint = if mint == NATIVE_SOL_MINT { WSOL_MINT } else { mint.as_str() };
+                let price = prices.get(price_mint).copied().unwrap_or(0.0);
+                let symbol = if mint == NATIVE_SOL_MINT {
+                    "SOL".to_string()
+                } else {
+                    symbols.get(&mint).cloned().unwrap_or_else(|| mint.chars().take(6).collect())
+                };
+                TokenBalance {
+                    token_mint: mint,
+                    symbol,
+                    amount,
+                    value_usd: amount * price,
+                    price,
+                }
+            })
+            .collect();
+        let total_value_usd = balances.iter().map(|b| b.value_usd).sum();
+
+        Ok(Portfolio {
+            wallet_address: self.wallet_address.clone(),
+            balances,
+            total_value_usd,
+            available_balance: total_value_usd,
+            last_updated: Utc::now().timestamp_millis(),
+        })
+    }
+
+    async fn get_sol_balance(&self) -> Result<f64> {
+        let result = self.rpc.call("getBalance", json!([self.wallet_address])).await?;
+        let lamports = result["value"].as_u64()
+            .ok_or_else(|| anyhow::anyhow!("Malformed getBalance response for {}", self.wallet_address))?;
+        Ok(lamports as f64 / LAMPORTS_PER_SOL)
+    }
+
+    // (mint, UI amount) of every non-empty token account the wallet owns
+    async fn get_token_balances(&self) -> Result<Vec<(String, f64)>> {
+        let result = self.rpc.call("getTokenAccountsByOwner", json!([
+            self.wallet_address,
+            { "programId": TOKEN_PROGRAM_ID },
+            { "encoding": "jsonParsed" },
+        ])).await?;
+
+        let accounts = result["value"].as_array()
+            .ok_or_else(|| anyhow::anyhow!("Malformed getTokenAccountsByOwner response"))?;
+        let mut totals: HashMap<String, f64> = HashMap::new();
+        for account in accounts {
+            let info = &account["account"]["data"]["parsed"]["info"];
+            let (Some(mint), Some(amount)) = (info["mint"].as_str(), parse_ui_amount(&info["tokenAmount"])) else {
+                debug!("Skipping unparseable token account {}", account["pubkey"]);
+                continue;
+            };
+            if amount > 0.0 {
+                *totals.entry(mint.to_string()).or_default() += amount;
+            }
+        }
+        Ok(totals.into_iter().collect())
+    }
+
+    async fn get_prices(&self, amounts: &[(String, f64)]) -> HashMap<String, f64> {
+        let Some(jupiter_client) = &self.jupiter_client else {
+            return HashMap::new();
+        };
+        let ids: Vec<String> = amounts.iter()
+            .map(|(mint, _)| if mint == NATIVE_SOL_MINT { WSOL_MINT.to_string() } else { mint.clone() })
+            .collect();
+        match jupiter_client.get_price(&ids).await {
+            Ok(fetch) => fetch.prices,
+            Err(e) => {
+                warn!("⚠️ Cannot price portfolio balances: {}", e);
+                HashMap::new()
+            }
+        }
+    }
+
+    async fn get_symbols(&self) -> HashMap<String, String> {
+        let Some(jupiter_client) = &self.jupiter_client else {
+            return HashMap::new();
+        };
+        match jupiter_client.get_tokens().await {
+            Ok(tokens) => tokens.into_iter().map(|(mint, token)| (mint, token.symbol)).collect(),
+            Err(e) => {
+                debug!("No token symbols for the portfolio: {}", e);
+                HashMap::new()
+            }
+        }
+    }
+}
+
+fn parse_ui_amount(token_amount: &Value) -> Option<f64> {
+    token_amount["uiAmountString"].as_str().and_then(|amount| amount.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_http::{reply, serve};
+    use crate::types::RENT_EXEMPT_MINIMUM_SOL;
+
+    fn token_account(mint: &str, ui_amount: &str) -> Value {
+        json!({
+            "pubkey": solana_sdk::pubkey::Pubkey::new_unique().to_string(),
+            "account": { "data": { "parsed": { "info": { "mint": mint, "tokenAmount": { "uiAmountString": ui_amount } } } } },
+        })
+    }
+
+    #[tokio::test]
+    async fn native_and_wrapped_sol_value_as_one_balance_less_the_native_reserve() {
+        const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        // 2 SOL native, 1.5 wrapped and 50 USDC, with SOL at $100
+        let rpc = serve(vec![("/", vec![
+            reply(200, &json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": 2_000_000_000u64 } }).to_string()),
+            reply(200, &json!({
+                "jsonrpc": "2.0", "id": 1,
+                "result": { "value": [token_account(WSOL_MINT, "1.5"), token_account(USDC, "50")] },
+            }).to_string()),
+        ])]).await;
+        let price = |id: &str, price: f64| json!({ "id": id, "mint_symbol": "", "vs_token": USDC, "vs_token_symbol": "USDC", "price": price });
+        let jupiter = serve(vec![("/price", vec![
+            reply(200, &json!({ WSOL_MINT: price(WSOL_MINT, 100.0), USDC: price(USDC, 1.0) }).to_string()),
+        ])]).await;
+
+        let mut config = Config::default();
+        config.wallet.public_key = solana_sdk::pubkey::Pubkey::new_unique().to_string();
+        config.rpc_endpoints.primary = rpc.url.clone();
+        config.jupiter.enabled = true;
+        config.jupiter.api_url = jupiter.url.clone();
+        config.jupiter.token_cache_path = None;
+        let portfolio = PortfolioManager::new(config.clone()).get_portfolio().await.unwrap();
+
+        // Tracked apart for trade mechanics
+        let sol_balances = |portfolio: &Portfolio| portfolio.balances.iter()
+            .filter(|b| b.token_mint == NATIVE_SOL_MINT || b.token_mint == WSOL_MINT)
+            .count();
+        assert_eq!(sol_balances(&portfolio), 2);
+        assert!((portfolio.total_value_usd - 400.0).abs() < 1e-9);
+
+        let consolidated = portfolio.consolidate_sol(config.wallet.min_sol_balance);
+        assert_eq!(sol_balances(&consolidated), 1);
+        assert_eq!(consolidated.balances[0].amount, 3.5);
+        assert!((consolidated.total_value_usd - 400.0).abs() < 1e-9);
+        let reserve_usd = (RENT_EXEMPT_MINIMUM_SOL + config.wallet.min_sol_balance) * 100.0;
+        assert!((consolidated.available_balance - (400.0 - reserve_usd)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{
+    config::Config,
+    jupiter_client::{JupiterClient, JupiterQuoteRequest},
+    rpc_client::SolanaRpcClient,
+    signer::{KeypairSigner, RemoteSigner, TransactionSigner},
+    types::{LAMPORTS_PER_SOL, WSOL_MINT},
+};
+use anyhow::Result;
+use serde_json::json;
+use std::future::Future;
+use std::path::Path;
+use std::time::Duration;
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: String,
+    // A failed critical check means the bot must not go live
+    pub critical: bool,
+    pub passed: bool,
+    pub detail: String,
+}
+
+async fn run_check<F>(name: &str, critical: bool, check: F) -> CheckResult
+where
+    F: Future<Output = Result<String>>,
+{
+    let (passed, detail) = match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(Ok(detail)) => (true, detail),
+        Ok(Err(e)) => (false, e.to_string()),
+        Err(_) => (false, format!("timed out after {:?}", CHECK_TIMEOUT)),
+    };
+    CheckResult { name: name.to_string(), critical, passed, detail }
+}
+
+// Runs every setup check; the config itself already passed validation on load
+pub async fn run_preflight(config: &Config, jupiter_client: Option<&JupiterClient>) -> Vec<CheckResult> {
+    let rpc = SolanaRpcClient::new(config.rpc_endpoints.primary.clone(), config.rpc_endpoints.timeout_ms);
+    let mut results = vec![CheckResult {
+        name: "Config".to_string(),
+        critical: true,
+        passed: true,
+        detail: "loaded and validated".to_string(),
+    }];
+
+    results.push(run_check("RPC", true, async {
+        let health = rpc.call("getHealth", json!([])).await?;
+        Ok(format!("{} is {}", config.rpc_endpoints.primary, health))
+    }).await);
+
+    for endpoint in &config.rpc_endpoints.secondary {
+        let secondary = SolanaRpcClient::new(endpoint.clone(), config.rpc_endpoints.timeout_ms);
+        results.push(run_check("Secondary RPC", false, async {
+            let health = secondary.call("getHealth", json!([])).await?;
+            Ok(format!("{} is {}", endpoint, health))
+        }).await);
+    }
+
+    results.push(run_check("Wallet", true, async { check_wallet(config, &rpc).await }).await);
+
+    results.push(match jupiter_client {
+        Some(jupiter_client) => run_check("Jupiter", true, async {
+            let quote = jupiter_client.get_quote(JupiterQuoteRequest {
+                input_mint: WSOL_MINT.to_string(),
+                output_mint: USDC_MINT.to_string(),
+                amount: 10_000_000, // 0.01 SOL
+                slippage_bps: config.jupiter.default_slippage_bps,
+                swap_mode: Some("ExactIn".to_string()),
+                dexes: None,
+                exclude_dexes: None,
+                platform_fee_bps: None,
+                max_accounts: None,
+                context_slot: None,
+            }).await?;
+            Ok(format!("test quote SOL -> USDC returned {} out", quote.out_amount))
+        }).await,
+        None => skipped("Jupiter", "disabled in config"),
+    });
+
+    results.push(if config.jito.enabled {
+        run_check("Jito", true, async {
+            let response = reqwest::Client::new().get(&config.jito.bundle_endpoint).send().await?;
+            Ok(format!("{} responded {}", config.jito.bundle_endpoint, response.status()))
+        }).await
+    } else {
+        skipped("Jito", "disabled in config")
+    });
+
+    results.push(run_check("Persistence", true, async {
+        let mut paths = vec![config.trading.pending_trades_path.clone()];
+        paths.extend(config.trading.history_path.clone());
+        for path in &paths {
+            check_writable(Path::new(path))?;
+        }
+        Ok(format!("writable: {}", paths.join(", ")))
+    }).await);
+
+    results
+}
+
+fn skipped(name: &str, reason: &str) -> CheckResult {
+    CheckResult {
+        name: name.to_string(),
+        critical: false,
+        passed: true,
+        detail: format!("skipped ({})", reason),
+    }
+}
+
+async fn check_wallet(config: &Config, rpc: &SolanaRpcClient) -> Result<String> {
+    let signer: Box<dyn TransactionSigner> = match &config.wallet.remote_signer {
+        Some(remote) => Box::new(RemoteSigner::new(remote, &config.wallet.public_key)?),
+        None => Box::new(KeypairSigner::from_base58(&config.wallet.private_key)?),
+    };
+    // A remote signer proves it is reachable and holds the key by signing
+    signer.sign_message(b"preflight").await?;
+
+    let public_key = signer.pubkey().to_string();
+    if public_key != config.wallet.public_key {
+        return Err(anyhow::anyhow!(
+            "signer holds {}, not the configured {}",
+            public_key, config.wallet.public_key
+        ));
+    }
+
+    let balance = rpc.call("getBalance", json!([public_key])).await?;
+    let sol = balance["value"].as_u64().unwrap_or(0) as f64 / LAMPORTS_PER_SOL;
+    if sol < config.wallet.min_sol_balance {
+        return Err(anyhow::anyhow!(
+            "balance {:.4} SOL is below min_sol_balance {:.4}",
+            sol, config.wallet.min_sol_balance
+        ));
+    }
+    Ok(format!("{} holds {:.4} SOL", public_key, sol))
+}
+
+// Probes the file's directory without touching the file itself
+fn check_writable(path: &Path) -> Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let probe = dir.join(".preflight_write_probe");
+    std::fs::write(&probe, b"ok")
+        .map_err(|e| anyhow::anyhow!("{} is not writable: {}", dir.display(), e))?;
+    std::fs::remove_file(&probe)?;
+    Ok(())
+}
@@ -0,0 +1,227 @@
+use crate::{supervisor::Heartbeat, types::PriceData};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::PathBuf;
+use tokio::sync::{Mutex, RwLock};
+use tracing::debug;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceSample {
+    pub timestamp: i64,
+    pub token_pair: String,
+    pub dex_name: String,
+    pub price: f64,
+    pub liquidity: f64,
+    pub price_impact: f64,
+}
+
+impl PriceSample {
+    fn from_price(price: &PriceData, timestamp: i64) -> Self {
+        Self {
+            timestamp,
+            token_pair: price.token_pair.clone(),
+            dex_name: price.dex_name.clone(),
+            price: price.price,
+            liquidity: price.liquidity,
+            price_impact: price.price_impact,
+        }
+    }
+}
+
+// Rolling per-pair price time series. Sampling only touches memory; samples
+// reach disk (JSON lines) when `flush` runs from the background flusher, and
+// anything older than the retention window is pruned from both.
+pub struct PriceHistory {
+    path: Option<PathBuf>,
+    sample_interval_ms: i64,
+    retention_ms: i64,
+    samples: RwLock<HashMap<String, VecDeque<PriceSample>>>,
+    // Last sample time per pair and DEX, to enforce the sampling interval
+    last_sampled: RwLock<HashMap<(String, String), i64>>,
+    unflushed: Mutex<Vec<PriceSample>>,
+    pruned_since_compaction: Mutex<usize>,
+}
+
+impl PriceHistory {
+    pub fn open(path: Option<PathBuf>, sample_interval_ms: u64, retention_secs: u64, now_ms: i64) -> Result<Self> {
+        let retention_ms = (retention_secs * 1000) as i64;
+        let mut samples: HashMap<String, VecDeque<PriceSample>> = HashMap::new();
+        if let Some(path) = &path {
+            match std::fs::read_to_string(path) {
+                Ok(content) => {
+                    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+                        let sample: PriceSample = serde_json::from_str(line)?;
+                        if now_ms - sample.timestamp <= retention_ms {
+                            samples.entry(sample.token_pair.clone()).or_default().push_back(sample);
+                        }
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        for series in samples.values_mut() {
+            series.make_contiguous().sort_by_key(|sample| sample.timestamp);
+        }
+
+        Ok(Self {
+            path,
+            sample_interval_ms: sample_interval_ms as i64,
+            retention_ms,
+            samples: RwLock::new(samples),
+            last_sampled: RwLock::new(HashMap::new()),
+            unflushed: Mutex::new(Vec::new()),
+            // Forces one compaction so expired samples loaded from disk are dropped
+            pruned_since_compaction: Mutex::new(1),
+        })
+    }
+
+    pub async fn record(&self, prices: &[PriceData], now_ms: i64) {
+        let mut last_sampled = self.last_sampled.write().await;
+        let mut new_samples = Vec::new();
+        for price in prices {
+            let key = (price.token_pair.clone(), price.dex_name.clone());
+            let due = last_sampled.get(&key).is_none_or(|last| now_ms - last >= self.sample_interval_ms);
+            if due {
+                last_sampled.insert(key, now_ms);
+                new_samples.push(PriceSample::from_price(price, now_ms));
+            }
+        }
+        drop(last_sampled);
+        if new_samples.is_empty() {
+            return;
+        }
+
+        let mut pruned = 0;
+        let mut samples = self.samples.write().await;
+        for sample in &new_samples {
+            let series = samples.entry(sample.token_pair.clone()).or_default();
+            series.push_back(sample.clone());
+            while series.front().is_some_and(|oldest| now_ms - oldest.timestamp > self.retention_ms) {
+                series.pop_front();
+                pruned += 1;
+            }
+        }
+        drop(samples);
+
+        *self.pruned_since_compaction.lock().await += pruned;
+        self.unflushed.lock().await.extend(new_samples);
+    }
+
+    // Samples for a pair within [from_ts, to_ts], oldest first
+    pub async fn query(&self, token_pair: &str, from_ts: i64, to_ts: i64) -> Vec<PriceSample> {
+        self.samples.read().await
+            .get(token_pair)
+            .map(|series| series.iter()
+                .filter(|sample| sample.timestamp >= from_ts && sample.timestamp <= to_ts)
+                .cloned()
+                .collect())
+            .unwrap_or_default()
+    }
+
+    pub async fn run_flusher(&self, flush_interval_ms: u64, heartbeat: Heartbeat) -> Result<()> {
+        let mut interval = tokio::time::interval(std::time::Duration::from_millis(flush_interval_ms));
+        loop {
+            interval.tick().await;
+            heartbeat.beat();
+            self.flush().await?;
+        }
+    }
+
+    pub async fn flush(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            self.unflushed.lock().await.clear();
+            return Ok(());
+        };
+
+        let mut pruned = self.pruned_since_compaction.lock().await;
+        if *pruned > 0 {
+            // Rewrite from memory so pruned samples leave the file too. The
+            // buffer is cleared first so a sample recorded meanwhile is
+            // appended by the next flush rather than lost.
+            self.unflushed.lock().await.clear();
+            let samples = self.samples.read().await;
+            let mut content = String::new();
+            for sample in samples.values().flatten() {
+                content.push_str(&serde_json::to_string(sample)?);
+                content.push('\n');
+            }
+            drop(samples);
+
+            let tmp_path = path.with_extension("tmp");
+            std::fs::write(&tmp_path, content)?;
+            std::fs::rename(&tmp_path, path)?;
+            debug!("🗜️ Compacted price history after pruning {} samples", *pruned);
+            *pruned = 0;
+            return Ok(());
+        }
+
+        let unflushed: Vec<PriceSample> = std::mem::take(&mut *self.unflushed.lock().await);
+        if unflushed.is_empty() {
+            return Ok(());
+        }
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        for sample in &unflushed {
+            writeln!(file, "{}", serde_json::to_string(sample)?)?;
+        }
+        debug!("💾 Flushed {} price samples", unflushed.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(dex_name: &str, price: f64) -> PriceData {
+        PriceData {
+            dex_name: dex_name.to_string(),
+            token_pair: "SOL/USDC".to_string(),
+            base_token: "SOL".to_string(),
+            quote_token: "USDC".to_string(),
+            price,
+            volume_24h: 0.0,
+            liquidity: 1_000.0,
+            timestamp: 0,
+            pool_address: String::new(),
+            price_impact: 0.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn samples_at_most_once_per_interval_and_prunes_old_samples() {
+        let history = PriceHistory::open(None, 1_000, 10, 0).unwrap();
+        history.record(&[price("Raydium", 100.0), price("Orca", 101.0)], 0).await;
+        history.record(&[price("Raydium", 102.0)], 500).await;
+        history.record(&[price("Raydium", 103.0)], 1_000).await;
+        assert_eq!(history.query("SOL/USDC", 0, 1_000).await.len(), 3);
+        assert_eq!(history.query("SOL/USDC", 1, 1_000).await.len(), 1);
+
+        history.record(&[price("Raydium", 104.0)], 12_000).await;
+        let remaining = history.query("SOL/USDC", 0, i64::MAX).await;
+        assert_eq!(remaining.iter().map(|s| s.timestamp).collect::<Vec<_>>(), vec![12_000]);
+    }
+
+    #[tokio::test]
+    async fn flushed_samples_survive_a_restart() {
+        let path = std::env::temp_dir().join(format!("price_history_test_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let history = PriceHistory::open(Some(path.clone()), 0, 60, 0).unwrap();
+        history.record(&[price("Raydium", 100.0)], 1_000).await;
+        history.flush().await.unwrap();
+        history.record(&[price("Raydium", 101.0)], 2_000).await;
+        history.flush().await.unwrap();
+
+        let reopened = PriceHistory::open(Some(path.clone()), 0, 60, 3_000).unwrap();
+        let prices: Vec<f64> = reopened.query("SOL/USDC", 0, i64::MAX).await.iter().map(|s| s.price).collect();
+        assert_eq!(prices, vec![100.0, 101.0]);
+
+        // Past retention, nothing is loaded back
+        let expired = PriceHistory::open(Some(path.clone()), 0, 60, 120_000).unwrap();
+        assert!(expired.query("SOL/USDC", 0, i64::MAX).await.is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+}
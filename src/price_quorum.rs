@@ -0,0 +1,62 @@
+use crate::types::PriceData;
+use std::collections::HashSet;
+
+// Prices that agree with the pair's consensus: within `tolerance_pct` of the
+// median across all feeds. Outliers, such as one manipulated pool, are left out.
+pub fn agreeing_prices(prices: &[PriceData], tolerance_pct: f64) -> Vec<PriceData> {
+    let mut sorted: Vec<f64> = prices.iter().map(|p| p.price).filter(|p| *p > 0.0).collect();
+    if sorted.is_empty() {
+        return Vec::new();
+    }
+    sorted.sort_by(f64::total_cmp);
+    let middle = sorted.len() / 2;
+    let median = if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    };
+
+    prices.iter()
+        .filter(|p| p.price > 0.0 && ((p.price - median) / median).abs() * 100.0 <= tolerance_pct)
+        .cloned()
+        .collect()
+}
+
+// Several pools on one DEX count as a single source
+pub fn independent_sources(prices: &[PriceData]) -> usize {
+    prices.iter().map(|p| p.dex_name.as_str()).collect::<HashSet<_>>().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(dex_name: &str, price: f64) -> PriceData {
+        PriceData {
+            dex_name: dex_name.to_string(),
+            token_pair: "SOL/USDC".to_string(),
+            base_token: "SOL".to_string(),
+            quote_token: "USDC".to_string(),
+            price,
+            volume_24h: 0.0,
+            liquidity: 0.0,
+            timestamp: 0,
+            pool_address: String::new(),
+            price_impact: 0.0,
+        }
+    }
+
+    #[test]
+    fn leaves_out_prices_far_from_the_median() {
+        let prices = vec![price("Raydium", 100.0), price("Orca", 100.5), price("Serum", 130.0), price("Saber", 0.0)];
+        let agreeing: Vec<f64> = agreeing_prices(&prices, 1.0).iter().map(|p| p.price).collect();
+        assert_eq!(agreeing, vec![100.0, 100.5]);
+        assert!(agreeing_prices(&[], 1.0).is_empty());
+    }
+
+    #[test]
+    fn pools_on_one_dex_are_one_source() {
+        let prices = vec![price("Raydium", 100.0), price("Raydium", 100.1), price("Orca", 100.0)];
+        assert_eq!(independent_sources(&prices), 2);
+    }
+}
@@ -0,0 +1,15 @@
+use crate::{dex_monitor::DexMonitor, types::PriceData};
+use anyhow::Result;
+use futures_util::future::BoxFuture;
+
+// Where the scanner gets its DEX prices from. `DexMonitor` is the live source;
+// benches and offline runs can substitute synthetic data.
+pub trait PriceSource: Send + Sync {
+    fn get_all_prices(&self) -> BoxFuture<'_, Result<Vec<PriceData>>>;
+}
+
+impl PriceSource for DexMonitor {
+    fn get_all_prices(&self) -> BoxFuture<'_, Result<Vec<PriceData>>> {
+        Box::pin(async move { DexMonitor::get_all_prices(self).await })
+    }
+}
@@ -0,0 +1,82 @@
+use crate::types::JupiterQuote;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QuoteKey {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount: u64,
+    pub slippage_bps: u16,
+}
+
+// Recent Jupiter quotes, reused while younger than the TTL the caller passes
+// in; volatile pairs look up with a short TTL, stable pairs with a long one
+#[derive(Debug, Default)]
+pub struct QuoteCache {
+    quotes: HashMap<QuoteKey, JupiterQuote>,
+}
+
+impl QuoteCache {
+    pub fn get(&self, key: &QuoteKey, ttl_ms: u64, now_ms: i64) -> Option<JupiterQuote> {
+        self.quotes
+            .get(key)
+            .filter(|quote| now_ms - quote.received_at < ttl_ms as i64)
+            .cloned()
+    }
+
+    // Entries older than `max_ttl_ms` can't be fresh for any pair and are dropped
+    pub fn insert(&mut self, key: QuoteKey, quote: JupiterQuote, max_ttl_ms: u64) {
+        let now_ms = quote.received_at;
+        self.quotes.retain(|_, cached| now_ms - cached.received_at < max_ttl_ms as i64);
+        self.quotes.insert(key, quote);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SwapMode;
+
+    fn key(amount: u64) -> QuoteKey {
+        QuoteKey { input_mint: "A".to_string(), output_mint: "B".to_string(), amount, slippage_bps: 50 }
+    }
+
+    fn quote(received_at: i64) -> JupiterQuote {
+        JupiterQuote {
+            input_mint: "A".to_string(),
+            in_amount: 100,
+            output_mint: "B".to_string(),
+            out_amount: 99,
+            platform_fee_amount: 0,
+            swap_mode: SwapMode::ExactIn,
+            other_amount_threshold: 98,
+            price_impact_pct: 0.0,
+            route_plan: Vec::new(),
+            context_slot: 0,
+            time_taken: 0.0,
+            slippage_bps: 50,
+            received_at,
+            request_id: String::new(),
+        }
+    }
+
+    #[test]
+    fn serves_quotes_only_while_younger_than_the_callers_ttl() {
+        let mut cache = QuoteCache::default();
+        cache.insert(key(100), quote(1_000), 10_000);
+
+        assert!(cache.get(&key(100), 500, 1_400).is_some());
+        assert!(cache.get(&key(100), 500, 1_500).is_none());
+        assert!(cache.get(&key(100), 5_000, 1_500).is_some());
+        assert!(cache.get(&key(200), 5_000, 1_000).is_none());
+    }
+
+    #[test]
+    fn inserting_drops_entries_past_the_longest_ttl() {
+        let mut cache = QuoteCache::default();
+        cache.insert(key(100), quote(1_000), 10_000);
+        cache.insert(key(200), quote(20_000), 10_000);
+        assert!(cache.get(&key(100), u64::MAX / 2, 20_000).is_none());
+        assert!(cache.get(&key(200), 10_000, 20_000).is_some());
+    }
+}
@@ -0,0 +1,55 @@
+use crate::jupiter_client::{JupiterClient, JupiterQuoteRequest};
+use anyhow::Result;
+use futures_util::future::BoxFuture;
+
+// Something that can say how much `output_mint` an exact `amount` of
+// `input_mint` buys right now. Used to cross-check Jupiter against an
+// independent quote before trusting it.
+pub trait QuoteSource: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn quote_out_amount<'a>(
+        &'a self,
+        input_mint: &'a str,
+        output_mint: &'a str,
+        amount: u64,
+    ) -> BoxFuture<'a, Result<u64>>;
+}
+
+impl QuoteSource for JupiterClient {
+    fn name(&self) -> &str {
+        "jupiter"
+    }
+
+    fn quote_out_amount<'a>(
+        &'a self,
+        input_mint: &'a str,
+        output_mint: &'a str,
+        amount: u64,
+    ) -> BoxFuture<'a, Result<u64>> {
+        Box::pin(async move {
+            let quote = self.get_quote(JupiterQuoteRequest {
+                input_mint: input_mint.to_string(),
+                output_mint: output_mint.to_string(),
+                amount,
+                // Slippage only affects the threshold, not the quoted output
+                slippage_bps: 50,
+                swap_mode: Some("ExactIn".to_string()),
+                dexes: None,
+                exclude_dexes: None,
+                platform_fee_bps: None,
+                max_accounts: None,
+                context_slot: None,
+            }).await?;
+            Ok(quote.out_amount)
+        })
+    }
+}
+
+// How far apart two quoted outputs for the same input are, relative to the primary
+pub fn quote_divergence_pct(primary_out: u64, secondary_out: u64) -> f64 {
+    if primary_out == 0 {
+        return if secondary_out == 0 { 0.0 } else { f64::INFINITY };
+    }
+    (primary_out as f64 - secondary_out as f64).abs() / primary_out as f64 * 100.0
+}
@@ -0,0 +1,187 @@
+use crate::clock::Clock;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+const LIMIT_HEADER: &str = "x-ratelimit-limit";
+const REMAINING_HEADER: &str = "x-ratelimit-remaining";
+const RESET_HEADER: &str = "x-ratelimit-reset";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestPriority {
+    // Needed to finish something already in flight; never held back
+    Critical,
+    Normal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitStatus {
+    pub limit: Option<u64>,
+    pub remaining: u64,
+    // Unix time in milliseconds when the quota refills
+    pub reset_time: i64,
+}
+
+impl RateLimitStatus {
+    pub fn is_exhausted(&self, now_ms: i64) -> bool {
+        self.remaining == 0 && self.ms_until_reset(now_ms) > 0
+    }
+
+    pub fn ms_until_reset(&self, now_ms: i64) -> u64 {
+        (self.reset_time - now_ms).max(0) as u64
+    }
+}
+
+// Tracks the quota an API reports in its rate-limit headers and holds back
+// normal requests while it is exhausted instead of collecting 429s
+#[derive(Debug)]
+pub struct RateLimitGovernor {
+    status: Mutex<Option<RateLimitStatus>>,
+    max_wait_ms: u64,
+    clock: Arc<dyn Clock>,
+}
+
+impl RateLimitGovernor {
+    pub fn new(max_wait_ms: u64, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            status: Mutex::new(None),
+            max_wait_ms,
+            clock,
+        }
+    }
+
+    // The same governor, quota included, reading time from `clock`
+    pub fn with_clock(&self, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            status: Mutex::new(self.status()),
+            max_wait_ms: self.max_wait_ms,
+            clock,
+        }
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.status().is_some_and(|status| status.is_exhausted(self.clock.now_ms()))
+    }
+
+    pub fn status(&self) -> Option<RateLimitStatus> {
+        self.status.lock().unwrap().clone()
+    }
+
+    // Waits out an exhausted quota; fails instead if the reset is further
+    // away than `max_wait_ms`
+    pub async fn acquire(&self, priority: RequestPriority) -> anyhow::Result<()> {
+        if priority == RequestPriority::Critical {
+            return Ok(());
+        }
+
+        let now = self.clock.now_ms();
+        let wait_ms = match self.status() {
+            Some(status) if status.is_exhausted(now) => status.ms_until_reset(now),
+            _ => return Ok(()),
+        };
+        if wait_ms > self.max_wait_ms {
+            return Err(anyhow::anyhow!("Rate limit exhausted for another {}ms", wait_ms));
+        }
+
+        debug!("🚦 Rate limit exhausted, holding request for {}ms", wait_ms);
+        tokio::time::sleep(Duration::from_millis(wait_ms)).await;
+        Ok(())
+    }
+
+    pub fn observe(&self, headers: &reqwest::header::HeaderMap, status: reqwest::StatusCode) {
+        let header = |name: &str| -> Option<u64> {
+            headers.get(name)?.to_str().ok()?.trim().parse().ok()
+        };
+        let now = self.clock.now_ms();
+
+        let update = match (header(REMAINING_HEADER), header(RESET_HEADER)) {
+            (Some(remaining), Some(reset)) => Some(RateLimitStatus {
+                limit: header(LIMIT_HEADER),
+                remaining,
+                reset_time: reset_to_unix_ms(reset, now),
+            }),
+            // A bare 429 still tells us to back off until Retry-After
+            _ if status == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                let retry_after = header(reqwest::header::RETRY_AFTER.as_str()).unwrap_or(1);
+                Some(RateLimitStatus {
+                    limit: header(LIMIT_HEADER),
+                    remaining: 0,
+                    reset_time: now + retry_after as i64 * 1000,
+                })
+            }
+            _ => None,
+        };
+
+        if let Some(update) = update {
+            if update.remaining == 0 {
+                warn!("🚦 Rate limit exhausted, resets in {}ms", update.ms_until_reset(now));
+            }
+            *self.status.lock().unwrap() = Some(update);
+        }
+    }
+}
+
+// Reset headers are either seconds until reset or a unix timestamp in seconds
+fn reset_to_unix_ms(reset: u64, now_ms: i64) -> i64 {
+    if reset > 1_000_000_000 {
+        reset as i64 * 1000
+    } else {
+        now_ms + reset as i64 * 1000
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    #[test]
+    fn reset_accepts_relative_seconds_or_a_unix_timestamp() {
+        assert_eq!(reset_to_unix_ms(30, 1_000), 31_000);
+        assert_eq!(reset_to_unix_ms(1_700_000_000, 1_000), 1_700_000_000_000);
+    }
+
+    #[tokio::test]
+    async fn holds_back_normal_requests_while_exhausted() {
+        let governor = RateLimitGovernor::new(1_000, Arc::new(ManualClock::new(1_000_000)));
+        let mut headers = HeaderMap::new();
+        headers.insert(REMAINING_HEADER, HeaderValue::from_static("0"));
+        headers.insert(RESET_HEADER, HeaderValue::from_static("60"));
+        governor.observe(&headers, reqwest::StatusCode::OK);
+
+        assert!(governor.is_exhausted());
+        assert!(governor.acquire(RequestPriority::Normal).await.is_err());
+        assert!(governor.acquire(RequestPriority::Critical).await.is_ok());
+    }
+
+    #[test]
+    fn a_bare_429_backs_off_until_retry_after() {
+        let governor = RateLimitGovernor::new(1_000, Arc::new(ManualClock::new(1_000_000)));
+        governor.observe(&HeaderMap::new(), reqwest::StatusCode::OK);
+        assert!(governor.status().is_none());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, HeaderValue::from_static("5"));
+        governor.observe(&headers, reqwest::StatusCode::TOO_MANY_REQUESTS);
+        let status = governor.status().unwrap();
+        assert_eq!(status.remaining, 0);
+        assert_eq!(status.ms_until_reset(1_000_000), 5_000);
+    }
+
+    #[test]
+    fn the_quota_resets_by_the_governors_clock_not_the_system_time() {
+        // Years behind the machine's clock, so a system-time check would see the reset long passed
+        let clock = Arc::new(ManualClock::new(1_000_000));
+        let governor = RateLimitGovernor::new(1_000, clock.clone());
+        let mut headers = HeaderMap::new();
+        headers.insert(REMAINING_HEADER, HeaderValue::from_static("0"));
+        headers.insert(RESET_HEADER, HeaderValue::from_static("60"));
+        governor.observe(&headers, reqwest::StatusCode::OK);
+        assert!(governor.is_exhausted());
+
+        clock.advance(60_000);
+        assert!(!governor.is_exhausted());
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    amounts::to_raw_amount,
+    config::Config,
+    types::{Portfolio, SwapRequest},
+};
+use std::collections::HashMap;
+use tracing::debug;
+
+// Computes the Jupiter swaps that move the portfolio back toward the configured
+// target weights. Over-weight tokens are sold into the base mint and
+// under-weight tokens are bought with it. Each trade is capped at the risk
+// manager's max position size.
+pub fn compute_rebalance_trades(
+    portfolio: &Portfolio,
+    config: &Config,
+    decimals: &HashMap<String, u8>,
+) -> Vec<SwapRequest> {
+    let settings = &config.rebalancing;
+    let total_value = portfolio.total_value_usd;
+    if total_value <= 0.0 {
+        return Vec::new();
+    }
+
+    let base_price = portfolio.balances.iter()
+        .find(|b| b.token_mint == settings.base_mint)
+        .map(|b| b.price)
+        .unwrap_or(0.0);
+
+    let mut trades = Vec::new();
+    for (mint, target_weight) in &settings.target_weights {
+        if *mint == settings.base_mint {
+            continue;
+        }
+
+        let (current_value, price) = portfolio.balances.iter()
+            .find(|b| &b.token_mint == mint)
+            .map(|b| (b.value_usd, b.price))
+            .unwrap_or((0.0, 0.0));
+
+        let drift_pct = (current_value / total_value - target_weight) * 100.0;
+        if drift_pct.abs() < settings.drift_tolerance_pct {
+            continue;
+        }
+
+        let trade_value = (drift_pct.abs() / 100.0 * total_value).min(config.risk_settings.max_position_size);
+
+        // Sell the over-weight token, or spend the base token on the under-weight one
+        let (input_mint, output_mint, input_price) = if drift_pct > 0.0 {
+            (mint.clone(), settings.base_mint.clone(), price)
+        } else {
+            (settings.base_mint.clone(), mint.clone(), base_price)
+        };
+
+        let Some(input_decimals) = decimals.get(&input_mint) else {
+            debug!("⏭️ Skipping rebalance of {}: unknown decimals for {}", mint, input_mint);
+            continue;
+        };
+        if input_price <= 0.0 {
+            debug!("⏭️ Skipping rebalance of {}: no price for {}", mint, input_mint);
+            continue;
+        }
+
+        let amount = match to_raw_amount(trade_value / input_price, *input_decimals) {
+            Ok(amount) if amount > 0 => amount,
+            Ok(_) => continue,
+            Err(e) => {
+                debug!("⏭️ Skipping rebalance of {}: {}", mint, e);
+                continue;
+            }
+        };
+
+        debug!("⚖️ Rebalance {}: drift {:.2}%, swapping ${:.2} {} -> {}",
+               mint, drift_pct, trade_value, input_mint, output_mint);
+
+        trades.push(SwapRequest {
+            input_mint,
+            output_mint,
+            amount,
+            user_public_key: config.wallet.public_key.clone(),
+            slippage: config.risk_settings.max_slippage,
+            priority_fee: config.jupiter.prioritization_fee_lamports,
+            allowed_dexes: Some(config.jupiter.preferred_dexes.clone()),
+            excluded_dexes: Some(config.jupiter.excluded_dexes.clone()),
+            use_jupiter: true,
+            dynamic_slippage: config.jupiter.dynamic_slippage.clone(),
+            compute_unit_price_micro_lamports: None,
+            tracking_account: None,
+            created_token_accounts: 0,
+        });
+    }
+
+    trades
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TokenBalance;
+
+    const SOL: &str = "SOL_MINT";
+    const USDC: &str = "USDC_MINT";
+
+    fn balance(mint: &str, value_usd: f64, price: f64) -> TokenBalance {
+        TokenBalance {
+            token_mint: mint.to_string(),
+            symbol: String::new(),
+            amount: value_usd / price,
+            value_usd,
+            price,
+        }
+    }
+
+    fn portfolio(sol_usd: f64, usdc_usd: f64) -> Portfolio {
+        Portfolio {
+            wallet_address: String::new(),
+            balances: vec![balance(SOL, sol_usd, 100.0), balance(USDC, usdc_usd, 1.0)],
+            total_value_usd: sol_usd + usdc_usd,
+            available_balance: 0.0,
+            last_updated: 0,
+        }
+    }
+
+    fn config() -> Config {
+        let mut config = Config::default();
+        config.rebalancing.base_mint = USDC.to_string();
+        config.rebalancing.target_weights = HashMap::from([(SOL.to_string(), 0.5), (USDC.to_string(), 0.5)]);
+        config.rebalancing.drift_tolerance_pct = 5.0;
+        config.risk_settings.max_position_size = 250.0;
+        config
+    }
+
+    fn decimals() -> HashMap<String, u8> {
+        HashMap::from([(SOL.to_string(), 9), (USDC.to_string(), 6)])
+    }
+
+    #[test]
+    fn sells_an_over_weight_token_into_the_base_mint() {
+        let trades = compute_rebalance_trades(&portfolio(700.0, 300.0), &config(), &decimals());
+        assert_eq!(trades.len(), 1);
+        assert_eq!((trades[0].input_mint.as_str(), trades[0].output_mint.as_str()), (SOL, USDC));
+        assert_eq!(trades[0].amount, 2_000_000_000);
+    }
+
+    #[test]
+    fn buys_an_under_weight_token_capped_at_the_position_size() {
+        let trades = compute_rebalance_trades(&portfolio(100.0, 900.0), &config(), &decimals());
+        assert_eq!((trades[0].input_mint.as_str(), trades[0].output_mint.as_str()), (USDC, SOL));
+        assert_eq!(trades[0].amount, 250_000_000);
+    }
+
+    #[test]
+    fn leaves_drift_within_tolerance_or_without_decimals_alone() {
+        assert!(compute_rebalance_trades(&portfolio(520.0, 480.0), &config(), &decimals()).is_empty());
+        assert!(compute_rebalance_trades(&portfolio(700.0, 300.0), &config(), &HashMap::new()).is_empty());
+    }
+}
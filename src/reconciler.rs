@@ -0,0 +1,278 @@
+use crate::{
+    rpc_client::SolanaRpcClient,
+    types::{TradeResponse, LAMPORTS_PER_SOL, WSOL_MINT},
+};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_sdk::signature::Signature;
+use std::str::FromStr;
+use tracing::{debug, warn};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Discrepancy {
+    MissingSignature,
+    NeverLanded,
+    RecordedSuccessButReverted(String),
+    RecordedFailureButLanded,
+    // Landed, but the wallet's profit-denomination balance moved by
+    // `on_chain`, not the recorded profit
+    ProfitMismatch { recorded: f64, on_chain: f64 },
+    // Could not be checked, e.g. the id is no signature or the RPC failed
+    Unknown(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeDiscrepancy {
+    pub transaction_id: String,
+    pub discrepancy: Discrepancy,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    pub checked: usize,
+    pub matched: usize,
+    pub discrepancies: Vec<TradeDiscrepancy>,
+}
+
+// The mint profits are recorded in, and how far the on-chain figure may
+// stray from the recorded one before it counts as a mismatch
+#[derive(Debug, Clone)]
+pub struct ProfitCheck {
+    pub denomination_mint: String,
+    pub tolerance: f64,
+}
+
+pub struct Reconciler {
+    rpc: SolanaRpcClient,
+    profit_check: Option<ProfitCheck>,
+}
+
+impl Reconciler {
+    pub fn new(rpc_url: String, timeout_ms: u64) -> Self {
+        Self {
+            rpc: SolanaRpcClient::new(rpc_url, timeout_ms),
+            profit_check: None,
+        }
+    }
+
+    pub fn with_profit_check(mut self, profit_check: ProfitCheck) -> Self {
+        self.profit_check = Some(profit_check);
+        self
+    }
+
+    pub async fn reconcile(&self, trades: &[TradeResponse]) -> Result<ReconciliationReport> {
+        let mut report = ReconciliationReport {
+            checked: trades.len(),
+            ..Default::default()
+        };
+
+        for trade in trades {
+            // One unreadable trade doesn't stop the rest from being checked
+            let outcome = self.check_trade(trade).await
+                .unwrap_or_else(|e| Some(Discrepancy::Unknown(e.to_string())));
+            match outcome {
+                Some(discrepancy) => {
+                    warn!("⚠️ {}: {:?}", trade.transaction_id, discrepancy);
+                    report.discrepancies.push(TradeDiscrepancy {
+                        transaction_id: trade.transaction_id.clone(),
+                        discrepancy,
+                    });
+                }
+                None => report.matched += 1,
+            }
+        }
+
+        Ok(report)
+    }
+
+    async fn check_trade(&self, trade: &TradeResponse) -> Result<Option<Discrepancy>> {
+        if trade.transaction_id.is_empty() {
+            return Ok(Some(Discrepancy::MissingSignature));
+        }
+        // Simulated trades record made-up ids that no RPC will find
+        if Signature::from_str(&trade.transaction_id).is_err() {
+            return Ok(Some(Discrepancy::Unknown("not a transaction signature".to_string())));
+        }
+
+        debug!("🔎 Fetching transaction {}", trade.transaction_id);
+        let transaction = self.rpc.call("getTransaction", json!([
+            trade.transaction_id,
+            { "encoding": "json", "maxSupportedTransactionVersion": 0, "commitment": "confirmed" }
+        ])).await?;
+
+        if transaction.is_null() {
+            return Ok(Some(Discrepancy::NeverLanded));
+        }
+
+        let meta = &transaction["meta"];
+        let reverted = !meta["err"].is_null();
+        if trade.success && reverted {
+            return Ok(Some(Discrepancy::RecordedSuccessButReverted(meta["err"].to_string())));
+        }
+        if !trade.success && !reverted {
+            return Ok(Some(Discrepancy::RecordedFailureButLanded));
+        }
+
+        if let (Some(check), true) = (&self.profit_check, trade.success) {
+            let Some(on_chain) = on_chain_profit(&transaction, trade, &check.denomination_mint) else {
+                return Ok(Some(Discrepancy::Unknown("transaction has no balance metadata".to_string())));
+            };
+            if (on_chain - trade.actual_profit).abs() > check.tolerance {
+                return Ok(Some(Discrepancy::ProfitMismatch { recorded: trade.actual_profit, on_chain }));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+// The trade's profit as the chain saw it, comparable with `actual_profit`:
+// the fee payer's change in `mint`, in UI units. A SOL denomination counts
+// lamports too, so fees are already in it; for any other mint the fees were
+// paid in SOL, so the recorded gas comes off as it did for `actual_profit`.
+pub fn on_chain_profit(transaction: &Value, trade: &TradeResponse, mint: &str) -> Option<f64> {
+    let meta = transaction.get("meta")?;
+    let payer = transaction["transaction"]["message"]["accountKeys"].get(0)?.as_str()?;
+
+    let holdings = |key: &str| -> Option<f64> {
+        let balances = meta.get(key)?.as_array()?;
+        Some(balances.iter()
+            .filter(|balance| balance["mint"] == mint && balance["owner"] == payer)
+            .filter_map(|balance| balance["uiTokenAmount"]["uiAmountString"].as_str()?.parse::<f64>().ok())
+            .sum())
+    };
+    let token_change = holdings("postTokenBalances")? - holdings("preTokenBalances")?;
+
+    if mint == WSOL_MINT {
+        let lamports = |key: &str| meta.get(key)?.get(0)?.as_i64();
+        let lamport_change = lamports("postBalances")? - lamports("preBalances")?;
+        Some(token_change + lamport_change as f64 / LAMPORTS_PER_SOL)
+    } else {
+        Some(token_change - trade.gas_used)
+    }
+}
+
+// Applies the on-chain outcome to the recorded trades
+pub fn apply_corrections(trades: &mut [TradeResponse], report: &ReconciliationReport) {
+    for entry in &report.discrepancies {
+        let Some(trade) = trades.iter_mut().find(|t| t.transaction_id == entry.transaction_id) else {
+            continue;
+        };
+
+        match &entry.discrepancy {
+            Discrepancy::NeverLanded => {
+                trade.success = false;
+                trade.actual_profit = 0.0;
+                trade.error_message = "Transaction never landed".to_string();
+            }
+            Discrepancy::RecordedSuccessButReverted(err) => {
+                trade.success = false;
+                trade.actual_profit = 0.0;
+                trade.error_message = format!("Transaction reverted: {}", err);
+            }
+            Discrepancy::RecordedFailureButLanded => {
+                trade.success = true;
+                trade.error_message.clear();
+            }
+            Discrepancy::ProfitMismatch { on_chain, .. } => {
+                trade.actual_profit = *on_chain;
+            }
+            Discrepancy::MissingSignature | Discrepancy::Unknown(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYER: &str = "Payer1111111111111111111111111111111111111";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn trade(actual_profit: f64, gas_used: f64) -> TradeResponse {
+        TradeResponse {
+            transaction_id: "sig".to_string(),
+            success: true,
+            actual_profit,
+            gas_used,
+            ..TradeResponse::rejected("")
+        }
+    }
+
+    fn balance(mint: &str, owner: &str, amount: &str) -> Value {
+        json!({ "mint": mint, "owner": owner, "uiTokenAmount": { "uiAmountString": amount } })
+    }
+
+    fn transaction(pre: Vec<Value>, post: Vec<Value>, lamports: (i64, i64)) -> Value {
+        json!({
+            "transaction": { "message": { "accountKeys": [PAYER] } },
+            "meta": {
+                "err": null,
+                "preTokenBalances": pre,
+                "postTokenBalances": post,
+                "preBalances": [lamports.0],
+                "postBalances": [lamports.1],
+            }
+        })
+    }
+
+    #[test]
+    fn profit_counts_only_the_payers_denomination_balance() {
+        let transaction = transaction(
+            vec![balance(USDC, PAYER, "100"), balance(USDC, "Pool", "5000")],
+            vec![balance(USDC, PAYER, "102.5"), balance(USDC, "Pool", "4997.5")],
+            (2_000_000_000, 1_999_990_000),
+        );
+        // Gas was paid in SOL, so it comes off as it does for the recorded profit
+        let on_chain = on_chain_profit(&transaction, &trade(2.0, 0.5), USDC).unwrap();
+        assert!((on_chain - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sol_profit_includes_the_lamports_spent_on_fees() {
+        let transaction = transaction(vec![], vec![], (2_000_000_000, 2_100_000_000));
+        let on_chain = on_chain_profit(&transaction, &trade(0.1, 0.0), WSOL_MINT).unwrap();
+        assert!((on_chain - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn mismatched_profit_is_corrected_and_unknowns_are_left_alone() {
+        let mut trades = vec![trade(5.0, 0.0), TradeResponse { transaction_id: "tx_mock".to_string(), ..trade(1.0, 0.0) }];
+        let report = ReconciliationReport {
+            checked: 2,
+            matched: 0,
+            discrepancies: vec![
+                TradeDiscrepancy {
+                    transaction_id: "sig".to_string(),
+                    discrepancy: Discrepancy::ProfitMismatch { recorded: 5.0, on_chain: 3.0 },
+                },
+                TradeDiscrepancy {
+                    transaction_id: "tx_mock".to_string(),
+                    discrepancy: Discrepancy::Unknown("not a transaction signature".to_string()),
+                },
+            ],
+        };
+
+        apply_corrections(&mut trades, &report);
+        assert_eq!(trades[0].actual_profit, 3.0);
+        assert!(trades[0].success);
+        assert_eq!(trades[1].actual_profit, 1.0);
+    }
+
+    #[tokio::test]
+    async fn unreadable_trades_do_not_stop_reconciliation() {
+        // Nothing listens here: the real signature's lookup fails, and mock ids never get that far
+        let reconciler = Reconciler::new("http://127.0.0.1:9".to_string(), 100);
+        let trades = vec![
+            TradeResponse { transaction_id: "tx_1234".to_string(), ..trade(1.0, 0.0) },
+            TradeResponse { transaction_id: Signature::default().to_string(), ..trade(1.0, 0.0) },
+            TradeResponse { transaction_id: "jito_abcd".to_string(), ..trade(1.0, 0.0) },
+        ];
+
+        let report = reconciler.reconcile(&trades).await.unwrap();
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.discrepancies.len(), 3);
+        assert!(report.discrepancies.iter().all(|d| matches!(d.discrepancy, Discrepancy::Unknown(_))));
+    }
+}
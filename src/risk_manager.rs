@@ -0,0 +1,168 @@
+use crate::{
+    config::RiskSettings,
+    types::{ArbitrageError, Portfolio, TradeRequest},
+};
+use anyhow::Result;
+use std::collections::VecDeque;
+use tracing::warn;
+
+const HOUR_MS: i64 = 60 * 60 * 1000;
+const DAY_MS: i64 = 24 * HOUR_MS;
+
+// Per-trade limits (size, slippage) plus the rolling trade-rate and daily
+// loss limits from [risk_settings]
+#[derive(Debug, Clone)]
+pub struct RiskManager {
+    settings: RiskSettings,
+    // (unix ms, realized profit) of trades over the last day, oldest first
+    recent_trades: VecDeque<(i64, f64)>,
+}
+
+impl RiskManager {
+    pub fn new(settings: RiskSettings) -> Self {
+        Self {
+            settings,
+            recent_trades: VecDeque::new(),
+        }
+    }
+
+    pub fn settings(&self) -> &RiskSettings {
+        &self.settings
+    }
+
+    pub async fn can_execute_trade(&self, request: &TradeRequest, now_ms: i64) -> Result<bool> {
+        if request.amount <= 0.0 || !request.amount.is_finite() {
+            warn!("🚫 Trade {} has invalid amount {}", request.opportunity_id, request.amount);
+            return Ok(false);
+        }
+        if request.amount > self.settings.max_position_size {
+            warn!("🚫 Trade {} amount {:.2} exceeds max position size {:.2}",
+                  request.opportunity_id, request.amount, self.settings.max_position_size);
+            return Ok(false);
+        }
+        if request.max_slippage > self.settings.max_slippage {
+            warn!("🚫 Trade {} allows {:.2}% slippage, limit is {:.2}%",
+                  request.opportunity_id, request.max_slippage, self.settings.max_slippage);
+            return Ok(false);
+        }
+
+        let trades_this_hour = self.recent_trades.iter()
+            .filter(|(at, _)| now_ms - at < HOUR_MS)
+            .count();
+        if trades_this_hour >= self.settings.max_trades_per_hour as usize {
+            warn!("🚫 {} trades in the last hour, limit is {}", trades_this_hour, self.settings.max_trades_per_hour);
+            return Ok(false);
+        }
+
+        let daily_loss = -self.daily_pnl(now_ms);
+        if daily_loss >= self.settings.max_daily_loss {
+            warn!("🚫 Daily loss {:.2} reached the limit of {:.2}", daily_loss, self.settings.max_daily_loss);
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    // Rejects a trade that would push the aggregate USD exposure of any group
+    // containing `mint` above that group's cap. `amount_usd` is the trade's
+    // value, not its size in token units.
+    pub fn check_group_exposure(&self, portfolio: &Portfolio, mint: &str, amount_usd: f64) -> Result<(), ArbitrageError> {
+        for (name, group) in &self.settings.exposure_groups {
+            if !group.mints.iter().any(|m| m == mint) {
+                continue;
+            }
+
+            let current_exposure: f64 = portfolio.balances.iter()
+                .filter(|b| group.mints.contains(&b.token_mint))
+                .map(|b| b.value_usd)
+                .sum();
+
+            if current_exposure + amount_usd > group.max_exposure_usd {
+                warn!("🚫 Exposure group {} would reach ${:.2} (cap ${:.2})",
+                      name, current_exposure + amount_usd, group.max_exposure_usd);
+                return Err(ArbitrageError::RiskCheckFailed);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Call once per executed trade with its realized profit (negative for a loss)
+    pub fn record_trade(&mut self, now_ms: i64, profit: f64) {
+        self.recent_trades.push_back((now_ms, profit));
+        while let Some((at, _)) = self.recent_trades.front() {
+            if now_ms - at < DAY_MS {
+                break;
+            }
+            self.recent_trades.pop_front();
+        }
+    }
+
+    // Net realized profit over the trailing 24 hours
+    pub fn daily_pnl(&self, now_ms: i64) -> f64 {
+        self.recent_trades.iter()
+            .filter(|(at, _)| now_ms - at < DAY_MS)
+            .map(|(_, profit)| profit)
+            .sum()
+    }
+
+    pub fn update_max_position_size(&mut self, size: f64) {
+        self.settings.max_position_size = size;
+    }
+
+    pub fn update_max_daily_loss(&mut self, loss: f64) {
+        self.settings.max_daily_loss = loss;
+    }
+
+    pub fn update_max_slippage(&mut self, slippage: f64) {
+        self.settings.max_slippage = slippage;
+    }
+
+    pub fn update_min_profit_threshold(&mut self, threshold: f64) {
+        self.settings.min_profit_threshold = threshold;
+    }
+
+    pub fn update_max_trades_per_hour(&mut self, trades: u32) {
+        self.settings.max_trades_per_hour = trades;
+    }
+
+    pub fn update_stop_loss(&mut self, enabled: bool, percentage: f64) {
+        self.settings.enable_stop_loss = enabled;
+        self.settings.stop_loss_percentage = percentage;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::{Config, ExposureGroup}, types::TokenBalance};
+
+    fn balance(mint: &str, value_usd: f64) -> TokenBalance {
+        TokenBalance { token_mint: mint.to_string(), symbol: String::new(), amount: 0.0, value_usd, price: 0.0 }
+    }
+
+    #[test]
+    fn group_cap_counts_every_mint_in_the_group() {
+        let mut settings = Config::default().risk_settings;
+        settings.exposure_groups.insert("sol-lsts".to_string(), ExposureGroup {
+            mints: vec!["mSOL".to_string(), "jitoSOL".to_string()],
+            max_exposure_usd: 1_000.0,
+        });
+        let risk_manager = RiskManager::new(settings);
+        let portfolio = Portfolio {
+            wallet_address: String::new(),
+            balances: vec![balance("mSOL", 400.0), balance("jitoSOL", 500.0), balance("USDC", 5_000.0)],
+            total_value_usd: 5_900.0,
+            available_balance: 0.0,
+            last_updated: 0,
+        };
+
+        assert!(risk_manager.check_group_exposure(&portfolio, "jitoSOL", 100.0).is_ok());
+        assert!(matches!(
+            risk_manager.check_group_exposure(&portfolio, "mSOL", 150.0),
+            Err(ArbitrageError::RiskCheckFailed)
+        ));
+        // Mints outside every group are not capped
+        assert!(risk_manager.check_group_exposure(&portfolio, "USDC", 10_000.0).is_ok());
+    }
+}
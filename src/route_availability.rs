@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+#[derive(Debug, Default)]
+struct PairRoutes {
+    consecutive_misses: u32,
+    // Scans sat out since the pair was dropped
+    skipped_scans: u32,
+}
+
+impl PairRoutes {
+    fn is_dropped(&self, miss_tolerance: u32) -> bool {
+        miss_tolerance > 0 && self.consecutive_misses >= miss_tolerance
+    }
+}
+
+// Tolerates Jupiter briefly finding no route for a pair: the pair stays in
+// scope, marked unroutable, until `miss_tolerance` consecutive scans find
+// nothing. A dropped pair is re-quoted every `recheck_scans` scans and
+// returns as soon as a route reappears.
+#[derive(Debug)]
+pub struct RouteAvailability {
+    miss_tolerance: u32,
+    recheck_scans: u32,
+    pairs: HashMap<String, PairRoutes>,
+}
+
+impl RouteAvailability {
+    pub fn new(miss_tolerance: u32, recheck_scans: u32) -> Self {
+        Self {
+            miss_tolerance,
+            recheck_scans: recheck_scans.max(1),
+            pairs: HashMap::new(),
+        }
+    }
+
+    // Call once per pair per scan; false means skip the pair this time
+    pub fn should_scan(&mut self, token_pair: &str) -> bool {
+        let Some(routes) = self.pairs.get_mut(token_pair) else {
+            return true;
+        };
+        if !routes.is_dropped(self.miss_tolerance) {
+            return true;
+        }
+
+        routes.skipped_scans += 1;
+        if routes.skipped_scans >= self.recheck_scans {
+            routes.skipped_scans = 0;
+            return true;
+        }
+        false
+    }
+
+    pub fn record_route(&mut self, token_pair: &str) {
+        if let Some(routes) = self.pairs.remove(token_pair) {
+            if routes.is_dropped(self.miss_tolerance) {
+                info!("🛣️ {} is routable again after {} scans without a route", token_pair, routes.consecutive_misses);
+            }
+        }
+    }
+
+    pub fn record_no_route(&mut self, token_pair: &str) {
+        let routes = self.pairs.entry(token_pair.to_string()).or_default();
+        routes.consecutive_misses += 1;
+        let misses = routes.consecutive_misses;
+        if misses == self.miss_tolerance {
+            info!("🛣️ Dropping {} from the scan: no route for {} consecutive scans", token_pair, misses);
+        } else {
+            debug!("🛣️ {} temporarily unroutable ({} consecutive scans)", token_pair, misses);
+        }
+    }
+
+    // Consecutive scans without a route, for every pair currently missing one
+    pub fn no_route_counts(&self) -> HashMap<String, u32> {
+        self.pairs.iter()
+            .map(|(pair, routes)| (pair.clone(), routes.consecutive_misses))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_a_pair_after_repeated_misses_and_rechecks_it() {
+        let mut routes = RouteAvailability::new(2, 3);
+        routes.record_no_route("SOL/USDC");
+        assert!(routes.should_scan("SOL/USDC"));
+        routes.record_no_route("SOL/USDC");
+        assert_eq!(routes.no_route_counts()["SOL/USDC"], 2);
+
+        assert!(!routes.should_scan("SOL/USDC"));
+        assert!(!routes.should_scan("SOL/USDC"));
+        assert!(routes.should_scan("SOL/USDC"));
+
+        routes.record_route("SOL/USDC");
+        assert!(routes.should_scan("SOL/USDC"));
+        assert!(routes.no_route_counts().is_empty());
+    }
+
+    #[test]
+    fn zero_tolerance_never_drops_a_pair() {
+        let mut routes = RouteAvailability::new(0, 3);
+        for _ in 0..10 {
+            routes.record_no_route("SOL/USDC");
+        }
+        assert!(routes.should_scan("SOL/USDC"));
+    }
+}
@@ -0,0 +1,68 @@
+use anyhow::Result;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+// Minimal JSON-RPC client for the handful of Solana RPC methods we call directly
+#[derive(Debug, Clone)]
+pub struct SolanaRpcClient {
+    client: Client,
+    rpc_url: String,
+    // Shared by clones: calls made and calls that failed
+    calls: Arc<AtomicU64>,
+    failures: Arc<AtomicU64>,
+}
+
+impl SolanaRpcClient {
+    pub fn new(rpc_url: String, timeout_ms: u64) -> Self {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_millis(timeout_ms))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            rpc_url,
+            calls: Arc::new(AtomicU64::new(0)),
+            failures: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    // Calls and failed calls since the client was created
+    pub fn call_counts(&self) -> (u64, u64) {
+        (self.calls.load(Ordering::Relaxed), self.failures.load(Ordering::Relaxed))
+    }
+
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        let result = self.send(method, params).await;
+        if result.is_err() {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+
+    async fn send(&self, method: &str, params: Value) -> Result<Value> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let response: Value = self.client
+            .post(&self.rpc_url)
+            .json(&body)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if let Some(err) = response.get("error") {
+            return Err(anyhow::anyhow!("RPC {} failed: {}", method, err));
+        }
+
+        Ok(response["result"].clone())
+    }
+}
@@ -0,0 +1,160 @@
+use crate::config::{secret_header_map, RemoteSignerConfig};
+use anyhow::Result;
+use base64::Engine;
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::VersionedTransaction,
+};
+use std::str::FromStr;
+
+// Signs transaction messages for the wallet. `KeypairSigner` holds the key in
+// memory; `RemoteSigner` keeps it behind a signing service or hardware device,
+// so the bot itself never sees the private key.
+pub trait TransactionSigner: Send + Sync {
+    fn pubkey(&self) -> Pubkey;
+
+    fn sign_message<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature>>;
+}
+
+pub struct KeypairSigner {
+    keypair: Keypair,
+}
+
+impl KeypairSigner {
+    pub fn from_base58(private_key: &str) -> Result<Self> {
+        let bytes = bs58::decode(private_key)
+            .into_vec()
+            .map_err(|e| anyhow::anyhow!("Wallet private key is not valid base58: {}", e))?;
+        let keypair = Keypair::try_from(bytes.as_slice())
+            .map_err(|e| anyhow::anyhow!("Wallet private key is not a valid keypair: {}", e))?;
+        Ok(Self { keypair })
+    }
+}
+
+impl TransactionSigner for KeypairSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    fn sign_message<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature>> {
+        Box::pin(async move { Ok(self.keypair.sign_message(message)) })
+    }
+}
+
+#[derive(Serialize)]
+struct SignRequest {
+    pubkey: String,
+    // base64 of the serialized transaction message
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    // base58
+    signature: String,
+}
+
+// POSTs each message to the signing service as `{ pubkey, message }` and
+// expects `{ signature }` back. The signature is verified against the wallet's
+// public key before use, so a faulty signer can't produce an unlandable transaction.
+pub struct RemoteSigner {
+    client: reqwest::Client,
+    url: String,
+    pubkey: Pubkey,
+}
+
+impl RemoteSigner {
+    pub fn new(config: &RemoteSignerConfig, public_key: &str) -> Result<Self> {
+        let pubkey = Pubkey::from_str(public_key)
+            .map_err(|e| anyhow::anyhow!("Wallet public key '{}' is invalid: {}", public_key, e))?;
+
+        let headers = secret_header_map(&config.headers)
+            .map_err(|e| anyhow::anyhow!("Remote signer {}", e))?;
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_millis(config.timeout_ms))
+            .default_headers(headers)
+            .build()?;
+        Ok(Self { client, url: config.url.clone(), pubkey })
+    }
+}
+
+impl TransactionSigner for RemoteSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    fn sign_message<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature>> {
+        Box::pin(async move {
+            let request = SignRequest {
+                pubkey: self.pubkey.to_string(),
+                message: base64::engine::general_purpose::STANDARD.encode(message),
+            };
+            let response = self.client.post(&self.url).json(&request).send().await?;
+            if !response.status().is_success() {
+                return Err(anyhow::anyhow!("Remote signer returned {}", response.status()));
+            }
+            let response: SignResponse = response.json().await?;
+
+            let signature = Signature::from_str(&response.signature)
+                .map_err(|e| anyhow::anyhow!("Remote signer returned an invalid signature: {}", e))?;
+            if !signature.verify(self.pubkey.as_ref(), message) {
+                return Err(anyhow::anyhow!("Remote signer's signature does not verify against {}", self.pubkey));
+            }
+            Ok(signature)
+        })
+    }
+}
+
+// Fills in the signer's signature on an otherwise unsigned legacy or v0
+// transaction. Other required signers' slots are left as they are.
+pub async fn sign_transaction(signer: &dyn TransactionSigner, transaction: &mut VersionedTransaction) -> Result<()> {
+    let pubkey = signer.pubkey();
+    let required = transaction.message.header().num_required_signatures as usize;
+    let position = transaction.message.static_account_keys()
+        .iter()
+        .take(required)
+        .position(|key| *key == pubkey)
+        .ok_or_else(|| anyhow::anyhow!("{} is not a signer of this transaction", pubkey))?;
+    if transaction.signatures.len() < required {
+        transaction.signatures.resize(required, Signature::default());
+    }
+    let signature = signer.sign_message(&transaction.message.serialize()).await?;
+    transaction.signatures[position] = signature;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{hash::Hash, message::{v0, VersionedMessage}};
+    use solana_system_interface::instruction::transfer;
+
+    fn unsigned_v0(payer: &Pubkey) -> VersionedTransaction {
+        let instruction = transfer(payer, &Pubkey::new_unique(), 1);
+        let message = v0::Message::try_compile(payer, &[instruction], &[], Hash::new_unique()).unwrap();
+        VersionedTransaction { signatures: Vec::new(), message: VersionedMessage::V0(message) }
+    }
+
+    #[tokio::test]
+    async fn signs_v0_transactions() {
+        let keypair = Keypair::new();
+        let signer = KeypairSigner::from_base58(&keypair.to_base58_string()).unwrap();
+        let mut transaction = unsigned_v0(&keypair.pubkey());
+
+        sign_transaction(&signer, &mut transaction).await.unwrap();
+
+        assert_eq!(transaction.signatures.len(), 1);
+        assert!(transaction.verify_with_results().iter().all(|ok| *ok));
+    }
+
+    #[tokio::test]
+    async fn refuses_transactions_it_does_not_sign() {
+        let signer = KeypairSigner::from_base58(&Keypair::new().to_base58_string()).unwrap();
+        let mut transaction = unsigned_v0(&Pubkey::new_unique());
+
+        assert!(sign_transaction(&signer, &mut transaction).await.is_err());
+    }
+}
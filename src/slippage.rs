@@ -0,0 +1,163 @@
+use crate::rpc_client::SolanaRpcClient;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+
+// Recent samples kept per pair for the distribution
+const MAX_SAMPLES_PER_PAIR: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SlippageSample {
+    pub realized_bps: f64,
+    pub tolerated_bps: u16,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SlippageDistribution {
+    pub count: usize,
+    pub mean_bps: f64,
+    pub p50_bps: f64,
+    pub p90_bps: f64,
+    pub p99_bps: f64,
+    pub max_bps: f64,
+    pub mean_tolerated_bps: f64,
+}
+
+// Shortfall of what landed against what was quoted, in bps. Positive means we
+// received less than quoted; negative means price improvement.
+pub fn realized_slippage_bps(quoted_out_amount: u64, received_amount: u64) -> f64 {
+    if quoted_out_amount == 0 {
+        return 0.0;
+    }
+    (quoted_out_amount as f64 - received_amount as f64) / quoted_out_amount as f64 * 10_000.0
+}
+
+// Net change of `owner`'s balance of `mint` in a confirmed transaction
+pub async fn received_amount(rpc: &SolanaRpcClient, signature: &str, owner: &str, mint: &str) -> Result<u64> {
+    let transaction = rpc.call("getTransaction", json!([
+        signature,
+        { "encoding": "json", "maxSupportedTransactionVersion": 0, "commitment": "confirmed" }
+    ])).await?;
+    if transaction.is_null() {
+        return Err(anyhow::anyhow!("Transaction {} not found", signature));
+    }
+
+    let meta = &transaction["meta"];
+    if !meta["err"].is_null() {
+        return Err(anyhow::anyhow!("Transaction {} reverted: {}", signature, meta["err"]));
+    }
+
+    let balance = |key: &str| -> u64 {
+        meta[key].as_array()
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry["owner"] == owner && entry["mint"] == mint)
+            .filter_map(token_amount)
+            .sum()
+    };
+    Ok(balance("postTokenBalances").saturating_sub(balance("preTokenBalances")))
+}
+
+fn token_amount(entry: &Value) -> Option<u64> {
+    entry["uiTokenAmount"]["amount"].as_str()?.parse().ok()
+}
+
+#[derive(Debug, Default)]
+pub struct SlippageTracker {
+    samples: HashMap<String, VecDeque<SlippageSample>>,
+    total_bps: f64,
+    total_count: u64,
+}
+
+impl SlippageTracker {
+    pub fn record(&mut self, token_pair: &str, sample: SlippageSample) {
+        let samples = self.samples.entry(token_pair.to_string()).or_default();
+        if samples.len() >= MAX_SAMPLES_PER_PAIR {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+        self.total_bps += sample.realized_bps;
+        self.total_count += 1;
+    }
+
+    // Mean realized slippage over every trade recorded since startup
+    pub fn average_bps(&self) -> f64 {
+        if self.total_count == 0 {
+            0.0
+        } else {
+            self.total_bps / self.total_count as f64
+        }
+    }
+
+    pub fn distribution(&self, token_pair: &str) -> Option<SlippageDistribution> {
+        self.samples.get(token_pair).map(distribution_of)
+    }
+
+    pub fn distributions(&self) -> HashMap<String, SlippageDistribution> {
+        self.samples.iter()
+            .map(|(pair, samples)| (pair.clone(), distribution_of(samples)))
+            .collect()
+    }
+}
+
+fn distribution_of(samples: &VecDeque<SlippageSample>) -> SlippageDistribution {
+    if samples.is_empty() {
+        return SlippageDistribution::default();
+    }
+
+    let mut realized: Vec<f64> = samples.iter().map(|s| s.realized_bps).collect();
+    realized.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let count = realized.len();
+    let at = |percentile: f64| realized[((percentile * count as f64).ceil() as usize).clamp(1, count) - 1];
+
+    SlippageDistribution {
+        count,
+        mean_bps: realized.iter().sum::<f64>() / count as f64,
+        p50_bps: at(0.5),
+        p90_bps: at(0.9),
+        p99_bps: at(0.99),
+        max_bps: realized[count - 1],
+        mean_tolerated_bps: samples.iter().map(|s| s.tolerated_bps as f64).sum::<f64>() / count as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn realized_slippage_is_the_shortfall_against_the_quote() {
+        assert_eq!(realized_slippage_bps(10_000, 9_990), 10.0);
+        assert_eq!(realized_slippage_bps(10_000, 10_010), -10.0);
+        assert_eq!(realized_slippage_bps(0, 5), 0.0);
+    }
+
+    #[test]
+    fn distribution_summarizes_recorded_samples() {
+        let mut tracker = SlippageTracker::default();
+        for realized_bps in 1..=100 {
+            tracker.record("SOL/USDC", SlippageSample { realized_bps: realized_bps as f64, tolerated_bps: 50 });
+        }
+        tracker.record("RAY/USDC", SlippageSample { realized_bps: -4.0, tolerated_bps: 30 });
+
+        let distribution = tracker.distribution("SOL/USDC").unwrap();
+        assert_eq!(distribution.count, 100);
+        assert_eq!(distribution.p50_bps, 50.0);
+        assert_eq!(distribution.p90_bps, 90.0);
+        assert_eq!(distribution.max_bps, 100.0);
+        assert_eq!(distribution.mean_tolerated_bps, 50.0);
+        assert!(tracker.distribution("ORCA/USDC").is_none());
+        assert_eq!(tracker.distributions().len(), 2);
+        assert_eq!(tracker.average_bps(), (5_050.0 - 4.0) / 101.0);
+    }
+
+    #[test]
+    fn keeps_a_bounded_window_per_pair() {
+        let mut tracker = SlippageTracker::default();
+        for _ in 0..MAX_SAMPLES_PER_PAIR + 10 {
+            tracker.record("SOL/USDC", SlippageSample { realized_bps: 1.0, tolerated_bps: 50 });
+        }
+        assert_eq!(tracker.distribution("SOL/USDC").unwrap().count, MAX_SAMPLES_PER_PAIR);
+    }
+}
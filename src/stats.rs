@@ -0,0 +1,198 @@
+use crate::types::{StrategyKind, TradingStats};
+use anyhow::Result;
+use tokio::sync::{mpsc, oneshot};
+
+enum StatsMessage {
+    Trade { strategy: StrategyKind, success: bool, profit: f64 },
+    // A successful trade undone by a reorg: it stays counted, as a failure with no profit
+    Reversal { profit: f64 },
+    Snapshot(oneshot::Sender<TradingStats>),
+}
+
+// Trading statistics behind a single aggregation task. Concurrent trades only
+// send messages; the task applies them one at a time, so totals and the
+// metrics derived from them (win rate, drawdown, Sharpe) always describe the
+// same set of trades. Recording waits for channel space rather than dropping,
+// so no trade goes uncounted.
+#[derive(Clone)]
+pub struct StatsHandle {
+    sender: mpsc::Sender<StatsMessage>,
+}
+
+impl StatsHandle {
+    pub fn start(channel_capacity: usize) -> Self {
+        let (sender, mut receiver) = mpsc::channel(channel_capacity.max(1));
+        tokio::spawn(async move {
+            let mut aggregate = StatsAggregate::default();
+            while let Some(message) = receiver.recv().await {
+                match message {
+                    StatsMessage::Trade { strategy, success, profit } => aggregate.record(strategy, success, profit),
+                    StatsMessage::Reversal { profit } => aggregate.reverse(profit),
+                    StatsMessage::Snapshot(reply) => {
+                        let _ = reply.send(aggregate.snapshot());
+                    }
+                }
+            }
+        });
+        Self { sender }
+    }
+
+    pub async fn record_trade(&self, strategy: StrategyKind, success: bool, profit: f64) -> Result<()> {
+        self.send(StatsMessage::Trade { strategy, success, profit }).await
+    }
+
+    pub async fn record_reversal(&self, profit: f64) -> Result<()> {
+        self.send(StatsMessage::Reversal { profit }).await
+    }
+
+    // Reflects every trade recorded before the call
+    pub async fn snapshot(&self) -> Result<TradingStats> {
+        let (reply, response) = oneshot::channel();
+        self.send(StatsMessage::Snapshot(reply)).await?;
+        Ok(response.await?)
+    }
+
+    async fn send(&self, message: StatsMessage) -> Result<()> {
+        self.sender.send(message).await
+            .map_err(|_| anyhow::anyhow!("Stats aggregator has stopped"))
+    }
+}
+
+#[derive(Debug, Default)]
+struct StatsAggregate {
+    total_trades: u32,
+    successful_trades: u32,
+    jupiter_trades: u32,
+    direct_dex_trades: u32,
+    // Per-trade profit sums for the mean and standard deviation
+    profit_sum: f64,
+    profit_sum_sq: f64,
+    equity: f64,
+    peak_equity: f64,
+    max_drawdown: f64,
+}
+
+impl StatsAggregate {
+    fn record(&mut self, strategy: StrategyKind, success: bool, profit: f64) {
+        self.total_trades += 1;
+        if success {
+            self.successful_trades += 1;
+        }
+        match strategy {
+            StrategyKind::Direct => self.direct_dex_trades += 1,
+            StrategyKind::JupiterRoute | StrategyKind::MultiHop => self.jupiter_trades += 1,
+        }
+        self.profit_sum += profit;
+        self.profit_sum_sq += profit * profit;
+        self.move_equity(profit);
+    }
+
+    fn reverse(&mut self, profit: f64) {
+        self.successful_trades = self.successful_trades.saturating_sub(1);
+        self.profit_sum -= profit;
+        self.profit_sum_sq -= profit * profit;
+        self.move_equity(-profit);
+    }
+
+    fn move_equity(&mut self, delta: f64) {
+        self.equity += delta;
+        self.peak_equity = self.peak_equity.max(self.equity);
+        self.max_drawdown = self.max_drawdown.max(self.peak_equity - self.equity);
+    }
+
+    fn snapshot(&self) -> TradingStats {
+        let trades = self.total_trades as f64;
+        let (win_rate, mean) = if self.total_trades > 0 {
+            (self.successful_trades as f64 / trades, self.profit_sum / trades)
+        } else {
+            (0.0, 0.0)
+        };
+        // Per-trade Sharpe ratio: mean profit over its standard deviation
+        let variance = if self.total_trades > 1 {
+            ((self.profit_sum_sq - trades * mean * mean) / (trades - 1.0)).max(0.0)
+        } else {
+            0.0
+        };
+        let sharpe_ratio = if variance > 0.0 { mean / variance.sqrt() } else { 0.0 };
+
+        TradingStats {
+            total_profit: self.profit_sum,
+            total_trades: self.total_trades,
+            successful_trades: self.successful_trades,
+            win_rate,
+            avg_profit_per_trade: mean,
+            max_drawdown: self.max_drawdown,
+            sharpe_ratio,
+            jupiter_trades: self.jupiter_trades,
+            direct_dex_trades: self.direct_dex_trades,
+            // Trades are counted by strategy; none mixes Jupiter and direct legs
+            hybrid_trades: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn aggregates_trades_by_strategy_with_drawdown() {
+        let stats = StatsHandle::start(4);
+        stats.record_trade(StrategyKind::Direct, true, 10.0).await.unwrap();
+        stats.record_trade(StrategyKind::MultiHop, false, -4.0).await.unwrap();
+        stats.record_trade(StrategyKind::JupiterRoute, true, 2.0).await.unwrap();
+
+        let snapshot = stats.snapshot().await.unwrap();
+        assert_eq!(snapshot.total_trades, 3);
+        assert_eq!(snapshot.successful_trades, 2);
+        assert_eq!((snapshot.direct_dex_trades, snapshot.jupiter_trades), (1, 2));
+        assert_eq!(snapshot.total_profit, 8.0);
+        assert_eq!(snapshot.max_drawdown, 4.0);
+        assert!(snapshot.sharpe_ratio > 0.0);
+    }
+
+    #[tokio::test]
+    async fn a_reversal_keeps_the_trade_but_drops_its_profit() {
+        let stats = StatsHandle::start(1);
+        stats.record_trade(StrategyKind::Direct, true, 5.0).await.unwrap();
+        stats.record_reversal(5.0).await.unwrap();
+
+        let snapshot = stats.snapshot().await.unwrap();
+        assert_eq!(snapshot.total_trades, 1);
+        assert_eq!(snapshot.successful_trades, 0);
+        assert_eq!(snapshot.total_profit, 0.0);
+        assert_eq!(snapshot.max_drawdown, 5.0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn concurrent_recordings_are_all_counted() {
+        const WORKERS: u32 = 64;
+        const TRADES_PER_WORKER: u32 = 250;
+        // A small channel, so most recordings wait for space
+        let stats = StatsHandle::start(8);
+
+        let workers: Vec<_> = (0..WORKERS).map(|worker| {
+            let stats = stats.clone();
+            tokio::spawn(async move {
+                for trade in 0..TRADES_PER_WORKER {
+                    // Every third trade loses a dollar; the rest win two
+                    let success = trade % 3 != 0;
+                    let strategy = if worker % 2 == 0 { StrategyKind::Direct } else { StrategyKind::JupiterRoute };
+                    stats.record_trade(strategy, success, if success { 2.0 } else { -1.0 }).await.unwrap();
+                }
+            })
+        }).collect();
+        for worker in workers {
+            worker.await.unwrap();
+        }
+
+        let losses_per_worker = TRADES_PER_WORKER.div_ceil(3);
+        let wins = WORKERS * (TRADES_PER_WORKER - losses_per_worker);
+        let snapshot = stats.snapshot().await.unwrap();
+        assert_eq!(snapshot.total_trades, WORKERS * TRADES_PER_WORKER);
+        assert_eq!(snapshot.successful_trades, wins);
+        assert_eq!((snapshot.direct_dex_trades, snapshot.jupiter_trades), (snapshot.total_trades / 2, snapshot.total_trades / 2));
+        assert_eq!(snapshot.total_profit, wins as f64 * 2.0 - (WORKERS * losses_per_worker) as f64);
+        assert_eq!(snapshot.win_rate, wins as f64 / snapshot.total_trades as f64);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{
+    config::RiskSettings,
+    types::{ArbitrageError, StrategyKind},
+};
+use std::collections::{HashMap, VecDeque};
+use tracing::warn;
+
+const HOUR_MS: i64 = 60 * 60 * 1000;
+const DAY_MS: i64 = 24 * HOUR_MS;
+
+#[derive(Debug, Default)]
+struct StrategyUsage {
+    trade_times_ms: VecDeque<i64>,
+    // UTC day `daily_pnl` belongs to, as days since the epoch
+    day: i64,
+    daily_pnl: f64,
+}
+
+// Gives each strategy a share of the global position size, trades/hour and
+// daily loss limits, so one misbehaving strategy can't use up the budget the
+// others depend on. Strategies without a share are held only to the global caps.
+#[derive(Debug)]
+pub struct StrategyBudgets {
+    shares: HashMap<StrategyKind, f64>,
+    usage: HashMap<StrategyKind, StrategyUsage>,
+}
+
+impl StrategyBudgets {
+    pub fn new(shares: HashMap<StrategyKind, f64>) -> Self {
+        Self { shares, usage: HashMap::new() }
+    }
+
+    // `max_position_size` is passed separately because equity sizing moves
+    // it away from the configured value
+    pub fn check(
+        &mut self,
+        strategy: StrategyKind,
+        amount: f64,
+        max_position_size: f64,
+        settings: &RiskSettings,
+        now_ms: i64,
+    ) -> Result<(), ArbitrageError> {
+        let Some(share) = self.shares.get(&strategy).copied() else {
+            return Ok(());
+        };
+        let usage = self.usage.entry(strategy).or_default();
+        usage.roll(now_ms);
+
+        let position_cap = max_position_size * share;
+        if amount > position_cap {
+            warn!("🚫 {:?} trade of {:.2} exceeds its position budget of {:.2}", strategy, amount, position_cap);
+            return Err(ArbitrageError::RiskCheckFailed);
+        }
+
+        let trades_cap = (settings.max_trades_per_hour as f64 * share).floor() as usize;
+        if usage.trade_times_ms.len() >= trades_cap {
+            warn!("🚫 {:?} used its budget of {} trades this hour", strategy, trades_cap);
+            return Err(ArbitrageError::RiskCheckFailed);
+        }
+
+        let loss_cap = settings.max_daily_loss * share;
+        if -usage.daily_pnl >= loss_cap {
+            warn!("🚫 {:?} lost {:.2} today, its daily loss budget is {:.2}", strategy, -usage.daily_pnl, loss_cap);
+            return Err(ArbitrageError::RiskCheckFailed);
+        }
+
+        Ok(())
+    }
+
+    pub fn record_trade(&mut self, strategy: StrategyKind, pnl: f64, now_ms: i64) {
+        if !self.shares.contains_key(&strategy) {
+            return;
+        }
+        let usage = self.usage.entry(strategy).or_default();
+        usage.roll(now_ms);
+        usage.trade_times_ms.push_back(now_ms);
+        usage.daily_pnl += pnl;
+    }
+
+    // Adjusts recorded PnL without counting a trade, e.g. when a reorg rolls one back
+    pub fn correct_pnl(&mut self, strategy: StrategyKind, delta: f64, now_ms: i64) {
+        if !self.shares.contains_key(&strategy) {
+            return;
+        }
+        let usage = self.usage.entry(strategy).or_default();
+        usage.roll(now_ms);
+        usage.daily_pnl += delta;
+    }
+}
+
+impl StrategyUsage {
+    // Forgets trades older than an hour and losses from previous days. The day
+    // only moves forward: a clock stepping back over midnight must not hand a
+    // strategy a fresh loss budget.
+    fn roll(&mut self, now_ms: i64) {
+        while self.trade_times_ms.front().is_some_and(|time| now_ms - time >= HOUR_MS) {
+            self.trade_times_ms.pop_front();
+        }
+        let day = now_ms.div_euclid(DAY_MS);
+        if day > self.day {
+            self.day = day;
+            self.daily_pnl = 0.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn settings() -> RiskSettings {
+        let mut settings = Config::default().risk_settings;
+        settings.max_trades_per_hour = 10;
+        settings.max_daily_loss = 100.0;
+        settings
+    }
+
+    fn budgets() -> StrategyBudgets {
+        StrategyBudgets::new(HashMap::from([(StrategyKind::MultiHop, 0.2)]))
+    }
+
+    #[test]
+    fn strategies_without_a_share_are_not_limited() {
+        let mut budgets = budgets();
+        assert!(budgets.check(StrategyKind::Direct, 1_000.0, 100.0, &settings(), 0).is_ok());
+    }
+
+    #[test]
+    fn position_and_trade_count_are_capped_by_the_share() {
+        let mut budgets = budgets();
+        assert!(budgets.check(StrategyKind::MultiHop, 30.0, 100.0, &settings(), 0).is_err());
+        assert!(budgets.check(StrategyKind::MultiHop, 20.0, 100.0, &settings(), 0).is_ok());
+
+        budgets.record_trade(StrategyKind::MultiHop, 1.0, 0);
+        budgets.record_trade(StrategyKind::MultiHop, 1.0, 1);
+        assert!(budgets.check(StrategyKind::MultiHop, 10.0, 100.0, &settings(), 2).is_err());
+        assert!(budgets.check(StrategyKind::MultiHop, 10.0, 100.0, &settings(), HOUR_MS + 1).is_ok());
+    }
+
+    #[test]
+    fn daily_losses_reset_only_when_the_day_moves_forward() {
+        let mut budgets = budgets();
+        let now = 10 * DAY_MS;
+        budgets.record_trade(StrategyKind::MultiHop, -15.0, now);
+        budgets.correct_pnl(StrategyKind::MultiHop, -5.0, now);
+        assert!(budgets.check(StrategyKind::MultiHop, 1.0, 100.0, &settings(), now + 2 * HOUR_MS).is_err());
+
+        // A clock stepping back a day keeps the loss
+        assert!(budgets.check(StrategyKind::MultiHop, 1.0, 100.0, &settings(), now - DAY_MS).is_err());
+        assert!(budgets.check(StrategyKind::MultiHop, 1.0, 100.0, &settings(), now + DAY_MS).is_ok());
+    }
+}
@@ -0,0 +1,180 @@
+use crate::clock::Clock;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+
+// Shared liveness marker a supervised task updates as it makes progress
+#[derive(Clone)]
+pub struct Heartbeat {
+    last_beat_ms: Arc<AtomicI64>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Heartbeat {
+    fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            last_beat_ms: Arc::new(AtomicI64::new(clock.now_ms())),
+            clock,
+        }
+    }
+
+    pub fn beat(&self) {
+        self.last_beat_ms.store(self.clock.now_ms(), Ordering::Relaxed);
+    }
+
+    fn millis_since_last_beat(&self) -> i64 {
+        self.clock.now_ms() - self.last_beat_ms.load(Ordering::Relaxed)
+    }
+}
+
+impl std::fmt::Debug for Heartbeat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Heartbeat").field(&self.last_beat_ms).finish()
+    }
+}
+
+// Restarts long-lived tasks that error, panic, or stop beating. A task that
+// returns Ok(()) is treated as a deliberate shutdown and is not restarted.
+#[derive(Clone)]
+pub struct Supervisor {
+    heartbeat_timeout: Duration,
+    restart_delay: Duration,
+    clock: Arc<dyn Clock>,
+    restarts: Arc<RwLock<HashMap<String, u64>>>,
+}
+
+impl Supervisor {
+    pub fn new(heartbeat_timeout_ms: u64, restart_delay_ms: u64, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            heartbeat_timeout: Duration::from_millis(heartbeat_timeout_ms),
+            restart_delay: Duration::from_millis(restart_delay_ms),
+            clock,
+            restarts: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    // For tasks that beat continuously, well inside the heartbeat timeout
+    pub fn supervise<F, Fut>(&self, name: &str, factory: F)
+    where
+        F: Fn(Heartbeat) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.spawn(name, self.heartbeat_timeout, self.restart_delay, factory);
+    }
+
+    // For tasks that work once per `period` and beat on each tick. A beat is
+    // due every period, so the timeout allows a full period plus the usual
+    // slack; a restarted task waits out a period first, as its interval runs
+    // the work immediately and would otherwise fire once per restart.
+    pub fn supervise_periodic<F, Fut>(&self, name: &str, period: Duration, factory: F)
+    where
+        F: Fn(Heartbeat) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.spawn(name, self.heartbeat_timeout + period, self.restart_delay.max(period), factory);
+    }
+
+    fn spawn<F, Fut>(&self, name: &str, heartbeat_timeout: Duration, restart_delay: Duration, factory: F)
+    where
+        F: Fn(Heartbeat) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let supervisor = self.clone();
+        let name = name.to_string();
+        let check_interval = (heartbeat_timeout / 4).max(Duration::from_millis(10));
+
+        tokio::spawn(async move {
+            loop {
+                let heartbeat = Heartbeat::new(supervisor.clock.clone());
+                let mut handle = tokio::spawn(factory(heartbeat.clone()));
+
+                loop {
+                    tokio::select! {
+                        result = &mut handle => {
+                            match result {
+                                Ok(Ok(())) => {
+                                    info!("🏁 Task {} finished", name);
+                                    return;
+                                }
+                                Ok(Err(e)) => error!("❌ Task {} failed: {}", name, e),
+                                Err(e) if e.is_panic() => error!("💥 Task {} panicked", name),
+                                Err(e) => error!("❌ Task {} was cancelled: {}", name, e),
+                            }
+                            break;
+                        }
+                        _ = tokio::time::sleep(check_interval) => {
+                            if heartbeat.millis_since_last_beat() > heartbeat_timeout.as_millis() as i64 {
+                                warn!("⏰ Task {} missed its heartbeat for {:?}, restarting",
+                                      name, heartbeat_timeout);
+                                handle.abort();
+                                break;
+                            }
+                        }
+                    }
+                }
+
+                let restarts = {
+                    let mut restarts = supervisor.restarts.write().await;
+                    let count = restarts.entry(name.clone()).or_default();
+                    *count += 1;
+                    *count
+                };
+                info!("🔁 Restarting task {} in {:?} (restart #{})", name, restart_delay, restarts);
+                tokio::time::sleep(restart_delay).await;
+            }
+        });
+    }
+
+    pub async fn get_restart_counts(&self) -> HashMap<String, u64> {
+        self.restarts.read().await.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use std::sync::atomic::AtomicU32;
+
+    #[tokio::test]
+    async fn periodic_task_is_not_restarted_between_ticks() {
+        let supervisor = Supervisor::new(20, 0, Arc::new(SystemClock));
+        supervisor.supervise_periodic("slow", Duration::from_millis(100), |heartbeat| async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(100));
+            loop {
+                interval.tick().await;
+                heartbeat.beat();
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        assert!(supervisor.get_restart_counts().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn restarted_periodic_task_waits_out_its_period() {
+        let supervisor = Supervisor::new(1000, 0, Arc::new(SystemClock));
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = runs.clone();
+        supervisor.supervise_periodic("failing", Duration::from_millis(500), move |_| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Err(anyhow::anyhow!("boom"))
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+}
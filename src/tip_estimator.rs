@@ -0,0 +1,118 @@
+use crate::{config::JitoConfig, types::LAMPORTS_PER_SOL};
+use anyhow::Result;
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+// Landed-tip percentiles over recent bundles, in SOL, as published by Jito
+#[derive(Debug, Deserialize)]
+struct TipFloor {
+    landed_tips_25th_percentile: f64,
+    landed_tips_50th_percentile: f64,
+    landed_tips_75th_percentile: f64,
+    landed_tips_95th_percentile: f64,
+    landed_tips_99th_percentile: f64,
+}
+
+impl TipFloor {
+    // Jito publishes only these percentiles; use the lowest at or above the one asked for
+    fn at_percentile(&self, percentile: u8) -> f64 {
+        match percentile {
+            0..=25 => self.landed_tips_25th_percentile,
+            26..=50 => self.landed_tips_50th_percentile,
+            51..=75 => self.landed_tips_75th_percentile,
+            76..=95 => self.landed_tips_95th_percentile,
+            _ => self.landed_tips_99th_percentile,
+        }
+    }
+}
+
+// Bids a percentile of recently landed Jito tips, so the tip rises with
+// bundle competition and falls back when slots are quiet. Bounded to
+// [min_tip_lamports, max_tip_lamports]; the tip floor is refetched at most
+// once per `tip_refresh_ms`.
+pub struct TipEstimator {
+    client: reqwest::Client,
+    url: String,
+    percentile: u8,
+    floor: u64,
+    ceiling: u64,
+    refresh_ms: i64,
+    // Last estimate and when it was fetched (unix ms)
+    latest: RwLock<Option<(u64, i64)>>,
+}
+
+impl TipEstimator {
+    pub fn new(config: &JitoConfig, timeout_ms: u64) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_millis(timeout_ms))
+            .build()?;
+        Ok(Self {
+            client,
+            url: config.tip_floor_url.clone(),
+            percentile: config.tip_percentile.min(100),
+            floor: config.min_tip_lamports,
+            ceiling: config.max_tip_lamports.max(config.min_tip_lamports),
+            refresh_ms: config.tip_refresh_ms as i64,
+            latest: RwLock::new(None),
+        })
+    }
+
+    pub async fn estimate(&self, now_ms: i64) -> Result<u64> {
+        if let Some((tip, fetched_at)) = *self.latest.read().await {
+            if now_ms - fetched_at < self.refresh_ms {
+                return Ok(tip);
+            }
+        }
+
+        let floors: Vec<TipFloor> = self.client.get(&self.url).send().await?.error_for_status()?.json().await?;
+        let floor = floors.first().ok_or_else(|| anyhow::anyhow!("Empty Jito tip floor response"))?;
+        let landed_lamports = (floor.at_percentile(self.percentile) * LAMPORTS_PER_SOL) as u64;
+        let tip = landed_lamports.clamp(self.floor, self.ceiling);
+        debug!("💰 Jito landed tip p{} = {} lamports, bidding {}", self.percentile, landed_lamports, tip);
+
+        *self.latest.write().await = Some((tip, now_ms));
+        Ok(tip)
+    }
+
+    // The last estimate, without fetching
+    pub async fn latest(&self) -> Option<u64> {
+        self.latest.read().await.map(|(tip, _)| tip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_up_to_the_next_published_percentile() {
+        let floor = TipFloor {
+            landed_tips_25th_percentile: 0.25,
+            landed_tips_50th_percentile: 0.5,
+            landed_tips_75th_percentile: 0.75,
+            landed_tips_95th_percentile: 0.95,
+            landed_tips_99th_percentile: 0.99,
+        };
+        assert_eq!(floor.at_percentile(10), 0.25);
+        assert_eq!(floor.at_percentile(50), 0.5);
+        assert_eq!(floor.at_percentile(60), 0.75);
+        assert_eq!(floor.at_percentile(90), 0.95);
+        assert_eq!(floor.at_percentile(100), 0.99);
+    }
+
+    #[tokio::test]
+    async fn serves_the_cached_estimate_until_refresh() {
+        let mut config = crate::config::Config::default().jito;
+        // Unroutable, so any fetch would fail
+        config.tip_floor_url = "http://127.0.0.1:9/tip_floor".to_string();
+        config.tip_refresh_ms = 1_000;
+        let estimator = TipEstimator::new(&config, 100).unwrap();
+        assert!(estimator.latest().await.is_none());
+
+        *estimator.latest.write().await = Some((5_000, 0));
+        assert_eq!(estimator.estimate(999).await.unwrap(), 5_000);
+        assert!(estimator.estimate(1_000).await.is_err());
+        assert_eq!(estimator.latest().await, Some(5_000));
+    }
+}
@@ -0,0 +1,341 @@
+use crate::{clock::check_timestamp, config::ClockConfig, rpc_client::SolanaRpcClient};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+// A blockhash is valid for ~150 slots; past this a transaction can no longer land
+pub const BLOCKHASH_EXPIRY_MS: i64 = 90_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTrade {
+    pub opportunity_id: String,
+    pub token_pair: String,
+    pub amount: f64,
+    pub signature: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecoveryOutcome {
+    Landed,
+    Reverted,
+    Dropped,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Admission {
+    Admitted,
+    AlreadyPending,
+    // Admitting the trade would push in-flight capital past the cap
+    OverCapacity { committed: f64 },
+}
+
+// An admitted trade's hold on its queue entry, and so on its share of
+// in-flight capital. Dropping the guard releases an entry that was never
+// signed, so early returns, errors and cancelled tasks free the capital of
+// trades that cannot land. A signed entry may still land and stays queued
+// for `recover` to resolve.
+pub struct PendingGuard {
+    queue: Arc<TradeQueue>,
+    // None once settled
+    opportunity_id: Option<String>,
+}
+
+impl PendingGuard {
+    pub fn new(queue: Arc<TradeQueue>, opportunity_id: &str) -> Self {
+        Self {
+            queue,
+            opportunity_id: Some(opportunity_id.to_string()),
+        }
+    }
+
+    // The outcome is final: landed, or confirmed not to
+    pub async fn settle(mut self) -> Result<()> {
+        match self.opportunity_id.take() {
+            Some(opportunity_id) => self.queue.mark_settled(&opportunity_id).await,
+            None => Ok(()),
+        }
+    }
+
+    // Leaves the entry for whoever learns the outcome later: finality
+    // tracking, or `recover` once the blockhash has expired
+    pub fn keep(mut self) {
+        self.opportunity_id = None;
+    }
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        let Some(opportunity_id) = self.opportunity_id.take() else {
+            return;
+        };
+        // Drop can't await, so the release runs as its own task
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!("⚠️ No runtime to settle pending trade {}; it is resolved on restart", opportunity_id);
+            return;
+        };
+        let queue = self.queue.clone();
+        runtime.spawn(async move {
+            if let Err(e) = queue.release_unsigned(&opportunity_id).await {
+                warn!("⚠️ Failed to release pending trade {}: {}", opportunity_id, e);
+            }
+        });
+    }
+}
+
+// Durable record of trades between the decision to submit and confirmation.
+// Every change is written to disk before the call returns, so a restart sees
+// exactly the trades whose outcome was never recorded.
+pub struct TradeQueue {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, PendingTrade>>,
+}
+
+impl TradeQueue {
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    // Refuses opportunities that are already pending, to prevent double
+    // submission, and trades that would commit more than `max_committed`
+    // across everything still unsettled. Checked under the same lock as the
+    // insert, so concurrent dispatches cannot overshoot the cap together.
+    pub async fn mark_pending(
+        &self,
+        opportunity_id: &str,
+        token_pair: &str,
+        amount: f64,
+        max_committed: Option<f64>,
+        now_ms: i64,
+    ) -> Result<Admission> {
+        let mut entries = self.entries.lock().await;
+        if entries.contains_key(opportunity_id) {
+            return Ok(Admission::AlreadyPending);
+        }
+        if let Some(max_committed) = max_committed {
+            let committed: f64 = entries.values().map(|entry| entry.amount).sum();
+            if committed + amount > max_committed {
+                return Ok(Admission::OverCapacity { committed });
+            }
+        }
+
+        entries.insert(opportunity_id.to_string(), PendingTrade {
+            opportunity_id: opportunity_id.to_string(),
+            token_pair: token_pair.to_string(),
+            amount,
+            signature: None,
+            created_at: now_ms,
+        });
+        self.persist(&entries)?;
+        Ok(Admission::Admitted)
+    }
+
+    pub async fn record_signature(&self, opportunity_id: &str, signature: &str, now_ms: i64) -> Result<()> {
+        let mut entries = self.entries.lock().await;
+        if let Some(entry) = entries.get_mut(opportunity_id) {
+            entry.signature = Some(signature.to_string());
+            entry.created_at = now_ms;
+            self.persist(&entries)?;
+        }
+        Ok(())
+    }
+
+    pub async fn mark_settled(&self, opportunity_id: &str) -> Result<()> {
+        let mut entries = self.entries.lock().await;
+        if entries.remove(opportunity_id).is_some() {
+            self.persist(&entries)?;
+        }
+        Ok(())
+    }
+
+    // Removes the entry only if no transaction was signed for it, so nothing
+    // can have been sent
+    pub async fn release_unsigned(&self, opportunity_id: &str) -> Result<()> {
+        let mut entries = self.entries.lock().await;
+        if entries.get(opportunity_id).is_some_and(|entry| entry.signature.is_none()) {
+            entries.remove(opportunity_id);
+            self.persist(&entries)?;
+        }
+        Ok(())
+    }
+
+    // Capital held by unsettled trades, released as each one settles
+    pub async fn committed_capital(&self) -> f64 {
+        self.entries.lock().await.values().map(|entry| entry.amount).sum()
+    }
+
+    pub async fn pending(&self) -> Vec<PendingTrade> {
+        self.entries.lock().await.values().cloned().collect()
+    }
+
+    // Resolves entries at least `min_age_ms` old against chain state: all of
+    // them after a restart, or the ones a live trade left queued. Landed,
+    // reverted and dropped trades are settled; unknown ones stay queued so
+    // their opportunity is not submitted again. An entry that can't be
+    // resolved (RPC error) counts as unknown and doesn't stop the rest.
+    pub async fn recover(
+        &self,
+        rpc: &SolanaRpcClient,
+        now_ms: i64,
+        clock: &ClockConfig,
+        min_age_ms: i64,
+    ) -> Vec<(PendingTrade, RecoveryOutcome)> {
+        let mut results = Vec::new();
+        for entry in self.pending().await {
+            if now_ms - entry.created_at < min_age_ms {
+                continue;
+            }
+            let outcome = match Self::resolve(rpc, &entry, now_ms, clock).await {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    warn!("⚠️ Could not resolve pending trade {}: {}", entry.opportunity_id, e);
+                    RecoveryOutcome::Unknown
+                }
+            };
+            match outcome {
+                RecoveryOutcome::Unknown => {
+                    warn!("❓ Pending trade {} has an unknown outcome, keeping it queued", entry.opportunity_id);
+                }
+                _ => {
+                    info!("♻️ Recovered pending trade {}: {:?}", entry.opportunity_id, outcome);
+                    if let Err(e) = self.mark_settled(&entry.opportunity_id).await {
+                        warn!("⚠️ Failed to settle recovered trade {}: {}", entry.opportunity_id, e);
+                    }
+                }
+            }
+            results.push((entry, outcome));
+        }
+        results
+    }
+
+    async fn resolve(rpc: &SolanaRpcClient, entry: &PendingTrade, now_ms: i64, clock: &ClockConfig) -> Result<RecoveryOutcome> {
+        // Written by an earlier run, possibly under a different clock. With
+        // no trustworthy age the trade can't be declared dropped.
+        let source = || format!("Pending trade {}", entry.opportunity_id);
+        let expired = check_timestamp(entry.created_at, now_ms, clock, source)
+            .is_some_and(|created_at| now_ms - created_at > BLOCKHASH_EXPIRY_MS);
+
+        let Some(signature) = &entry.signature else {
+            // Never signed, so nothing was sent; once any blockhash it could
+            // have fetched has expired it can't be in flight either
+            return Ok(if expired { RecoveryOutcome::Dropped } else { RecoveryOutcome::Unknown });
+        };
+
+        let result = rpc.call("getSignatureStatuses", json!([
+            [signature],
+            { "searchTransactionHistory": true }
+        ])).await?;
+
+        let status = &result["value"][0];
+        if status.is_null() {
+            return Ok(if expired { RecoveryOutcome::Dropped } else { RecoveryOutcome::Unknown });
+        }
+
+        Ok(if status["err"].is_null() { RecoveryOutcome::Landed } else { RecoveryOutcome::Reverted })
+    }
+
+    fn persist(&self, entries: &HashMap<String, PendingTrade>) -> Result<()> {
+        // Write-then-rename so a crash never leaves a truncated queue behind
+        let tmp_path = self.path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(entries)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FutureTimestampPolicy;
+
+    fn clock() -> ClockConfig {
+        ClockConfig { max_future_skew_ms: 1_000, future_timestamps: FutureTimestampPolicy::Reject }
+    }
+
+    fn queue(name: &str) -> Arc<TradeQueue> {
+        let path = std::env::temp_dir().join(format!("trade_queue_{}_{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        Arc::new(TradeQueue::open(path).unwrap())
+    }
+
+    #[tokio::test]
+    async fn dropped_guard_releases_unsigned_capital() {
+        let queue = queue("dropped_guard");
+        queue.mark_pending("a", "SOL/USDC", 5.0, Some(10.0), 0).await.unwrap();
+        let guard = PendingGuard::new(queue.clone(), "a");
+        assert_eq!(queue.committed_capital().await, 5.0);
+
+        drop(guard);
+        tokio::task::yield_now().await;
+        assert_eq!(queue.committed_capital().await, 0.0);
+    }
+
+    #[tokio::test]
+    async fn dropped_guard_keeps_signed_trades_queued() {
+        let queue = queue("signed_guard");
+        queue.mark_pending("a", "SOL/USDC", 5.0, None, 0).await.unwrap();
+        queue.record_signature("a", "sig", 1).await.unwrap();
+
+        drop(PendingGuard::new(queue.clone(), "a"));
+        tokio::task::yield_now().await;
+        assert_eq!(queue.pending().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn expired_unsigned_entries_are_dropped_without_rpc() {
+        let queue = queue("expired_unsigned");
+        queue.mark_pending("old", "SOL/USDC", 1.0, None, 0).await.unwrap();
+        queue.mark_pending("new", "SOL/USDC", 1.0, None, BLOCKHASH_EXPIRY_MS).await.unwrap();
+        // Unreachable: an unsigned entry never needs the chain
+        let rpc = SolanaRpcClient::new("http://127.0.0.1:9".to_string(), 100);
+
+        let now = BLOCKHASH_EXPIRY_MS + 1;
+        let results = queue.recover(&rpc, now, &clock(), 0).await;
+        let outcome = |id: &str| results.iter().find(|(entry, _)| entry.opportunity_id == id).map(|(_, o)| *o);
+        assert_eq!(outcome("old"), Some(RecoveryOutcome::Dropped));
+        assert_eq!(outcome("new"), Some(RecoveryOutcome::Unknown));
+        assert_eq!(queue.pending().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unreachable_rpc_leaves_signed_entries_unknown() {
+        let queue = queue("rpc_error");
+        queue.mark_pending("a", "SOL/USDC", 1.0, None, 0).await.unwrap();
+        queue.record_signature("a", "sig", 0).await.unwrap();
+        let rpc = SolanaRpcClient::new("http://127.0.0.1:9".to_string(), 100);
+
+        let results = queue.recover(&rpc, BLOCKHASH_EXPIRY_MS * 2, &clock(), 0).await;
+        assert_eq!(results[0].1, RecoveryOutcome::Unknown);
+        assert_eq!(queue.pending().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn cap_counts_unsettled_trades_until_settled() {
+        let queue = queue("cap");
+        assert_eq!(queue.mark_pending("a", "SOL/USDC", 6.0, Some(10.0), 0).await.unwrap(), Admission::Admitted);
+        assert_eq!(queue.mark_pending("a", "SOL/USDC", 1.0, Some(10.0), 0).await.unwrap(), Admission::AlreadyPending);
+        assert_eq!(
+            queue.mark_pending("b", "SOL/USDC", 6.0, Some(10.0), 0).await.unwrap(),
+            Admission::OverCapacity { committed: 6.0 },
+        );
+
+        PendingGuard::new(queue.clone(), "a").settle().await.unwrap();
+        assert_eq!(queue.mark_pending("b", "SOL/USDC", 6.0, Some(10.0), 0).await.unwrap(), Admission::Admitted);
+    }
+}
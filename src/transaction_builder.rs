@@ -0,0 +1,138 @@
+use crate::types::{ArbitrageOpportunity, StrategyKind};
+use anyhow::Result;
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, AddressLookupTableAccount, VersionedMessage},
+    pubkey::Pubkey,
+    transaction::VersionedTransaction,
+};
+
+// Address lookup table accounts are a 56-byte metadata header followed by
+// the stored addresses, 32 bytes each
+const LOOKUP_TABLE_META_SIZE: usize = 56;
+
+// One swap of a trade: `input_mint` to `output_mint`, on `dex` when the
+// opportunity was priced on a specific venue, otherwise wherever Jupiter routes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeLeg {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub dex: Option<String>,
+}
+
+// The swaps that realize an opportunity, in order. A direct opportunity buys
+// the base token on the cheap DEX with the quote token and sells it back on
+// the dear one; a cycle's token pair holds its base and intermediate mints.
+pub fn trade_legs(opportunity: &ArbitrageOpportunity, base_mint: &str, quote_mint: &str) -> Vec<TradeLeg> {
+    let (buy_dex, sell_dex) = match opportunity.strategy {
+        StrategyKind::MultiHop => (None, None),
+        _ => (Some(opportunity.buy_dex.clone()), Some(opportunity.sell_dex.clone())),
+    };
+    vec![
+        TradeLeg { input_mint: quote_mint.to_string(), output_mint: base_mint.to_string(), dex: buy_dex },
+        TradeLeg { input_mint: base_mint.to_string(), output_mint: quote_mint.to_string(), dex: sell_dex },
+    ]
+}
+
+pub fn parse_lookup_table(key: Pubkey, data: &[u8]) -> Result<AddressLookupTableAccount> {
+    let stored = data.get(LOOKUP_TABLE_META_SIZE..)
+        .ok_or_else(|| anyhow::anyhow!("Lookup table {} is truncated", key))?;
+    if stored.len() % 32 != 0 {
+        return Err(anyhow::anyhow!("Lookup table {} has a partial address", key));
+    }
+    let addresses = stored.chunks_exact(32)
+        .map(|bytes| Pubkey::try_from(bytes).map_err(|e| anyhow::anyhow!("Lookup table {}: {}", key, e)))
+        .collect::<Result<Vec<Pubkey>>>()?;
+    Ok(AddressLookupTableAccount { key, addresses })
+}
+
+// An unsigned v0 transaction with one signature slot for the payer
+pub fn compile_transaction(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> Result<VersionedTransaction> {
+    let message = v0::Message::try_compile(payer, instructions, lookup_tables, blockhash)?;
+    Ok(VersionedTransaction {
+        signatures: Vec::new(),
+        message: VersionedMessage::V0(message),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_system_interface::instruction::transfer;
+
+    fn opportunity(strategy: StrategyKind) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            id: String::new(),
+            token_pair: "SOL/USDC".to_string(),
+            buy_dex: "Raydium".to_string(),
+            sell_dex: "Orca".to_string(),
+            buy_price: 100.0,
+            sell_price: 101.0,
+            profit_percentage: 1.0,
+            estimated_profit: 1.0,
+            max_amount: 1.0,
+            gas_cost: 0.0,
+            timestamp: 0,
+            buy_pool: String::new(),
+            sell_pool: String::new(),
+            slippage: 0.5,
+            is_profitable: true,
+            rejection_reason: None,
+            price_impact: 0.0,
+            price_timestamp: 0,
+            expires_at: 0,
+            strategy,
+            edge_over_fees_pct: 0.0,
+        }
+    }
+
+    #[test]
+    fn direct_legs_buy_on_the_cheap_dex_and_sell_on_the_dear_one() {
+        let legs = trade_legs(&opportunity(StrategyKind::Direct), "SOL", "USDC");
+        assert_eq!(legs, vec![
+            TradeLeg { input_mint: "USDC".into(), output_mint: "SOL".into(), dex: Some("Raydium".into()) },
+            TradeLeg { input_mint: "SOL".into(), output_mint: "USDC".into(), dex: Some("Orca".into()) },
+        ]);
+    }
+
+    #[test]
+    fn cycle_legs_route_freely() {
+        let legs = trade_legs(&opportunity(StrategyKind::MultiHop), "X", "BASE");
+        assert!(legs.iter().all(|leg| leg.dex.is_none()));
+        assert_eq!(legs[0].input_mint, "BASE");
+        assert_eq!(legs[1].output_mint, "BASE");
+    }
+
+    #[test]
+    fn parses_lookup_table_addresses_after_the_header() {
+        let key = Pubkey::new_unique();
+        let stored = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut data = vec![0u8; LOOKUP_TABLE_META_SIZE];
+        for address in &stored {
+            data.extend_from_slice(address.as_ref());
+        }
+
+        let table = parse_lookup_table(key, &data).unwrap();
+        assert_eq!(table.key, key);
+        assert_eq!(table.addresses, stored);
+        assert!(parse_lookup_table(key, &data[..LOOKUP_TABLE_META_SIZE + 31]).is_err());
+        assert!(parse_lookup_table(key, &data[..10]).is_err());
+    }
+
+    #[test]
+    fn compiles_a_payer_signed_v0_transaction() {
+        let payer = Pubkey::new_unique();
+        let instruction = transfer(&payer, &Pubkey::new_unique(), 1);
+        let transaction = compile_transaction(&payer, &[instruction], &[], Hash::new_unique()).unwrap();
+
+        assert!(matches!(transaction.message, VersionedMessage::V0(_)));
+        assert_eq!(transaction.message.header().num_required_signatures, 1);
+        assert_eq!(transaction.message.static_account_keys()[0], payer);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{rpc_client::SolanaRpcClient, types::WSOL_MINT};
+use anyhow::Result;
+use serde_json::{json, Value};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use solana_system_interface::program as system_program;
+use std::str::FromStr;
+use tracing::{debug, error};
+
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
+#[derive(Debug, Clone)]
+pub struct TransactionSimulator {
+    rpc: SolanaRpcClient,
+}
+
+impl TransactionSimulator {
+    pub fn new(rpc_url: String, timeout_ms: u64) -> Self {
+        Self {
+            rpc: SolanaRpcClient::new(rpc_url, timeout_ms),
+        }
+    }
+
+    // Simulates a signed transaction and returns how the owner's holding of
+    // `mint` changes, in raw units. wSOL is read from the owner's lamports,
+    // since Jupiter wraps and unwraps it, so fees and tips count against it.
+    pub async fn simulate_balance_change(
+        &self,
+        transaction_base64: &str,
+        owner: &str,
+        mint: &str,
+    ) -> Result<i128> {
+        let native = mint == WSOL_MINT;
+        let watched = if native { owner.to_string() } else { associated_token_address(owner, mint)? };
+        let pre_balance = if native { self.get_lamports(&watched).await? } else { self.get_token_balance(&watched).await? };
+
+        debug!("🧪 Simulating trade, watching {} (pre-balance {})", watched, pre_balance);
+
+        let result = self.rpc.call("simulateTransaction", json!([
+            transaction_base64,
+            {
+                "encoding": "base64",
+                "sigVerify": false,
+                "replaceRecentBlockhash": true,
+                "accounts": {
+                    "encoding": "jsonParsed",
+                    "addresses": [watched],
+                },
+            }
+        ])).await?;
+
+        let value = &result["value"];
+        if !value["err"].is_null() {
+            error!("❌ Trade simulation failed: {}", value["err"]);
+            return Err(anyhow::anyhow!("Trade simulation failed: {}", value["err"]));
+        }
+
+        let account = &value["accounts"][0];
+        let post_balance = if native {
+            account["lamports"].as_u64()
+        } else {
+            parse_token_amount(&account["data"]["parsed"]["info"]["tokenAmount"])
+        }
+        .ok_or_else(|| anyhow::anyhow!("Simulation did not return {}", watched))?;
+
+        Ok(post_balance as i128 - pre_balance as i128)
+    }
+
+    // Zero for an account that doesn't exist yet; the swap will create it.
+    // RPC failures are errors, not an empty balance.
+    async fn get_token_balance(&self, token_account: &str) -> Result<u64> {
+        let result = self.rpc.call("getAccountInfo", json!([token_account, { "encoding": "jsonParsed" }])).await?;
+        let account = &result["value"];
+        if account.is_null() {
+            return Ok(0);
+        }
+        parse_token_amount(&account["data"]["parsed"]["info"]["tokenAmount"])
+            .ok_or_else(|| anyhow::anyhow!("Malformed token account {}", token_account))
+    }
+
+    async fn get_lamports(&self, address: &str) -> Result<u64> {
+        let result = self.rpc.call("getBalance", json!([address])).await?;
+        result["value"].as_u64()
+            .ok_or_else(|| anyhow::anyhow!("Malformed balance for {}", address))
+    }
+}
+
+fn parse_token_amount(token_amount: &Value) -> Option<u64> {
+    token_amount["amount"].as_str().and_then(|amount| amount.parse().ok())
+}
+
+pub fn associated_token_address(owner: &str, mint: &str) -> Result<String> {
+    let owner = Pubkey::from_str(owner)?;
+    let mint = Pubkey::from_str(mint)?;
+    let token_program = Pubkey::from_str(TOKEN_PROGRAM_ID)?;
+    let ata_program = Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID)?;
+
+    let (address, _) = Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ata_program,
+    );
+    Ok(address.to_string())
+}
+
+// CreateIdempotent: succeeds without changes if the account already exists
+pub fn create_associated_token_account_instruction(payer: &Pubkey, owner: &Pubkey, mint: &str) -> Result<Instruction> {
+    let mint = Pubkey::from_str(mint)?;
+    let address = Pubkey::from_str(&associated_token_address(&owner.to_string(), &mint.to_string())?)?;
+
+    Ok(Instruction {
+        program_id: Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID)?,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(address, false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(Pubkey::from_str(TOKEN_PROGRAM_ID)?, false),
+        ],
+        data: vec![1],
+    })
+}
+
+// CloseAccount: sends the account's lamports (rent plus, for wSOL, the
+// wrapped balance) to the owner. Fails unless any non-native balance is zero.
+pub fn close_token_account_instruction(owner: &Pubkey, mint: &str) -> Result<Instruction> {
+    let address = Pubkey::from_str(&associated_token_address(&owner.to_string(), mint)?)?;
+
+    Ok(Instruction {
+        program_id: Pubkey::from_str(TOKEN_PROGRAM_ID)?,
+        accounts: vec![
+            AccountMeta::new(address, false),
+            AccountMeta::new(*owner, false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+        data: vec![9],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn associated_token_address_is_per_owner_and_mint() {
+        let owner = Pubkey::new_unique().to_string();
+        let address = associated_token_address(&owner, WSOL_MINT).unwrap();
+        assert_eq!(address, associated_token_address(&owner, WSOL_MINT).unwrap());
+        assert_ne!(address, associated_token_address(&Pubkey::new_unique().to_string(), WSOL_MINT).unwrap());
+        assert!(associated_token_address("not-a-key", WSOL_MINT).is_err());
+    }
+
+    #[test]
+    fn builds_create_and_close_instructions_for_the_same_account() {
+        let payer = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let address = Pubkey::from_str(&associated_token_address(&owner.to_string(), WSOL_MINT).unwrap()).unwrap();
+
+        let create = create_associated_token_account_instruction(&payer, &owner, WSOL_MINT).unwrap();
+        assert_eq!(create.data, vec![1]);
+        assert_eq!(create.accounts[1].pubkey, address);
+        assert!(create.accounts[0].is_signer);
+
+        let close = close_token_account_instruction(&owner, WSOL_MINT).unwrap();
+        assert_eq!(close.data, vec![9]);
+        assert_eq!(close.accounts[0].pubkey, address);
+        assert!(close.accounts[2].is_signer);
+    }
+
+    #[test]
+    fn reads_raw_token_amounts() {
+        assert_eq!(parse_token_amount(&json!({ "amount": "12345", "decimals": 6 })), Some(12_345));
+        assert_eq!(parse_token_amount(&json!({ "uiAmount": 1.0 })), None);
+    }
+}
@@ -16,7 +16,7 @@ pub struct PriceData {
     pub price_impact: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ArbitrageOpportunity {
     pub id: String,
     pub token_pair: String,
@@ -33,6 +33,115 @@ pub struct ArbitrageOpportunity {
     pub sell_pool: String,
     pub slippage: f64,
     pub is_profitable: bool,
+    pub rejection_reason: Option<RejectionReason>,
+    // Combined price impact of both legs, in percent
+    #[serde(default)]
+    pub price_impact: f64,
+    // When the older of the two prices was observed
+    #[serde(default)]
+    pub price_timestamp: i64,
+    // Past this (unix ms) the opportunity is stale and must not be acted on;
+    // 0 means no expiry
+    #[serde(default)]
+    pub expires_at: i64,
+    #[serde(default)]
+    pub strategy: StrategyKind,
+    // Net profit as a percentage of trade size after impact, swap fees, gas,
+    // tip and platform fee: the one number to compare against thresholds
+    #[serde(default)]
+    pub edge_over_fees_pct: f64,
+}
+
+// Scan mode an opportunity came from, so strategies can be switched off and
+// PnL attributed independently
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyKind {
+    // Buy on one DEX, sell on another
+    #[default]
+    Direct,
+    // Jupiter's aggregated route against the best direct DEX price
+    JupiterRoute,
+    // base -> X -> base cycles quoted through Jupiter
+    MultiHop,
+}
+
+impl ArbitrageOpportunity {
+    // Stable id derived from what makes an opportunity logically distinct, so
+    // the same opportunity gets the same id across scans and components.
+    pub fn compute_id(&self) -> String {
+        let key = format!(
+            "{}|{}|{}|{:.6}|{:.6}|{:.2}",
+            self.token_pair, self.buy_dex, self.sell_dex, self.buy_price, self.sell_price, self.max_amount
+        );
+        stable_id(&key)
+    }
+
+    pub fn is_expired(&self, now_ms: i64) -> bool {
+        self.expires_at > 0 && now_ms > self.expires_at
+    }
+}
+
+// First 16 bytes of the key's SHA-256, hex encoded
+fn stable_id(key: &str) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, key.as_bytes());
+    digest.as_ref()[..16].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Why an opportunity that was found did not get executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RejectionReason {
+    BelowMinProfit,
+    BelowGasCost,
+    PriceImpactTooHigh,
+    RiskCheckFailed,
+    InsufficientBalance,
+    Cooldown,
+    CircuitOpen,
+    Blacklisted,
+    StaleQuote,
+    SimulationMismatch,
+    OracleDeviation,
+    LowPriceConfidence,
+    DepegRisk,
+    SharedPool,
+    BelowMinNotional,
+    QuoteDivergence,
+    RestrictedMint,
+    InputBudgetExceeded,
+    BelowMinEdge,
+    ClaimedByOtherWallet,
+    StrategyBudgetExceeded,
+    InsufficientSources,
+}
+
+impl std::fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RejectionReason::BelowMinProfit => write!(f, "below minimum profit"),
+            RejectionReason::BelowGasCost => write!(f, "profit below gas cost"),
+            RejectionReason::PriceImpactTooHigh => write!(f, "price impact too high"),
+            RejectionReason::RiskCheckFailed => write!(f, "risk check failed"),
+            RejectionReason::InsufficientBalance => write!(f, "insufficient balance"),
+            RejectionReason::Cooldown => write!(f, "cooldown"),
+            RejectionReason::CircuitOpen => write!(f, "circuit breaker open"),
+            RejectionReason::Blacklisted => write!(f, "blacklisted"),
+            RejectionReason::StaleQuote => write!(f, "stale quote"),
+            RejectionReason::SimulationMismatch => write!(f, "simulation mismatch"),
+            RejectionReason::OracleDeviation => write!(f, "deviates from oracle price"),
+            RejectionReason::LowPriceConfidence => write!(f, "price confidence too low"),
+            RejectionReason::DepegRisk => write!(f, "stablecoin off peg"),
+            RejectionReason::SharedPool => write!(f, "legs share a pool"),
+            RejectionReason::BelowMinNotional => write!(f, "trade size below minimum notional"),
+            RejectionReason::QuoteDivergence => write!(f, "quote disagrees with secondary source"),
+            RejectionReason::RestrictedMint => write!(f, "mint can be frozen or is non-transferable"),
+            RejectionReason::InputBudgetExceeded => write!(f, "required input exceeds budget"),
+            RejectionReason::BelowMinEdge => write!(f, "edge over fees below minimum"),
+            RejectionReason::ClaimedByOtherWallet => write!(f, "claimed by another wallet"),
+            RejectionReason::StrategyBudgetExceeded => write!(f, "strategy risk budget exhausted"),
+            RejectionReason::InsufficientSources => write!(f, "too few DEX sources agree on the price"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,9 +152,22 @@ pub struct TradeRequest {
     pub max_slippage: f64,
     pub priority_fee: i32,
     pub use_jito: bool,
+    // Lamports, as a decimal string
     pub jito_tip: String,
 }
 
+impl TradeRequest {
+    // The tip actually paid: zero unless the trade goes through Jito
+    pub fn jito_tip_lamports(&self) -> Result<u64, ArbitrageError> {
+        if !self.use_jito {
+            return Ok(0);
+        }
+        self.jito_tip.trim().parse().map_err(|_| {
+            ArbitrageError::InvalidRequest(format!("jito_tip '{}' is not a lamport amount", self.jito_tip))
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeResponse {
     pub transaction_id: String,
@@ -55,6 +177,32 @@ pub struct TradeResponse {
     pub gas_used: f64,
     pub execution_time: i64,
     pub bundle_id: String,
+    #[serde(default)]
+    pub submission_path: SubmissionPath,
+}
+
+// How a trade reached the chain, if it did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SubmissionPath {
+    #[default]
+    NotSubmitted,
+    Jito,
+    Rpc,
+}
+
+impl TradeResponse {
+    pub fn rejected(error_message: impl Into<String>) -> Self {
+        Self {
+            transaction_id: String::new(),
+            success: false,
+            error_message: error_message.into(),
+            actual_profit: 0.0,
+            gas_used: 0.0,
+            execution_time: 0,
+            bundle_id: String::new(),
+            submission_path: SubmissionPath::NotSubmitted,
+        }
+    }
 }
 
 // New Jupiter-specific types
@@ -63,12 +211,42 @@ pub struct JupiterQuote {
     pub input_mint: String,
     pub in_amount: u64,
     pub output_mint: String,
+    // What actually lands in our account: always net of any platform fee,
+    // whichever convention the endpoint uses. Profit math must use this.
     pub out_amount: u64,
+    // Platform fee Jupiter takes from the output mint (0 when none is set)
+    pub platform_fee_amount: u64,
+    #[serde(default)]
+    pub swap_mode: SwapMode,
+    // Slippage-adjusted bound: minimum output for ExactIn, maximum input for ExactOut
+    #[serde(default)]
+    pub other_amount_threshold: u64,
     pub price_impact_pct: f64,
     pub route_plan: Vec<RoutePlan>,
     pub context_slot: u64,
     pub time_taken: f64,
     pub slippage_bps: u16,
+    pub received_at: i64,
+    // Jupiter's x-request-id, or our own correlation id if none was returned
+    pub request_id: String,
+}
+
+// Which side of a quote is fixed. Variant names match Jupiter's `swapMode`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwapMode {
+    #[default]
+    ExactIn,
+    // Output is fixed; the input spent is what varies and must be bounded
+    ExactOut,
+}
+
+impl SwapMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SwapMode::ExactIn => "ExactIn",
+            SwapMode::ExactOut => "ExactOut",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,6 +273,28 @@ pub struct JupiterSwap {
     pub last_valid_block_height: u64,
     pub prioritization_fee_lamports: u64,
     pub compute_unit_limit: u32,
+    pub slippage_bps: u16,
+    pub other_amount_threshold: u64,
+    pub request_id: String,
+    // Slot the swap endpoint simulated the transaction at, when it reports one
+    pub simulation_slot: Option<u64>,
+}
+
+// Bounds for Jupiter's per-route recommended slippage; overrides static slippage_bps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DynamicSlippageConfig {
+    pub min_bps: u16,
+    pub max_bps: u16,
+}
+
+// Integrator fee charged on our swaps, paid out of the output mint to `fee_account`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformFeeConfig {
+    pub fee_bps: u16,
+    pub fee_account: String,
+    // Whether the endpoint's quoted outAmount already has the fee taken out;
+    // if not, the fee is subtracted before the amount is used anywhere
+    pub deducted_from_out_amount: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,6 +308,15 @@ pub struct SwapRequest {
     pub allowed_dexes: Option<Vec<String>>,
     pub excluded_dexes: Option<Vec<String>>,
     pub use_jupiter: bool,
+    pub dynamic_slippage: Option<DynamicSlippageConfig>,
+    // Replaces `priority_fee` with an explicit per-CU price when set
+    pub compute_unit_price_micro_lamports: Option<u64>,
+    // Read-only account added to the swap so on-chain volume can be attributed
+    pub tracking_account: Option<String>,
+    // Token accounts the swap opens and pays rent for; the caller, who can
+    // see the wallet, counts them so the reported gas includes the rent
+    #[serde(default)]
+    pub created_token_accounts: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -120,6 +329,80 @@ pub struct SwapResponse {
     pub execution_time: i64,
     pub bundle_id: String,
     pub quote: Option<JupiterQuote>,
+    pub swap_request_id: Option<String>,
+}
+
+// Which transaction format swaps are built in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionVersion {
+    #[default]
+    Versioned,
+    Legacy,
+    // Versioned, rebuilt as legacy for a trade that fails as version-incompatible
+    Auto,
+}
+
+// What to do when a swap is built against a much newer slot than its quote
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SlotDriftAction {
+    // Fail the swap; the caller decides whether to try again
+    Abort,
+    // Quote afresh and rebuild once, failing if that drifts too
+    #[default]
+    Requote,
+}
+
+// How the Jupiter API is coping, as seen by the health poller
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApiHealth {
+    #[default]
+    Healthy,
+    // Slow, erroring or rate limited: trade less and demand more edge
+    Degraded,
+    // Down for maintenance: no scanning or trading until it recovers
+    Maintenance,
+}
+
+// Emergency stop for all execution; scanning continues while engaged
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KillSwitchState {
+    // The configured sentinel file exists
+    pub file_present: bool,
+    // Toggled by SIGUSR2
+    pub signalled: bool,
+}
+
+impl KillSwitchState {
+    pub fn engaged(&self) -> bool {
+        self.file_present || self.signalled
+    }
+}
+
+// Whether a pair has proven it can actually be traded on-chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CanaryStatus {
+    // Newly watched; full-size trades wait for a canary trade to land
+    Required,
+    Passed,
+    // The canary reverted or failed; the pair stays blacklisted
+    Failed(String),
+}
+
+// Jupiter Price API v2 confidence level, ordered from least to most reliable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceConfidence {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceWithConfidence {
+    pub price: f64,
+    pub confidence: PriceConfidence,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -162,6 +445,54 @@ pub struct EnhancedArbitrageOpportunity {
     pub slippage: f64,
     pub is_profitable: bool,
     pub execution_method: ExecutionMethod,
+    pub price_confidence: Option<PriceConfidence>,
+    // Net profit as a percentage of trade size after impact, fees, gas and tip
+    #[serde(default)]
+    pub edge_over_fees_pct: f64,
+}
+
+impl EnhancedArbitrageOpportunity {
+    // Same scheme as `ArbitrageOpportunity::compute_id`: a rescan at the same
+    // prices and size yields the same id
+    pub fn compute_id(&self) -> String {
+        stable_id(&format!(
+            "{}|{}|{}|{:.6}|{:.6}|{:.2}",
+            self.token_pair, self.input_mint, self.output_mint,
+            self.best_jupiter_price, self.best_direct_price, self.max_amount
+        ))
+    }
+}
+
+// Round trip base -> intermediate -> base using two independent Jupiter routes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleArbitrageOpportunity {
+    pub id: String,
+    pub base_mint: String,
+    pub intermediate_mint: String,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub profit_percentage: f64,
+    pub first_leg: JupiterQuote,
+    pub second_leg: JupiterQuote,
+    pub timestamp: i64,
+    // AMM keys both legs route through; profit_percentage already accounts
+    // for the self-impact when the overlap policy is Adjust
+    #[serde(default)]
+    pub shared_pools: Vec<String>,
+    // Both in the profit denomination; profit_percentage is already net of gas
+    #[serde(default)]
+    pub gas_cost: f64,
+    #[serde(default)]
+    pub estimated_profit: f64,
+}
+
+impl CycleArbitrageOpportunity {
+    pub fn compute_id(&self) -> String {
+        stable_id(&format!(
+            "cycle|{}|{}|{}|{}",
+            self.base_mint, self.intermediate_mint, self.amount_in, self.amount_out
+        ))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -199,6 +530,80 @@ pub struct TokenBalance {
     pub price: f64,
 }
 
+pub const NATIVE_SOL_MINT: &str = "11111111111111111111111111111111";
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+// Rent-exempt minimum for a plain system account
+pub const RENT_EXEMPT_MINIMUM_SOL: f64 = 0.00089088;
+
+pub const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+pub const BASE_FEE_LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+// Rent-exempt deposit for a 165-byte SPL token account
+pub const TOKEN_ACCOUNT_RENT_LAMPORTS: u64 = 2_039_280;
+
+// Everything a transaction costs in the native fee token
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GasCost {
+    pub signatures: u32,
+    pub priority_fee_lamports: u64,
+    pub created_token_accounts: u32,
+}
+
+impl GasCost {
+    pub fn total_lamports(&self) -> u64 {
+        self.signatures as u64 * BASE_FEE_LAMPORTS_PER_SIGNATURE
+            + self.priority_fee_lamports
+            + self.created_token_accounts as u64 * TOKEN_ACCOUNT_RENT_LAMPORTS
+    }
+
+    pub fn total_sol(&self) -> f64 {
+        self.total_lamports() as f64 / LAMPORTS_PER_SOL
+    }
+
+    pub fn total_usd(&self, sol_price_usd: f64) -> f64 {
+        self.total_sol() * sol_price_usd
+    }
+}
+
+impl Portfolio {
+    // Merges native SOL and wSOL into a single logical SOL balance for display
+    // and sizing. The native portion must keep rent and a fee buffer, so that
+    // reserve is taken out of the available balance.
+    pub fn consolidate_sol(&self, fee_buffer_sol: f64) -> Portfolio {
+        let is_sol = |b: &TokenBalance| b.token_mint == NATIVE_SOL_MINT || b.token_mint == WSOL_MINT;
+        let (sol, mut balances): (Vec<TokenBalance>, Vec<TokenBalance>) =
+            self.balances.iter().cloned().partition(|b| is_sol(b));
+
+        if sol.is_empty() {
+            return self.clone();
+        }
+
+        let native_amount: f64 = sol.iter()
+            .filter(|b| b.token_mint == NATIVE_SOL_MINT)
+            .map(|b| b.amount)
+            .sum();
+        let price = sol.iter().map(|b| b.price).find(|p| *p > 0.0).unwrap_or(0.0);
+        let amount: f64 = sol.iter().map(|b| b.amount).sum();
+
+        let reserved_sol = native_amount.min(RENT_EXEMPT_MINIMUM_SOL + fee_buffer_sol);
+
+        balances.insert(0, TokenBalance {
+            token_mint: WSOL_MINT.to_string(),
+            symbol: "SOL".to_string(),
+            amount,
+            value_usd: amount * price,
+            price,
+        });
+
+        Portfolio {
+            wallet_address: self.wallet_address.clone(),
+            balances,
+            total_value_usd: self.total_value_usd,
+            available_balance: (self.available_balance - reserved_sol * price).max(0.0),
+            last_updated: self.last_updated,
+        }
+    }
+}
+
 // Risk management types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskSettings {
@@ -235,6 +640,7 @@ pub struct TradingStats {
 pub struct PerformanceMetrics {
     pub execution_time_avg: f64,
     pub price_impact_avg: f64,
+    // Mean realized slippage in bps (quoted vs received) over confirmed trades
     pub slippage_avg: f64,
     pub gas_efficiency: f64,
     pub jupiter_success_rate: f64,
@@ -242,10 +648,34 @@ pub struct PerformanceMetrics {
 }
 
 // Configuration types for Jupiter integration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JupiterApiType {
+    Public,
+    Lite,
+    Pro,
+    Ultra,
+}
+
+impl JupiterApiType {
+    pub fn default_base_url(&self) -> &'static str {
+        match self {
+            JupiterApiType::Public => "https://quote-api.jup.ag/v6",
+            JupiterApiType::Lite => "https://lite-api.jup.ag/swap/v1",
+            JupiterApiType::Pro => "https://api.jup.ag/swap/v1",
+            JupiterApiType::Ultra => "https://api.jup.ag/ultra/v1",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JupiterConfig {
     pub enabled: bool,
     pub api_url: String,
+    // When set, the client talks to this tier's base URL (its `base_urls`
+    // override or built-in default) instead of `api_url`
+    #[serde(default)]
+    pub api_type: Option<JupiterApiType>,
     pub api_key: Option<String>,
     pub timeout_ms: u64,
     pub retry_attempts: u32,
@@ -256,6 +686,82 @@ pub struct JupiterConfig {
     pub use_shared_accounts: bool,
     pub dynamic_compute_unit_limit: bool,
     pub prioritization_fee_lamports: u64,
+    #[serde(default)]
+    pub base_urls: HashMap<JupiterApiType, String>,
+    pub token_cache_path: Option<String>,
+    // How long the token list is served from memory before it is revalidated
+    pub token_list_ttl_ms: u64,
+    pub dynamic_slippage: Option<DynamicSlippageConfig>,
+    // Send our own x-request-id so requests can be traced when Jupiter doesn't return one
+    pub send_correlation_id: bool,
+    pub platform_fee: Option<PlatformFeeConfig>,
+    // Re-quote and rebuild once when the swap build still fails after retries
+    pub requote_on_swap_failure: bool,
+    // Hold non-critical requests until the rate limit resets, waiting at most
+    // this long; None sends regardless and relies on retries
+    pub rate_limit_max_wait_ms: Option<u64>,
+    pub http: HttpClientConfig,
+    // Upper bound on accounts a quoted route may touch
+    pub max_accounts: u8,
+    // Quotes whose route plan has more legs are rejected before being parsed
+    pub max_route_legs: usize,
+    // Quote with this lower cap first, bumping to max_accounts only when no route fits
+    pub adaptive_max_accounts_start: Option<u8>,
+    // Quoted price impacts above this are treated as bogus and clamped
+    pub price_impact_clamp_pct: f64,
+    // How long a quote is reused before re-fetching; 0 disables the cache.
+    // Overridable per pair
+    pub quote_cache_ttl_ms: u64,
+    // Ids per price request; batches are fetched concurrently
+    pub price_batch_size: usize,
+    pub transaction_version: TransactionVersion,
+    // Fraction of quotes (0-1) logged with full request/response detail;
+    // failed and slow quotes are always logged
+    pub trace_sample_rate: f64,
+    pub trace_slow_request_ms: u64,
+    // Tracking account per strategy, tagged onto that strategy's swaps
+    #[serde(default)]
+    pub tracking_accounts: HashMap<StrategyKind, String>,
+    // Extra headers sent on every request, e.g. whatever auth a self-hosted
+    // gateway expects; values may reference ${ENV_VAR}
+    #[serde(default)]
+    pub custom_headers: HashMap<String, String>,
+    // Slots a swap may be built past its quote's context slot; None disables the check
+    pub max_swap_slot_drift: Option<u64>,
+    pub slot_drift_action: SlotDriftAction,
+}
+
+// Connection settings for the HTTP client used to reach Jupiter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpClientConfig {
+    // http://, https:// or socks5:// proxy for all requests
+    pub proxy_url: Option<String>,
+    pub pool_max_idle_per_host: usize,
+    pub tcp_keepalive_secs: Option<u64>,
+    pub pool_idle_timeout_secs: Option<u64>,
+    // Ask for gzip/brotli responses
+    pub compression: bool,
+    // Bodies larger than this (after decompression) are aborted mid-download
+    pub max_response_bytes: usize,
+    // The token list is far bigger than any quote, so it gets its own limit
+    pub max_token_list_bytes: usize,
+}
+
+impl JupiterConfig {
+    // Where the client sends requests
+    pub fn base_url(&self) -> String {
+        match self.api_type {
+            Some(api_type) => self.base_url_for(api_type),
+            None => self.api_url.clone(),
+        }
+    }
+
+    pub fn base_url_for(&self, api_type: JupiterApiType) -> String {
+        self.base_urls
+            .get(&api_type)
+            .cloned()
+            .unwrap_or_else(|| api_type.default_base_url().to_string())
+    }
 }
 
 // Error types
@@ -270,6 +776,11 @@ pub enum ArbitrageError {
     RiskCheckFailed,
     TransactionFailed(String),
     NetworkError(String),
+    BlockhashExpired,
+    // Sent, but neither confirmed nor expired within the confirmation timeout
+    ConfirmationTimeout,
+    SimulationMismatch(String),
+    InvalidRequest(String),
 }
 
 impl std::fmt::Display for ArbitrageError {
@@ -284,8 +795,27 @@ impl std::fmt::Display for ArbitrageError {
             ArbitrageError::RiskCheckFailed => write!(f, "Risk check failed"),
             ArbitrageError::TransactionFailed(msg) => write!(f, "Transaction failed: {}", msg),
             ArbitrageError::NetworkError(msg) => write!(f, "Network error: {}", msg),
+            ArbitrageError::BlockhashExpired => write!(f, "Transaction expired before landing"),
+            ArbitrageError::ConfirmationTimeout => write!(f, "Transaction not confirmed in time"),
+            ArbitrageError::SimulationMismatch(msg) => write!(f, "Simulation mismatch: {}", msg),
+            ArbitrageError::InvalidRequest(msg) => write!(f, "Invalid request: {}", msg),
         }
     }
 }
 
 impl std::error::Error for ArbitrageError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gas_includes_rent_for_created_token_accounts() {
+        let without_ata = GasCost { signatures: 1, priority_fee_lamports: 10_000, created_token_accounts: 0 };
+        let with_ata = GasCost { created_token_accounts: 1, ..without_ata };
+
+        assert_eq!(without_ata.total_lamports(), BASE_FEE_LAMPORTS_PER_SIGNATURE + 10_000);
+        assert_eq!(with_ata.total_lamports() - without_ata.total_lamports(), TOKEN_ACCOUNT_RENT_LAMPORTS);
+        assert!((with_ata.total_usd(150.0) - with_ata.total_sol() * 150.0).abs() < 1e-12);
+    }
+}
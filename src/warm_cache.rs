@@ -0,0 +1,53 @@
+use crate::{config::Config, jupiter_client::JupiterClient, rpc_client::SolanaRpcClient};
+use anyhow::Result;
+use serde_json::json;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+// Data fetched once at startup so the first scans don't pay cold-start costs
+#[derive(Debug, Clone, Default)]
+pub struct WarmCache {
+    pub ready: bool,
+    pub token_decimals: HashMap<String, u8>,
+    pub prices: HashMap<String, f64>,
+    pub latest_blockhash: Option<String>,
+}
+
+impl WarmCache {
+    pub async fn load(
+        config: &Config,
+        jupiter_client: Option<&JupiterClient>,
+        mints: Vec<String>,
+    ) -> Result<Self> {
+        let mut cache = WarmCache::default();
+
+        let rpc = SolanaRpcClient::new(
+            config.rpc_endpoints.primary.clone(),
+            config.rpc_endpoints.timeout_ms,
+        );
+        let health = rpc.call("getHealth", json!([])).await?;
+        info!("🔥 [1/4] RPC healthy: {}", health);
+
+        let blockhash = rpc.call("getLatestBlockhash", json!([{ "commitment": "confirmed" }])).await?;
+        cache.latest_blockhash = blockhash["value"]["blockhash"].as_str().map(|s| s.to_string());
+        info!("🔥 [2/4] Initial blockhash: {:?}", cache.latest_blockhash);
+
+        if let Some(jupiter_client) = jupiter_client {
+            let tokens = jupiter_client.get_tokens().await?;
+            cache.token_decimals = tokens.iter().map(|(mint, token)| (mint.clone(), token.decimals)).collect();
+            info!("🔥 [3/4] Token list loaded: {} tokens", cache.token_decimals.len());
+
+            let fetch = jupiter_client.get_price(&mints).await?;
+            if !fetch.failed_ids.is_empty() {
+                warn!("🔥 No price baseline for {} mints: {}", fetch.failed_ids.len(), fetch.failed_ids.join(", "));
+            }
+            cache.prices = fetch.prices;
+            info!("🔥 [4/4] Price baselines primed for {} mints", cache.prices.len());
+        } else {
+            info!("🔥 [3/4] [4/4] Jupiter disabled, skipping token list and prices");
+        }
+
+        cache.ready = true;
+        Ok(cache)
+    }
+}
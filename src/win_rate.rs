@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WinRateAlert {
+    pub win_rate: f64,
+    pub window: usize,
+}
+
+// Win rate over the last `window` trades, so recent degradation shows up
+// instead of being diluted by all-time stats
+#[derive(Debug)]
+pub struct WinRateMonitor {
+    window: usize,
+    floor: f64,
+    outcomes: VecDeque<bool>,
+}
+
+impl WinRateMonitor {
+    pub fn new(window: usize, floor: f64) -> Self {
+        Self {
+            window: window.max(1),
+            floor,
+            outcomes: VecDeque::new(),
+        }
+    }
+
+    // Alerts once the window is full and below the floor, then starts a fresh
+    // window so a sustained slump alerts every `window` trades, not every trade
+    pub fn record(&mut self, win: bool) -> Option<WinRateAlert> {
+        self.outcomes.push_back(win);
+        if self.outcomes.len() > self.window {
+            self.outcomes.pop_front();
+        }
+        if self.outcomes.len() < self.window {
+            return None;
+        }
+
+        let win_rate = self.win_rate()?;
+        if win_rate < self.floor {
+            self.outcomes.clear();
+            return Some(WinRateAlert { win_rate, window: self.window });
+        }
+        None
+    }
+
+    // A trade recorded as a win was rolled back: the latest win still in the
+    // window becomes the loss it turned out to be
+    pub fn reverse_win(&mut self) {
+        if let Some(win) = self.outcomes.iter_mut().rev().find(|win| **win) {
+            *win = false;
+        }
+    }
+
+    pub fn win_rate(&self) -> Option<f64> {
+        if self.outcomes.is_empty() {
+            return None;
+        }
+        let wins = self.outcomes.iter().filter(|win| **win).count();
+        Some(wins as f64 / self.outcomes.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alerts_on_a_full_window_below_the_floor_then_starts_over() {
+        let mut monitor = WinRateMonitor::new(4, 0.5);
+        assert_eq!(monitor.win_rate(), None);
+        assert_eq!(monitor.record(false), None);
+        assert_eq!(monitor.record(false), None);
+        assert_eq!(monitor.record(true), None);
+
+        assert_eq!(monitor.record(false), Some(WinRateAlert { win_rate: 0.25, window: 4 }));
+        assert_eq!(monitor.win_rate(), None);
+        for _ in 0..3 {
+            assert_eq!(monitor.record(false), None);
+        }
+        assert!(monitor.record(false).is_some());
+    }
+
+    #[test]
+    fn rolls_old_outcomes_out_of_the_window() {
+        let mut monitor = WinRateMonitor::new(2, 0.5);
+        monitor.record(false);
+        monitor.record(true);
+        assert_eq!(monitor.record(true), None);
+        assert_eq!(monitor.win_rate(), Some(1.0));
+    }
+
+    #[test]
+    fn a_reversed_win_counts_as_a_loss() {
+        let mut monitor = WinRateMonitor::new(4, 0.5);
+        monitor.record(true);
+        monitor.record(false);
+        monitor.reverse_win();
+        assert_eq!(monitor.win_rate(), Some(0.0));
+
+        // Nothing left to reverse
+        monitor.reverse_win();
+        assert_eq!(monitor.win_rate(), Some(0.0));
+    }
+}